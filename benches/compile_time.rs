@@ -1,5 +1,12 @@
-use cairo_native::{context::NativeContext, module_to_object};
+use cairo_native::{
+    context::NativeContext,
+    module_to_object,
+    parallel::compile_to_objects,
+    utils::{find_entry_point, PassPipeline},
+    OptLevel,
+};
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::{num::NonZeroUsize, path::Path, thread::available_parallelism};
 use util::prepare_programs;
 
 mod util;
@@ -14,7 +21,9 @@ pub fn bench_compile_time(c: &mut Criterion) {
             c.bench_with_input(BenchmarkId::new(filename, 1), &program, |b, program| {
                 b.iter(|| {
                     let native_context = NativeContext::new();
-                    native_context.compile(program).unwrap();
+                    native_context
+                        .compile(program, OptLevel::default())
+                        .unwrap();
                     // pass manager internally verifies the MLIR output is correct.
                 })
             });
@@ -29,7 +38,9 @@ pub fn bench_compile_time(c: &mut Criterion) {
         for (program, filename) in &programs {
             c.bench_with_input(BenchmarkId::new(filename, 1), &program, |b, program| {
                 b.iter(|| {
-                    native_context.compile(program).unwrap();
+                    native_context
+                        .compile(program, OptLevel::default())
+                        .unwrap();
                     // pass manager internally verifies the MLIR output is correct.
                 })
             });
@@ -43,8 +54,10 @@ pub fn bench_compile_time(c: &mut Criterion) {
             c.bench_with_input(BenchmarkId::new(filename, 1), &program, |b, program| {
                 b.iter(|| {
                     let native_context = NativeContext::new();
-                    let module = native_context.compile(black_box(program)).unwrap();
-                    let object = module_to_object(module.module())
+                    let module = native_context
+                        .compile(black_box(program), OptLevel::default())
+                        .unwrap();
+                    let object = module_to_object(module.module(), OptLevel::default())
                         .expect("to compile correctly to a object file");
                     black_box(object)
                 })
@@ -60,14 +73,80 @@ pub fn bench_compile_time(c: &mut Criterion) {
         for (program, filename) in &programs {
             c.bench_with_input(BenchmarkId::new(filename, 1), &program, |b, program| {
                 b.iter(|| {
-                    let module = native_context.compile(black_box(program)).unwrap();
-                    let object = module_to_object(module.module())
+                    let module = native_context
+                        .compile(black_box(program), OptLevel::default())
+                        .unwrap();
+                    let object = module_to_object(module.module(), OptLevel::default())
                         .expect("to compile correctly to a object file");
                     black_box(object)
                 })
             });
         }
     }
+
+    {
+        let mut c = c.benchmark_group("Compilation With Entry Points To Object Code");
+
+        let native_context = NativeContext::new();
+
+        for (program, filename) in &programs {
+            // Every program's entry point is named after its file.
+            let name = Path::new(filename).file_stem().unwrap().to_str().unwrap();
+            let entry_point = find_entry_point(program, &format!("{name}::{name}"))
+                .expect("the program should have an entry point")
+                .id
+                .clone();
+
+            c.bench_with_input(BenchmarkId::new(filename, 1), &program, |b, program| {
+                b.iter(|| {
+                    let module = native_context
+                        .compile_with_entry_points(
+                            black_box(program),
+                            &[entry_point.clone()],
+                            OptLevel::default(),
+                        )
+                        .unwrap();
+                    let object = module_to_object(module.module(), OptLevel::default())
+                        .expect("to compile correctly to a object file");
+                    black_box(object)
+                })
+            });
+        }
+    }
+
+    {
+        let mut c = c.benchmark_group("Parallel Compilation To Object Code");
+
+        let pipeline = PassPipeline::from(OptLevel::default());
+        let max_threads = available_parallelism().map_or(1, NonZeroUsize::get);
+
+        for (program, filename) in &programs {
+            for num_threads in (0..)
+                .map(|i| 1 << i)
+                .take_while(|&num_threads| num_threads <= max_threads)
+            {
+                let num_partitions = NonZeroUsize::new(num_threads).unwrap();
+                c.bench_with_input(
+                    BenchmarkId::new(filename, num_threads),
+                    &program,
+                    |b, program| {
+                        b.iter(|| {
+                            let objects = compile_to_objects(
+                                black_box(program),
+                                &pipeline,
+                                OptLevel::default(),
+                                num_partitions,
+                                false,
+                                None,
+                            )
+                            .expect("to compile correctly to object files");
+                            black_box(objects)
+                        })
+                    },
+                );
+            }
+        }
+    }
 }
 
 criterion_group!(benches, bench_compile_time);
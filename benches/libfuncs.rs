@@ -1,5 +1,7 @@
 use cairo_lang_runner::StarknetState;
-use cairo_native::{context::NativeContext, executor::JitNativeExecutor};
+use cairo_native::{
+    context::NativeContext, executor::JitNativeExecutor, utils::PassPipeline, OptLevel,
+};
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 use util::{create_vm_runner, prepare_programs};
 
@@ -50,9 +52,11 @@ pub fn bench_libfuncs(c: &mut Criterion) {
                 |b, program| {
                     let native_context = NativeContext::new();
                     b.iter(|| {
-                        let module = native_context.compile(program).unwrap();
+                        let module = native_context
+                            .compile(program, OptLevel::default())
+                            .unwrap();
                         // pass manager internally verifies the MLIR output is correct.
-                        let native_executor = JitNativeExecutor::new(module);
+                        let native_executor = JitNativeExecutor::new(module, OptLevel::default());
 
                         // Execute the program.
                         let result = native_executor
@@ -63,31 +67,42 @@ pub fn bench_libfuncs(c: &mut Criterion) {
                 },
             );
 
-            c.bench_with_input(
-                BenchmarkId::new(filename, "jit-hot"),
-                program,
-                |b, program| {
-                    let native_context = NativeContext::new();
-                    let module = native_context.compile(program).unwrap();
-                    // pass manager internally verifies the MLIR output is correct.
-                    let native_executor = JitNativeExecutor::new(module);
+            // Compare the execution time of each pass pipeline preset, along with its matching
+            // optimization level.
+            for opt_level in [OptLevel::None, OptLevel::Less, OptLevel::Default] {
+                let pipeline = PassPipeline::from(opt_level);
 
-                    // warmup
-                    for _ in 0..5 {
-                        native_executor
-                            .invoke_dynamic(&entry.id, &[], Some(u64::MAX as u128), None)
+                c.bench_with_input(
+                    BenchmarkId::new(
+                        filename,
+                        format!("jit-hot-{}", format!("{pipeline:?}").to_lowercase()),
+                    ),
+                    program,
+                    |b, program| {
+                        let native_context = NativeContext::new();
+                        let module = native_context
+                            .compile_with_pipeline(program, &pipeline)
                             .unwrap();
-                    }
+                        // pass manager internally verifies the MLIR output is correct.
+                        let native_executor = JitNativeExecutor::new(module, opt_level);
 
-                    b.iter(|| {
-                        // Execute the program.
-                        let result = native_executor
-                            .invoke_dynamic(&entry.id, &[], Some(u64::MAX as u128), None)
-                            .unwrap();
-                        black_box(result)
-                    })
-                },
-            );
+                        // warmup
+                        for _ in 0..5 {
+                            native_executor
+                                .invoke_dynamic(&entry.id, &[], Some(u64::MAX as u128), None)
+                                .unwrap();
+                        }
+
+                        b.iter(|| {
+                            // Execute the program.
+                            let result = native_executor
+                                .invoke_dynamic(&entry.id, &[], Some(u64::MAX as u128), None)
+                                .unwrap();
+                            black_box(result)
+                        })
+                    },
+                );
+            }
         }
     }
 }
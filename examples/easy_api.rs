@@ -1,6 +1,7 @@
 use cairo_native::context::NativeContext;
 use cairo_native::executor::JitNativeExecutor;
 use cairo_native::values::JitValue;
+use cairo_native::OptLevel;
 use starknet_types_core::felt::Felt;
 use std::path::Path;
 
@@ -14,7 +15,9 @@ fn main() {
     let native_context = NativeContext::new();
 
     // Compile the sierra program into a MLIR module.
-    let native_program = native_context.compile(&sierra_program).unwrap();
+    let native_program = native_context
+        .compile(&sierra_program, OptLevel::default())
+        .unwrap();
 
     // The parameters of the entry point.
     let params = &[JitValue::Felt252(Felt::from_bytes_be_slice(b"user"))];
@@ -24,7 +27,7 @@ fn main() {
     let entry_point_id = cairo_native::utils::find_function_id(&sierra_program, entry_point);
 
     // Instantiate the executor.
-    let native_executor = JitNativeExecutor::new(native_program);
+    let native_executor = JitNativeExecutor::new(native_program, OptLevel::default());
 
     // Execute the program.
     let result = native_executor
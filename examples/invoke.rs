@@ -1,6 +1,6 @@
 use cairo_native::executor::JitNativeExecutor;
 use cairo_native::utils::find_entry_point;
-use cairo_native::{context::NativeContext, values::JitValue};
+use cairo_native::{context::NativeContext, values::JitValue, OptLevel};
 use std::path::Path;
 use tracing_subscriber::{EnvFilter, FmtSubscriber};
 
@@ -20,7 +20,9 @@ fn main() {
 
     let native_context = NativeContext::new();
 
-    let native_program = native_context.compile(&sierra_program).unwrap();
+    let native_program = native_context
+        .compile(&sierra_program, OptLevel::default())
+        .unwrap();
 
     // Call the echo function from the contract using the generated wrapper.
 
@@ -28,7 +30,7 @@ fn main() {
 
     let fn_id = &entry_point_fn.id;
 
-    let native_executor = JitNativeExecutor::new(native_program);
+    let native_executor = JitNativeExecutor::new(native_program, OptLevel::default());
 
     let output = native_executor.invoke_dynamic(fn_id, &[JitValue::Felt252(1.into())], None, None);
 
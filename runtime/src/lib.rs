@@ -4,7 +4,21 @@ use lazy_static::lazy_static;
 use starknet_crypto::FieldElement;
 use starknet_curve::AffinePoint;
 use starknet_types_core::felt::Felt;
-use std::{collections::HashMap, fs::File, io::Write, os::fd::FromRawFd, ptr::NonNull, slice};
+use std::{
+    cell::Cell,
+    collections::HashMap,
+    ffi::c_void,
+    fs::File,
+    io::Write,
+    os::fd::FromRawFd,
+    ptr::{null_mut, NonNull},
+    slice,
+};
+
+thread_local! {
+    /// The syscall handler of the program being executed in the current thread.
+    static SYSCALL_HANDLER: Cell<*mut c_void> = Cell::new(null_mut());
+}
 
 lazy_static! {
     pub static ref HALF_PRIME: FieldElement = FieldElement::from_dec_str(
@@ -143,7 +157,18 @@ pub unsafe extern "C" fn cairo_native__libfunc__hades_permutation(
     op2.copy_from_slice(&state[2].to_bytes_be());
 }
 
-/// Allocates a new dictionary. Internally a rust hashmap: `HashMap<[u8; 32], NonNull<()>`
+/// The runtime representation of a `Felt252Dict`.
+///
+/// Besides the values, it keeps track of the number of accesses (`felt252_dict_entry_get`) to the
+/// dictionary, which are needed to compute the range checks used when squashing it.
+#[derive(Debug, Default)]
+#[repr(C)]
+pub struct FeltDict {
+    pub mappings: HashMap<[u8; 32], NonNull<std::ffi::c_void>>,
+    pub count: u64,
+}
+
+/// Allocates a new dictionary. Internally a [`FeltDict`].
 ///
 /// # Safety
 ///
@@ -151,7 +176,7 @@ pub unsafe extern "C" fn cairo_native__libfunc__hades_permutation(
 /// definitely unsafe to use manually.
 #[no_mangle]
 pub unsafe extern "C" fn cairo_native__alloc_dict() -> *mut std::ffi::c_void {
-    Box::into_raw(Box::<HashMap<[u8; 32], NonNull<std::ffi::c_void>>>::default()) as _
+    Box::into_raw(Box::<FeltDict>::default()) as _
 }
 
 /// Frees the dictionary.
@@ -161,18 +186,17 @@ pub unsafe extern "C" fn cairo_native__alloc_dict() -> *mut std::ffi::c_void {
 /// This function is intended to be called from MLIR, deals with pointers, and is therefore
 /// definitely unsafe to use manually.
 #[no_mangle]
-pub unsafe extern "C" fn cairo_native__dict_free(
-    ptr: *mut HashMap<[u8; 32], NonNull<std::ffi::c_void>>,
-) {
-    let mut map = Box::from_raw(ptr);
+pub unsafe extern "C" fn cairo_native__dict_free(ptr: *mut FeltDict) {
+    let mut dict = Box::from_raw(ptr);
 
     // Free the entries manually.
-    for (_, entry) in map.drain() {
+    for (_, entry) in dict.mappings.drain() {
         libc::free(entry.as_ptr().cast());
     }
 }
 
-/// Gets the value for a given key, the returned pointer is null if not found.
+/// Gets the value for a given key, the returned pointer is null if not found. Every call counts as
+/// an access to the dictionary.
 ///
 /// # Safety
 ///
@@ -180,12 +204,13 @@ pub unsafe extern "C" fn cairo_native__dict_free(
 /// definitely unsafe to use manually.
 #[no_mangle]
 pub unsafe extern "C" fn cairo_native__dict_get(
-    ptr: *const HashMap<[u8; 32], NonNull<std::ffi::c_void>>,
+    ptr: *mut FeltDict,
     key: &[u8; 32],
 ) -> *const std::ffi::c_void {
-    let map: &HashMap<[u8; 32], NonNull<std::ffi::c_void>> = &*ptr;
+    let dict: &mut FeltDict = &mut *ptr;
+    dict.count += 1;
 
-    if let Some(v) = map.get(key) {
+    if let Some(v) = dict.mappings.get(key) {
         v.as_ptr()
     } else {
         std::ptr::null_mut()
@@ -200,12 +225,11 @@ pub unsafe extern "C" fn cairo_native__dict_get(
 /// definitely unsafe to use manually.
 #[no_mangle]
 pub unsafe extern "C" fn cairo_native__dict_insert(
-    ptr: *mut HashMap<[u8; 32], NonNull<std::ffi::c_void>>,
+    ptr: *mut FeltDict,
     key: &[u8; 32],
     value: NonNull<std::ffi::c_void>,
 ) -> *mut std::ffi::c_void {
-    let ptr = ptr.cast::<HashMap<[u8; 32], NonNull<std::ffi::c_void>>>();
-    let old_ptr = (*ptr).insert(*key, value);
+    let old_ptr = (*ptr).mappings.insert(*key, value);
 
     if let Some(v) = old_ptr {
         v.as_ptr()
@@ -214,6 +238,42 @@ pub unsafe extern "C" fn cairo_native__dict_insert(
     }
 }
 
+/// Compute the number of range checks that the VM uses when squashing the dictionary.
+///
+/// It mirrors the `felt252_dict_squash` CASM implementation: two range checks to validate the
+/// dictionary index, then the squashing itself, which depends on the number of accesses, the number
+/// of unique keys and whether any key is not smaller than 2^128 (big keys).
+///
+/// # Safety
+///
+/// This function is intended to be called from MLIR, deals with pointers, and is therefore
+/// definitely unsafe to use manually.
+#[no_mangle]
+pub unsafe extern "C" fn cairo_native__dict_squash_range_checks(ptr: *const FeltDict) -> u64 {
+    let dict: &FeltDict = &*ptr;
+
+    let n_accesses = dict.count;
+    let n_unique_keys = dict.mappings.len() as u64;
+    if n_accesses == 0 || n_unique_keys == 0 {
+        return 2;
+    }
+
+    // Keys are stored in little endian.
+    let big_keys = dict
+        .mappings
+        .keys()
+        .any(|key| key[16..].iter().any(|&byte| byte != 0));
+
+    // Every unique key uses two range checks (its first access index and the slack), every other
+    // access uses one, and every key after the first one is checked against the previous key.
+    let repeated_accesses = n_accesses.saturating_sub(n_unique_keys);
+    if big_keys {
+        2 + 2 * n_unique_keys + repeated_accesses + 4 * (n_unique_keys - 1)
+    } else {
+        2 + 1 + 2 * n_unique_keys + repeated_accesses + (n_unique_keys - 1)
+    }
+}
+
 /// Compute `ec_point_from_x_nz(x)` and store it.
 ///
 /// # Panics
@@ -458,6 +518,24 @@ pub unsafe extern "C" fn cairo_native__libfunc__ec__ec_state_try_finalize_nz(
     }
 }
 
+/// Replace the syscall handler of the current thread, returning the previous one.
+///
+/// The testing libfuncs (`starknet::testing::*`) don't receive the syscall handler as an argument,
+/// therefore the executor has to make it available here before invoking a program.
+///
+/// Shared libraries generated by the AOT compiler may link their own copy of this library, in
+/// which case the executor has to use the copy exported by the library instead.
+#[no_mangle]
+pub extern "C" fn cairo_native__set_syscall_handler(ptr: *mut c_void) -> *mut c_void {
+    SYSCALL_HANDLER.with(|x| x.replace(ptr))
+}
+
+/// Return the syscall handler of the current thread, or null if there is none.
+#[no_mangle]
+pub extern "C" fn cairo_native__get_syscall_handler() -> *mut c_void {
+    SYSCALL_HANDLER.with(Cell::get)
+}
+
 // TODO: Remove from here.
 #[no_mangle]
 pub extern "C" fn __debug__breakpoint_marker() {
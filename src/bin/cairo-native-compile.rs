@@ -13,9 +13,10 @@ use cairo_lang_starknet::{
     plugin::StarkNetPlugin,
 };
 use cairo_native::{
-    debug_info::{DebugInfo, DebugLocations},
+    debug_info::{DebugInfo, DebugPositions},
     metadata::{runtime_bindings::RuntimeBindingsMeta, MetadataStorage},
-    utils::run_pass_manager,
+    utils::{run_pass_manager, PassPipeline},
+    LinkerConfig, OptLevel,
 };
 use clap::Parser;
 use melior::{
@@ -27,6 +28,7 @@ use melior::{
 use std::{
     ffi::OsStr,
     fs,
+    num::NonZeroUsize,
     path::{Path, PathBuf},
     sync::Arc,
 };
@@ -39,11 +41,43 @@ struct Args {
     input: PathBuf,
 
     /// Output file, .so on linux, .dylib on macOS
-    output: PathBuf,
+    #[arg(required_unless_present = "check")]
+    output: Option<PathBuf>,
+
+    /// Only check whether the program can be compiled, listing its unsupported types and libfuncs.
+    /// Exits with a non-zero status when any is found.
+    #[arg(long)]
+    check: bool,
 
     /// Whether the program is a contract.
     #[arg(short, long)]
     starknet: bool,
+
+    /// Optimization level (0-3).
+    #[arg(short = 'O', long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=3))]
+    opt_level: u8,
+
+    /// MLIR pass pipeline: `minimal`, `standard`, `aggressive` or a comma-separated list of passes.
+    /// Chosen from the optimization level when not provided.
+    #[arg(long)]
+    pass_pipeline: Option<PassPipeline>,
+
+    /// Linker binary. Defaults to `$CAIRO_NATIVE_LINKER`, or `ld`.
+    #[arg(long)]
+    linker: Option<PathBuf>,
+
+    /// Extra argument passed to the linker. May be repeated.
+    #[arg(long = "link-arg", allow_hyphen_values = true)]
+    link_args: Vec<String>,
+
+    /// Do not pass the platform's hardcoded library search paths to the linker.
+    #[arg(long)]
+    no_default_search_paths: bool,
+
+    /// Number of threads used to compile the program, each one compiling a partition of its
+    /// functions.
+    #[arg(short, long)]
+    jobs: Option<NonZeroUsize>,
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -57,12 +91,71 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             .finish(),
     )?;
 
-    // Load the program.
-    let context = Context::new();
-    let (program, debug_info) =
-        load_program(Path::new(&args.input), Some(&context), args.starknet)?;
+    if args.check {
+        let (program, _) = load_program(Path::new(&args.input), false, args.starknet)?;
+
+        let unsupported_items = cairo_native::support::check_support(&program, false)?;
+        for item in &unsupported_items {
+            println!("{item}");
+        }
+
+        if !unsupported_items.is_empty() {
+            eprintln!(
+                "The program has {} unsupported declarations.",
+                unsupported_items.len()
+            );
+            std::process::exit(1);
+        }
+
+        return Ok(());
+    }
+
+    let opt_level = OptLevel::try_from(args.opt_level)?;
+    let pipeline = args
+        .pass_pipeline
+        .unwrap_or_else(|| PassPipeline::from(opt_level));
+
+    let (program, debug_positions) = load_program(Path::new(&args.input), true, args.starknet)?;
+    let objects = match args.jobs {
+        Some(jobs) if jobs.get() > 1 => cairo_native::parallel::compile_to_objects(
+            &program,
+            &pipeline,
+            opt_level,
+            jobs,
+            false,
+            debug_positions.as_ref(),
+        )?,
+        _ => vec![compile_to_object(
+            &program,
+            debug_positions.as_ref(),
+            &pipeline,
+            opt_level,
+        )?],
+    };
+
+    let mut linker_config =
+        LinkerConfig::default().with_default_search_paths(!args.no_default_search_paths);
+    if let Some(linker) = args.linker {
+        linker_config = linker_config.with_linker(linker);
+    }
+    linker_config.extra_args.extend(args.link_args);
+    cairo_native::objects_to_shared_lib_with_config(
+        &objects.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+        &args.output.expect("required by clap unless checking"),
+        &linker_config,
+    )?;
 
+    Ok(())
+}
+
+fn compile_to_object(
+    program: &Program,
+    debug_positions: Option<&DebugPositions>,
+    pipeline: &PassPipeline,
+    opt_level: OptLevel,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
     // Initialize MLIR.
+    let context = Context::new();
     context.append_dialect_registry(&{
         let registry = DialectRegistry::new();
         register_all_dialects(&registry);
@@ -74,7 +167,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Compile the program.
     let mut module = Module::new(Location::unknown(&context));
     let mut metadata = MetadataStorage::new();
-    let registry = ProgramRegistry::<CoreType, CoreLibfunc>::new(&program)?;
+    let registry = ProgramRegistry::<CoreType, CoreLibfunc>::new(program)?;
+    let debug_locations =
+        debug_positions.map(|debug_positions| debug_positions.to_locations(&context));
 
     // Make the runtime library available.
     metadata.insert(RuntimeBindingsMeta::default()).unwrap();
@@ -82,25 +177,22 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     cairo_native::compile::<CoreType, CoreLibfunc>(
         &context,
         &module,
-        &program,
+        program,
         &registry,
         &mut metadata,
-        debug_info.as_ref(),
+        debug_locations.as_ref(),
     )?;
 
-    run_pass_manager(&context, &mut module)?;
-
-    let object = cairo_native::module_to_object(&module)?;
-    cairo_native::object_to_shared_lib(&object, &args.output)?;
+    run_pass_manager(&context, &mut module, pipeline)?;
 
-    Ok(())
+    Ok(cairo_native::module_to_object(&module, opt_level)?)
 }
 
-fn load_program<'c>(
+fn load_program(
     path: &Path,
-    context: Option<&'c Context>,
+    with_debug_info: bool,
     is_contract: bool,
-) -> Result<(Program, Option<DebugLocations<'c>>), Box<dyn std::error::Error>> {
+) -> Result<(Program, Option<DebugPositions>), Box<dyn std::error::Error>> {
     Ok(match path.extension().and_then(OsStr::to_str) {
         Some("cairo") if !is_contract => {
             let mut db = RootDatabase::builder().detect_corelib().build()?;
@@ -115,19 +207,19 @@ fn load_program<'c>(
             )?)
             .clone();
 
-            let debug_locations = if let Some(context) = context {
+            let debug_positions = if with_debug_info {
                 let debug_info = DebugInfo::extract(&db, &program).map_err(|_| {
                     let mut buffer = String::new();
                     assert!(DiagnosticsReporter::write_to_string(&mut buffer).check(&db));
                     buffer
                 })?;
 
-                Some(DebugLocations::extract(context, &db, &debug_info))
+                Some(DebugPositions::extract(&db, &debug_info))
             } else {
                 None
             };
 
-            (program, debug_locations)
+            (program, debug_positions)
         }
         Some("cairo") if is_contract => {
             // mimics cairo_lang_starknet::contract_class::compile_path
@@ -151,19 +243,19 @@ fn load_program<'c>(
 
             let program = contract.extract_sierra_program()?;
 
-            let debug_locations = if let Some(context) = context {
+            let debug_positions = if with_debug_info {
                 let debug_info = DebugInfo::extract(&db, &program).map_err(|_| {
                     let mut buffer = String::new();
                     assert!(DiagnosticsReporter::write_to_string(&mut buffer).check(&db));
                     buffer
                 })?;
 
-                Some(DebugLocations::extract(context, &db, &debug_info))
+                Some(DebugPositions::extract(&db, &debug_info))
             } else {
                 None
             };
 
-            (program, debug_locations)
+            (program, debug_positions)
         }
         Some("sierra") => {
             let program_src = fs::read_to_string(path)?;
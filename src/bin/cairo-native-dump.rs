@@ -15,6 +15,8 @@ use cairo_lang_starknet::{
 use cairo_native::{
     debug_info::{DebugInfo, DebugLocations},
     metadata::{runtime_bindings::RuntimeBindingsMeta, MetadataStorage},
+    utils::{run_pass_manager, PassPipeline},
+    OptLevel,
 };
 use clap::Parser;
 use melior::{
@@ -56,7 +58,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     context.load_all_available_dialects();
 
     // Compile the program.
-    let module = Module::new(Location::unknown(&context));
+    let mut module = Module::new(Location::unknown(&context));
     let mut metadata = MetadataStorage::new();
     let registry = ProgramRegistry::<CoreType, CoreLibfunc>::new(&program)?;
 
@@ -72,6 +74,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         debug_info.as_ref(),
     )?;
 
+    // Lower the program when either an optimization level or a pass pipeline is given.
+    let pipeline = match (args.pass_pipeline, args.opt_level) {
+        (Some(pipeline), _) => Some(pipeline),
+        (None, Some(opt_level)) => Some(PassPipeline::from(OptLevel::try_from(opt_level)?)),
+        (None, None) => None,
+    };
+    if let Some(pipeline) = pipeline {
+        run_pass_manager(&context, &mut module, &pipeline)?;
+    }
+
     // Write the output.
     let output_str = module
         .as_operation()
@@ -178,6 +190,15 @@ struct CmdLine {
     /// Compile a starknet contract
     #[clap(long)]
     starknet: bool,
+
+    /// Run the pass pipeline with the given optimization level (0-3) before dumping.
+    #[clap(short = 'O', long = "opt-level", value_parser = clap::value_parser!(u8).range(0..=3))]
+    opt_level: Option<u8>,
+
+    /// Run the given pass pipeline before dumping: `minimal`, `standard`, `aggressive` or a
+    /// comma-separated list of passes.
+    #[clap(long = "pass-pipeline")]
+    pass_pipeline: Option<PassPipeline>,
 }
 
 #[derive(Clone, Debug)]
@@ -1,10 +1,28 @@
 use cairo_lang_compiler::{
     compile_prepared_db, db::RootDatabase, project::setup_project, CompilerConfig,
 };
-use cairo_lang_sierra::{ids::FunctionId, program::Program, ProgramParser};
-use cairo_native::{context::NativeContext, executor::JitNativeExecutor, values::JitValue};
-use clap::Parser;
-use itertools::Itertools;
+use cairo_lang_sierra::{
+    extensions::core::{CoreLibfunc, CoreType, CoreTypeConcrete},
+    ids::{ConcreteTypeId, FunctionId},
+    program::Program,
+    program_registry::ProgramRegistry,
+    ProgramParser,
+};
+use cairo_lang_starknet::contract_class::compile_path;
+use cairo_native::{
+    cache::AotProgramCache,
+    context::NativeContext,
+    executor::{JitNativeExecutor, NativeExecutor},
+    metadata::syscall_handler::SyscallHandlerMeta,
+    starknet::InMemorySyscallHandler,
+    types::TypeBuilder,
+    utils::PassPipeline,
+    values::{JitValue, JitValueSeed},
+    OptLevel,
+};
+use clap::{Parser, ValueEnum};
+use serde::de::DeserializeSeed;
+use serde_json::Value;
 use starknet_types_core::felt::Felt;
 use std::{
     borrow::Cow,
@@ -28,7 +46,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     )?;
 
     // Load the program.
-    let sierra_program = load_program(Path::new(&args.input))?;
+    let sierra_program = load_program(Path::new(&args.input), args.starknet)?;
 
     let entry_point = match sierra_program
         .funcs
@@ -46,8 +64,18 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let native_context = NativeContext::new();
 
     // Compile the sierra program into a MLIR module.
-    let native_program = native_context.compile(&sierra_program).unwrap();
-    let native_executor = JitNativeExecutor::new(native_program);
+    let opt_level = OptLevel::try_from(args.opt_level)?;
+    let native_executor: NativeExecutor = if args.aot {
+        // The AOT cache always uses the optimization level's pipeline.
+        let cache = AotProgramCache::new(&native_context);
+        NativeExecutor::Aot(cache.compile_and_insert((), &sierra_program, opt_level)?)
+    } else {
+        let pipeline = args
+            .pass_pipeline
+            .unwrap_or_else(|| PassPipeline::from(opt_level));
+        let native_program = native_context.compile_with_pipeline(&sierra_program, &pipeline)?;
+        JitNativeExecutor::new(native_program, opt_level).into()
+    };
 
     // Initialize arguments and return values.
     let params_input = match args.inputs {
@@ -56,28 +84,52 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         None => Cow::Borrowed(""),
     };
 
-    let params = params_input
-        .split_whitespace()
-        .map(|x| {
-            JitValue::Felt252(Felt::from_dec_str(x).expect("input parameter is not a valid Felt"))
-        })
-        .collect_vec();
+    let gas = Some(args.gas.unwrap_or(u64::MAX.into()));
+    let result = if args.starknet {
+        let calldata = parse_calldata(
+            native_executor.program_registry(),
+            &entry_point.signature.param_types,
+            &params_input,
+        )?;
+
+        let mut syscall_handler = InMemorySyscallHandler::new();
+        let result = native_executor.invoke_contract_dynamic(
+            &entry_point.id,
+            &calldata,
+            gas,
+            Some(&SyscallHandlerMeta::new(&mut syscall_handler)),
+        )?;
+
+        match args.output_format {
+            OutputFormat::Debug => format!("{result:#?}"),
+            OutputFormat::Json => serde_json::to_string_pretty(&result)?,
+        }
+    } else {
+        let params = parse_params(
+            native_executor.program_registry(),
+            &entry_point.signature.param_types,
+            &params_input,
+        )?;
+
+        let result = native_executor.invoke_dynamic(&entry_point.id, &params, gas, None)?;
 
-    let result = native_executor
-        .invoke_dynamic(&entry_point.id, &params, Some(u64::MAX.into()), None)
-        .unwrap();
+        match args.output_format {
+            OutputFormat::Debug => format!("{result:#?}"),
+            OutputFormat::Json => serde_json::to_string_pretty(&result)?,
+        }
+    };
 
     match args.outputs {
         Some(StdioOrPath::Stdio) => {
-            println!("{:#?}", result);
+            println!("{result}");
         }
         Some(StdioOrPath::Path(path)) => {
             let mut file = File::create(path)?;
-            writeln!(file, "{:#?}", result)?;
+            writeln!(file, "{result}")?;
         }
         None => {
             if args.print_outputs {
-                println!("{:#?}", result);
+                println!("{result}");
             }
         }
     }
@@ -85,8 +137,20 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-fn load_program(path: &Path) -> Result<Program, Box<dyn std::error::Error>> {
+fn load_program(path: &Path, is_contract: bool) -> Result<Program, Box<dyn std::error::Error>> {
     Ok(match path.extension().and_then(OsStr::to_str) {
+        Some("cairo") if is_contract => {
+            let contract = compile_path(
+                path,
+                None,
+                CompilerConfig {
+                    replace_ids: true,
+                    ..Default::default()
+                },
+            )?;
+
+            contract.extract_sierra_program()?
+        }
         Some("cairo") => {
             let mut db = RootDatabase::builder().detect_corelib().build()?;
             let main_crate_ids = setup_project(&mut db, path)?;
@@ -126,6 +190,37 @@ struct CmdLine {
     outputs: Option<StdioOrPath>,
     #[clap(short = 'p', long = "print-outputs")]
     print_outputs: bool,
+    /// Format of the printed execution result.
+    #[clap(long = "output-format", value_enum, default_value_t = OutputFormat::Debug)]
+    output_format: OutputFormat,
+
+    /// Optimization level (0-3).
+    #[clap(short = 'O', long = "opt-level", default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=3))]
+    opt_level: u8,
+
+    /// MLIR pass pipeline: `minimal`, `standard`, `aggressive` or a comma-separated list of passes.
+    /// Chosen from the optimization level when not provided. Ignored when using `--aot`.
+    #[clap(long = "pass-pipeline")]
+    pass_pipeline: Option<PassPipeline>,
+
+    /// Compile the program ahead of time instead of using the JIT engine.
+    #[clap(long = "aot")]
+    aot: bool,
+    /// Available gas. Defaults to `u64::MAX`.
+    #[clap(long = "gas")]
+    gas: Option<u128>,
+    /// Run a Starknet contract entry point, using an in-memory syscall handler. The inputs are
+    /// the entry point's calldata.
+    #[clap(long = "starknet")]
+    starknet: bool,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum OutputFormat {
+    /// Rust's pretty-printed debug format.
+    Debug,
+    /// The execution result serialized as JSON.
+    Json,
 }
 
 #[derive(Clone, Debug)]
@@ -163,3 +258,86 @@ fn parse_io(input: &str) -> Result<StdioOrPath, String> {
         })
     })
 }
+
+/// Parse the entry point's arguments.
+///
+/// The input is a sequence of whitespace-separated JSON values, one for every non-builtin parameter.
+/// See [`JitValueSeed`] for how every value is decoded.
+fn parse_params(
+    registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    param_types: &[ConcreteTypeId],
+    input: &str,
+) -> Result<Vec<JitValue>, String> {
+    let values = serde_json::Deserializer::from_str(input)
+        .into_iter::<Value>()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("invalid input: {e}"))?;
+
+    let is_builtin = <CoreTypeConcrete as TypeBuilder<CoreType, CoreLibfunc>>::is_builtin;
+    let param_types = param_types
+        .iter()
+        .filter_map(|ty| match registry.get_type(ty) {
+            Ok(type_info) if is_builtin(type_info) => None,
+            Ok(_) => Some(Ok(ty)),
+            Err(e) => Some(Err(e.to_string())),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if param_types.len() != values.len() {
+        return Err(format!(
+            "expected {} arguments, got {}",
+            param_types.len(),
+            values.len()
+        ));
+    }
+
+    param_types
+        .into_iter()
+        .zip(&values)
+        .enumerate()
+        .map(|(idx, (ty, value))| {
+            JitValueSeed::new(registry, ty)
+                .deserialize(value)
+                .map_err(|e| format!("argument {idx}: {e}"))
+        })
+        .collect()
+}
+
+/// Parse a contract entry point's calldata.
+///
+/// The input is a sequence of whitespace-separated JSON values, which are decoded as felts.
+fn parse_calldata(
+    registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    param_types: &[ConcreteTypeId],
+    input: &str,
+) -> Result<Vec<Felt>, String> {
+    // Contract entry points receive their calldata as a `Span<felt252>`, which is a struct
+    // containing a snapshot of an array.
+    let felt_ty = param_types
+        .iter()
+        .find_map(|ty| match registry.get_type(ty).ok()? {
+            CoreTypeConcrete::Struct(info) => {
+                match registry.get_type(info.members.first()?).ok()? {
+                    CoreTypeConcrete::Snapshot(info) => match registry.get_type(&info.ty).ok()? {
+                        CoreTypeConcrete::Array(info) => Some(&info.ty),
+                        _ => None,
+                    },
+                    _ => None,
+                }
+            }
+            _ => None,
+        })
+        .ok_or_else(|| "the entry point does not receive calldata".to_string())?;
+
+    serde_json::Deserializer::from_str(input)
+        .into_iter::<Value>()
+        .enumerate()
+        .map(|(idx, value)| {
+            let value = value.map_err(|e| format!("invalid input: {e}"))?;
+            match JitValueSeed::new(registry, felt_ty).deserialize(&value) {
+                Ok(JitValue::Felt252(value)) => Ok(value),
+                Ok(_) => Err(format!("argument {idx}: expected a felt")),
+                Err(e) => Err(format!("argument {idx}: {e}")),
+            }
+        })
+        .collect()
+}
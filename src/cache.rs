@@ -1,7 +1,15 @@
-pub use self::{aot::AotProgramCache, jit::JitProgramCache};
+#[cfg(feature = "with-serde")]
+pub use self::disk::{CompileSettings, DiskCache};
+pub use self::{
+    aot::{AotCacheError, AotProgramCache},
+    jit::JitProgramCache,
+};
 use std::hash::Hash;
 
 pub mod aot;
+// The disk cache stores the Sierra programs and the entries' metadata as JSON.
+#[cfg(feature = "with-serde")]
+pub mod disk;
 pub mod jit;
 
 #[derive(Debug)]
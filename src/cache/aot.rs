@@ -1,22 +1,58 @@
+#[cfg(feature = "with-serde")]
+use super::{CompileSettings, DiskCache};
 use crate::{
-    context::NativeContext, executor::AotNativeExecutor, metadata::gas::GasMetadata,
-    module::NativeModule, utils::SHARED_LIBRARY_EXT,
+    context::{NativeContext, ProgramMetadata},
+    executor::AotNativeExecutor,
+    ffi::LinkError,
+    parallel::ParallelCompileError,
+    utils::SHARED_LIBRARY_EXT,
+    OptLevel,
+};
+use cairo_lang_sierra::{
+    extensions::core::{CoreLibfunc, CoreType},
+    program::Program,
+    program_registry::{ProgramRegistry, ProgramRegistryError},
 };
-use cairo_lang_sierra::program::Program;
 use libloading::Library;
 use std::{
     collections::HashMap,
     fmt::{self, Debug},
     hash::Hash,
-    rc::Rc,
+    io,
+    sync::{Arc, Mutex, RwLock},
 };
+use thiserror::Error;
+
+/// An error produced while compiling a program into an [`AotProgramCache`].
+#[derive(Debug, Error)]
+pub enum AotCacheError {
+    #[error(transparent)]
+    ProgramRegistry(#[from] Box<ProgramRegistryError>),
+    #[error(transparent)]
+    Compile(#[from] ParallelCompileError),
+    #[error(transparent)]
+    Link(#[from] LinkError),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    LibraryLoading(#[from] libloading::Error),
+}
 
+/// A thread-safe cache of AOT-compiled programs.
+///
+/// The returned executors can be shared and invoked from multiple threads at once.
 pub struct AotProgramCache<'a, K>
 where
     K: PartialEq + Eq + Hash,
 {
     context: &'a NativeContext,
-    cache: HashMap<K, Rc<AotNativeExecutor>>,
+    cache: RwLock<HashMap<K, Arc<AotNativeExecutor>>>,
+    #[cfg(feature = "with-serde")]
+    disk_cache: Option<DiskCache>,
+
+    // Programs are compiled one at a time since MLIR isn't guaranteed to support compiling multiple
+    // modules concurrently within the same context.
+    compile_lock: Mutex<()>,
 }
 
 impl<'a, K> AotProgramCache<'a, K>
@@ -27,46 +63,179 @@ where
         Self {
             context,
             cache: Default::default(),
+            #[cfg(feature = "with-serde")]
+            disk_cache: None,
+            compile_lock: Mutex::new(()),
         }
     }
 
-    pub fn get(&self, key: &K) -> Option<Rc<AotNativeExecutor>> {
-        self.cache.get(key).cloned()
+    /// Create a cache which persists the compiled programs into a [`DiskCache`].
+    ///
+    /// Programs already present on disk (for example, from a previous run) are loaded instead of
+    /// being compiled again.
+    #[cfg(feature = "with-serde")]
+    pub fn with_disk_cache(context: &'a NativeContext, disk_cache: DiskCache) -> Self {
+        Self {
+            context,
+            cache: Default::default(),
+            disk_cache: Some(disk_cache),
+            compile_lock: Mutex::new(()),
+        }
     }
 
-    pub fn compile_and_insert(&mut self, key: K, program: &Program) -> Rc<AotNativeExecutor> {
-        let NativeModule {
-            module,
-            registry,
-            metadata,
-        } = self.context.compile(program).expect("should compile");
-
-        // Compile module into an object.
-        let object_data = crate::ffi::module_to_object(&module).unwrap();
-
-        // Compile object into a shared library.
-        let shared_library_path = tempfile::Builder::new()
-            .prefix("lib")
-            .suffix(SHARED_LIBRARY_EXT)
-            .tempfile()
-            .unwrap()
-            .into_temp_path();
-        crate::ffi::object_to_shared_lib(&object_data, &shared_library_path).unwrap();
-
-        let shared_library = unsafe { Library::new(shared_library_path).unwrap() };
-        let executor = AotNativeExecutor::new(
-            shared_library,
-            registry,
-            metadata.get::<GasMetadata>().cloned(),
-        );
+    pub fn get(&self, key: &K) -> Option<Arc<AotNativeExecutor>> {
+        self.cache.read().unwrap().get(key).cloned()
+    }
+
+    /// Compile a program (or load it from the disk cache) and insert it into the cache.
+    ///
+    /// Failing to load or store the program in the disk cache isn't an error: the program is
+    /// compiled and linked into a temporary library instead.
+    pub fn compile_and_insert(
+        &self,
+        key: K,
+        program: &Program,
+        opt_level: OptLevel,
+    ) -> Result<Arc<AotNativeExecutor>, AotCacheError> {
+        let _guard = self.compile_lock.lock().unwrap();
+
+        // Another thread may have compiled the program while we were waiting for the lock.
+        if let Some(executor) = self.get(&key) {
+            return Ok(executor);
+        }
+
+        #[cfg(feature = "with-serde")]
+        let executor = match &self.disk_cache {
+            Some(disk_cache) => {
+                compile_aot_with_disk_cache(self.context, program, opt_level, disk_cache)?
+            }
+            None => compile_aot(self.context, program, opt_level)?,
+        };
+        #[cfg(not(feature = "with-serde"))]
+        let executor = compile_aot(self.context, program, opt_level)?;
 
-        let executor = Rc::new(executor);
-        self.cache.insert(key, executor.clone());
+        let executor = Arc::new(executor);
+        self.cache.write().unwrap().insert(key, executor.clone());
 
-        executor
+        Ok(executor)
     }
 }
 
+/// Compile a program into an executor linked into a temporary shared library.
+///
+/// The program is compiled using the context's settings, including its number of jobs and its
+/// linker configuration.
+pub(crate) fn compile_aot(
+    context: &NativeContext,
+    program: &Program,
+    opt_level: OptLevel,
+) -> Result<AotNativeExecutor, AotCacheError> {
+    let (registry, program_metadata, objects) = compile_objects(context, program, opt_level)?;
+    let shared_library = link_temporary_library(
+        context,
+        &objects.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+    )?;
+
+    Ok(AotNativeExecutor::new(
+        shared_library,
+        registry,
+        program_metadata.gas_metadata().cloned(),
+    )
+    .with_builtin_counters(context.builtin_counters()))
+}
+
+/// Same as [`compile_aot`], but the program is loaded from (or stored into) a disk cache.
+///
+/// Failing to load or store the program in the disk cache isn't an error: the program is compiled
+/// and linked into a temporary library instead.
+#[cfg(feature = "with-serde")]
+pub(crate) fn compile_aot_with_disk_cache(
+    context: &NativeContext,
+    program: &Program,
+    opt_level: OptLevel,
+    disk_cache: &DiskCache,
+) -> Result<AotNativeExecutor, AotCacheError> {
+    let settings = CompileSettings {
+        builtin_counters: context.builtin_counters(),
+        ..CompileSettings::from(opt_level)
+    };
+
+    // A broken disk cache shouldn't prevent the program from being compiled, so failing to load
+    // an entry is treated as a miss.
+    match disk_cache.load(program, &settings) {
+        Ok(Some(executor)) => return Ok(executor),
+        Ok(None) => {}
+        Err(e) => tracing::warn!("Failed to load program from the AOT disk cache: {e}."),
+    }
+
+    let (registry, program_metadata, objects) = compile_objects(context, program, opt_level)?;
+    let objects = objects.iter().map(Vec::as_slice).collect::<Vec<_>>();
+
+    // If the program can't be stored in the disk cache, it is linked into a temporary file just
+    // like when there is no disk cache.
+    let shared_library = match disk_cache.store_objects_with_config(
+        program,
+        &settings,
+        &objects,
+        context.linker_config(),
+    ) {
+        Ok(shared_library_path) => unsafe { Library::new(shared_library_path)? },
+        Err(e) => {
+            tracing::warn!("Failed to store program in the AOT disk cache: {e}.");
+            link_temporary_library(context, &objects)?
+        }
+    };
+
+    Ok(AotNativeExecutor::new(
+        shared_library,
+        registry,
+        program_metadata.gas_metadata().cloned(),
+    )
+    .with_builtin_counters(settings.builtin_counters))
+}
+
+/// Compile the program into objects (more than one if the context compiles in parallel).
+fn compile_objects(
+    context: &NativeContext,
+    program: &Program,
+    opt_level: OptLevel,
+) -> Result<
+    (
+        ProgramRegistry<CoreType, CoreLibfunc>,
+        ProgramMetadata,
+        Vec<Vec<u8>>,
+    ),
+    AotCacheError,
+> {
+    let registry = ProgramRegistry::<CoreType, CoreLibfunc>::new(program)?;
+    let program_metadata = ProgramMetadata::new(program, &registry, context.builtin_counters())?;
+    let objects =
+        context.compile_to_objects_with_metadata(program, &program_metadata, opt_level)?;
+
+    Ok((registry, program_metadata, objects))
+}
+
+/// Link the objects into a temporary shared library and load it.
+///
+/// The library file is removed once loaded, since the loader keeps it mapped.
+fn link_temporary_library(
+    context: &NativeContext,
+    objects: &[&[u8]],
+) -> Result<Library, AotCacheError> {
+    let shared_library_path = tempfile::Builder::new()
+        .prefix("lib")
+        .suffix(SHARED_LIBRARY_EXT)
+        .tempfile()?
+        .into_temp_path();
+    crate::ffi::objects_to_shared_lib_with_config(
+        objects,
+        &shared_library_path,
+        context.linker_config(),
+    )?;
+
+    Ok(unsafe { Library::new(&shared_library_path)? })
+}
+
 impl<'a, K> Debug for AotProgramCache<'a, K>
 where
     K: PartialEq + Eq + Hash,
@@ -75,3 +244,214 @@ where
         f.write_str("AotProgramCache")
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        utils::{find_function_id, test::load_cairo},
+        values::JitValue,
+    };
+    use starknet_types_core::felt::Felt;
+    use std::thread;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_executor_is_send_sync() {
+        assert_send_sync::<AotNativeExecutor>();
+        assert_send_sync::<AotProgramCache<'static, &'static str>>();
+    }
+
+    #[test]
+    fn test_concurrent_invocations() {
+        let (module_name, program) = load_cairo!(
+            fn run_test(n: felt252) -> felt252 {
+                fib(0, 1, n)
+            }
+
+            fn fib(a: felt252, b: felt252, n: felt252) -> felt252 {
+                match n {
+                    0 => a,
+                    _ => fib(b, a + b, n - 1),
+                }
+            }
+        );
+        let function_id = find_function_id(&program, &format!("{0}::{0}::run_test", module_name));
+
+        let context = NativeContext::new();
+        let cache = AotProgramCache::new(&context);
+        let executor = cache
+            .compile_and_insert("fib", &program, OptLevel::default())
+            .unwrap();
+
+        thread::scope(|scope| {
+            for thread_idx in 0..8u64 {
+                let executor = executor.clone();
+                scope.spawn(move || {
+                    for n in 0..100u64 {
+                        let n = (thread_idx * 100 + n) % 50;
+                        let result = executor
+                            .invoke_dynamic(
+                                function_id,
+                                &[JitValue::Felt252(Felt::from(n))],
+                                Some(u128::MAX),
+                                None,
+                            )
+                            .unwrap();
+
+                        let (mut a, mut b) = (Felt::from(0), Felt::from(1));
+                        for _ in 0..n {
+                            (a, b) = (b, a + b);
+                        }
+                        assert_eq!(result.return_value, JitValue::Felt252(a));
+                    }
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn test_concurrent_cache() {
+        let (module_name, program) = load_cairo!(
+            fn run_test(lhs: felt252, rhs: felt252) -> felt252 {
+                lhs + rhs
+            }
+        );
+        let function_id = find_function_id(&program, &format!("{0}::{0}::run_test", module_name));
+
+        let context = NativeContext::new();
+        let cache = AotProgramCache::new(&context);
+
+        thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    let executor = match cache.get(&"sum") {
+                        Some(executor) => executor,
+                        None => cache
+                            .compile_and_insert("sum", &program, OptLevel::default())
+                            .unwrap(),
+                    };
+
+                    for n in 0..100u64 {
+                        let result = executor
+                            .invoke_dynamic(
+                                function_id,
+                                &[
+                                    JitValue::Felt252(Felt::from(n)),
+                                    JitValue::Felt252(Felt::from(1)),
+                                ],
+                                None,
+                                None,
+                            )
+                            .unwrap();
+                        assert_eq!(result.return_value, JitValue::Felt252(Felt::from(n + 1)));
+                    }
+                });
+            }
+        });
+
+        // Every thread should have ended up with the same executor.
+        assert_eq!(cache.cache.read().unwrap().len(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "with-serde")]
+    fn test_parallel_compilation() {
+        let (module_name, program) = load_cairo!(
+            fn run_test(n: felt252) -> felt252 {
+                fib(0, 1, n)
+            }
+
+            fn fib(a: felt252, b: felt252, n: felt252) -> felt252 {
+                match n {
+                    0 => a,
+                    _ => fib(b, a + b, n - 1),
+                }
+            }
+        );
+
+        let cache_dir = tempfile::tempdir().unwrap();
+        let context = NativeContext::new().with_jobs(std::num::NonZeroUsize::new(2).unwrap());
+        let cache =
+            AotProgramCache::with_disk_cache(&context, DiskCache::open(cache_dir.path()).unwrap());
+        let executor = cache
+            .compile_and_insert("fib", &program, OptLevel::default())
+            .unwrap();
+
+        let result = executor
+            .invoke_dynamic(
+                find_function_id(&program, &format!("{0}::{0}::run_test", module_name)),
+                &[JitValue::Felt252(Felt::from(10))],
+                Some(u128::MAX),
+                None,
+            )
+            .unwrap();
+        assert_eq!(result.return_value, JitValue::Felt252(Felt::from(55)));
+    }
+
+    #[test]
+    #[cfg(feature = "with-serde")]
+    fn test_unusable_disk_cache() {
+        let (module_name, program) = load_cairo!(
+            fn run_test(lhs: felt252, rhs: felt252) -> felt252 {
+                lhs + rhs
+            }
+        );
+
+        let cache_dir = tempfile::tempdir().unwrap();
+        let disk_cache = DiskCache::open(cache_dir.path()).unwrap();
+        // Neither loading nor storing can succeed once the directory is gone.
+        cache_dir.close().unwrap();
+
+        let context = NativeContext::new();
+        let cache = AotProgramCache::with_disk_cache(&context, disk_cache);
+        let executor = cache
+            .compile_and_insert("sum", &program, OptLevel::default())
+            .unwrap();
+
+        let result = executor
+            .invoke_dynamic(
+                find_function_id(&program, &format!("{0}::{0}::run_test", module_name)),
+                &[
+                    JitValue::Felt252(Felt::from(1)),
+                    JitValue::Felt252(Felt::from(2)),
+                ],
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(result.return_value, JitValue::Felt252(Felt::from(3)));
+    }
+
+    #[test]
+    fn test_context_linker_config() {
+        let (_, program) = load_cairo!(
+            fn run_test(lhs: felt252, rhs: felt252) -> felt252 {
+                lhs + rhs
+            }
+        );
+
+        // The cache must link using the context's configuration, both with and without a disk
+        // cache.
+        let context = NativeContext::new().with_linker_config(
+            crate::LinkerConfig::default().with_linker("/nonexistent/cairo-native-linker"),
+        );
+        let cache = AotProgramCache::new(&context);
+        assert!(matches!(
+            cache.compile_and_insert("sum", &program, OptLevel::default()),
+            Err(AotCacheError::Link(_))
+        ));
+
+        #[cfg(feature = "with-serde")]
+        {
+            let cache_dir = tempfile::tempdir().unwrap();
+            let disk_cache = DiskCache::open(cache_dir.path()).unwrap();
+            let cache = AotProgramCache::with_disk_cache(&context, disk_cache);
+            assert!(matches!(
+                cache.compile_and_insert("sum", &program, OptLevel::default()),
+                Err(AotCacheError::Link(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,570 @@
+//! # Persistent AOT artifact storage
+//!
+//! Every cached program is stored as three files inside the cache directory, all of them named
+//! after the program's hash:
+//!
+//!   - `<hash>.so` (or `.dylib`): The compiled shared library.
+//!   - `<hash>.sierra.json`: The Sierra program, from which the registry and gas metadata are
+//!     rebuilt when loading.
+//!   - `<hash>.meta.json`: The entry's metadata, used to verify the artifacts and to track their
+//!     last use for eviction purposes.
+//!
+//! The hash covers the Sierra program, the [`CompileSettings`] and the compiler version, therefore
+//! upgrading the compiler invalidates every existing entry.
+
+use crate::{
+    executor::AotNativeExecutor,
+    metadata::gas::{GasMetadata, MetadataComputationConfig},
+    utils::{has_gas_builtin, PassPipeline, SHARED_LIBRARY_EXT},
+    LinkerConfig, OptLevel,
+};
+use cairo_lang_sierra::{
+    extensions::core::{CoreLibfunc, CoreType},
+    program::Program,
+    program_registry::ProgramRegistry,
+};
+use libloading::Library;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const COMPILER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// An on-disk store of AOT-compiled programs.
+#[derive(Debug)]
+pub struct DiskCache {
+    path: PathBuf,
+
+    max_size: Option<u64>,
+    max_entries: Option<usize>,
+}
+
+/// The settings a program was compiled with.
+///
+/// All of them change the generated code, so programs compiled with different settings are stored
+/// as different entries.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CompileSettings {
+    pub opt_level: OptLevel,
+    pub pass_pipeline: PassPipeline,
+    pub builtin_counters: bool,
+}
+
+impl From<OptLevel> for CompileSettings {
+    /// The settings used by [`NativeContext::compile`](crate::context::NativeContext::compile) with
+    /// the builtin counters disabled.
+    fn from(opt_level: OptLevel) -> Self {
+        Self {
+            opt_level,
+            pass_pipeline: PassPipeline::from(opt_level),
+            builtin_counters: false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct EntryMetadata {
+    compiler_version: String,
+    program_hash: String,
+
+    library_size: u64,
+    library_checksum: String,
+    program_checksum: String,
+
+    /// Last use as milliseconds since the UNIX epoch.
+    last_used: u64,
+}
+
+impl DiskCache {
+    /// Open (or create) a cache directory.
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        fs::create_dir_all(&path)?;
+
+        Ok(Self {
+            path,
+            max_size: None,
+            max_entries: None,
+        })
+    }
+
+    /// Limit the total size in bytes of the stored artifacts.
+    pub fn with_max_size(mut self, max_size: u64) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Limit the number of stored programs.
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = Some(max_entries);
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Compute the key of a program, which depends on the program, the compile settings and the
+    /// compiler version.
+    pub fn program_hash(program: &Program, settings: &CompileSettings) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(COMPILER_VERSION.as_bytes());
+        hasher.update([
+            usize::from(settings.opt_level) as u8,
+            settings.builtin_counters as u8,
+        ]);
+        match &settings.pass_pipeline {
+            PassPipeline::Minimal => hasher.update(b"minimal"),
+            PassPipeline::Standard => hasher.update(b"standard"),
+            PassPipeline::Aggressive => hasher.update(b"aggressive"),
+            PassPipeline::Custom(passes) => {
+                hasher.update(b"custom:");
+                hasher.update(passes.join(",").as_bytes());
+            }
+        }
+        hasher.update(serde_json::to_vec(program).expect("programs are always serializable"));
+
+        hex::encode(hasher.finalize())
+    }
+
+    /// Load a program's executor from the cache.
+    ///
+    /// Returns `None` when the program isn't cached. Entries that fail verification are removed
+    /// and treated as missing.
+    pub fn load(
+        &self,
+        program: &Program,
+        settings: &CompileSettings,
+    ) -> io::Result<Option<AotNativeExecutor>> {
+        let hash = Self::program_hash(program, settings);
+        if !self.meta_path(&hash).exists() {
+            return Ok(None);
+        }
+
+        let (mut entry_metadata, program) = match self.verify(&hash) {
+            Ok(x) => x,
+            Err(e) => {
+                tracing::warn!("Discarding invalid AOT cache entry {hash}: {e}.");
+                self.remove(&hash)?;
+                return Ok(None);
+            }
+        };
+
+        entry_metadata.last_used = now();
+        write_json(&self.meta_path(&hash), &entry_metadata)?;
+
+        let registry = ProgramRegistry::<CoreType, CoreLibfunc>::new(&program)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        let gas_metadata = has_gas_builtin(&program)
+            .then(|| GasMetadata::new(&program, MetadataComputationConfig::default()));
+
+        let library = unsafe { Library::new(self.library_path(&hash)) }
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+
+        Ok(Some(
+            AotNativeExecutor::new(library, registry, gas_metadata)
+                .with_builtin_counters(settings.builtin_counters),
+        ))
+    }
+
+    /// Link an object file into the cache and record its program.
+    ///
+    /// Returns the path of the resulting shared library. Old entries may be evicted afterwards to
+    /// honor the configured limits.
+    pub fn store(
+        &self,
+        program: &Program,
+        settings: &CompileSettings,
+        object_data: &[u8],
+    ) -> io::Result<PathBuf> {
+        self.store_objects(program, settings, &[object_data])
+    }
+
+    /// Same as [`store`](Self::store), but linking multiple object files together, like the ones
+    /// produced by a [parallel compilation](crate::parallel).
+    pub fn store_objects(
+        &self,
+        program: &Program,
+        settings: &CompileSettings,
+        objects: &[&[u8]],
+    ) -> io::Result<PathBuf> {
+        self.store_objects_with_config(program, settings, objects, &LinkerConfig::default())
+    }
+
+    /// Same as [`store_objects`](Self::store_objects), but with an explicit linker configuration.
+    pub fn store_objects_with_config(
+        &self,
+        program: &Program,
+        settings: &CompileSettings,
+        objects: &[&[u8]],
+        linker_config: &LinkerConfig,
+    ) -> io::Result<PathBuf> {
+        let hash = Self::program_hash(program, settings);
+
+        // Link into a uniquely named temporary file first so that neither a crash nor another
+        // process storing the same program ever leaves a partial library behind.
+        let library_path = self.library_path(&hash);
+        let tmp_library = tempfile::Builder::new()
+            .prefix(&hash)
+            .suffix(&format!(".{SHARED_LIBRARY_EXT}.tmp"))
+            .tempfile_in(&self.path)?;
+        crate::ffi::objects_to_shared_lib_with_config(objects, tmp_library.path(), linker_config)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        tmp_library.persist(&library_path)?;
+
+        let program_data = serde_json::to_vec(program)?;
+        write_atomic(&self.program_path(&hash), &program_data)?;
+
+        let library_data = fs::read(&library_path)?;
+        write_json(
+            &self.meta_path(&hash),
+            &EntryMetadata {
+                compiler_version: COMPILER_VERSION.to_string(),
+                program_hash: hash.clone(),
+                library_size: library_data.len() as u64,
+                library_checksum: checksum(&library_data),
+                program_checksum: checksum(&program_data),
+                last_used: now(),
+            },
+        )?;
+
+        self.evict(Some(&hash))?;
+        Ok(library_path)
+    }
+
+    /// Remove the least recently used entries until the cache is within its limits.
+    ///
+    /// The entry whose hash is `keep`, if any, is never evicted.
+    pub fn evict(&self, keep: Option<&str>) -> io::Result<()> {
+        if self.max_size.is_none() && self.max_entries.is_none() {
+            return Ok(());
+        }
+
+        let mut entries = Vec::new();
+        for dir_entry in fs::read_dir(&self.path)? {
+            let dir_entry = dir_entry?;
+            let file_name = dir_entry.file_name();
+            let Some(hash) = file_name
+                .to_str()
+                .and_then(|x| x.strip_suffix(".meta.json"))
+            else {
+                continue;
+            };
+
+            let last_used = read_json::<EntryMetadata>(&dir_entry.path())
+                .map(|x| x.last_used)
+                .unwrap_or_default();
+            let size = [
+                self.library_path(hash),
+                self.program_path(hash),
+                self.meta_path(hash),
+            ]
+            .iter()
+            .filter_map(|path| fs::metadata(path).ok())
+            .map(|metadata| metadata.len())
+            .sum::<u64>();
+
+            entries.push((hash.to_string(), last_used, size));
+        }
+
+        // Oldest entries go first.
+        entries.sort_by_key(|(_, last_used, _)| *last_used);
+
+        let mut total_size = entries.iter().map(|(_, _, size)| size).sum::<u64>();
+        let mut num_entries = entries.len();
+        for (hash, _, size) in entries {
+            let exceeds_size = self.max_size.is_some_and(|max_size| total_size > max_size);
+            let exceeds_entries = self
+                .max_entries
+                .is_some_and(|max_entries| num_entries > max_entries);
+            if !(exceeds_size || exceeds_entries) {
+                break;
+            }
+
+            if keep == Some(hash.as_str()) {
+                continue;
+            }
+
+            self.remove(&hash)?;
+            total_size -= size;
+            num_entries -= 1;
+        }
+
+        Ok(())
+    }
+
+    fn verify(&self, hash: &str) -> io::Result<(EntryMetadata, Program)> {
+        let invalid_data = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg);
+
+        let entry_metadata = read_json::<EntryMetadata>(&self.meta_path(hash))?;
+        if entry_metadata.compiler_version != COMPILER_VERSION {
+            return Err(invalid_data("compiler version mismatch"));
+        }
+        if entry_metadata.program_hash != hash {
+            return Err(invalid_data("program hash mismatch"));
+        }
+
+        let library_data = fs::read(self.library_path(hash))?;
+        if library_data.len() as u64 != entry_metadata.library_size
+            || checksum(&library_data) != entry_metadata.library_checksum
+        {
+            return Err(invalid_data("shared library checksum mismatch"));
+        }
+
+        let program_data = fs::read(self.program_path(hash))?;
+        if checksum(&program_data) != entry_metadata.program_checksum {
+            return Err(invalid_data("program checksum mismatch"));
+        }
+
+        Ok((entry_metadata, serde_json::from_slice(&program_data)?))
+    }
+
+    fn remove(&self, hash: &str) -> io::Result<()> {
+        for path in [
+            self.library_path(hash),
+            self.program_path(hash),
+            self.meta_path(hash),
+        ] {
+            match fs::remove_file(path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn library_path(&self, hash: &str) -> PathBuf {
+        self.path.join(format!("{hash}.{SHARED_LIBRARY_EXT}"))
+    }
+
+    fn program_path(&self, hash: &str) -> PathBuf {
+        self.path.join(format!("{hash}.sierra.json"))
+    }
+
+    fn meta_path(&self, hash: &str) -> PathBuf {
+        self.path.join(format!("{hash}.meta.json"))
+    }
+}
+
+fn checksum(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+fn read_json<T>(path: &Path) -> io::Result<T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    Ok(serde_json::from_slice(&fs::read(path)?)?)
+}
+
+fn write_json<T>(path: &Path, value: &T) -> io::Result<()>
+where
+    T: Serialize,
+{
+    write_atomic(path, &serde_json::to_vec(value)?)
+}
+
+/// Write a file through a uniquely named temporary file in the same directory, so that concurrent
+/// readers and writers only ever see complete files.
+fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    let mut tmp_file = tempfile::NamedTempFile::new_in(path.parent().unwrap_or(Path::new(".")))?;
+    tmp_file.write_all(data)?;
+    tmp_file.persist(path)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        context::NativeContext,
+        utils::{find_function_id, test::load_cairo},
+        values::JitValue,
+    };
+    use starknet_types_core::felt::Felt;
+
+    fn compile_object(context: &NativeContext, program: &Program) -> Vec<u8> {
+        let module = context.compile(program, OptLevel::default()).unwrap();
+        crate::module_to_object(module.module(), OptLevel::default()).unwrap()
+    }
+
+    #[test]
+    fn test_reload_and_verify() {
+        let (module_name, program) = load_cairo!(
+            fn run_test(lhs: felt252, rhs: felt252) -> felt252 {
+                lhs + rhs
+            }
+        );
+
+        let context = NativeContext::new();
+        let cache_dir = tempfile::tempdir().unwrap();
+
+        let disk_cache = DiskCache::open(cache_dir.path()).unwrap();
+        assert!(disk_cache
+            .load(&program, &OptLevel::default().into())
+            .unwrap()
+            .is_none());
+        disk_cache
+            .store(
+                &program,
+                &OptLevel::default().into(),
+                &compile_object(&context, &program),
+            )
+            .unwrap();
+
+        // A fresh instance (like after a restart) should reuse the stored artifacts.
+        let disk_cache = DiskCache::open(cache_dir.path()).unwrap();
+        let executor = disk_cache
+            .load(&program, &OptLevel::default().into())
+            .unwrap()
+            .expect("entry exists");
+        let result = executor
+            .invoke_dynamic(
+                find_function_id(&program, &format!("{0}::{0}::run_test", module_name)),
+                &[
+                    JitValue::Felt252(Felt::from(1)),
+                    JitValue::Felt252(Felt::from(2)),
+                ],
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(result.return_value, JitValue::Felt252(Felt::from(3)));
+
+        // Corrupted artifacts must be discarded.
+        let hash = DiskCache::program_hash(&program, &OptLevel::default().into());
+        fs::write(disk_cache.library_path(&hash), b"corrupted").unwrap();
+        assert!(disk_cache
+            .load(&program, &OptLevel::default().into())
+            .unwrap()
+            .is_none());
+        assert!(!disk_cache.meta_path(&hash).exists());
+    }
+
+    #[test]
+    fn test_concurrent_store() {
+        let (_, program) = load_cairo!(
+            fn run_test(lhs: felt252, rhs: felt252) -> felt252 {
+                lhs + rhs
+            }
+        );
+
+        let context = NativeContext::new();
+        let object = compile_object(&context, &program);
+        let cache_dir = tempfile::tempdir().unwrap();
+
+        // Multiple writers storing the same program must not clobber each other's temporary files.
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    DiskCache::open(cache_dir.path())
+                        .unwrap()
+                        .store(&program, &OptLevel::default().into(), &object)
+                        .unwrap();
+                });
+            }
+        });
+
+        let disk_cache = DiskCache::open(cache_dir.path()).unwrap();
+        assert!(disk_cache
+            .load(&program, &OptLevel::default().into())
+            .unwrap()
+            .is_some());
+        assert!(fs::read_dir(cache_dir.path()).unwrap().all(|entry| !entry
+            .unwrap()
+            .path()
+            .to_string_lossy()
+            .ends_with(".tmp")));
+    }
+
+    #[test]
+    fn test_settings_are_part_of_the_key() {
+        let (_, program) = load_cairo!(
+            fn run_test(lhs: felt252, rhs: felt252) -> felt252 {
+                lhs + rhs
+            }
+        );
+
+        let settings = CompileSettings::from(OptLevel::default());
+        let hashes = [
+            settings.clone(),
+            CompileSettings {
+                builtin_counters: true,
+                ..settings.clone()
+            },
+            CompileSettings {
+                pass_pipeline: PassPipeline::Minimal,
+                ..settings.clone()
+            },
+            CompileSettings {
+                pass_pipeline: PassPipeline::Custom(vec!["cse".to_string()]),
+                ..settings.clone()
+            },
+            CompileSettings {
+                opt_level: OptLevel::None,
+                ..settings
+            },
+        ]
+        .map(|settings| DiskCache::program_hash(&program, &settings));
+
+        for (i, hash) in hashes.iter().enumerate() {
+            assert!(!hashes[i + 1..].contains(hash));
+        }
+    }
+
+    #[test]
+    fn test_eviction() {
+        let (_, program1) = load_cairo!(
+            fn run_test(lhs: felt252, rhs: felt252) -> felt252 {
+                lhs + rhs
+            }
+        );
+        let (_, program2) = load_cairo!(
+            fn run_test(lhs: felt252, rhs: felt252) -> felt252 {
+                lhs - rhs
+            }
+        );
+
+        let context = NativeContext::new();
+        let cache_dir = tempfile::tempdir().unwrap();
+        let disk_cache = DiskCache::open(cache_dir.path())
+            .unwrap()
+            .with_max_entries(1);
+
+        disk_cache
+            .store(
+                &program1,
+                &OptLevel::default().into(),
+                &compile_object(&context, &program1),
+            )
+            .unwrap();
+        disk_cache
+            .store(
+                &program2,
+                &OptLevel::default().into(),
+                &compile_object(&context, &program2),
+            )
+            .unwrap();
+
+        let hash1 = DiskCache::program_hash(&program1, &OptLevel::default().into());
+        let hash2 = DiskCache::program_hash(&program2, &OptLevel::default().into());
+        assert!(!disk_cache.meta_path(&hash1).exists());
+        assert!(!disk_cache.library_path(&hash1).exists());
+        assert!(disk_cache.meta_path(&hash2).exists());
+        assert!(disk_cache.library_path(&hash2).exists());
+    }
+}
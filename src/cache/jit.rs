@@ -1,4 +1,4 @@
-use crate::{context::NativeContext, executor::JitNativeExecutor};
+use crate::{context::NativeContext, executor::JitNativeExecutor, OptLevel};
 use cairo_lang_sierra::program::Program;
 use std::{
     collections::HashMap,
@@ -39,9 +39,17 @@ where
         self.cache.get(key).cloned()
     }
 
-    pub fn compile_and_insert(&mut self, key: K, program: &Program) -> Rc<JitNativeExecutor<'a>> {
-        let module = self.context.compile(program).expect("should compile");
-        let executor = JitNativeExecutor::new(module);
+    pub fn compile_and_insert(
+        &mut self,
+        key: K,
+        program: &Program,
+        opt_level: OptLevel,
+    ) -> Rc<JitNativeExecutor<'a>> {
+        let module = self
+            .context
+            .compile(program, opt_level)
+            .expect("should compile");
+        let executor = JitNativeExecutor::new(module, opt_level);
 
         let executor = Rc::new(executor);
         self.cache.insert(key, executor.clone());
@@ -83,7 +91,7 @@ mod test {
         let mut cache: JitProgramCache<&'static str> = JitProgramCache::new(&context);
 
         let start = Instant::now();
-        cache.compile_and_insert("program1", &program1);
+        cache.compile_and_insert("program1", &program1, OptLevel::default());
         let diff_1 = Instant::now().duration_since(start);
 
         let start = Instant::now();
@@ -93,7 +101,7 @@ mod test {
         assert!(diff_2 < diff_1);
 
         let start = Instant::now();
-        cache.compile_and_insert("program2", &program2);
+        cache.compile_and_insert("program2", &program2, OptLevel::default());
         let diff_1 = Instant::now().duration_since(start);
 
         let start = Instant::now();
@@ -47,11 +47,12 @@
 use crate::{
     debug_info::DebugLocations,
     error::{
-        compile::{make_libfunc_builder_error, make_type_builder_error},
+        compile::{make_libfunc_builder_error, make_type_builder_error, ErrorImpl},
         CompileError,
     },
     libfuncs::{BranchArg, LibfuncBuilder, LibfuncHelper},
     metadata::{
+        builtin_counters::{BranchIncrements, BuiltinCountersMeta},
         gas::{GasCost, GasMetadata},
         tail_recursion::TailRecursionMeta,
         MetadataStorage,
@@ -63,9 +64,9 @@ use bumpalo::Bump;
 use cairo_lang_sierra::{
     edit_state,
     extensions::{gas::CostTokenType, ConcreteLibfunc, GenericLibfunc, GenericType},
-    ids::{ConcreteTypeId, VarId},
+    ids::{ConcreteLibfuncId, ConcreteTypeId, FunctionId, VarId},
     program::{Function, Invocation, Program, Statement, StatementIdx},
-    program_registry::ProgramRegistry,
+    program_registry::{ProgramRegistry, ProgramRegistryError},
 };
 use itertools::Itertools;
 use melior::{
@@ -78,13 +79,13 @@ use melior::{
     ir::{
         attribute::{IntegerAttribute, StringAttribute, TypeAttribute},
         r#type::{FunctionType, IntegerType, MemRefType},
-        Attribute, Block, BlockRef, Identifier, Location, Module, Region, Type, Value,
+        Attribute, Block, BlockRef, Identifier, Location, Module, Region, Type, Value, ValueLike,
     },
     Context,
 };
 use std::{
     cell::Cell,
-    collections::{hash_map::Entry, BTreeMap, HashMap, HashSet},
+    collections::{btree_map, hash_map::Entry, BTreeMap, HashMap, HashSet},
     ops::Deref,
 };
 
@@ -123,7 +124,138 @@ where
         TypeBuilder<TType, TLibfunc, Error = crate::error::types::Error>,
     <TLibfunc as GenericLibfunc>::Concrete: LibfuncBuilder<TType, TLibfunc>,
 {
-    for function in &program.funcs {
+    compile_funcs(
+        context,
+        module,
+        program,
+        &program.funcs,
+        registry,
+        metadata,
+        debug_info,
+    )
+}
+
+/// Run the compiler on the functions of a program which are reachable from the given entry points.
+///
+/// Functions which can't be reached (by following the `function_call` libfuncs) from any of the
+/// entry points aren't compiled. Check out [compile](self::compile) for a description of the other
+/// arguments.
+pub fn compile_with_entry_points<TType, TLibfunc>(
+    context: &Context,
+    module: &Module,
+    program: &Program,
+    registry: &ProgramRegistry<TType, TLibfunc>,
+    metadata: &mut MetadataStorage,
+    debug_info: Option<&DebugLocations>,
+    entry_points: &[FunctionId],
+) -> Result<(), CompileError<TType, TLibfunc>>
+where
+    TType: GenericType,
+    TLibfunc: GenericLibfunc,
+    <TType as GenericType>::Concrete:
+        TypeBuilder<TType, TLibfunc, Error = crate::error::types::Error>,
+    <TLibfunc as GenericLibfunc>::Concrete: LibfuncBuilder<TType, TLibfunc>,
+{
+    let reachable_functions = find_reachable_functions(program, registry, entry_points)?;
+    tracing::info!(
+        "Found {} reachable functions out of {}.",
+        reachable_functions.len(),
+        program.funcs.len()
+    );
+
+    compile_funcs(
+        context,
+        module,
+        program,
+        program
+            .funcs
+            .iter()
+            .filter(|function| reachable_functions.contains(&function.id)),
+        registry,
+        metadata,
+        debug_info,
+    )
+}
+
+/// Run the compiler on a partition of the functions of a program.
+///
+/// Only the functions in the partition are defined. The functions of other partitions which are
+/// called from it are declared, so that the module can be linked against the others afterwards.
+/// Check out [compile](self::compile) for a description of the other arguments.
+pub(crate) fn compile_partition<TType, TLibfunc>(
+    context: &Context,
+    module: &Module,
+    program: &Program,
+    registry: &ProgramRegistry<TType, TLibfunc>,
+    metadata: &mut MetadataStorage,
+    debug_info: Option<&DebugLocations>,
+    partition: &[&Function],
+) -> Result<(), CompileError<TType, TLibfunc>>
+where
+    TType: GenericType,
+    TLibfunc: GenericLibfunc,
+    <TType as GenericType>::Concrete:
+        TypeBuilder<TType, TLibfunc, Error = crate::error::types::Error>,
+    <TLibfunc as GenericLibfunc>::Concrete: LibfuncBuilder<TType, TLibfunc>,
+{
+    compile_funcs(
+        context,
+        module,
+        program,
+        partition.iter().copied(),
+        registry,
+        metadata,
+        debug_info,
+    )?;
+
+    let defined_functions = partition
+        .iter()
+        .map(|function| &function.id)
+        .collect::<HashSet<_>>();
+    let mut external_functions = Vec::new();
+    for function in partition {
+        for function_id in find_function_calls(program, registry, function)? {
+            if !defined_functions.contains(&function_id) {
+                external_functions.push(function_id);
+            }
+        }
+    }
+
+    // Sorting keeps the declarations (and therefore the output) in a deterministic order.
+    external_functions.sort_by_key(|function_id| function_id.id);
+    external_functions.dedup();
+
+    for function_id in &external_functions {
+        tracing::debug!("Declaring external function `{function_id}`.");
+        declare_func(
+            context,
+            module,
+            registry,
+            registry.get_function(function_id)?,
+            metadata,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn compile_funcs<'a, TType, TLibfunc>(
+    context: &Context,
+    module: &Module,
+    program: &Program,
+    functions: impl IntoIterator<Item = &'a Function>,
+    registry: &ProgramRegistry<TType, TLibfunc>,
+    metadata: &mut MetadataStorage,
+    debug_info: Option<&DebugLocations>,
+) -> Result<(), CompileError<TType, TLibfunc>>
+where
+    TType: GenericType,
+    TLibfunc: GenericLibfunc,
+    <TType as GenericType>::Concrete:
+        TypeBuilder<TType, TLibfunc, Error = crate::error::types::Error>,
+    <TLibfunc as GenericLibfunc>::Concrete: LibfuncBuilder<TType, TLibfunc>,
+{
+    for function in functions {
         tracing::info!("Compiling function `{}`.", function.id);
         compile_func::<TType, TLibfunc>(
             context,
@@ -140,6 +272,65 @@ where
     Ok(())
 }
 
+/// Find every function reachable from the entry points, including themselves, by following the
+/// `function_call` libfuncs.
+fn find_reachable_functions<TType, TLibfunc>(
+    program: &Program,
+    registry: &ProgramRegistry<TType, TLibfunc>,
+    entry_points: &[FunctionId],
+) -> Result<HashSet<FunctionId>, Box<ProgramRegistryError>>
+where
+    TType: GenericType,
+    TLibfunc: GenericLibfunc,
+{
+    let mut reachable_functions = HashSet::new();
+    let mut queue = entry_points.to_vec();
+
+    while let Some(function_id) = queue.pop() {
+        let function = registry.get_function(&function_id)?;
+        if !reachable_functions.insert(function_id) {
+            continue;
+        }
+
+        queue.extend(find_function_calls(program, registry, function)?);
+    }
+
+    Ok(reachable_functions)
+}
+
+/// Find the functions called (through `function_call` libfuncs) by a function.
+fn find_function_calls<TType, TLibfunc>(
+    program: &Program,
+    registry: &ProgramRegistry<TType, TLibfunc>,
+    function: &Function,
+) -> Result<Vec<FunctionId>, Box<ProgramRegistryError>>
+where
+    TType: GenericType,
+    TLibfunc: GenericLibfunc,
+{
+    let mut function_calls = Vec::new();
+    foreach_statement_in_function::<_, Box<ProgramRegistryError>>(
+        &program.statements,
+        function.entry_point,
+        (),
+        |statement_idx, ()| {
+            Ok(match &program.statements[statement_idx.0] {
+                Statement::Invocation(invocation) => {
+                    let concrete_libfunc = registry.get_libfunc(&invocation.libfunc_id)?;
+                    if let Some(target) = concrete_libfunc.is_function_call() {
+                        function_calls.push(target.id.clone());
+                    }
+
+                    vec![(); invocation.branches.len()]
+                }
+                Statement::Return(_) => Vec::new(),
+            })
+        },
+    )?;
+
+    Ok(function_calls)
+}
+
 /// Compile a single Sierra function.
 ///
 /// The function accepts a `Function` argument, which provides the function's entry point, signature
@@ -165,79 +356,14 @@ where
     let region = Region::new();
     let blocks_arena = Bump::new();
 
-    let mut arg_types = extract_types(
-        context,
-        module,
-        &function.signature.param_types,
-        registry,
-        metadata,
-    )
-    .collect::<Result<Vec<_>, _>>()?;
-    let mut ret_types = extract_types(
-        context,
-        module,
-        &function.signature.ret_types,
-        registry,
-        metadata,
-    )
-    .collect::<Result<Vec<_>, _>>()?;
-
-    // Replace memory-allocated arguments with pointers.
-    for (ty, type_info) in
-        arg_types
-            .iter_mut()
-            .zip(function.signature.param_types.iter().filter_map(|type_id| {
-                let type_info = registry.get_type(type_id).unwrap();
-                if type_info.is_builtin() && type_info.is_zst(registry) {
-                    None
-                } else {
-                    Some(type_info)
-                }
-            }))
-    {
-        if type_info.is_memory_allocated(registry) {
-            *ty = llvm::r#type::opaque_pointer(context);
-        }
-    }
-
-    // Extract memory-allocated return types from ret_types and insert them in arg_types as a
-    // pointer.
-    let return_types = function
-        .signature
-        .ret_types
-        .iter()
-        .filter_map(|type_id| {
-            let type_info = registry.get_type(type_id).unwrap();
-            if type_info.is_builtin() && type_info.is_zst(registry) {
-                None
-            } else {
-                Some((type_id, type_info))
-            }
-        })
-        .collect::<Vec<_>>();
-    // Possible values:
-    //   None        => Doesn't return anything.
-    //   Some(false) => Has a complex return type.
-    //   Some(true)  => Has a manual return type which is in `arg_types[0]`.
-    let has_return_ptr = if return_types.len() > 1 {
-        Some(false)
-    } else if return_types
-        .first()
-        .is_some_and(|(_, type_info)| type_info.is_memory_allocated(registry))
-    {
-        assert_eq!(ret_types.len(), 1);
-
-        ret_types.remove(0);
-        arg_types.insert(0, llvm::r#type::opaque_pointer(context));
-
-        Some(true)
-    } else {
-        None
-    };
+    let builtin_counters = metadata.get::<BuiltinCountersMeta>().is_some();
+    let (arg_types, ret_types, has_return_ptr) =
+        build_func_signature(context, module, registry, function, metadata)?;
+    let return_types = extract_return_types(registry, function, builtin_counters)?;
 
     tracing::debug!("Generating function structure (region with blocks).");
     let (entry_block, blocks) = generate_function_structure(
-        context, module, &region, registry, function, statements, metadata,
+        context, module, &region, registry, function, statements, metadata, debug_info,
     )?;
 
     tracing::debug!("Generating the function implementation.");
@@ -261,7 +387,9 @@ where
 
             values.push((
                 &param.id,
-                if type_info.is_builtin() && type_info.is_zst(registry) {
+                if type_info.is_builtin()
+                    && type_info.is_zst_with_counters(registry, builtin_counters)
+                {
                     pre_entry_block
                         .append_operation(llvm::undef(
                             type_info
@@ -348,7 +476,11 @@ where
 
                     let (state, _) = edit_state::take_args(state, invocation.args.iter())?;
 
-                    let helper = LibfuncHelper {
+                    let location = debug_info
+                        .and_then(|debug_info| debug_info.statements.get(&statement_idx).copied())
+                        .unwrap_or_else(|| Location::unknown(context));
+
+                    let mut helper = LibfuncHelper {
                         module,
                         init_block: &pre_entry_block,
                         region: &region,
@@ -368,6 +500,33 @@ where
                             .collect::<Vec<_>>(),
                     };
 
+                    // Branches which use builtins are redirected through an intermediate block
+                    // which increments the builtin counters.
+                    let counter_increments = metadata
+                        .get::<BuiltinCountersMeta>()
+                        .and_then(|meta| meta.get(&invocation.libfunc_id))
+                        .map(<[_]>::to_vec)
+                        .unwrap_or_default();
+                    let counter_targets = counter_increments
+                        .iter()
+                        .zip(&invocation.branches)
+                        .enumerate()
+                        .map(|(branch_idx, (increments, branch_info))| {
+                            (!increments.is_empty()).then(|| {
+                                let counter_block = helper.append_block(Block::new(&[]));
+                                std::mem::replace(
+                                    &mut helper.branches[branch_idx],
+                                    (
+                                        counter_block,
+                                        (0..branch_info.results.len())
+                                            .map(BranchArg::Returned)
+                                            .collect(),
+                                    ),
+                                )
+                            })
+                        })
+                        .collect::<Vec<_>>();
+
                     let concrete_libfunc = registry.get_libfunc(&invocation.libfunc_id)?;
                     if let Some(target) = concrete_libfunc.is_function_call() {
                         if target == &function.id && state.is_empty() {
@@ -403,27 +562,48 @@ where
                                 ),
                             );
 
-                            metadata
+                            // The metadata is always removed after building the invocation.
+                            let inserted = metadata
                                 .insert(TailRecursionMeta::new(op0.result(0)?.into(), &entry_block))
-                                .expect("should not have this metadata inserted yet");
+                                .is_some();
+                            debug_assert!(inserted, "should not have this metadata inserted yet");
                         }
                     }
 
                     concrete_libfunc
-                        .build(
-                            context,
-                            registry,
-                            block,
-                            debug_info
-                                .and_then(|debug_info| {
-                                    debug_info.statements.get(&statement_idx).copied()
-                                })
-                                .unwrap_or_else(|| Location::unknown(context)),
-                            &helper,
-                            metadata,
-                        )
-                        .map_err(make_libfunc_builder_error(&invocation.libfunc_id))?;
-                    assert!(block.terminator().is_some());
+                        .build(context, registry, block, location, &helper, metadata)
+                        .map_err(|error| {
+                            make_libfunc_builder_error(
+                                &invocation.libfunc_id,
+                                statement_idx,
+                                statement_location(debug_info, statement_idx),
+                            )(error)
+                        })?;
+                    if block.terminator().is_none() {
+                        return Err(ErrorImpl::MissingBlockTerminator {
+                            libfunc_id: invocation.libfunc_id.clone(),
+                            statement_idx,
+                            location: statement_location(debug_info, statement_idx),
+                        }
+                        .into());
+                    }
+
+                    for (branch_idx, (increments, target)) in
+                        counter_increments.iter().zip(counter_targets).enumerate()
+                    {
+                        if let Some(target) = target {
+                            build_builtin_counters(
+                                context,
+                                &helper,
+                                &invocation.libfunc_id,
+                                statement_idx,
+                                debug_info,
+                                branch_idx,
+                                increments,
+                                target,
+                            )?;
+                        }
+                    }
 
                     if let Some(tailrec_meta) = metadata.remove::<TailRecursionMeta>() {
                         if let Some(return_block) = tailrec_meta.return_target() {
@@ -436,12 +616,19 @@ where
                         .branches
                         .iter()
                         .zip(helper.results())
-                        .map(|(branch_info, result_values)| {
-                            assert_eq!(
-                                branch_info.results.len(),
-                                result_values.len(),
-                                "Mismatched number of returned values from branch."
-                            );
+                        .enumerate()
+                        .map(|(branch_idx, (branch_info, result_values))| {
+                            if branch_info.results.len() != result_values.len() {
+                                return Err(ErrorImpl::MismatchedBranchResults {
+                                    libfunc_id: invocation.libfunc_id.clone(),
+                                    statement_idx,
+                                    location: statement_location(debug_info, statement_idx),
+                                    branch_idx,
+                                    expected: branch_info.results.len(),
+                                    actual: result_values.len(),
+                                }
+                                .into());
+                            }
 
                             Ok((
                                 edit_state::put_results(
@@ -504,47 +691,50 @@ where
                             ));
 
                             let recursive_values = match has_return_ptr {
-                                Some(true) => function
-                                    .signature
-                                    .ret_types
-                                    .iter()
-                                    .zip(&values)
-                                    .filter_map(|(type_id, value)| {
-                                        let type_info = registry.get_type(type_id).unwrap();
-                                        if type_info.is_zst(registry)
-                                            || type_info.is_memory_allocated(registry)
+                                Some(true) => {
+                                    let mut recursive_values = Vec::new();
+                                    for (type_id, value) in
+                                        function.signature.ret_types.iter().zip(&values)
+                                    {
+                                        let type_info = registry.get_type(type_id)?;
+                                        if !type_info
+                                            .is_zst_with_counters(registry, builtin_counters)
+                                            && !type_info.is_memory_allocated(registry)
                                         {
-                                            None
-                                        } else {
-                                            Some(*value)
+                                            recursive_values.push(*value);
+                                        }
+                                    }
+
+                                    recursive_values
+                                }
+                                Some(false) => {
+                                    let mut recursive_values = Vec::new();
+                                    for (type_id, value) in
+                                        function.signature.ret_types.iter().zip(&values)
+                                    {
+                                        let type_info = registry.get_type(type_id)?;
+                                        if type_info
+                                            .is_zst_with_counters(registry, builtin_counters)
+                                        {
+                                            continue;
                                         }
-                                    })
-                                    .collect::<Vec<_>>(),
-                                Some(false) => function
-                                    .signature
-                                    .ret_types
-                                    .iter()
-                                    .zip(&values)
-                                    .filter_map(|(type_id, value)| {
-                                        let type_info = registry.get_type(type_id).unwrap();
-                                        if type_info.is_zst(registry) {
-                                            None
-                                        } else {
-                                            let value = *value;
 
-                                            Some(if type_info.is_memory_allocated(registry) {
+                                        recursive_values.push(
+                                            if type_info.is_memory_allocated(registry) {
                                                 let ty = type_info
                                                     .build(
                                                         context, module, registry, metadata,
                                                         type_id,
                                                     )
-                                                    .unwrap();
-                                                let layout = type_info.layout(registry).unwrap();
+                                                    .map_err(make_type_builder_error(type_id))?;
+                                                let layout = type_info
+                                                    .layout(registry)
+                                                    .map_err(make_type_builder_error(type_id))?;
 
                                                 block
                                                     .append_operation(llvm::load(
                                                         context,
-                                                        value,
+                                                        *value,
                                                         ty,
                                                         Location::unknown(context),
                                                         LoadStoreOptions::new().align(Some(
@@ -555,16 +745,24 @@ where
                                                             ),
                                                         )),
                                                     ))
-                                                    .result(0)
-                                                    .unwrap()
+                                                    .result(0)?
                                                     .into()
                                             } else {
-                                                value
-                                            })
-                                        }
-                                    })
-                                    .collect::<Vec<_>>(),
-                                None => todo!(),
+                                                *value
+                                            },
+                                        );
+                                    }
+
+                                    recursive_values
+                                }
+                                None => {
+                                    return Err(ErrorImpl::UnsupportedTailRecursion {
+                                        libfunc_id: invocation.libfunc_id.clone(),
+                                        statement_idx,
+                                        location: statement_location(debug_info, statement_idx),
+                                    }
+                                    .into())
+                                }
                             };
 
                             block.append_operation(cf::cond_br(
@@ -584,7 +782,9 @@ where
                     // Remove ZST builtins from the return values.
                     for (idx, type_id) in function.signature.ret_types.iter().enumerate().rev() {
                         let type_info = registry.get_type(type_id)?;
-                        if type_info.is_builtin() && type_info.is_zst(registry) {
+                        if type_info.is_builtin()
+                            && type_info.is_zst_with_counters(registry, builtin_counters)
+                        {
                             values.remove(idx);
                         }
                     }
@@ -710,6 +910,146 @@ where
     Ok(())
 }
 
+/// Build the MLIR argument and return types of a Sierra function.
+///
+/// Memory-allocated arguments are replaced by pointers, and a memory-allocated return value is
+/// returned through a pointer which is inserted as the first argument. The third element tells
+/// whether the function returns through that pointer (check out [compile_func] for the possible
+/// values).
+#[allow(clippy::type_complexity)]
+fn build_func_signature<'c, TType, TLibfunc>(
+    context: &'c Context,
+    module: &Module<'c>,
+    registry: &ProgramRegistry<TType, TLibfunc>,
+    function: &Function,
+    metadata: &mut MetadataStorage,
+) -> Result<(Vec<Type<'c>>, Vec<Type<'c>>, Option<bool>), CompileError<TType, TLibfunc>>
+where
+    TType: GenericType,
+    TLibfunc: GenericLibfunc,
+    <TType as GenericType>::Concrete:
+        TypeBuilder<TType, TLibfunc, Error = crate::error::types::Error>,
+    <TLibfunc as GenericLibfunc>::Concrete: LibfuncBuilder<TType, TLibfunc>,
+{
+    let builtin_counters = metadata.get::<BuiltinCountersMeta>().is_some();
+    let mut arg_types = extract_types(
+        context,
+        module,
+        &function.signature.param_types,
+        registry,
+        metadata,
+    )
+    .collect::<Result<Vec<_>, _>>()?;
+    let mut ret_types = extract_types(
+        context,
+        module,
+        &function.signature.ret_types,
+        registry,
+        metadata,
+    )
+    .collect::<Result<Vec<_>, _>>()?;
+
+    // Replace memory-allocated arguments with pointers.
+    let mut arg_type_infos = Vec::with_capacity(function.signature.param_types.len());
+    for type_id in &function.signature.param_types {
+        let type_info = registry.get_type(type_id)?;
+        if !(type_info.is_builtin() && type_info.is_zst_with_counters(registry, builtin_counters)) {
+            arg_type_infos.push(type_info);
+        }
+    }
+    for (ty, type_info) in arg_types.iter_mut().zip(arg_type_infos) {
+        if type_info.is_memory_allocated(registry) {
+            *ty = llvm::r#type::opaque_pointer(context);
+        }
+    }
+
+    // Extract memory-allocated return types from ret_types and insert them in arg_types as a
+    // pointer.
+    let return_types = extract_return_types(registry, function, builtin_counters)?;
+    // Possible values:
+    //   None        => Doesn't return anything.
+    //   Some(false) => Has a complex return type.
+    //   Some(true)  => Has a manual return type which is in `arg_types[0]`.
+    let has_return_ptr = if return_types.len() > 1 {
+        Some(false)
+    } else if return_types
+        .first()
+        .is_some_and(|(_, type_info)| type_info.is_memory_allocated(registry))
+    {
+        debug_assert_eq!(ret_types.len(), 1);
+
+        ret_types.remove(0);
+        arg_types.insert(0, llvm::r#type::opaque_pointer(context));
+
+        Some(true)
+    } else {
+        None
+    };
+
+    Ok((arg_types, ret_types, has_return_ptr))
+}
+
+/// Return the function's return types, excluding the zero-sized builtins.
+#[allow(clippy::type_complexity)]
+fn extract_return_types<'a, TType, TLibfunc>(
+    registry: &'a ProgramRegistry<TType, TLibfunc>,
+    function: &'a Function,
+    builtin_counters: bool,
+) -> Result<
+    Vec<(&'a ConcreteTypeId, &'a <TType as GenericType>::Concrete)>,
+    CompileError<TType, TLibfunc>,
+>
+where
+    TType: GenericType,
+    TLibfunc: GenericLibfunc,
+    <TType as GenericType>::Concrete: TypeBuilder<TType, TLibfunc>,
+    <TLibfunc as GenericLibfunc>::Concrete: LibfuncBuilder<TType, TLibfunc>,
+{
+    let mut return_types = Vec::with_capacity(function.signature.ret_types.len());
+    for type_id in &function.signature.ret_types {
+        let type_info = registry.get_type(type_id)?;
+        if !(type_info.is_builtin() && type_info.is_zst_with_counters(registry, builtin_counters)) {
+            return_types.push((type_id, type_info));
+        }
+    }
+
+    Ok(return_types)
+}
+
+/// Declare a Sierra function which is defined in another module.
+fn declare_func<TType, TLibfunc>(
+    context: &Context,
+    module: &Module,
+    registry: &ProgramRegistry<TType, TLibfunc>,
+    function: &Function,
+    metadata: &mut MetadataStorage,
+) -> Result<(), CompileError<TType, TLibfunc>>
+where
+    TType: GenericType,
+    TLibfunc: GenericLibfunc,
+    <TType as GenericType>::Concrete:
+        TypeBuilder<TType, TLibfunc, Error = crate::error::types::Error>,
+    <TLibfunc as GenericLibfunc>::Concrete: LibfuncBuilder<TType, TLibfunc>,
+{
+    let (arg_types, ret_types, _) =
+        build_func_signature(context, module, registry, function, metadata)?;
+
+    module.body().append_operation(func::func(
+        context,
+        StringAttribute::new(context, &generate_function_name(&function.id)),
+        TypeAttribute::new(FunctionType::new(context, &arg_types, &ret_types).into()),
+        Region::new(),
+        &[(
+            Identifier::new(context, "sym_visibility"),
+            StringAttribute::new(context, "private").into(),
+        )],
+        Location::unknown(context),
+    ));
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
 fn generate_function_structure<'c, 'a, TType, TLibfunc>(
     context: &'c Context,
     module: &'a Module<'c>,
@@ -718,6 +1058,7 @@ fn generate_function_structure<'c, 'a, TType, TLibfunc>(
     function: &Function,
     statements: &[Statement],
     metadata_storage: &mut MetadataStorage,
+    debug_info: Option<&DebugLocations>,
 ) -> Result<(BlockRef<'c, 'a>, BlockStorage<'c, 'a>), CompileError<TType, TLibfunc>>
 where
     TType: GenericType,
@@ -725,6 +1066,8 @@ where
     <TType as GenericType>::Concrete: TypeBuilder<TType, TLibfunc>,
     <TLibfunc as GenericLibfunc>::Concrete: LibfuncBuilder<TType, TLibfunc>,
 {
+    let builtin_counters = metadata_storage.get::<BuiltinCountersMeta>().is_some();
+
     let initial_state = edit_state::put_results::<(Type, bool)>(
         HashMap::new(),
         function
@@ -755,15 +1098,14 @@ where
         function.entry_point,
         initial_state,
         |statement_idx, state| {
-            let block = {
-                if let std::collections::btree_map::Entry::Vacant(e) = blocks.entry(statement_idx.0)
-                {
-                    e.insert(Block::new(&[]));
-                    blocks
-                        .get_mut(&statement_idx.0)
-                        .expect("the block should exist")
-                } else {
-                    panic!("statement index already present in block");
+            let block = match blocks.entry(statement_idx.0) {
+                btree_map::Entry::Vacant(entry) => entry.insert(Block::new(&[])),
+                btree_map::Entry::Occupied(_) => {
+                    return Err(ErrorImpl::DuplicateStatementBlock {
+                        statement_idx,
+                        location: statement_location(debug_info, statement_idx),
+                    }
+                    .into());
                 }
             };
 
@@ -829,7 +1171,15 @@ where
                                     Entry::Occupied(entry) => entry.into_mut(),
                                     Entry::Vacant(entry) => entry.insert((state.clone(), 0)),
                                 };
-                            assert_eq!(prev_state, &state, "Branch target states do not match.");
+                            if prev_state != &state {
+                                return Err(ErrorImpl::MismatchedBranchStates {
+                                    libfunc_id: invocation.libfunc_id.clone(),
+                                    statement_idx,
+                                    location: statement_location(debug_info, statement_idx),
+                                    target_idx: statement_idx.next(&branch.target),
+                                }
+                                .into());
+                            }
                             *pred_count += 1;
 
                             Ok(state)
@@ -842,10 +1192,13 @@ where
                     );
 
                     let (state, types) = edit_state::take_args(state.clone(), var_ids.iter())?;
-                    assert!(
-                        state.is_empty(),
-                        "State must be empty after a return statement."
-                    );
+                    if !state.is_empty() {
+                        return Err(ErrorImpl::NonEmptyReturnState {
+                            statement_idx,
+                            location: statement_location(debug_info, statement_idx),
+                        }
+                        .into());
+                    }
 
                     for (ty, is_memory_allocated) in types {
                         block.add_argument(
@@ -886,7 +1239,9 @@ where
                     Err(e) => return Some(Err(e)),
                 };
 
-                if type_info.is_builtin() && type_info.is_zst(registry) {
+                if type_info.is_builtin()
+                    && type_info.is_zst_with_counters(registry, builtin_counters)
+                {
                     None
                 } else {
                     Some(Ok(type_info))
@@ -967,6 +1322,16 @@ where
     ))
 }
 
+/// Return the Cairo source location of a statement as a string, if the debug info is available.
+fn statement_location(
+    debug_info: Option<&DebugLocations>,
+    statement_idx: StatementIdx,
+) -> Option<String> {
+    debug_info
+        .and_then(|debug_info| debug_info.statements.get(&statement_idx))
+        .map(ToString::to_string)
+}
+
 fn extract_types<'c, 'a, TType, TLibfunc>(
     context: &'c Context,
     module: &'a Module<'c>,
@@ -981,13 +1346,15 @@ where
     <TType as GenericType>::Concrete: TypeBuilder<TType, TLibfunc>,
     <TLibfunc as GenericLibfunc>::Concrete: LibfuncBuilder<TType, TLibfunc>,
 {
-    type_ids.iter().filter_map(|id| {
+    let builtin_counters = metadata_storage.get::<BuiltinCountersMeta>().is_some();
+
+    type_ids.iter().filter_map(move |id| {
         let type_info = match registry.get_type(id) {
             Ok(x) => x,
             Err(e) => return Some(Err(e.into())),
         };
 
-        if type_info.is_builtin() && type_info.is_zst(registry) {
+        if type_info.is_builtin() && type_info.is_zst_with_counters(registry, builtin_counters) {
             None
         } else {
             Some(
@@ -999,7 +1366,7 @@ where
     })
 }
 
-fn foreach_statement_in_function<S, E>(
+pub(crate) fn foreach_statement_in_function<S, E>(
     statements: &[Statement],
     entry_point: StatementIdx,
     initial_state: S,
@@ -1039,6 +1406,77 @@ where
     Ok(())
 }
 
+/// Implement the intermediate block of a branch which uses builtins: increment the builtin counters
+/// and jump into the branch's original target.
+#[allow(clippy::too_many_arguments)]
+fn build_builtin_counters<'ctx, 'this, TType, TLibfunc>(
+    context: &'ctx Context,
+    helper: &LibfuncHelper<'ctx, 'this>,
+    libfunc_id: &ConcreteLibfuncId,
+    statement_idx: StatementIdx,
+    debug_info: Option<&DebugLocations>,
+    branch_idx: usize,
+    increments: &BranchIncrements,
+    (target, operands): (&'this Block<'ctx>, Vec<BranchArg<'ctx, 'this>>),
+) -> Result<(), CompileError<TType, TLibfunc>>
+where
+    TType: GenericType,
+    TLibfunc: GenericLibfunc,
+    <TType as GenericType>::Concrete:
+        TypeBuilder<TType, TLibfunc, Error = crate::error::types::Error>,
+    <TLibfunc as GenericLibfunc>::Concrete: LibfuncBuilder<TType, TLibfunc>,
+{
+    let counter_block = helper.branches[branch_idx].0;
+    let location = debug_info
+        .and_then(|debug_info| debug_info.statements.get(&statement_idx).copied())
+        .unwrap_or_else(|| Location::unknown(context));
+
+    let mut values = helper.results[branch_idx]
+        .iter()
+        .enumerate()
+        .map(|(result_idx, value)| {
+            let value = value.get().ok_or_else(|| ErrorImpl::MissingBranchResult {
+                libfunc_id: libfunc_id.clone(),
+                statement_idx,
+                location: statement_location(debug_info, statement_idx),
+                branch_idx,
+                result_idx,
+            })?;
+            Ok(counter_block.add_argument(value.r#type(), location))
+        })
+        .collect::<Result<Vec<_>, CompileError<TType, TLibfunc>>>()?;
+
+    for &(result_idx, amount) in increments {
+        let amount = counter_block
+            .append_operation(arith::constant(
+                context,
+                IntegerAttribute::new(amount as i64, IntegerType::new(context, 64).into()).into(),
+                location,
+            ))
+            .result(0)?
+            .into();
+        values[result_idx] = counter_block
+            .append_operation(arith::addi(values[result_idx], amount, location))
+            .result(0)?
+            .into();
+    }
+
+    for (dst, src) in helper.results[branch_idx].iter().zip(&values) {
+        dst.set(Some(*src));
+    }
+
+    let operands = operands
+        .into_iter()
+        .map(|op| match op {
+            BranchArg::External(x) => x,
+            BranchArg::Returned(i) => values[i],
+        })
+        .collect::<Vec<_>>();
+    counter_block.append_operation(cf::br(target, &operands, location));
+
+    Ok(())
+}
+
 fn generate_branching_targets<'ctx, 'this, 'a>(
     blocks: &'this BlockStorage<'ctx, 'this>,
     statements: &'this [Statement],
@@ -1095,3 +1533,49 @@ where
         })
         .collect()
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utils::{find_function_id, test::load_cairo};
+    use cairo_lang_sierra::extensions::core::{CoreLibfunc, CoreType};
+
+    #[test]
+    fn reachable_functions() {
+        let (module_name, program) = load_cairo! {
+            fn run_test() -> felt252 {
+                a() + b()
+            }
+
+            fn run_other() -> felt252 {
+                c()
+            }
+
+            #[inline(never)]
+            fn a() -> felt252 {
+                b()
+            }
+
+            #[inline(never)]
+            fn b() -> felt252 {
+                1
+            }
+
+            #[inline(never)]
+            fn c() -> felt252 {
+                2
+            }
+        };
+        let registry = ProgramRegistry::<CoreType, CoreLibfunc>::new(&program).unwrap();
+        let function_id = |name: &str| {
+            find_function_id(&program, &format!("{0}::{0}::{name}", module_name)).clone()
+        };
+
+        let reachable_functions =
+            find_reachable_functions(&program, &registry, &[function_id("run_test")]).unwrap();
+        assert_eq!(
+            reachable_functions,
+            HashSet::from([function_id("run_test"), function_id("a"), function_id("b")])
+        );
+    }
+}
@@ -1,17 +1,22 @@
 use crate::{
     error::compile::CompileError,
+    ffi::{module_to_object, LinkerConfig, OptLevel},
     metadata::{
+        builtin_counters::BuiltinCountersMeta,
         gas::{GasMetadata, MetadataComputationConfig},
         runtime_bindings::RuntimeBindingsMeta,
         MetadataStorage,
     },
     module::NativeModule,
-    utils::run_pass_manager,
+    parallel::ParallelCompileError,
+    support::{check_support, UnsupportedItem},
+    utils::{has_gas_builtin, run_pass_manager, PassPipeline},
 };
 use cairo_lang_sierra::{
     extensions::core::{CoreLibfunc, CoreType},
+    ids::FunctionId,
     program::Program,
-    program_registry::ProgramRegistry,
+    program_registry::{ProgramRegistry, ProgramRegistryError},
 };
 use melior::{
     dialect::DialectRegistry,
@@ -19,11 +24,15 @@ use melior::{
     utility::{register_all_dialects, register_all_llvm_translations, register_all_passes},
     Context,
 };
+use std::num::NonZeroUsize;
 
 /// Context of IRs, dialects and passes for Cairo programs compilation.
 #[derive(Debug, Eq, PartialEq)]
 pub struct NativeContext {
     context: Context,
+    builtin_counters: bool,
+    jobs: NonZeroUsize,
+    linker_config: LinkerConfig,
 }
 
 unsafe impl Send for NativeContext {}
@@ -38,50 +47,257 @@ impl Default for NativeContext {
 impl NativeContext {
     pub fn new() -> Self {
         let context = initialize_mlir();
-        Self { context }
+        Self {
+            context,
+            builtin_counters: false,
+            jobs: NonZeroUsize::MIN,
+            linker_config: LinkerConfig::default(),
+        }
+    }
+
+    /// Enable or disable the builtin counters.
+    ///
+    /// When enabled, the compiled programs will count the builtin instances they use, which will be
+    /// returned in the execution result's [`BuiltinStats`](crate::execution_result::BuiltinStats).
+    /// It is disabled by default since it has a runtime cost.
+    pub fn with_builtin_counters(mut self, enabled: bool) -> Self {
+        self.builtin_counters = enabled;
+        self
+    }
+
+    /// Return whether the builtin counters are enabled.
+    pub fn builtin_counters(&self) -> bool {
+        self.builtin_counters
+    }
+
+    /// Set the number of threads used by [`compile_to_objects`](Self::compile_to_objects).
+    ///
+    /// It is a single thread by default, which compiles the program using this context.
+    pub fn with_jobs(mut self, jobs: NonZeroUsize) -> Self {
+        self.jobs = jobs;
+        self
+    }
+
+    /// Return the number of threads used by [`compile_to_objects`](Self::compile_to_objects).
+    pub fn jobs(&self) -> NonZeroUsize {
+        self.jobs
+    }
+
+    /// Set the linker configuration used by the [AOT caches](crate::cache::AotProgramCache) and
+    /// executors built on this context.
+    pub fn with_linker_config(mut self, linker_config: LinkerConfig) -> Self {
+        self.linker_config = linker_config;
+        self
+    }
+
+    /// Return the linker configuration used by the AOT caches and executors built on this context.
+    pub fn linker_config(&self) -> &LinkerConfig {
+        &self.linker_config
+    }
+
+    /// Return the type and libfunc declarations of the program which can't be compiled.
+    ///
+    /// Programs with unsupported declarations should be executed somewhere else, since compiling
+    /// them would fail.
+    pub fn check_support(&self, program: &Program) -> Result<Vec<UnsupportedItem>, CompileError> {
+        Ok(check_support(program, self.builtin_counters)?)
     }
 
     /// Compiles a sierra program into MLIR and then lowers to LLVM.
     /// Returns the corresponding NativeModule struct.
-    pub fn compile(&self, program: &Program) -> Result<NativeModule, CompileError> {
-        let mut module = Module::new(Location::unknown(&self.context));
+    ///
+    /// The pass pipeline is chosen from the optimization level.
+    pub fn compile(
+        &self,
+        program: &Program,
+        opt_level: OptLevel,
+    ) -> Result<NativeModule, CompileError> {
+        self.compile_with_pipeline(program, &PassPipeline::from(opt_level))
+    }
 
-        let has_gas_builtin = program
-            .type_declarations
-            .iter()
-            .any(|decl| decl.long_id.generic_id.0.as_str() == "GasBuiltin");
+    /// Same as [`compile`](Self::compile), but with an explicit pass pipeline.
+    pub fn compile_with_pipeline(
+        &self,
+        program: &Program,
+        pipeline: &PassPipeline,
+    ) -> Result<NativeModule, CompileError> {
+        self.compile_impl(program, pipeline, None)
+    }
 
-        let mut metadata = MetadataStorage::new();
-        // Make the runtime library available.
-        metadata.insert(RuntimeBindingsMeta::default());
-        // We assume that GasMetadata will be always present when the program uses the gas builtin.
-        if has_gas_builtin {
-            let gas_metadata = GasMetadata::new(program, MetadataComputationConfig::default());
-            // Unwrapping here is not necessary since the insertion will only fail if there was
-            // already some metadata of the same type.
-            metadata.insert(gas_metadata);
-        }
+    /// Same as [`compile`](Self::compile), but only the functions reachable from the entry points
+    /// are compiled.
+    ///
+    /// Contracts usually contain lots of library code which their entry points never call, so
+    /// skipping it makes the compilation faster and the output smaller. Functions which haven't
+    /// been compiled can't be invoked.
+    pub fn compile_with_entry_points(
+        &self,
+        program: &Program,
+        entry_points: &[FunctionId],
+        opt_level: OptLevel,
+    ) -> Result<NativeModule, CompileError> {
+        self.compile_impl(program, &PassPipeline::from(opt_level), Some(entry_points))
+    }
 
-        // Create the Sierra program registry
-        let registry = ProgramRegistry::<CoreType, CoreLibfunc>::new(program)?;
+    /// Compile a program into object files, which have to be linked together (for example, using
+    /// [`objects_to_shared_lib_with_config`](crate::objects_to_shared_lib_with_config)).
+    ///
+    /// When multiple [jobs](Self::with_jobs) are configured, the program is split into partitions
+    /// which are compiled in parallel (see [`parallel`](crate::parallel)). Otherwise it is compiled
+    /// into a single object using this context.
+    pub fn compile_to_objects(
+        &self,
+        program: &Program,
+        opt_level: OptLevel,
+    ) -> Result<Vec<Vec<u8>>, ParallelCompileError> {
+        let registry =
+            ProgramRegistry::<CoreType, CoreLibfunc>::new(program).map_err(CompileError::from)?;
+        let program_metadata = ProgramMetadata::new(program, &registry, self.builtin_counters)
+            .map_err(CompileError::from)?;
+
+        self.compile_to_objects_with_metadata(program, &program_metadata, opt_level)
+    }
 
-        crate::compile(
-            &self.context,
-            &module,
+    /// Same as [`compile_to_objects`](Self::compile_to_objects), but with already computed
+    /// metadata.
+    pub(crate) fn compile_to_objects_with_metadata(
+        &self,
+        program: &Program,
+        program_metadata: &ProgramMetadata,
+        opt_level: OptLevel,
+    ) -> Result<Vec<Vec<u8>>, ParallelCompileError> {
+        let pipeline = PassPipeline::from(opt_level);
+        if self.jobs.get() > 1 {
+            return crate::parallel::compile_to_objects_with_metadata(
+                program,
+                program_metadata,
+                None,
+                &pipeline,
+                opt_level,
+                self.jobs,
+            );
+        }
+
+        let registry =
+            ProgramRegistry::<CoreType, CoreLibfunc>::new(program).map_err(CompileError::from)?;
+        let native_module = self.compile_module(
             program,
-            &registry,
-            &mut metadata,
+            registry,
+            program_metadata.clone().into_storage(),
+            &pipeline,
             None,
         )?;
 
-        run_pass_manager(&self.context, &mut module)?;
+        Ok(vec![module_to_object(native_module.module(), opt_level)?])
+    }
+
+    fn compile_impl(
+        &self,
+        program: &Program,
+        pipeline: &PassPipeline,
+        entry_points: Option<&[FunctionId]>,
+    ) -> Result<NativeModule, CompileError> {
+        // Create the Sierra program registry
+        let registry = ProgramRegistry::<CoreType, CoreLibfunc>::new(program)?;
+        let metadata =
+            ProgramMetadata::new(program, &registry, self.builtin_counters)?.into_storage();
+
+        self.compile_module(program, registry, metadata, pipeline, entry_points)
+    }
+
+    fn compile_module(
+        &self,
+        program: &Program,
+        registry: ProgramRegistry<CoreType, CoreLibfunc>,
+        mut metadata: MetadataStorage,
+        pipeline: &PassPipeline,
+        entry_points: Option<&[FunctionId]>,
+    ) -> Result<NativeModule, CompileError> {
+        let mut module = Module::new(Location::unknown(&self.context));
+
+        match entry_points {
+            Some(entry_points) => crate::compile_with_entry_points(
+                &self.context,
+                &module,
+                program,
+                &registry,
+                &mut metadata,
+                None,
+                entry_points,
+            )?,
+            None => crate::compile(
+                &self.context,
+                &module,
+                program,
+                &registry,
+                &mut metadata,
+                None,
+            )?,
+        }
+
+        run_pass_manager(&self.context, &mut module, pipeline)?;
 
         Ok(NativeModule::new(module, registry, metadata))
     }
 }
 
+/// The metadata which depends only on the program and the compilation settings.
+///
+/// It is computed once per program and shared by all the modules the program is compiled into, so
+/// that they all use the same ABI.
+#[derive(Clone)]
+pub(crate) struct ProgramMetadata {
+    gas_metadata: Option<GasMetadata>,
+    builtin_counters: Option<BuiltinCountersMeta>,
+}
+
+impl ProgramMetadata {
+    pub fn new(
+        program: &Program,
+        registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+        builtin_counters: bool,
+    ) -> Result<Self, Box<ProgramRegistryError>> {
+        Ok(Self {
+            // We assume that GasMetadata will be always present when the program uses the gas
+            // builtin.
+            gas_metadata: has_gas_builtin(program)
+                .then(|| GasMetadata::new(program, MetadataComputationConfig::default())),
+            builtin_counters: builtin_counters
+                .then(|| BuiltinCountersMeta::new(program, registry))
+                .transpose()?,
+        })
+    }
+
+    pub fn gas_metadata(&self) -> Option<&GasMetadata> {
+        self.gas_metadata.as_ref()
+    }
+
+    /// Create the metadata storage of a module.
+    pub fn into_storage(self) -> MetadataStorage {
+        let mut metadata = MetadataStorage::new();
+        // Make the runtime library available.
+        metadata.insert(RuntimeBindingsMeta::default());
+        if let Some(gas_metadata) = self.gas_metadata {
+            metadata.insert(gas_metadata);
+        }
+        if let Some(builtin_counters) = self.builtin_counters {
+            metadata.insert(builtin_counters);
+        }
+
+        metadata
+    }
+}
+
 /// Initialize an MLIR context.
 pub fn initialize_mlir() -> Context {
+    register_all_passes();
+    create_mlir_context()
+}
+
+/// Create an MLIR context with every dialect loaded, without registering the passes.
+///
+/// The pass registry is global, so it only has to be populated once (see [`initialize_mlir`]).
+pub(crate) fn create_mlir_context() -> Context {
     let context = Context::new();
     context.append_dialect_registry(&{
         let registry = DialectRegistry::new();
@@ -89,7 +305,6 @@ pub fn initialize_mlir() -> Context {
         registry
     });
     context.load_all_available_dialects();
-    register_all_passes();
     register_all_llvm_translations(&context);
     context
 }
@@ -14,7 +14,7 @@ use cairo_lang_sierra::{
     program::{Program, StatementIdx},
 };
 use melior::{ir::Location, Context};
-use std::collections::HashMap;
+use std::{collections::HashMap, hash::Hash};
 
 mod funcs;
 mod libfunc_declarations;
@@ -79,51 +79,74 @@ pub struct DebugLocations<'c> {
 
 impl<'c> DebugLocations<'c> {
     pub fn extract(context: &'c Context, db: &RootDatabase, debug_info: &DebugInfo) -> Self {
+        DebugPositions::extract(db, debug_info).to_locations(context)
+    }
+}
+
+/// The source positions of a program's declarations and statements.
+///
+/// Unlike [`DebugLocations`], they don't depend on an MLIR context, so they can be shared between
+/// the threads of a [parallel compilation](crate::parallel). Items within virtual files don't have
+/// a position.
+#[derive(Clone, Debug)]
+pub struct DebugPositions {
+    pub type_declarations: HashMap<ConcreteTypeId, SourcePosition>,
+    pub libfunc_declarations: HashMap<ConcreteLibfuncId, SourcePosition>,
+    pub statements: HashMap<StatementIdx, SourcePosition>,
+    pub funcs: HashMap<FunctionId, SourcePosition>,
+}
+
+/// A position within a source file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SourcePosition {
+    pub path: String,
+    pub line: usize,
+    pub col: usize,
+}
+
+impl DebugPositions {
+    pub fn extract(db: &RootDatabase, debug_info: &DebugInfo) -> Self {
         let type_declarations = debug_info
             .type_declarations
             .iter()
-            .map(|(type_id, stable_loc)| {
-                (
+            .filter_map(|(type_id, stable_loc)| {
+                Some((
                     type_id.clone(),
-                    extract_location_from_stable_loc(context, db, *stable_loc),
-                )
+                    extract_position_from_stable_loc(db, *stable_loc)?,
+                ))
             })
             .collect();
 
         let libfunc_declarations = debug_info
             .libfunc_declarations
             .iter()
-            .map(|(libfunc_id, stable_loc)| {
-                (
+            .filter_map(|(libfunc_id, stable_loc)| {
+                Some((
                     libfunc_id.clone(),
-                    extract_location_from_stable_loc(context, db, *stable_loc),
-                )
+                    extract_position_from_stable_loc(db, *stable_loc)?,
+                ))
             })
             .collect();
 
         let statements = debug_info
             .statements
             .iter()
-            .map(|(statement_idx, location_id)| {
-                (
+            .filter_map(|(statement_idx, location_id)| {
+                Some((
                     *statement_idx,
-                    extract_location_from_stable_loc(
-                        context,
-                        db,
-                        location_id.get(db).stable_location,
-                    ),
-                )
+                    extract_position_from_stable_loc(db, location_id.get(db).stable_location)?,
+                ))
             })
             .collect();
 
         let funcs = debug_info
             .funcs
             .iter()
-            .map(|(function_id, stable_loc)| {
-                (
+            .filter_map(|(function_id, stable_loc)| {
+                Some((
                     function_id.clone(),
-                    extract_location_from_stable_loc(context, db, *stable_loc),
-                )
+                    extract_position_from_stable_loc(db, *stable_loc)?,
+                ))
             })
             .collect();
 
@@ -134,18 +157,45 @@ impl<'c> DebugLocations<'c> {
             funcs,
         }
     }
+
+    /// Create the MLIR locations of the positions within a context.
+    pub fn to_locations<'c>(&self, context: &'c Context) -> DebugLocations<'c> {
+        fn convert<'c, K>(
+            context: &'c Context,
+            positions: &HashMap<K, SourcePosition>,
+        ) -> HashMap<K, Location<'c>>
+        where
+            K: Clone + Eq + Hash,
+        {
+            positions
+                .iter()
+                .map(|(key, position)| {
+                    (
+                        key.clone(),
+                        Location::new(context, &position.path, position.line, position.col),
+                    )
+                })
+                .collect()
+        }
+
+        DebugLocations {
+            type_declarations: convert(context, &self.type_declarations),
+            libfunc_declarations: convert(context, &self.libfunc_declarations),
+            statements: convert(context, &self.statements),
+            funcs: convert(context, &self.funcs),
+        }
+    }
 }
 
-fn extract_location_from_stable_loc<'c>(
-    context: &'c Context,
+fn extract_position_from_stable_loc(
     db: &RootDatabase,
     stable_loc: StableLocation,
-) -> Location<'c> {
+) -> Option<SourcePosition> {
     let diagnostic_location = stable_loc.diagnostic_location(db);
 
     let path = match db.lookup_intern_file(diagnostic_location.file_id) {
         FileLongId::OnDisk(path) => path,
-        FileLongId::Virtual(_) => return Location::unknown(context),
+        FileLongId::Virtual(_) => return None,
     };
 
     let pos = diagnostic_location
@@ -154,5 +204,9 @@ fn extract_location_from_stable_loc<'c>(
         .position_in_file(db, diagnostic_location.file_id)
         .unwrap();
 
-    Location::new(context, &path.to_string_lossy(), pos.line, pos.col)
+    Some(SourcePosition {
+        path: path.to_string_lossy().into_owned(),
+        line: pos.line,
+        col: pos.col,
+    })
 }
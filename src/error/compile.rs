@@ -6,6 +6,7 @@ use cairo_lang_sierra::{
         GenericLibfunc, GenericType,
     },
     ids::{ConcreteLibfuncId, ConcreteTypeId},
+    program::StatementIdx,
     program_registry::ProgramRegistryError,
 };
 use std::{fmt, ops::Deref};
@@ -118,11 +119,90 @@ where
         type_id: ConcreteTypeId,
         error: <<TType as GenericType>::Concrete as TypeBuilder<TType, TLibfunc>>::Error,
     },
-    #[error("Error building type '{libfunc_id}': {error}")]
+    #[error(
+        "Error building libfunc '{libfunc_id}' at statement {statement_idx}{}: {error}",
+        fmt_location(.location)
+    )]
     LibfuncBuilderError {
         libfunc_id: ConcreteLibfuncId,
+        statement_idx: StatementIdx,
+        location: Option<String>,
         error: <<TLibfunc as GenericLibfunc>::Concrete as LibfuncBuilder<TType, TLibfunc>>::Error,
     },
+    #[error(
+        "Unsupported tail recursion through '{libfunc_id}' at statement {statement_idx}{}: the function doesn't return any value",
+        fmt_location(.location)
+    )]
+    UnsupportedTailRecursion {
+        libfunc_id: ConcreteLibfuncId,
+        statement_idx: StatementIdx,
+        location: Option<String>,
+    },
+    #[error(
+        "Statement {statement_idx}{} was reached twice while generating the function structure",
+        fmt_location(.location)
+    )]
+    DuplicateStatementBlock {
+        statement_idx: StatementIdx,
+        location: Option<String>,
+    },
+    #[error(
+        "Libfunc '{libfunc_id}' at statement {statement_idx}{} didn't provide result #{result_idx} of branch {branch_idx}",
+        fmt_location(.location)
+    )]
+    MissingBranchResult {
+        libfunc_id: ConcreteLibfuncId,
+        statement_idx: StatementIdx,
+        location: Option<String>,
+        branch_idx: usize,
+        result_idx: usize,
+    },
+    #[error(
+        "Libfunc '{libfunc_id}' at statement {statement_idx}{} didn't terminate its block",
+        fmt_location(.location)
+    )]
+    MissingBlockTerminator {
+        libfunc_id: ConcreteLibfuncId,
+        statement_idx: StatementIdx,
+        location: Option<String>,
+    },
+    #[error(
+        "Libfunc '{libfunc_id}' at statement {statement_idx}{} returned {actual} values on branch {branch_idx}, but {expected} were expected",
+        fmt_location(.location)
+    )]
+    MismatchedBranchResults {
+        libfunc_id: ConcreteLibfuncId,
+        statement_idx: StatementIdx,
+        location: Option<String>,
+        branch_idx: usize,
+        expected: usize,
+        actual: usize,
+    },
+    #[error(
+        "Libfunc '{libfunc_id}' at statement {statement_idx}{} jumps to statement {target_idx} with a state that doesn't match its other predecessors",
+        fmt_location(.location)
+    )]
+    MismatchedBranchStates {
+        libfunc_id: ConcreteLibfuncId,
+        statement_idx: StatementIdx,
+        location: Option<String>,
+        target_idx: StatementIdx,
+    },
+    #[error(
+        "Return statement {statement_idx}{} leaves unused variables in the state",
+        fmt_location(.location)
+    )]
+    NonEmptyReturnState {
+        statement_idx: StatementIdx,
+        location: Option<String>,
+    },
+}
+
+fn fmt_location(location: &Option<String>) -> String {
+    location
+        .as_ref()
+        .map(|location| format!(" ({location})"))
+        .unwrap_or_default()
 }
 
 // Manual implementation necessary because `#[derive(Debug)]` requires that `TType` and `TLibfunc`
@@ -146,11 +226,96 @@ where
                 .field("type_id", type_id)
                 .field("error", error)
                 .finish(),
-            Self::LibfuncBuilderError { libfunc_id, error } => f
+            Self::LibfuncBuilderError {
+                libfunc_id,
+                statement_idx,
+                location,
+                error,
+            } => f
                 .debug_struct("LibfuncBuilderError")
                 .field("libfunc_id", libfunc_id)
+                .field("statement_idx", statement_idx)
+                .field("location", location)
                 .field("error", error)
                 .finish(),
+            Self::UnsupportedTailRecursion {
+                libfunc_id,
+                statement_idx,
+                location,
+            } => f
+                .debug_struct("UnsupportedTailRecursion")
+                .field("libfunc_id", libfunc_id)
+                .field("statement_idx", statement_idx)
+                .field("location", location)
+                .finish(),
+            Self::DuplicateStatementBlock {
+                statement_idx,
+                location,
+            } => f
+                .debug_struct("DuplicateStatementBlock")
+                .field("statement_idx", statement_idx)
+                .field("location", location)
+                .finish(),
+            Self::MissingBranchResult {
+                libfunc_id,
+                statement_idx,
+                location,
+                branch_idx,
+                result_idx,
+            } => f
+                .debug_struct("MissingBranchResult")
+                .field("libfunc_id", libfunc_id)
+                .field("statement_idx", statement_idx)
+                .field("location", location)
+                .field("branch_idx", branch_idx)
+                .field("result_idx", result_idx)
+                .finish(),
+            Self::MissingBlockTerminator {
+                libfunc_id,
+                statement_idx,
+                location,
+            } => f
+                .debug_struct("MissingBlockTerminator")
+                .field("libfunc_id", libfunc_id)
+                .field("statement_idx", statement_idx)
+                .field("location", location)
+                .finish(),
+            Self::MismatchedBranchResults {
+                libfunc_id,
+                statement_idx,
+                location,
+                branch_idx,
+                expected,
+                actual,
+            } => f
+                .debug_struct("MismatchedBranchResults")
+                .field("libfunc_id", libfunc_id)
+                .field("statement_idx", statement_idx)
+                .field("location", location)
+                .field("branch_idx", branch_idx)
+                .field("expected", expected)
+                .field("actual", actual)
+                .finish(),
+            Self::MismatchedBranchStates {
+                libfunc_id,
+                statement_idx,
+                location,
+                target_idx,
+            } => f
+                .debug_struct("MismatchedBranchStates")
+                .field("libfunc_id", libfunc_id)
+                .field("statement_idx", statement_idx)
+                .field("location", location)
+                .field("target_idx", target_idx)
+                .finish(),
+            Self::NonEmptyReturnState {
+                statement_idx,
+                location,
+            } => f
+                .debug_struct("NonEmptyReturnState")
+                .field("statement_idx", statement_idx)
+                .field("location", location)
+                .finish(),
         }
     }
 }
@@ -178,6 +343,8 @@ where
 
 pub fn make_libfunc_builder_error<TType, TLibfunc>(
     id: &ConcreteLibfuncId,
+    statement_idx: StatementIdx,
+    location: Option<String>,
 ) -> impl '_
        + FnOnce(
     <<TLibfunc as GenericLibfunc>::Concrete as LibfuncBuilder<TType, TLibfunc>>::Error,
@@ -191,6 +358,8 @@ where
     move |source| {
         ErrorImpl::LibfuncBuilderError {
             libfunc_id: id.clone(),
+            statement_idx,
+            location,
             error: source,
         }
         .into()
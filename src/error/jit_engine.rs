@@ -4,9 +4,10 @@ use cairo_lang_sierra::{
         core::{CoreLibfunc, CoreType},
         GenericType,
     },
-    ids::ConcreteTypeId,
+    ids::{ConcreteTypeId, FunctionId},
     program_registry::ProgramRegistryError,
 };
+use starknet_types_core::felt::Felt;
 use std::{alloc::LayoutError, fmt, ops::Deref};
 use thiserror::Error;
 
@@ -91,6 +92,33 @@ pub enum ErrorImpl {
 
     #[error("a syscall handler was expected but was not provided")]
     MissingSyscallHandler,
+
+    #[error("the function requires gas but none was provided")]
+    MissingGas,
+
+    #[error("wrong number of arguments, expected '{expected}' got '{got}'")]
+    ArgumentCountMismatch { expected: usize, got: usize },
+
+    #[error("argument mismatch at parameter {param_index}, expected value of type '{expected}'")]
+    ArgumentTypeMismatch {
+        param_index: usize,
+        expected: String,
+    },
+
+    #[error("function '{function}' is not a contract entry point: {reason}")]
+    InvalidContractEntryPoint { function: String, reason: String },
+
+    #[error("no entry point found with selector '{0}'")]
+    UnknownEntryPointSelector(String),
+
+    #[error("function '{0}' is not part of the compiled code")]
+    FunctionNotCompiled(String),
+
+    #[error("returning values of type '{0}' is not supported")]
+    UnsupportedReturnType(String),
+
+    #[error("testing syscall '{name}' failed with {reason:?}")]
+    TestingSyscallFailed { name: String, reason: Vec<Felt> },
 }
 
 impl fmt::Debug for ErrorImpl {
@@ -114,6 +142,40 @@ impl fmt::Debug for ErrorImpl {
                 .field("needed", needed)
                 .field("have", have)
                 .finish(),
+            Self::MissingGas => f.debug_struct("MissingGas").finish(),
+            Self::ArgumentCountMismatch { expected, got } => f
+                .debug_struct("ArgumentCountMismatch")
+                .field("expected", expected)
+                .field("got", got)
+                .finish(),
+            Self::ArgumentTypeMismatch {
+                param_index,
+                expected,
+            } => f
+                .debug_struct("ArgumentTypeMismatch")
+                .field("param_index", param_index)
+                .field("expected", expected)
+                .finish(),
+            Self::InvalidContractEntryPoint { function, reason } => f
+                .debug_struct("InvalidContractEntryPoint")
+                .field("function", function)
+                .field("reason", reason)
+                .finish(),
+            Self::UnknownEntryPointSelector(arg0) => f
+                .debug_tuple("UnknownEntryPointSelector")
+                .field(arg0)
+                .finish(),
+            Self::FunctionNotCompiled(arg0) => {
+                f.debug_tuple("FunctionNotCompiled").field(arg0).finish()
+            }
+            Self::UnsupportedReturnType(arg0) => {
+                f.debug_tuple("UnsupportedReturnType").field(arg0).finish()
+            }
+            Self::TestingSyscallFailed { name, reason } => f
+                .debug_struct("TestingSyscallFailed")
+                .field("name", name)
+                .field("reason", reason)
+                .finish(),
         }
     }
 }
@@ -150,3 +212,61 @@ pub fn make_missing_parameter(ty: &ConcreteTypeId) -> Error {
     )
     .into()
 }
+
+pub fn make_argument_count_mismatch_error(expected: usize, got: usize) -> Error {
+    ErrorImpl::ArgumentCountMismatch { expected, got }.into()
+}
+
+pub fn make_argument_type_mismatch_error(param_index: usize, ty: &ConcreteTypeId) -> Error {
+    ErrorImpl::ArgumentTypeMismatch {
+        param_index,
+        expected: ty
+            .debug_name
+            .as_ref()
+            .map(|x| x.to_string())
+            .unwrap_or_else(|| ty.id.to_string()),
+    }
+    .into()
+}
+
+pub fn make_invalid_contract_entry_point_error(
+    function_id: &FunctionId,
+    reason: impl Into<String>,
+) -> Error {
+    ErrorImpl::InvalidContractEntryPoint {
+        function: function_id
+            .debug_name
+            .as_ref()
+            .map(|x| x.to_string())
+            .unwrap_or_else(|| function_id.id.to_string()),
+        reason: reason.into(),
+    }
+    .into()
+}
+
+pub fn make_unknown_entry_point_selector_error(selector: &Felt) -> Error {
+    ErrorImpl::UnknownEntryPointSelector(format!("{:#x}", selector.to_biguint())).into()
+}
+
+pub fn make_function_not_compiled_error(function_id: &FunctionId) -> Error {
+    ErrorImpl::FunctionNotCompiled(
+        function_id
+            .debug_name
+            .as_ref()
+            .map(|x| x.to_string())
+            .unwrap_or_else(|| function_id.id.to_string()),
+    )
+    .into()
+}
+
+pub fn make_unsupported_return_type_error(type_id: &ConcreteTypeId) -> Error {
+    ErrorImpl::UnsupportedReturnType(type_id.to_string()).into()
+}
+
+pub fn make_testing_syscall_failed_error(name: &str, reason: Vec<Felt>) -> Error {
+    ErrorImpl::TestingSyscallFailed {
+        name: name.to_string(),
+        reason,
+    }
+    .into()
+}
@@ -53,6 +53,8 @@ pub enum ErrorImpl {
     ParseAttributeError,
     #[error("missing metadata")]
     MissingMetadata,
+    #[error("unsupported: {0}")]
+    Unsupported(String),
 }
 
 impl From<super::CoreTypeBuilderError> for ErrorImpl {
@@ -1,114 +1,310 @@
 use crate::{
-    error::{jit_engine::ErrorImpl, JitRunnerError},
+    error::{jit_engine::make_unexpected_value_error, JitRunnerError},
     values::JitValue,
 };
+use num_bigint::BigUint;
 use starknet_types_core::felt::Felt;
 
+/// Number of builtin instances used by an execution, as the Cairo VM would report them.
+///
+/// The counters are only updated when the program has been compiled with builtin counters enabled
+/// (see [`NativeContext::with_builtin_counters`](crate::context::NativeContext::with_builtin_counters)),
+/// otherwise they're all zero.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BuiltinStats {
+    pub range_check: usize,
+    pub pedersen: usize,
+    pub poseidon: usize,
+    pub bitwise: usize,
+    pub ec_op: usize,
+    pub segment_arena: usize,
+}
+
 /// The result of the JIT execution.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExecutionResult {
     pub remaining_gas: Option<u128>,
     pub return_value: JitValue,
+    pub builtin_stats: BuiltinStats,
 }
 
 /// Starknet contract execution result.
 #[derive(Debug, Default)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ContractExecutionResult {
     pub remaining_gas: u128,
     pub failure_flag: bool,
     pub return_values: Vec<Felt>,
+    /// The decoded panic data, if the contract failed. See [`decode_panic_data`].
     pub error_msg: Option<String>,
+    /// The raw panic data, if the contract failed.
+    pub panic_data: Vec<Felt>,
+    pub builtin_stats: BuiltinStats,
 }
 
 impl ContractExecutionResult {
-    /// Convert a [`ExecuteResult`] to a [`NativeExecutionResult`]
+    /// Convert a [`ExecutionResult`] to a [`ContractExecutionResult`].
     pub fn from_execution_result(result: ExecutionResult) -> Result<Self, JitRunnerError> {
-        let mut error_msg = None;
-        let failure_flag;
-
-        let return_values = match &result.return_value {
-            JitValue::Enum { tag, value, .. } => {
-                failure_flag = *tag != 0;
-
-                if !failure_flag {
-                    if let JitValue::Struct { fields, .. } = &**value {
-                        if let JitValue::Struct { fields, .. } = &fields[0] {
-                            if let JitValue::Array(data) = &fields[0] {
-                                let felt_vec: Vec<_> = data
-                                    .iter()
-                                    .map(|x| {
-                                        if let JitValue::Felt252(f) = x {
-                                            *f
-                                        } else {
-                                            panic!("should always be a felt")
-                                        }
-                                    })
-                                    .collect();
-                                felt_vec
-                            } else {
-                                Err(JitRunnerError::from(ErrorImpl::UnexpectedValue(format!(
-                                    "wrong type, expect: array, value: {:?}",
-                                    value
-                                ))))?
-                            }
-                        } else {
-                            Err(JitRunnerError::from(ErrorImpl::UnexpectedValue(format!(
-                                "wrong type, expect: struct, value: {:?}",
-                                value
-                            ))))?
-                        }
-                    } else {
-                        Err(JitRunnerError::from(ErrorImpl::UnexpectedValue(format!(
-                            "wrong type, expect: struct, value: {:?}",
-                            value
-                        ))))?
-                    }
-                } else if let JitValue::Struct { fields, .. } = &**value {
-                    if let JitValue::Array(data) = &fields[1] {
-                        let felt_vec: Vec<_> = data
-                            .iter()
-                            .map(|x| {
-                                if let JitValue::Felt252(f) = x {
-                                    *f
-                                } else {
-                                    panic!("should always be a felt")
-                                }
-                            })
-                            .collect();
-
-                        let str_error =
-                            String::from_utf8(felt_vec.get(0).unwrap().to_bytes_be().to_vec())
-                                .unwrap()
-                                .trim_start_matches('\0')
-                                .to_owned();
-                        error_msg = Some(str_error);
-                        felt_vec
-                    } else {
-                        Err(JitRunnerError::from(ErrorImpl::UnexpectedValue(format!(
-                            "wrong type, expect: array, value: {:?}",
-                            value
-                        ))))?
-                    }
-                } else {
-                    Err(JitRunnerError::from(ErrorImpl::UnexpectedValue(format!(
-                        "wrong type, expect: struct, value: {:?}",
-                        value
-                    ))))?
-                }
+        let (tag, value) = match &result.return_value {
+            JitValue::Enum { tag, value, .. } => (*tag, &**value),
+            _ => {
+                return Err(make_unexpected_value_error(
+                    "wrong return value type, expected an enum".to_string(),
+                ))
             }
+        };
+
+        let failure_flag = tag != 0;
+        let fields = match value {
+            JitValue::Struct { fields, .. } => fields,
             _ => {
-                failure_flag = true;
-                Err(JitRunnerError::from(ErrorImpl::UnexpectedValue(
-                    "wrong return value type expected a enum".to_string(),
-                )))?
+                return Err(make_unexpected_value_error(format!(
+                    "wrong type, expect: struct, value: {value:?}"
+                )))
             }
         };
 
+        let (return_values, error_msg, panic_data) = if failure_flag {
+            // The panic variant contains a `(Panic, Array<felt252>)` struct.
+            let panic_data = match fields.get(1) {
+                Some(panic_data) => felts_from_array(panic_data)?,
+                None => {
+                    return Err(make_unexpected_value_error(format!(
+                        "wrong type, expect: panic data, value: {value:?}"
+                    )))
+                }
+            };
+
+            (
+                panic_data.clone(),
+                Some(decode_panic_data(&panic_data)),
+                panic_data,
+            )
+        } else {
+            // The success variant contains a `(Span<felt252>,)` struct.
+            let return_values = match fields.first() {
+                Some(JitValue::Struct { fields, .. }) if !fields.is_empty() => {
+                    felts_from_array(&fields[0])?
+                }
+                _ => {
+                    return Err(make_unexpected_value_error(format!(
+                        "wrong type, expect: struct, value: {value:?}"
+                    )))
+                }
+            };
+
+            (return_values, None, Vec::new())
+        };
+
         Ok(Self {
             remaining_gas: result.remaining_gas.unwrap_or(0),
-            return_values,
             failure_flag,
+            return_values,
             error_msg,
+            panic_data,
+            builtin_stats: result.builtin_stats,
         })
     }
 }
+
+/// Magic value (in hex) which marks the start of a serialized `ByteArray` within the panic data.
+pub const BYTE_ARRAY_MAGIC: &str =
+    "46a6158a16a947e5916b2a2ca68501a45e93d7110e81aa2d6438b1c57c879a3";
+
+/// Decode the panic data into a human readable message.
+///
+/// Serialized `ByteArray`s (starting with [`BYTE_ARRAY_MAGIC`]) are decoded into their string.
+/// Every other felt is decoded as a short string if it's printable, or shown as hex otherwise. The
+/// decoded items are joined by commas.
+pub fn decode_panic_data(panic_data: &[Felt]) -> String {
+    let byte_array_magic = byte_array_magic();
+
+    let mut items = Vec::new();
+    let mut remaining = panic_data;
+    while let Some((first, rest)) = remaining.split_first() {
+        if *first == byte_array_magic {
+            if let Some((value, rest)) = decode_byte_array(rest) {
+                items.push(value);
+                remaining = rest;
+                continue;
+            }
+        }
+
+        items.push(
+            decode_short_string(first).unwrap_or_else(|| format!("{:#x}", first.to_biguint())),
+        );
+        remaining = rest;
+    }
+
+    items.join(", ")
+}
+
+fn byte_array_magic() -> Felt {
+    Felt::from(&BigUint::parse_bytes(BYTE_ARRAY_MAGIC.as_bytes(), 16).unwrap())
+}
+
+/// Decode a serialized `ByteArray`, returning the string and the remaining felts.
+///
+/// The serialization is the number of full words, the full words (31 bytes each), the pending word
+/// and the pending word's length in bytes.
+fn decode_byte_array(data: &[Felt]) -> Option<(String, &[Felt])> {
+    let (n_words, data) = data.split_first()?;
+    let n_words = usize::try_from(n_words.to_biguint()).ok()?;
+    if data.len() < n_words.checked_add(2)? {
+        return None;
+    }
+
+    let (words, data) = data.split_at(n_words);
+    let (pending_word, pending_len, data) = (&data[0], &data[1], &data[2..]);
+    let pending_len = usize::try_from(pending_len.to_biguint()).ok()?;
+    if pending_len >= 31 {
+        return None;
+    }
+
+    let mut bytes = Vec::with_capacity(31 * n_words + pending_len);
+    for word in words {
+        bytes.extend_from_slice(&word.to_bytes_be()[1..]);
+    }
+    bytes.extend_from_slice(&pending_word.to_bytes_be()[32 - pending_len..]);
+
+    Some((String::from_utf8_lossy(&bytes).into_owned(), data))
+}
+
+/// Decode a felt as a short string, if all its bytes are printable.
+fn decode_short_string(value: &Felt) -> Option<String> {
+    let bytes = value.to_bytes_be();
+    let bytes = &bytes[bytes.iter().position(|&x| x != 0)?..];
+
+    bytes
+        .iter()
+        .all(|&x| x.is_ascii_graphic() || x == b' ')
+        .then(|| String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// Extract the felts of an array or span value.
+fn felts_from_array(value: &JitValue) -> Result<Vec<Felt>, JitRunnerError> {
+    match value {
+        JitValue::Array(data) => data
+            .iter()
+            .map(|x| match x {
+                JitValue::Felt252(value) => Ok(*value),
+                _ => Err(make_unexpected_value_error(format!(
+                    "wrong type, expect: felt252, value: {x:?}"
+                ))),
+            })
+            .collect(),
+        _ => Err(make_unexpected_value_error(format!(
+            "wrong type, expect: array, value: {value:?}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn short_string(value: &str) -> Felt {
+        Felt::from_bytes_be_slice(value.as_bytes())
+    }
+
+    #[test]
+    fn decode_short_strings() {
+        assert_eq!(
+            decode_panic_data(&[short_string("Out of gas")]),
+            "Out of gas"
+        );
+        assert_eq!(
+            decode_panic_data(&[short_string("Index out of bounds"), short_string("u32_sub")]),
+            "Index out of bounds, u32_sub",
+        );
+    }
+
+    #[test]
+    fn decode_non_printable() {
+        assert_eq!(decode_panic_data(&[Felt::ZERO]), "0x0");
+        assert_eq!(
+            decode_panic_data(&[short_string("error"), Felt::from(0x1234)]),
+            "error, 0x1234",
+        );
+    }
+
+    #[test]
+    fn decode_byte_arrays() {
+        let message = "This is a long message which spans more than one word.";
+        let (full, pending) = message.as_bytes().split_at(31);
+
+        let panic_data = [
+            byte_array_magic(),
+            Felt::from(1),
+            Felt::from_bytes_be_slice(full),
+            Felt::from_bytes_be_slice(pending),
+            Felt::from(pending.len()),
+            short_string("trailing"),
+        ];
+
+        assert_eq!(
+            decode_panic_data(&panic_data),
+            format!("{message}, trailing"),
+        );
+    }
+
+    #[test]
+    fn decode_invalid_byte_arrays() {
+        let panic_data = [byte_array_magic(), Felt::from(5)];
+
+        assert_eq!(
+            decode_panic_data(&panic_data),
+            format!("0x{BYTE_ARRAY_MAGIC}, 0x5"),
+        );
+    }
+
+    #[test]
+    fn contract_result_panic_data() {
+        let panic_data = vec![short_string("Out of gas"), Felt::from(7)];
+        let result = ContractExecutionResult::from_execution_result(ExecutionResult {
+            remaining_gas: Some(10),
+            return_value: JitValue::Enum {
+                tag: 1,
+                value: Box::new(JitValue::Struct {
+                    fields: vec![
+                        JitValue::Struct {
+                            fields: Vec::new(),
+                            debug_name: None,
+                        },
+                        JitValue::Array(
+                            panic_data.iter().copied().map(JitValue::Felt252).collect(),
+                        ),
+                    ],
+                    debug_name: None,
+                }),
+                debug_name: None,
+            },
+            builtin_stats: BuiltinStats::default(),
+        })
+        .unwrap();
+
+        assert!(result.failure_flag);
+        assert_eq!(result.panic_data, panic_data);
+        assert_eq!(result.error_msg.as_deref(), Some("Out of gas, 0x7"));
+    }
+
+    #[test]
+    fn contract_result_unexpected_value() {
+        let result = ContractExecutionResult::from_execution_result(ExecutionResult {
+            remaining_gas: None,
+            return_value: JitValue::Enum {
+                tag: 1,
+                value: Box::new(JitValue::Struct {
+                    fields: vec![JitValue::Array(vec![JitValue::Uint8(0)])],
+                    debug_name: None,
+                }),
+                debug_name: None,
+            },
+            builtin_stats: BuiltinStats::default(),
+        });
+
+        assert!(result.is_err());
+    }
+}
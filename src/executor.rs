@@ -1,27 +1,44 @@
-pub use self::{aot::AotNativeExecutor, jit::JitNativeExecutor};
+pub use self::{
+    aot::AotNativeExecutor,
+    contract::{ContractExecutor, ContractLoadError, EntryPointType},
+    jit::JitNativeExecutor,
+};
 use crate::{
-    execution_result::ExecutionResult, types::TypeBuilder, utils::get_integer_layout,
+    error::jit_engine::{
+        make_argument_count_mismatch_error, make_argument_type_mismatch_error,
+        make_invalid_contract_entry_point_error, make_testing_syscall_failed_error,
+        make_type_builder_error, make_unexpected_value_error, make_unsupported_return_type_error,
+        ErrorImpl, RunnerError,
+    },
+    execution_result::{BuiltinStats, ContractExecutionResult, ExecutionResult},
+    metadata::syscall_handler::SyscallHandlerMeta,
+    starknet::handler::replace_testing_syscall_error,
+    types::TypeBuilder,
+    utils::get_integer_layout,
     values::JitValue,
 };
 use bumpalo::Bump;
 use cairo_lang_sierra::{
     extensions::{
         core::{CoreLibfunc, CoreType, CoreTypeConcrete},
-        starknet::StarkNetTypeConcrete,
+        starknet::{secp256::Secp256PointTypeConcrete, StarkNetTypeConcrete},
     },
-    ids::ConcreteTypeId,
+    ids::{ConcreteTypeId, FunctionId},
     program::FunctionSignature,
-    program_registry::{ProgramRegistry, ProgramRegistryError},
+    program_registry::ProgramRegistry,
 };
 use libc::c_void;
+use starknet_types_core::felt::Felt;
 use std::{
     alloc::Layout,
     arch::global_asm,
     ptr::{null_mut, NonNull},
     rc::Rc,
+    sync::Arc,
 };
 
 mod aot;
+mod contract;
 mod jit;
 
 #[cfg(target_arch = "aarch64")]
@@ -43,13 +60,56 @@ extern "C" {
 }
 
 pub enum NativeExecutor<'m> {
-    Aot(Rc<AotNativeExecutor>),
+    Aot(Arc<AotNativeExecutor>),
     Jit(Rc<JitNativeExecutor<'m>>),
 }
 
+impl<'m> NativeExecutor<'m> {
+    pub fn program_registry(&self) -> &ProgramRegistry<CoreType, CoreLibfunc> {
+        match self {
+            NativeExecutor::Aot(executor) => executor.program_registry(),
+            NativeExecutor::Jit(executor) => executor.program_registry(),
+        }
+    }
+
+    pub fn invoke_dynamic(
+        &self,
+        function_id: &FunctionId,
+        args: &[JitValue],
+        gas: Option<u128>,
+        syscall_handler: Option<&SyscallHandlerMeta>,
+    ) -> Result<ExecutionResult, RunnerError> {
+        match self {
+            NativeExecutor::Aot(executor) => {
+                executor.invoke_dynamic(function_id, args, gas, syscall_handler)
+            }
+            NativeExecutor::Jit(executor) => {
+                executor.invoke_dynamic(function_id, args, gas, syscall_handler)
+            }
+        }
+    }
+
+    pub fn invoke_contract_dynamic(
+        &self,
+        function_id: &FunctionId,
+        args: &[Felt],
+        gas: Option<u128>,
+        syscall_handler: Option<&SyscallHandlerMeta>,
+    ) -> Result<ContractExecutionResult, RunnerError> {
+        match self {
+            NativeExecutor::Aot(executor) => {
+                executor.invoke_contract_dynamic(function_id, args, gas, syscall_handler)
+            }
+            NativeExecutor::Jit(executor) => {
+                executor.invoke_contract_dynamic(function_id, args, gas, syscall_handler)
+            }
+        }
+    }
+}
+
 impl<'m> From<AotNativeExecutor> for NativeExecutor<'m> {
     fn from(value: AotNativeExecutor) -> Self {
-        Self::Aot(Rc::new(value))
+        Self::Aot(Arc::new(value))
     }
 }
 
@@ -59,6 +119,22 @@ impl<'m> From<JitNativeExecutor<'m>> for NativeExecutor<'m> {
     }
 }
 
+/// Signature of the runtime's `cairo_native__set_syscall_handler`.
+type SetSyscallHandlerFn = extern "C" fn(*mut c_void) -> *mut c_void;
+
+/// Return the `cairo_native__set_syscall_handler` of the runtime linked into the current process.
+#[cfg(feature = "with-runtime")]
+fn host_set_syscall_handler() -> Option<SetSyscallHandlerFn> {
+    Some(cairo_native_runtime::cairo_native__set_syscall_handler)
+}
+
+/// Return the `cairo_native__set_syscall_handler` of the runtime linked into the current process.
+#[cfg(not(feature = "with-runtime"))]
+fn host_set_syscall_handler() -> Option<SetSyscallHandlerFn> {
+    None
+}
+
+#[allow(clippy::too_many_arguments)]
 fn invoke_dynamic(
     registry: &ProgramRegistry<CoreType, CoreLibfunc>,
     function_ptr: *const c_void,
@@ -66,7 +142,9 @@ fn invoke_dynamic(
     args: &[JitValue],
     gas: Option<u128>,
     syscall_handler: Option<NonNull<()>>,
-) -> ExecutionResult {
+    set_syscall_handler: Option<SetSyscallHandlerFn>,
+    builtin_counters: bool,
+) -> Result<ExecutionResult, RunnerError> {
     tracing::info!("Invoking function with signature: {function_signature:?}.");
 
     let arena = Bump::new();
@@ -76,7 +154,8 @@ fn invoke_dynamic(
     //
     // Generated when either:
     //   - There are more than one non-zst return values.
-    //     - All builtins except GasBuiltin and Starknet are ZST.
+    //     - All builtins except GasBuiltin and Starknet are ZST, unless the builtin counters are
+    //       enabled.
     //     - The unit struct is a ZST.
     //   - The return argument is complex.
     let mut ret_types_iter = function_signature
@@ -86,9 +165,10 @@ fn invoke_dynamic(
             let info = registry.get_type(id).unwrap();
 
             let is_builtin = <CoreTypeConcrete as TypeBuilder<CoreType, CoreLibfunc>>::is_builtin;
-            let is_zst = <CoreTypeConcrete as TypeBuilder<CoreType, CoreLibfunc>>::is_zst;
+            let is_zst =
+                <CoreTypeConcrete as TypeBuilder<CoreType, CoreLibfunc>>::is_zst_with_counters;
 
-            !(is_builtin(info) && is_zst(info, registry))
+            !(is_builtin(info) && is_zst(info, registry, builtin_counters))
         })
         .peekable();
 
@@ -101,7 +181,11 @@ fn invoke_dynamic(
         let layout = ret_types_iter.fold(Layout::new::<()>(), |layout, id| {
             let type_info = registry.get_type(id).unwrap();
             layout
-                .extend(type_info.layout(registry).unwrap())
+                .extend(
+                    type_info
+                        .layout_with_counters(registry, builtin_counters)
+                        .unwrap(),
+                )
                 .unwrap()
                 .0
         });
@@ -118,12 +202,19 @@ fn invoke_dynamic(
     };
 
     // Generate argument list.
+    let is_builtin = <CoreTypeConcrete as TypeBuilder<CoreType, CoreLibfunc>>::is_builtin;
+    let num_params = function_signature
+        .param_types
+        .iter()
+        .filter(|id| !is_builtin(registry.get_type(id).unwrap()))
+        .count();
+    if num_params != args.len() {
+        return Err(make_argument_count_mismatch_error(num_params, args.len()));
+    }
+
     let mut iter = args.iter();
-    for type_id in function_signature.param_types.iter().filter(|id| {
-        let info = registry.get_type(id).unwrap();
-        !<CoreTypeConcrete as TypeBuilder<CoreType, CoreLibfunc>>::is_zst(info, registry)
-    }) {
-        let type_info = registry.get_type(type_id).unwrap();
+    for (param_index, type_id) in function_signature.param_types.iter().enumerate() {
+        let type_info = registry.get_type(type_id)?;
 
         // Process gas requirements and syscall handler.
         match type_info {
@@ -134,18 +225,35 @@ fn invoke_dynamic(
                         &[gas as u64, (gas >> 64) as u64],
                     );
                 }
-                None => panic!("Gas is required"),
+                None => return Err(ErrorImpl::MissingGas.into()),
             },
             CoreTypeConcrete::StarkNet(StarkNetTypeConcrete::System(_)) => match syscall_handler {
                 Some(syscall_handler) => invoke_data.push_aligned(
                     get_integer_layout(64).align(),
                     &[syscall_handler.as_ptr() as u64],
                 ),
-                None => panic!("Syscall handler is required"),
+                None => return Err(ErrorImpl::MissingSyscallHandler.into()),
             },
-            _ => invoke_data
-                .push(type_id, type_info, iter.next().unwrap())
-                .unwrap(),
+            // The remaining builtins are either ZSTs or counters which always start at zero.
+            _ if is_builtin(type_info) => {
+                if !type_info.is_zst_with_counters(registry, builtin_counters) {
+                    invoke_data.push_aligned(get_integer_layout(64).align(), &[0]);
+                }
+            }
+            _ => {
+                // The argument count has already been checked, therefore there's always a value.
+                let value = iter.next().unwrap();
+                if !type_info.is_zst(registry) {
+                    invoke_data
+                        .push(type_id, type_info, value)
+                        .map_err(|e| match e.source {
+                            ErrorImpl::UnexpectedValue(_) => {
+                                make_argument_type_mismatch_error(param_index, type_id)
+                            }
+                            _ => e,
+                        })?;
+                }
+            }
         }
     }
 
@@ -155,6 +263,12 @@ fn invoke_dynamic(
     #[cfg(target_arch = "aarch64")]
     let mut ret_registers = [0; 4];
 
+    // The testing libfuncs find the syscall handler through the runtime the program is linked
+    // against, and record their failures instead of returning them to the program.
+    let prev_syscall_handler = set_syscall_handler
+        .map(|set| set(syscall_handler.map_or(null_mut(), |x| x.as_ptr().cast())));
+    let prev_testing_syscall_error = replace_testing_syscall_error(None);
+
     unsafe {
         aot_trampoline(
             function_ptr,
@@ -164,17 +278,24 @@ fn invoke_dynamic(
         );
     }
 
-    // Parse final gas.
+    let testing_syscall_error = replace_testing_syscall_error(prev_testing_syscall_error);
+    if let (Some(set), Some(prev_syscall_handler)) = (set_syscall_handler, prev_syscall_handler) {
+        set(prev_syscall_handler);
+    }
+    if let Some((name, reason)) = testing_syscall_error {
+        return Err(make_testing_syscall_failed_error(name, reason));
+    }
+
+    // Parse final gas and builtin counters.
     let mut remaining_gas = None;
+    let mut builtin_stats = BuiltinStats::default();
     for type_id in &function_signature.ret_types {
         let type_info = registry.get_type(type_id).unwrap();
         match type_info {
             CoreTypeConcrete::GasBuiltin(_) => {
                 remaining_gas = Some(match &mut return_ptr {
                     Some(return_ptr) => unsafe {
-                        let ptr = return_ptr.cast::<u128>();
-                        *return_ptr = NonNull::new_unchecked(ptr.as_ptr().add(1)).cast();
-                        *ptr.as_ref()
+                        *next_return_value::<u128>(return_ptr, type_info.layout(registry).unwrap())
                     },
                     None => {
                         // If there's no return ptr then the function only returned the gas. We don't
@@ -185,35 +306,223 @@ fn invoke_dynamic(
             }
             CoreTypeConcrete::StarkNet(StarkNetTypeConcrete::System(_)) => match &mut return_ptr {
                 Some(return_ptr) => unsafe {
-                    let ptr = return_ptr.cast::<*mut ()>();
-                    *return_ptr = NonNull::new_unchecked(ptr.as_ptr().add(1)).cast();
+                    next_return_value::<*mut ()>(return_ptr, type_info.layout(registry).unwrap());
                 },
                 None => {}
             },
             _ if <CoreTypeConcrete as TypeBuilder<CoreType, CoreLibfunc>>::is_builtin(
                 type_info,
-            ) => {}
+            ) =>
+            {
+                if type_info.is_zst_with_counters(registry, builtin_counters) {
+                    continue;
+                }
+
+                let value = match &mut return_ptr {
+                    Some(return_ptr) => unsafe {
+                        *next_return_value::<u64>(return_ptr, get_integer_layout(64))
+                    },
+                    // If there's no return ptr then the function only returned this builtin.
+                    None => ret_registers[0],
+                } as usize;
+
+                match type_info {
+                    CoreTypeConcrete::RangeCheck(_) => builtin_stats.range_check = value,
+                    CoreTypeConcrete::Pedersen(_) => builtin_stats.pedersen = value,
+                    CoreTypeConcrete::Poseidon(_) => builtin_stats.poseidon = value,
+                    CoreTypeConcrete::Bitwise(_) => builtin_stats.bitwise = value,
+                    CoreTypeConcrete::EcOp(_) => builtin_stats.ec_op = value,
+                    CoreTypeConcrete::SegmentArena(_) => builtin_stats.segment_arena = value,
+                    _ => {}
+                }
+            }
             _ => break,
         }
     }
 
+    // Align the return pointer to the actual return value.
+    if let Some(return_ptr) = &mut return_ptr {
+        let type_info = registry
+            .get_type(function_signature.ret_types.last().unwrap())
+            .unwrap();
+        let offset = return_ptr
+            .as_ptr()
+            .cast::<u8>()
+            .align_offset(type_info.layout(registry).unwrap().align());
+        *return_ptr =
+            unsafe { NonNull::new_unchecked(return_ptr.as_ptr().cast::<u8>().add(offset).cast()) };
+    }
+
     // Parse return values.
     let return_value = parse_result(
         function_signature.ret_types.last().unwrap(),
         registry,
         return_ptr,
         ret_registers,
-    );
+    )?;
 
     // FIXME: Arena deallocation.
     std::mem::forget(arena);
 
-    ExecutionResult {
+    Ok(ExecutionResult {
         remaining_gas,
         return_value,
+        builtin_stats,
+    })
+}
+
+/// Check that a function has the Starknet contract entry point signature and build its argument
+/// from the calldata.
+///
+/// Contract entry points receive only builtins (including `System`) and a `Span<felt252>`, and
+/// return the builtins followed by a `PanicResult<(Span<felt252>,)>`.
+fn contract_entry_point_args(
+    registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    function_id: &FunctionId,
+    function_signature: &FunctionSignature,
+    args: &[Felt],
+) -> Result<JitValue, RunnerError> {
+    let is_builtin = |type_id: &ConcreteTypeId| {
+        registry
+            .get_type(type_id)
+            .map(<CoreTypeConcrete as TypeBuilder<CoreType, CoreLibfunc>>::is_builtin)
+            .map_err(|_| {
+                make_invalid_contract_entry_point_error(
+                    function_id,
+                    format!("unknown type `{type_id}`"),
+                )
+            })
+    };
+    let is_system = |type_id: &ConcreteTypeId| {
+        matches!(
+            registry.get_type(type_id),
+            Ok(CoreTypeConcrete::StarkNet(StarkNetTypeConcrete::System(_)))
+        )
+    };
+
+    let mut params = Vec::new();
+    for type_id in &function_signature.param_types {
+        if !is_builtin(type_id)? {
+            params.push(type_id);
+        }
+    }
+    let span_ty = match params.as_slice() {
+        [span_ty] if is_felt252_span(registry, span_ty) => span_ty,
+        _ => {
+            return Err(make_invalid_contract_entry_point_error(
+                function_id,
+                "expected a single `Span<felt252>` parameter besides the builtins",
+            ))
+        }
+    };
+    if !function_signature.param_types.iter().any(is_system) {
+        return Err(make_invalid_contract_entry_point_error(
+            function_id,
+            "missing the `System` parameter",
+        ));
+    }
+
+    let (ret_ty, ret_builtins) = function_signature
+        .ret_types
+        .split_last()
+        .ok_or_else(|| make_invalid_contract_entry_point_error(function_id, "no return values"))?;
+    for type_id in ret_builtins {
+        if !is_builtin(type_id)? {
+            return Err(make_invalid_contract_entry_point_error(
+                function_id,
+                "expected only builtins (including `System`) before the return value",
+            ));
+        }
+    }
+    if !ret_builtins.iter().any(is_system) {
+        return Err(make_invalid_contract_entry_point_error(
+            function_id,
+            "expected only builtins (including `System`) before the return value",
+        ));
+    }
+    if !is_contract_panic_result(registry, ret_ty) {
+        return Err(make_invalid_contract_entry_point_error(
+            function_id,
+            "expected a `PanicResult<(Span<felt252>,)>` return value",
+        ));
+    }
+
+    Ok(JitValue::Struct {
+        fields: vec![JitValue::Array(
+            args.iter().copied().map(JitValue::Felt252).collect(),
+        )],
+        debug_name: span_ty.debug_name.as_ref().map(|x| x.to_string()),
+    })
+}
+
+/// Return whether a type is `Span<felt252>`: a struct whose only member is a snapshot of an
+/// `Array<felt252>`.
+fn is_felt252_span(
+    registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    type_id: &ConcreteTypeId,
+) -> bool {
+    let member_ty = match registry.get_type(type_id) {
+        Ok(CoreTypeConcrete::Struct(info)) if info.members.len() == 1 => &info.members[0],
+        _ => return false,
+    };
+    let member_ty = match registry.get_type(member_ty) {
+        Ok(CoreTypeConcrete::Snapshot(info)) => &info.ty,
+        _ => return false,
+    };
+
+    match registry.get_type(member_ty) {
+        Ok(CoreTypeConcrete::Array(info)) => {
+            matches!(
+                registry.get_type(&info.ty),
+                Ok(CoreTypeConcrete::Felt252(_))
+            )
+        }
+        _ => false,
     }
 }
 
+/// Return whether a type is `PanicResult<(Span<felt252>,)>`: an enum whose first variant is a
+/// tuple with a `Span<felt252>` and whose second one is a `(Panic, Array<felt252>)` tuple.
+fn is_contract_panic_result(
+    registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    type_id: &ConcreteTypeId,
+) -> bool {
+    let variants = match registry.get_type(type_id) {
+        Ok(CoreTypeConcrete::Enum(info)) if info.variants.len() == 2 => &info.variants,
+        _ => return false,
+    };
+
+    let is_ok_variant = match registry.get_type(&variants[0]) {
+        Ok(CoreTypeConcrete::Struct(info)) => {
+            info.members.len() == 1 && is_felt252_span(registry, &info.members[0])
+        }
+        _ => false,
+    };
+    let is_err_variant = match registry.get_type(&variants[1]) {
+        Ok(CoreTypeConcrete::Struct(info)) => {
+            info.members.len() == 2
+                && matches!(
+                    registry.get_type(&info.members[1]),
+                    Ok(CoreTypeConcrete::Array(info))
+                        if matches!(registry.get_type(&info.ty), Ok(CoreTypeConcrete::Felt252(_)))
+                )
+        }
+        _ => false,
+    };
+
+    is_ok_variant && is_err_variant
+}
+
+/// Read the next value of the return pointer, taking its alignment into account, and advance the
+/// pointer past it.
+unsafe fn next_return_value<'a, T>(return_ptr: &mut NonNull<()>, layout: Layout) -> &'a T {
+    let ptr = return_ptr.as_ptr().cast::<u8>();
+    let ptr = ptr.add(ptr.align_offset(layout.align()));
+
+    *return_ptr = NonNull::new_unchecked(ptr.add(layout.size()).cast());
+    &*ptr.cast::<T>()
+}
+
 pub struct ArgumentMapper<'a> {
     arena: &'a Bump,
     registry: &'a ProgramRegistry<CoreType, CoreLibfunc>,
@@ -269,13 +578,34 @@ impl<'a> ArgumentMapper<'a> {
         type_id: &ConcreteTypeId,
         type_info: &CoreTypeConcrete,
         value: &JitValue,
-    ) -> Result<(), Box<ProgramRegistryError>> {
-        match (type_info, value) {
-            (CoreTypeConcrete::Array(info), JitValue::Array(values)) => {
-                // TODO: Assert that `info.ty` matches all the values' types.
+    ) -> Result<(), RunnerError> {
+        let unexpected_value = || {
+            make_unexpected_value_error(
+                type_id
+                    .debug_name
+                    .as_ref()
+                    .map(|x| x.to_string())
+                    .unwrap_or_else(|| type_id.id.to_string()),
+            )
+        };
 
+        match (type_info, value) {
+            (
+                CoreTypeConcrete::Array(info) | CoreTypeConcrete::Span(info),
+                JitValue::Array(values),
+            ) => {
                 let type_info = self.registry.get_type(&info.ty)?;
-                let type_layout = type_info.layout(self.registry).unwrap().pad_to_align();
+                let type_layout = type_info
+                    .layout(self.registry)
+                    .map_err(make_type_builder_error(&info.ty))?
+                    .pad_to_align();
+
+                // Convert every element before allocating, so that a mismatched element doesn't
+                // leak the array data.
+                let value_ptrs = values
+                    .iter()
+                    .map(|value| value.to_jit(self.arena, self.registry, &info.ty))
+                    .collect::<Result<Vec<_>, _>>()?;
 
                 // This needs to be a heap-allocated pointer because it's the actual array data.
                 let ptr = if values.is_empty() {
@@ -284,14 +614,10 @@ impl<'a> ArgumentMapper<'a> {
                     unsafe { libc::realloc(null_mut(), type_layout.size() * values.len()) }
                 };
 
-                for (idx, value) in values.iter().enumerate() {
+                for (idx, value_ptr) in value_ptrs.into_iter().enumerate() {
                     unsafe {
                         std::ptr::copy_nonoverlapping(
-                            value
-                                .to_jit(self.arena, self.registry, &info.ty)
-                                .unwrap()
-                                .cast()
-                                .as_ptr(),
+                            value_ptr.cast().as_ptr(),
                             (ptr as usize + type_layout.size() * idx) as *mut u8,
                             type_layout.size(),
                         );
@@ -313,14 +639,39 @@ impl<'a> ArgumentMapper<'a> {
                 self.push_aligned(get_integer_layout(252).align(), &c.to_le_digits());
                 self.push_aligned(get_integer_layout(252).align(), &d.to_le_digits());
             }
+            (
+                CoreTypeConcrete::StarkNet(StarkNetTypeConcrete::Secp256Point(
+                    Secp256PointTypeConcrete::K1(_),
+                )),
+                JitValue::Secp256K1Point { x, y },
+            )
+            | (
+                CoreTypeConcrete::StarkNet(StarkNetTypeConcrete::Secp256Point(
+                    Secp256PointTypeConcrete::R1(_),
+                )),
+                JitValue::Secp256R1Point { x, y },
+            ) => {
+                for value in [x.0, x.1, y.0, y.1] {
+                    self.push_aligned(
+                        get_integer_layout(128).align(),
+                        &[value as u64, (value >> 64) as u64],
+                    );
+                }
+            }
             (CoreTypeConcrete::Enum(info), JitValue::Enum { tag, value, .. }) => {
+                if *tag >= info.variants.len() {
+                    return Err(unexpected_value());
+                }
+
                 if type_info.is_memory_allocated(self.registry) {
                     let (layout, tag_layout, variant_layouts) =
                         crate::types::r#enum::get_layout_for_variants(
                             self.registry,
                             &info.variants,
                         )
-                        .unwrap();
+                        .map_err(make_type_builder_error(type_id))?;
+                    let payload_ptr =
+                        value.to_jit(self.arena, self.registry, &info.variants[*tag])?;
 
                     let ptr = self.arena.alloc_layout(layout);
                     unsafe {
@@ -334,10 +685,7 @@ impl<'a> ArgumentMapper<'a> {
                         }
                     }
 
-                    let offset = tag_layout.extend(variant_layouts[*tag]).unwrap().1;
-                    let payload_ptr = value
-                        .to_jit(self.arena, self.registry, &info.variants[*tag])
-                        .unwrap();
+                    let offset = tag_layout.extend(variant_layouts[*tag])?.1;
                     unsafe {
                         std::ptr::copy_nonoverlapping(
                             payload_ptr.cast::<u8>().as_ptr(),
@@ -355,7 +703,7 @@ impl<'a> ArgumentMapper<'a> {
                     }
 
                     // Write the payload.
-                    let type_info = self.registry.get_type(&info.variants[*tag]).unwrap();
+                    let type_info = self.registry.get_type(&info.variants[*tag])?;
                     self.push(&info.variants[*tag], type_info, value)?;
                 }
             }
@@ -371,20 +719,32 @@ impl<'a> ArgumentMapper<'a> {
             ) => {
                 self.push_aligned(get_integer_layout(252).align(), &value.to_le_digits());
             }
+            (CoreTypeConcrete::Bytes31(_), JitValue::Bytes31(value)) => {
+                let mut data = [0u8; 32];
+                data[..31].copy_from_slice(value);
+
+                self.push_aligned(
+                    get_integer_layout(248).align(),
+                    &data
+                        .chunks_exact(8)
+                        .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+                        .collect::<Vec<_>>(),
+                );
+            }
             (CoreTypeConcrete::Felt252Dict(_), JitValue::Felt252Dict { .. }) => {
                 #[cfg(not(feature = "cairo-native-runtime"))]
                 unimplemented!("enable the `cairo-native-runtime` feature to use felt252 dicts");
 
                 // TODO: Assert that `info.ty` matches all the values' types.
 
-                self.invoke_data.push(
-                    value
-                        .to_jit(self.arena, self.registry, type_id)
-                        .unwrap()
-                        .as_ptr() as u64,
-                );
+                self.invoke_data
+                    .push(value.to_jit(self.arena, self.registry, type_id)?.as_ptr() as u64);
             }
             (CoreTypeConcrete::Struct(info), JitValue::Struct { fields, .. }) => {
+                if fields.len() != info.members.len() {
+                    return Err(unexpected_value());
+                }
+
                 for (field_type_id, field_value) in info.members.iter().zip(fields) {
                     self.push(
                         field_type_id,
@@ -443,44 +803,7 @@ impl<'a> ArgumentMapper<'a> {
             | (CoreTypeConcrete::Poseidon(_), _)
             | (CoreTypeConcrete::RangeCheck(_), _)
             | (CoreTypeConcrete::SegmentArena(_), _) => {}
-            (sierra_ty, arg_ty) => match sierra_ty {
-                CoreTypeConcrete::Array(_) => todo!("Array {arg_ty:?}"),
-                CoreTypeConcrete::Bitwise(_) => todo!("Bitwise {arg_ty:?}"),
-                CoreTypeConcrete::Box(_) => todo!("Box {arg_ty:?}"),
-                CoreTypeConcrete::EcOp(_) => todo!("EcOp {arg_ty:?}"),
-                CoreTypeConcrete::EcPoint(_) => todo!("EcPoint {arg_ty:?}"),
-                CoreTypeConcrete::EcState(_) => todo!("EcState {arg_ty:?}"),
-                CoreTypeConcrete::Felt252(_) => todo!("Felt252 {arg_ty:?}"),
-                CoreTypeConcrete::GasBuiltin(_) => todo!("GasBuiltin {arg_ty:?}"),
-                CoreTypeConcrete::BuiltinCosts(_) => todo!("BuiltinCosts {arg_ty:?}"),
-                CoreTypeConcrete::Uint8(_) => todo!("Uint8 {arg_ty:?}"),
-                CoreTypeConcrete::Uint16(_) => todo!("Uint16 {arg_ty:?}"),
-                CoreTypeConcrete::Uint32(_) => todo!("Uint32 {arg_ty:?}"),
-                CoreTypeConcrete::Uint64(_) => todo!("Uint64 {arg_ty:?}"),
-                CoreTypeConcrete::Uint128(_) => todo!("Uint128 {arg_ty:?}"),
-                CoreTypeConcrete::Uint128MulGuarantee(_) => todo!("Uint128MulGuarantee {arg_ty:?}"),
-                CoreTypeConcrete::Sint8(_) => todo!("Sint8 {arg_ty:?}"),
-                CoreTypeConcrete::Sint16(_) => todo!("Sint16 {arg_ty:?}"),
-                CoreTypeConcrete::Sint32(_) => todo!("Sint32 {arg_ty:?}"),
-                CoreTypeConcrete::Sint64(_) => todo!("Sint64 {arg_ty:?}"),
-                CoreTypeConcrete::Sint128(_) => todo!("Sint128 {arg_ty:?}"),
-                CoreTypeConcrete::NonZero(_) => todo!("NonZero {arg_ty:?}"),
-                CoreTypeConcrete::Nullable(_) => todo!("Nullable {arg_ty:?}"),
-                CoreTypeConcrete::RangeCheck(_) => todo!("RangeCheck {arg_ty:?}"),
-                CoreTypeConcrete::Uninitialized(_) => todo!("Uninitialized {arg_ty:?}"),
-                CoreTypeConcrete::Enum(_) => todo!("Enum {arg_ty:?}"),
-                CoreTypeConcrete::Struct(_) => todo!("Struct {arg_ty:?}"),
-                CoreTypeConcrete::Felt252Dict(_) => todo!("Felt252Dict {arg_ty:?}"),
-                CoreTypeConcrete::Felt252DictEntry(_) => todo!("Felt252DictEntry {arg_ty:?}"),
-                CoreTypeConcrete::SquashedFelt252Dict(_) => todo!("SquashedFelt252Dict {arg_ty:?}"),
-                CoreTypeConcrete::Pedersen(_) => todo!("Pedersen {arg_ty:?}"),
-                CoreTypeConcrete::Poseidon(_) => todo!("Poseidon {arg_ty:?}"),
-                CoreTypeConcrete::Span(_) => todo!("Span {arg_ty:?}"),
-                CoreTypeConcrete::StarkNet(_) => todo!("StarkNet {arg_ty:?}"),
-                CoreTypeConcrete::SegmentArena(_) => todo!("SegmentArena {arg_ty:?}"),
-                CoreTypeConcrete::Snapshot(_) => todo!("Snapshot {arg_ty:?}"),
-                CoreTypeConcrete::Bytes31(_) => todo!("Bytes31 {arg_ty:?}"),
-            },
+            _ => return Err(unexpected_value()),
         }
 
         Ok(())
@@ -493,11 +816,13 @@ fn parse_result(
     return_ptr: Option<NonNull<()>>,
     #[cfg(target_arch = "x86_64")] ret_registers: [u64; 2],
     #[cfg(target_arch = "aarch64")] ret_registers: [u64; 4],
-) -> JitValue {
-    let type_info = registry.get_type(type_id).unwrap();
+) -> Result<JitValue, RunnerError> {
+    let type_info = registry.get_type(type_id)?;
 
-    match type_info {
-        CoreTypeConcrete::Array(_) => JitValue::from_jit(return_ptr.unwrap(), type_id, registry),
+    Ok(match type_info {
+        CoreTypeConcrete::Array(_) | CoreTypeConcrete::Span(_) => {
+            JitValue::from_jit(return_ptr.unwrap(), type_id, registry)
+        }
         CoreTypeConcrete::Box(info) => unsafe {
             let ptr = return_ptr.unwrap_or(NonNull::new_unchecked(ret_registers[0] as *mut ()));
             let value = JitValue::from_jit(ptr, &info.ty, registry);
@@ -506,6 +831,22 @@ fn parse_result(
         },
         CoreTypeConcrete::EcPoint(_) => JitValue::from_jit(return_ptr.unwrap(), type_id, registry),
         CoreTypeConcrete::EcState(_) => JitValue::from_jit(return_ptr.unwrap(), type_id, registry),
+        CoreTypeConcrete::StarkNet(StarkNetTypeConcrete::Secp256Point(_)) => {
+            JitValue::from_jit(return_ptr.unwrap(), type_id, registry)
+        }
+        CoreTypeConcrete::Bytes31(_) => {
+            #[cfg(target_arch = "x86_64")]
+            let value = JitValue::from_jit(return_ptr.unwrap(), type_id, registry);
+
+            #[cfg(target_arch = "aarch64")]
+            let value = JitValue::Bytes31(
+                unsafe { std::mem::transmute::<&[u64; 4], &[u8; 32]>(&ret_registers) }[..31]
+                    .try_into()
+                    .unwrap(),
+            );
+
+            value
+        }
         CoreTypeConcrete::Felt252(_)
         | CoreTypeConcrete::StarkNet(
             StarkNetTypeConcrete::ClassHash(_)
@@ -545,7 +886,7 @@ fn parse_result(
                 JitValue::Uint128(((ret_registers[1] as u128) << 64) | ret_registers[0] as u128)
             }
         },
-        CoreTypeConcrete::Uint128MulGuarantee(_) => todo!(),
+        CoreTypeConcrete::Uint128MulGuarantee(_) => JitValue::Uint128MulGuarantee,
         CoreTypeConcrete::Sint8(_) => match return_ptr {
             Some(return_ptr) => JitValue::Sint8(unsafe { *return_ptr.cast().as_ref() }),
             None => JitValue::Sint8(ret_registers[0] as i8),
@@ -569,7 +910,7 @@ fn parse_result(
             }
         },
         CoreTypeConcrete::NonZero(info) => {
-            parse_result(&info.ty, registry, return_ptr, ret_registers)
+            parse_result(&info.ty, registry, return_ptr, ret_registers)?
         }
         CoreTypeConcrete::Nullable(info) => unsafe {
             let ptr = return_ptr.map_or(ret_registers[0] as *mut (), |x| {
@@ -584,7 +925,7 @@ fn parse_result(
                 value
             }
         },
-        CoreTypeConcrete::Uninitialized(_) => todo!(),
+        CoreTypeConcrete::Uninitialized(_) => JitValue::Uninitialized,
         CoreTypeConcrete::Enum(info) => {
             let (_, tag_layout, variant_layouts) =
                 crate::types::r#enum::get_layout_for_variants(registry, &info.variants).unwrap();
@@ -646,19 +987,347 @@ fn parse_result(
                 JitValue::from_jit(return_ptr.unwrap(), type_id, registry)
             }
         }
-        CoreTypeConcrete::Felt252Dict(_) => match return_ptr {
-            Some(return_ptr) => JitValue::from_jit(return_ptr, type_id, registry),
-            None => JitValue::from_jit(
-                NonNull::new(ret_registers[0] as *mut ()).unwrap(),
-                type_id,
-                registry,
-            ),
-        },
-        CoreTypeConcrete::Felt252DictEntry(_) => todo!(),
-        CoreTypeConcrete::SquashedFelt252Dict(_) => todo!(),
-        CoreTypeConcrete::Span(_) => todo!(),
-        CoreTypeConcrete::Snapshot(_) => todo!(),
-        CoreTypeConcrete::Bytes31(_) => todo!(),
-        _ => unreachable!(),
+        CoreTypeConcrete::Felt252Dict(_) | CoreTypeConcrete::SquashedFelt252Dict(_) => {
+            match return_ptr {
+                Some(return_ptr) => JitValue::from_jit(return_ptr, type_id, registry),
+                None => JitValue::from_jit(
+                    NonNull::new(ret_registers[0] as *mut ()).unwrap(),
+                    type_id,
+                    registry,
+                ),
+            }
+        }
+        CoreTypeConcrete::Felt252DictEntry(_) => {
+            return Err(make_unsupported_return_type_error(type_id));
+        }
+        CoreTypeConcrete::Snapshot(info) => {
+            parse_result(&info.ty, registry, return_ptr, ret_registers)?
+        }
+        _ => return Err(make_unsupported_return_type_error(type_id)),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        context::NativeContext,
+        ffi::OptLevel,
+        utils::{find_function_id, test::load_cairo},
+    };
+    use cairo_lang_sierra::program::Program;
+    use starknet_types_core::felt::Felt;
+
+    fn invoke(
+        (module_name, program): &(String, Program),
+        args: &[JitValue],
+        gas: Option<u128>,
+    ) -> Result<ExecutionResult, RunnerError> {
+        let context = NativeContext::new();
+        let executor = JitNativeExecutor::new(
+            context.compile(program, OptLevel::default()).unwrap(),
+            OptLevel::default(),
+        );
+
+        executor.invoke_dynamic(
+            find_function_id(program, &format!("{0}::{0}::run_test", module_name)),
+            args,
+            gas,
+            None,
+        )
+    }
+
+    #[test]
+    fn insufficient_gas() {
+        let program = load_cairo! {
+            fn run_test() -> felt252 {
+                fib(0, 1, 10)
+            }
+
+            fn fib(a: felt252, b: felt252, n: felt252) -> felt252 {
+                match n {
+                    0 => a,
+                    _ => fib(b, a + b, n - 1),
+                }
+            }
+        };
+
+        let error = invoke(&program, &[], Some(0)).unwrap_err();
+        assert!(matches!(
+            error.source,
+            ErrorImpl::InsufficientGasError { have: 0, .. }
+        ));
+
+        let error = invoke(&program, &[], None).unwrap_err();
+        assert!(matches!(error.source, ErrorImpl::MissingGas));
+    }
+
+    #[test]
+    fn invalid_contract_entry_point() {
+        let (module_name, program) = load_cairo! {
+            fn run_test(a: felt252) -> felt252 {
+                a
+            }
+        };
+
+        let context = NativeContext::new();
+        let executor = JitNativeExecutor::new(
+            context.compile(&program, OptLevel::default()).unwrap(),
+            OptLevel::default(),
+        );
+
+        let error = executor
+            .invoke_contract_dynamic(
+                find_function_id(&program, &format!("{0}::{0}::run_test", module_name)),
+                &[Felt::from(1)],
+                None,
+                None,
+            )
+            .unwrap_err();
+        assert!(matches!(
+            error.source,
+            ErrorImpl::InvalidContractEntryPoint { .. }
+        ));
+    }
+
+    #[test]
+    fn invalid_contract_entry_point_non_builtin_param() {
+        let (module_name, program) = load_cairo! {
+            use starknet::{ContractAddress, SyscallResultTrait};
+
+            // Has the `System` builtin and the return value of a contract entry point.
+            fn run_test(address: ContractAddress, data: Span<felt252>) -> Span<felt252> {
+                starknet::get_block_hash_syscall(0).unwrap_syscall();
+                data
+            }
+        };
+
+        let context = NativeContext::new();
+        let executor = JitNativeExecutor::new(
+            context.compile(&program, OptLevel::default()).unwrap(),
+            OptLevel::default(),
+        );
+
+        let error = executor
+            .invoke_contract_dynamic(
+                find_function_id(&program, &format!("{0}::{0}::run_test", module_name)),
+                &[Felt::from(1)],
+                None,
+                None,
+            )
+            .unwrap_err();
+        assert!(matches!(
+            error.source,
+            ErrorImpl::InvalidContractEntryPoint { ref reason, .. }
+                if reason.contains("`Span<felt252>`")
+        ));
+    }
+
+    #[test]
+    fn missing_syscall_handler() {
+        let program = load_cairo! {
+            use starknet::SyscallResultTrait;
+
+            fn run_test() -> felt252 {
+                starknet::get_block_hash_syscall(0).unwrap_syscall()
+            }
+        };
+
+        let error = invoke(&program, &[], Some(u128::MAX)).unwrap_err();
+        assert!(matches!(error.source, ErrorImpl::MissingSyscallHandler));
+    }
+
+    #[test]
+    fn argument_mismatch() {
+        let program = load_cairo! {
+            fn run_test(lhs: u8, rhs: felt252) -> felt252 {
+                lhs.into() + rhs
+            }
+        };
+
+        let error = invoke(&program, &[JitValue::Uint8(1)], None).unwrap_err();
+        assert!(matches!(
+            error.source,
+            ErrorImpl::ArgumentCountMismatch {
+                expected: 2,
+                got: 1
+            }
+        ));
+
+        let error = invoke(
+            &program,
+            &[
+                JitValue::Felt252(Felt::from(1)),
+                JitValue::Felt252(Felt::from(1)),
+            ],
+            None,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error.source,
+            ErrorImpl::ArgumentTypeMismatch { param_index: 0, .. }
+        ));
+
+        let result = invoke(
+            &program,
+            &[JitValue::Uint8(1), JitValue::Felt252(Felt::from(1))],
+            None,
+        )
+        .unwrap();
+        assert_eq!(result.return_value, JitValue::Felt252(Felt::from(2)));
+    }
+
+    #[test]
+    fn secp256_point_argument_mismatch() {
+        let program = load_cairo! {
+            use starknet::secp256r1::Secp256r1Point;
+
+            fn run_test(p: Secp256r1Point) -> Secp256r1Point {
+                p
+            }
+        };
+
+        let error = invoke(
+            &program,
+            &[JitValue::Secp256K1Point {
+                x: (1, 0),
+                y: (2, 0),
+            }],
+            None,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error.source,
+            ErrorImpl::ArgumentTypeMismatch { param_index: 0, .. }
+        ));
+
+        let value = JitValue::Secp256R1Point {
+            x: (1, 0),
+            y: (2, 0),
+        };
+        let result = invoke(&program, &[value.clone()], None).unwrap();
+        assert_eq!(result.return_value, value);
+    }
+
+    #[test]
+    fn contract_address_argument() {
+        let program = load_cairo! {
+            use starknet::{ContractAddress, contract_address_to_felt252};
+
+            fn run_test(address: ContractAddress, offset: felt252) -> felt252 {
+                contract_address_to_felt252(address) + offset
+            }
+        };
+
+        // The contract address is a value, not a builtin, so it has to be passed explicitly.
+        let error = invoke(&program, &[JitValue::Felt252(Felt::from(1))], None).unwrap_err();
+        assert!(matches!(
+            error.source,
+            ErrorImpl::ArgumentCountMismatch {
+                expected: 2,
+                got: 1
+            }
+        ));
+
+        let result = invoke(
+            &program,
+            &[
+                JitValue::Felt252(Felt::from(1234)),
+                JitValue::Felt252(Felt::from(1)),
+            ],
+            None,
+        )
+        .unwrap();
+        assert_eq!(result.return_value, JitValue::Felt252(Felt::from(1235)));
+    }
+
+    #[test]
+    fn malformed_argument_mismatch() {
+        let program = load_cairo! {
+            #[derive(Drop)]
+            enum MyEnum {
+                A: u8,
+                B: felt252,
+            }
+
+            #[derive(Drop)]
+            struct MyStruct {
+                a: u8,
+                b: felt252,
+            }
+
+            fn run_test(e: MyEnum, s: MyStruct, a: Array<u8>) -> u32 {
+                a.len()
+            }
+        };
+
+        let valid_enum = JitValue::Enum {
+            tag: 0,
+            value: Box::new(JitValue::Uint8(1)),
+            debug_name: None,
+        };
+        let valid_struct = JitValue::Struct {
+            fields: vec![JitValue::Uint8(1), JitValue::Felt252(Felt::from(2))],
+            debug_name: None,
+        };
+        let valid_array = JitValue::Array(vec![JitValue::Uint8(1), JitValue::Uint8(2)]);
+
+        // Enum tag out of range.
+        let error = invoke(
+            &program,
+            &[
+                JitValue::Enum {
+                    tag: 2,
+                    value: Box::new(JitValue::Uint8(1)),
+                    debug_name: None,
+                },
+                valid_struct.clone(),
+                valid_array.clone(),
+            ],
+            None,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error.source,
+            ErrorImpl::ArgumentTypeMismatch { param_index: 0, .. }
+        ));
+
+        // Struct with a missing member.
+        let error = invoke(
+            &program,
+            &[
+                valid_enum.clone(),
+                JitValue::Struct {
+                    fields: vec![JitValue::Uint8(1)],
+                    debug_name: None,
+                },
+                valid_array.clone(),
+            ],
+            None,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error.source,
+            ErrorImpl::ArgumentTypeMismatch { param_index: 1, .. }
+        ));
+
+        // Array element of the wrong type.
+        let error = invoke(
+            &program,
+            &[
+                valid_enum.clone(),
+                valid_struct.clone(),
+                JitValue::Array(vec![JitValue::Uint8(1), JitValue::Felt252(Felt::from(2))]),
+            ],
+            None,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error.source,
+            ErrorImpl::ArgumentTypeMismatch { param_index: 2, .. }
+        ));
+
+        let result = invoke(&program, &[valid_enum, valid_struct, valid_array], None).unwrap();
+        assert_eq!(result.return_value, JitValue::Uint32(2));
     }
 }
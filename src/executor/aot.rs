@@ -1,6 +1,10 @@
+use super::{host_set_syscall_handler, SetSyscallHandlerFn};
 use crate::{
-    error::jit_engine::RunnerError,
+    error::jit_engine::{
+        make_function_not_compiled_error, make_insufficient_gas_error, RunnerError,
+    },
     execution_result::{ContractExecutionResult, ExecutionResult},
+    ffi::{InMemoryObject, LinkError},
     metadata::{gas::GasMetadata, syscall_handler::SyscallHandlerMeta},
     utils::generate_function_name,
     values::JitValue,
@@ -15,11 +19,17 @@ use libc::c_void;
 use libloading::Library;
 use starknet_types_core::felt::Felt;
 
+/// An executor for AOT-compiled programs.
+///
+/// It is both `Send` and `Sync`, so a single loaded library can be shared (for example, through an
+/// `Arc`) and invoked from multiple threads at once.
 pub struct AotNativeExecutor {
-    library: Library,
+    code: LoadedCode,
     registry: ProgramRegistry<CoreType, CoreLibfunc>,
 
     gas_metadata: Option<GasMetadata>,
+    builtin_counters: bool,
+    set_syscall_handler: Option<SetSyscallHandlerFn>,
 }
 
 impl AotNativeExecutor {
@@ -28,31 +38,85 @@ impl AotNativeExecutor {
         registry: ProgramRegistry<CoreType, CoreLibfunc>,
         gas_metadata: Option<GasMetadata>,
     ) -> Self {
+        // The library links its own copy of the runtime (and therefore of the syscall handler used
+        // by the testing libfuncs) unless it's linked dynamically.
+        let set_syscall_handler =
+            unsafe { library.get::<SetSyscallHandlerFn>(b"cairo_native__set_syscall_handler") }
+                .ok()
+                .map(|symbol| *symbol)
+                .or_else(host_set_syscall_handler);
+
         Self {
-            library,
+            code: LoadedCode::Library(library),
             registry,
             gas_metadata,
+            builtin_counters: false,
+            set_syscall_handler,
         }
     }
+
+    /// Load an object file (as returned by [`module_to_object`](crate::module_to_object))
+    /// directly into memory, without linking it into a shared library first.
+    ///
+    /// Runtime symbols are resolved by the loader itself, therefore the `with-runtime` feature
+    /// should be enabled.
+    pub fn from_object_bytes(
+        object: &[u8],
+        registry: ProgramRegistry<CoreType, CoreLibfunc>,
+        gas_metadata: Option<GasMetadata>,
+    ) -> Result<Self, LinkError> {
+        Ok(Self {
+            code: LoadedCode::InMemory(InMemoryObject::load(object)?),
+            registry,
+            gas_metadata,
+            builtin_counters: false,
+            // Runtime symbols are resolved against the current process.
+            set_syscall_handler: host_set_syscall_handler(),
+        })
+    }
+
+    /// Tell the executor whether the code was compiled with the builtin counters enabled, which
+    /// changes the calling convention of the builtins. Defaults to `false`.
+    ///
+    /// Check out [`NativeContext::with_builtin_counters`](crate::context::NativeContext::with_builtin_counters)
+    /// for more information.
+    pub fn with_builtin_counters(mut self, builtin_counters: bool) -> Self {
+        self.builtin_counters = builtin_counters;
+        self
+    }
+}
+
+/// Where the program's machine code lives.
+enum LoadedCode {
+    Library(Library),
+    InMemory(InMemoryObject),
 }
 
 impl AotNativeExecutor {
+    pub fn program_registry(&self) -> &ProgramRegistry<CoreType, CoreLibfunc> {
+        &self.registry
+    }
+
     pub fn invoke_dynamic(
         &self,
         function_id: &FunctionId,
         args: &[JitValue],
         mut gas: Option<u128>,
         syscall_handler: Option<&SyscallHandlerMeta>,
-    ) -> ExecutionResult {
-        self.process_required_initial_gas(function_id, gas.as_mut());
+    ) -> Result<ExecutionResult, RunnerError> {
+        let function_ptr = self.find_function_ptr(function_id)?;
+        let function_signature = self.extract_signature(function_id)?;
+        self.process_required_initial_gas(function_id, gas.as_mut())?;
 
         super::invoke_dynamic(
             &self.registry,
-            self.find_function_ptr(function_id),
-            self.extract_signature(function_id),
+            function_ptr,
+            function_signature,
             args,
             gas,
             syscall_handler.map(SyscallHandlerMeta::as_ptr),
+            self.set_syscall_handler,
+            self.builtin_counters,
         )
     }
 
@@ -63,44 +127,61 @@ impl AotNativeExecutor {
         mut gas: Option<u128>,
         syscall_handler: Option<&SyscallHandlerMeta>,
     ) -> Result<ContractExecutionResult, RunnerError> {
-        self.process_required_initial_gas(function_id, gas.as_mut());
+        let function_ptr = self.find_function_ptr(function_id)?;
+        let function_signature = self.extract_signature(function_id)?;
+        self.process_required_initial_gas(function_id, gas.as_mut())?;
+
+        let args = super::contract_entry_point_args(
+            &self.registry,
+            function_id,
+            function_signature,
+            args,
+        )?;
 
-        // TODO: Check signature for contract interface.
         ContractExecutionResult::from_execution_result(super::invoke_dynamic(
             &self.registry,
-            self.find_function_ptr(function_id),
-            self.extract_signature(function_id),
-            &[JitValue::Struct {
-                fields: vec![JitValue::Array(
-                    args.iter().cloned().map(JitValue::Felt252).collect(),
-                )],
-                // TODO: Populate `debug_name`.
-                debug_name: None,
-            }],
+            function_ptr,
+            function_signature,
+            &[args],
             gas,
             syscall_handler.map(SyscallHandlerMeta::as_ptr),
-        ))
+            self.set_syscall_handler,
+            self.builtin_counters,
+        )?)
     }
 
-    fn find_function_ptr(&self, function_id: &FunctionId) -> *mut c_void {
+    fn find_function_ptr(&self, function_id: &FunctionId) -> Result<*mut c_void, RunnerError> {
         let function_name = generate_function_name(function_id);
         let function_name = format!("_mlir_ciface_{function_name}");
 
         // Arguments and return values are hardcoded since they'll be handled by the trampoline.
-        unsafe {
-            self.library
-                .get::<extern "C" fn()>(function_name.as_bytes())
-                .unwrap()
-                .into_raw()
-                .into_raw()
-        }
+        let function_ptr = match &self.code {
+            LoadedCode::Library(library) => unsafe {
+                library
+                    .get::<extern "C" fn()>(function_name.as_bytes())
+                    .ok()
+                    .map(|symbol| symbol.into_raw().into_raw())
+            },
+            LoadedCode::InMemory(object) => object.lookup(&function_name).ok(),
+        };
+
+        function_ptr
+            .filter(|function_ptr| !function_ptr.is_null())
+            .ok_or_else(|| make_function_not_compiled_error(function_id))
     }
 
-    fn extract_signature(&self, function_id: &FunctionId) -> &FunctionSignature {
-        &self.registry.get_function(function_id).unwrap().signature
+    fn extract_signature(
+        &self,
+        function_id: &FunctionId,
+    ) -> Result<&FunctionSignature, RunnerError> {
+        Ok(&self.registry.get_function(function_id)?.signature)
     }
 
-    fn process_required_initial_gas(&self, function_id: &FunctionId, gas: Option<&mut u128>) {
+    fn process_required_initial_gas(
+        &self,
+        function_id: &FunctionId,
+        gas: Option<&mut u128>,
+    ) -> Result<(), RunnerError> {
         if let (Some(gas), Some(required_init_gas)) = (
             gas,
             self.gas_metadata
@@ -108,10 +189,224 @@ impl AotNativeExecutor {
                 .and_then(|gas_metadata| gas_metadata.get_initial_required_gas(function_id)),
         ) {
             if required_init_gas > *gas {
-                panic!("Not enough gas");
+                return Err(make_insufficient_gas_error(required_init_gas, *gas));
             }
 
             *gas -= required_init_gas;
         }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        context::NativeContext,
+        module_to_object, object_to_shared_lib,
+        starknet::InMemorySyscallHandler,
+        utils::{find_function_id, test::load_cairo, SHARED_LIBRARY_EXT},
+        OptLevel,
+    };
+
+    #[test]
+    fn test_from_object_bytes() {
+        let (module_name, program) = load_cairo! {
+            use core::dict::Felt252DictTrait;
+
+            fn run_test(n: felt252) -> felt252 {
+                let mut dict: Felt252Dict<felt252> = Default::default();
+                dict.insert(1, n);
+                dict.insert(2, fib(0, 1, n));
+                dict.get(1) + dict.get(2)
+            }
+
+            fn fib(a: felt252, b: felt252, n: felt252) -> felt252 {
+                match n {
+                    0 => a,
+                    _ => fib(b, a + b, n - 1),
+                }
+            }
+        };
+        let function_id = find_function_id(&program, &format!("{0}::{0}::run_test", module_name));
+
+        let context = NativeContext::new();
+        let native_module = context.compile(&program, OptLevel::default()).unwrap();
+        let object = module_to_object(native_module.module(), OptLevel::default()).unwrap();
+
+        let executor = AotNativeExecutor::from_object_bytes(
+            &object,
+            native_module.registry,
+            native_module.metadata.get::<GasMetadata>().cloned(),
+        )
+        .unwrap();
+
+        let result = executor
+            .invoke_dynamic(
+                function_id,
+                &[JitValue::Felt252(Felt::from(10))],
+                Some(u128::MAX),
+                None,
+            )
+            .unwrap();
+        assert_eq!(result.return_value, JitValue::Felt252(Felt::from(65)));
+    }
+
+    #[test]
+    fn test_testing_syscall_from_library() {
+        let (module_name, program) = load_cairo! {
+            use box::BoxTrait;
+            use starknet::{get_block_info, testing::set_block_number};
+
+            fn run_test() -> u64 {
+                set_block_number(1234);
+                get_block_info().unbox().block_number
+            }
+        };
+        let function_id = find_function_id(&program, &format!("{0}::{0}::run_test", module_name));
+
+        let context = NativeContext::new();
+        let native_module = context.compile(&program, OptLevel::default()).unwrap();
+        let object = module_to_object(native_module.module(), OptLevel::default()).unwrap();
+
+        let library_path = tempfile::Builder::new()
+            .prefix("lib")
+            .suffix(&format!(".{SHARED_LIBRARY_EXT}"))
+            .tempfile()
+            .unwrap()
+            .into_temp_path();
+        object_to_shared_lib(&object, &library_path).unwrap();
+
+        let executor = AotNativeExecutor::new(
+            unsafe { Library::new(&library_path).unwrap() },
+            native_module.registry,
+            native_module.metadata.get::<GasMetadata>().cloned(),
+        );
+
+        // The library's runtime has to see the syscall handler too.
+        let mut syscall_handler = InMemorySyscallHandler::new();
+        let result = executor
+            .invoke_dynamic(
+                function_id,
+                &[],
+                Some(u128::MAX),
+                Some(&SyscallHandlerMeta::new(&mut syscall_handler)),
+            )
+            .unwrap();
+        assert_eq!(
+            result.return_value,
+            JitValue::Enum {
+                tag: 0,
+                value: Box::new(JitValue::Struct {
+                    fields: vec![JitValue::Uint64(1234)],
+                    debug_name: None,
+                }),
+                debug_name: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_missing_function() {
+        let (_, program) = load_cairo! {
+            fn run_test() -> felt252 {
+                1
+            }
+        };
+        let (other_module_name, other_program) = load_cairo! {
+            fn run_test() -> felt252 {
+                2
+            }
+        };
+
+        let context = NativeContext::new();
+        let native_module = context.compile(&program, OptLevel::default()).unwrap();
+        let object = module_to_object(native_module.module(), OptLevel::default()).unwrap();
+
+        // The registry knows about the function, but the object doesn't contain it.
+        let executor = AotNativeExecutor::from_object_bytes(
+            &object,
+            ProgramRegistry::new(&other_program).unwrap(),
+            None,
+        )
+        .unwrap();
+
+        let error = executor
+            .invoke_dynamic(
+                find_function_id(
+                    &other_program,
+                    &format!("{0}::{0}::run_test", other_module_name),
+                ),
+                &[],
+                None,
+                None,
+            )
+            .unwrap_err();
+        assert!(matches!(
+            error.source,
+            crate::error::jit_engine::ErrorImpl::FunctionNotCompiled(_)
+        ));
+    }
+
+    #[test]
+    fn test_compile_with_entry_points() {
+        let (module_name, program) = load_cairo! {
+            fn run_test(n: felt252) -> felt252 {
+                double(n)
+            }
+
+            #[inline(never)]
+            fn double(n: felt252) -> felt252 {
+                n * 2
+            }
+
+            fn unused(n: felt252) -> felt252 {
+                n + 1
+            }
+        };
+        let run_test_id = find_function_id(&program, &format!("{0}::{0}::run_test", module_name));
+        let unused_id = find_function_id(&program, &format!("{0}::{0}::unused", module_name));
+
+        let context = NativeContext::new();
+        let native_module = context
+            .compile_with_entry_points(&program, &[run_test_id.clone()], OptLevel::default())
+            .unwrap();
+        let object = module_to_object(native_module.module(), OptLevel::default()).unwrap();
+
+        let executor = AotNativeExecutor::from_object_bytes(
+            &object,
+            native_module.registry,
+            native_module.metadata.get::<GasMetadata>().cloned(),
+        )
+        .unwrap();
+
+        let result = executor
+            .invoke_dynamic(
+                run_test_id,
+                &[JitValue::Felt252(Felt::from(21))],
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(result.return_value, JitValue::Felt252(Felt::from(42)));
+
+        let error = executor
+            .invoke_dynamic(unused_id, &[JitValue::Felt252(Felt::from(21))], None, None)
+            .unwrap_err();
+        assert!(matches!(
+            error.source,
+            crate::error::jit_engine::ErrorImpl::FunctionNotCompiled(_)
+        ));
+    }
+
+    #[test]
+    fn test_from_invalid_object_bytes() {
+        let (_, program) = load_cairo! {
+            fn run_test() {}
+        };
+        let registry = ProgramRegistry::new(&program).unwrap();
+
+        assert!(AotNativeExecutor::from_object_bytes(&[0; 16], registry, None).is_err());
     }
 }
@@ -0,0 +1,163 @@
+//! # Starknet contract executor
+//!
+//! Compiles a Sierra contract class and dispatches calls to its entry points by selector, instead
+//! of having to find the entry point's [`FunctionId`] manually.
+
+use super::{JitNativeExecutor, NativeExecutor};
+#[cfg(feature = "with-serde")]
+use crate::cache::{aot::compile_aot_with_disk_cache, DiskCache};
+use crate::{
+    cache::{aot::compile_aot, AotCacheError},
+    context::NativeContext,
+    error::{
+        jit_engine::{make_unknown_entry_point_selector_error, RunnerError},
+        CompileError,
+    },
+    execution_result::ContractExecutionResult,
+    ffi::{LLVMCompileError, LinkError},
+    metadata::syscall_handler::SyscallHandlerMeta,
+    utils::find_entry_point_by_idx,
+    OptLevel,
+};
+use cairo_lang_sierra::{ids::FunctionId, program::Program};
+use cairo_lang_starknet::{contract_class::ContractClass, felt252_serde::Felt252SerdeError};
+use starknet_types_core::felt::Felt;
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// The kind of a contract entry point.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum EntryPointType {
+    External,
+    L1Handler,
+    Constructor,
+}
+
+#[derive(Debug, Error)]
+pub enum ContractLoadError {
+    #[error("invalid sierra program: {0}")]
+    InvalidProgram(#[from] Felt252SerdeError),
+    #[error("entry point with function index {0} not found in the program")]
+    MissingEntryPoint(usize),
+    #[error(transparent)]
+    CompileError(#[from] CompileError),
+    #[error(transparent)]
+    LLVMCompileError(#[from] LLVMCompileError),
+    #[error(transparent)]
+    LinkError(#[from] LinkError),
+    #[error(transparent)]
+    AotCacheError(#[from] AotCacheError),
+}
+
+/// An executor for a compiled Starknet contract class.
+///
+/// The contract class is usually obtained by deserializing its JSON representation (for example,
+/// using `serde_json`).
+pub struct ContractExecutor<'m> {
+    executor: NativeExecutor<'m>,
+    entry_points: HashMap<Felt, (EntryPointType, FunctionId)>,
+}
+
+impl<'m> ContractExecutor<'m> {
+    /// Compile the contract class and execute it using the JIT engine.
+    pub fn new_jit(
+        context: &'m NativeContext,
+        contract_class: &ContractClass,
+        opt_level: OptLevel,
+    ) -> Result<Self, ContractLoadError> {
+        let program = contract_class.extract_sierra_program()?;
+        let entry_points = collect_entry_points(contract_class, &program)?;
+
+        let native_module = context.compile(&program, opt_level)?;
+
+        Ok(Self {
+            executor: JitNativeExecutor::new(native_module, opt_level).into(),
+            entry_points,
+        })
+    }
+
+    /// Compile the contract class into a shared library and load it for execution.
+    ///
+    /// The contract is compiled using the context's settings, like an
+    /// [`AotProgramCache`](crate::cache::AotProgramCache) would.
+    pub fn new_aot(
+        context: &NativeContext,
+        contract_class: &ContractClass,
+        opt_level: OptLevel,
+    ) -> Result<Self, ContractLoadError> {
+        let program = contract_class.extract_sierra_program()?;
+        let entry_points = collect_entry_points(contract_class, &program)?;
+
+        Ok(Self {
+            executor: compile_aot(context, &program, opt_level)?.into(),
+            entry_points,
+        })
+    }
+
+    /// Same as [`new_aot`](Self::new_aot), but the compiled contract is loaded from (or stored
+    /// into) a [`DiskCache`].
+    #[cfg(feature = "with-serde")]
+    pub fn new_aot_with_disk_cache(
+        context: &NativeContext,
+        disk_cache: &DiskCache,
+        contract_class: &ContractClass,
+        opt_level: OptLevel,
+    ) -> Result<Self, ContractLoadError> {
+        let program = contract_class.extract_sierra_program()?;
+        let entry_points = collect_entry_points(contract_class, &program)?;
+
+        Ok(Self {
+            executor: compile_aot_with_disk_cache(context, &program, opt_level, disk_cache)?.into(),
+            entry_points,
+        })
+    }
+
+    /// Return the type of the entry point with the given selector, if it exists.
+    pub fn entry_point_type(&self, selector: &Felt) -> Option<EntryPointType> {
+        self.entry_points.get(selector).map(|(ty, _)| *ty)
+    }
+
+    /// Call the entry point with the given selector.
+    pub fn call(
+        &self,
+        selector: &Felt,
+        calldata: &[Felt],
+        gas: Option<u128>,
+        syscall_handler: Option<&SyscallHandlerMeta>,
+    ) -> Result<ContractExecutionResult, RunnerError> {
+        let (_, function_id) = self
+            .entry_points
+            .get(selector)
+            .ok_or_else(|| make_unknown_entry_point_selector_error(selector))?;
+
+        self.executor
+            .invoke_contract_dynamic(function_id, calldata, gas, syscall_handler)
+    }
+}
+
+fn collect_entry_points(
+    contract_class: &ContractClass,
+    program: &Program,
+) -> Result<HashMap<Felt, (EntryPointType, FunctionId)>, ContractLoadError> {
+    let entry_points = &contract_class.entry_points_by_type;
+    let entry_points = [
+        (EntryPointType::External, &entry_points.external),
+        (EntryPointType::L1Handler, &entry_points.l1_handler),
+        (EntryPointType::Constructor, &entry_points.constructor),
+    ];
+
+    entry_points
+        .into_iter()
+        .flat_map(|(ty, entry_points)| entry_points.iter().map(move |x| (ty, x)))
+        .map(|(ty, entry_point)| {
+            let function = find_entry_point_by_idx(program, entry_point.function_idx).ok_or(
+                ContractLoadError::MissingEntryPoint(entry_point.function_idx),
+            )?;
+
+            Ok((
+                Felt::from_bytes_be_slice(&entry_point.selector.to_bytes_be()),
+                (ty, function.id.clone()),
+            ))
+        })
+        .collect()
+}
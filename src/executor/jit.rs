@@ -1,7 +1,13 @@
 use crate::{
-    error::jit_engine::RunnerError,
+    error::jit_engine::{
+        make_function_not_compiled_error, make_insufficient_gas_error, RunnerError,
+    },
     execution_result::{ContractExecutionResult, ExecutionResult},
-    metadata::{gas::GasMetadata, syscall_handler::SyscallHandlerMeta},
+    ffi::OptLevel,
+    metadata::{
+        builtin_counters::BuiltinCountersMeta, gas::GasMetadata,
+        syscall_handler::SyscallHandlerMeta,
+    },
     module::NativeModule,
     utils::{create_engine, generate_function_name},
     values::JitValue,
@@ -24,10 +30,11 @@ pub struct JitNativeExecutor<'m> {
     registry: ProgramRegistry<CoreType, CoreLibfunc>,
 
     gas_metadata: Option<GasMetadata>,
+    builtin_counters: bool,
 }
 
 impl<'m> JitNativeExecutor<'m> {
-    pub fn new(native_module: NativeModule<'m>) -> Self {
+    pub fn new(native_module: NativeModule<'m>, opt_level: OptLevel) -> Self {
         let NativeModule {
             module,
             registry,
@@ -35,10 +42,11 @@ impl<'m> JitNativeExecutor<'m> {
         } = native_module;
 
         Self {
-            engine: create_engine(&module, &metadata),
+            engine: create_engine(&module, &metadata, opt_level),
             module,
             registry,
             gas_metadata: metadata.get::<GasMetadata>().cloned(),
+            builtin_counters: metadata.get::<BuiltinCountersMeta>().is_some(),
         }
     }
 
@@ -60,16 +68,20 @@ impl<'m> JitNativeExecutor<'m> {
         mut gas: Option<u128>,
         syscall_handler: Option<&SyscallHandlerMeta>,
     ) -> Result<ExecutionResult, RunnerError> {
-        self.process_required_initial_gas(function_id, gas.as_mut());
+        let function_ptr = self.find_function_ptr(function_id)?;
+        let function_signature = self.extract_signature(function_id)?;
+        self.process_required_initial_gas(function_id, gas.as_mut())?;
 
-        Ok(super::invoke_dynamic(
+        super::invoke_dynamic(
             &self.registry,
-            self.find_function_ptr(function_id),
-            self.extract_signature(function_id),
+            function_ptr,
+            function_signature,
             args,
             gas,
             syscall_handler.map(SyscallHandlerMeta::as_ptr),
-        ))
+            super::host_set_syscall_handler(),
+            self.builtin_counters,
+        )
     }
 
     pub fn invoke_contract_dynamic(
@@ -79,42 +91,54 @@ impl<'m> JitNativeExecutor<'m> {
         mut gas: Option<u128>,
         syscall_handler: Option<&SyscallHandlerMeta>,
     ) -> Result<ContractExecutionResult, RunnerError> {
-        self.process_required_initial_gas(function_id, gas.as_mut());
+        let function_ptr = self.find_function_ptr(function_id)?;
+        let function_signature = self.extract_signature(function_id)?;
+        self.process_required_initial_gas(function_id, gas.as_mut())?;
+
+        let args = super::contract_entry_point_args(
+            &self.registry,
+            function_id,
+            function_signature,
+            args,
+        )?;
 
-        // TODO: Check signature for contract interface.
         ContractExecutionResult::from_execution_result(super::invoke_dynamic(
             &self.registry,
-            self.find_function_ptr(function_id),
-            self.extract_signature(function_id),
-            &[JitValue::Struct {
-                fields: vec![JitValue::Array(
-                    args.iter().cloned().map(JitValue::Felt252).collect(),
-                )],
-                // TODO: Populate `debug_name`.
-                debug_name: None,
-            }],
+            function_ptr,
+            function_signature,
+            &[args],
             gas,
             syscall_handler.map(SyscallHandlerMeta::as_ptr),
-        ))
+            super::host_set_syscall_handler(),
+            self.builtin_counters,
+        )?)
     }
 
-    fn find_function_ptr(&self, function_id: &FunctionId) -> *mut c_void {
+    fn find_function_ptr(&self, function_id: &FunctionId) -> Result<*mut c_void, RunnerError> {
         let function_name = generate_function_name(function_id);
         let function_name = format!("_mlir_ciface_{function_name}");
 
         // Arguments and return values are hardcoded since they'll be handled by the trampoline.
-        self.engine.lookup(&function_name) as *mut c_void
+        let function_ptr = self.engine.lookup(&function_name) as *mut c_void;
+        if function_ptr.is_null() {
+            return Err(make_function_not_compiled_error(function_id));
+        }
+
+        Ok(function_ptr)
     }
 
-    fn extract_signature(&self, function_id: &FunctionId) -> &FunctionSignature {
-        &self
-            .program_registry()
-            .get_function(function_id)
-            .unwrap()
-            .signature
+    fn extract_signature(
+        &self,
+        function_id: &FunctionId,
+    ) -> Result<&FunctionSignature, RunnerError> {
+        Ok(&self.registry.get_function(function_id)?.signature)
     }
 
-    fn process_required_initial_gas(&self, function_id: &FunctionId, gas: Option<&mut u128>) {
+    fn process_required_initial_gas(
+        &self,
+        function_id: &FunctionId,
+        gas: Option<&mut u128>,
+    ) -> Result<(), RunnerError> {
         if let (Some(gas), Some(required_init_gas)) = (
             gas,
             self.gas_metadata
@@ -122,10 +146,64 @@ impl<'m> JitNativeExecutor<'m> {
                 .and_then(|gas_metadata| gas_metadata.get_initial_required_gas(function_id)),
         ) {
             if required_init_gas > *gas {
-                panic!("Not enough gas");
+                return Err(make_insufficient_gas_error(required_init_gas, *gas));
             }
 
             *gas -= required_init_gas;
         }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        context::NativeContext,
+        error::jit_engine::ErrorImpl,
+        utils::{find_function_id, test::load_cairo},
+    };
+
+    #[test]
+    fn test_compile_with_entry_points() {
+        let (module_name, program) = load_cairo! {
+            fn run_test(n: felt252) -> felt252 {
+                double(n)
+            }
+
+            #[inline(never)]
+            fn double(n: felt252) -> felt252 {
+                n * 2
+            }
+
+            fn unused(n: felt252) -> felt252 {
+                n + 1
+            }
+        };
+        let run_test_id = find_function_id(&program, &format!("{0}::{0}::run_test", module_name));
+        let unused_id = find_function_id(&program, &format!("{0}::{0}::unused", module_name));
+
+        let context = NativeContext::new();
+        let native_module = context
+            .compile_with_entry_points(&program, &[run_test_id.clone()], OptLevel::default())
+            .unwrap();
+        let executor = JitNativeExecutor::new(native_module, OptLevel::default());
+
+        let result = executor
+            .invoke_dynamic(
+                run_test_id,
+                &[JitValue::Felt252(Felt::from(21))],
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(result.return_value, JitValue::Felt252(Felt::from(42)));
+
+        // The function wasn't compiled, so there's nothing to call.
+        let error = executor
+            .invoke_dynamic(unused_id, &[JitValue::Felt252(Felt::from(21))], None, None)
+            .unwrap_err();
+        assert!(matches!(error.source, ErrorImpl::FunctionNotCompiled(_)));
     }
 }
@@ -1,7 +1,23 @@
+#[cfg(feature = "with-runtime")]
+use llvm_sys::orc2::{
+    lljit::LLVMOrcLLJITMangleAndIntern, LLVMJITEvaluatedSymbol, LLVMJITSymbolFlags,
+    LLVMJITSymbolGenericFlags, LLVMOrcAbsoluteSymbols, LLVMOrcCSymbolMapPair,
+    LLVMOrcExecutorAddress, LLVMOrcJITDylibDefine,
+};
 use llvm_sys::{
     core::{
-        LLVMContextCreate, LLVMContextDispose, LLVMDisposeMemoryBuffer, LLVMDisposeMessage,
-        LLVMDisposeModule, LLVMGetBufferSize, LLVMGetBufferStart,
+        LLVMContextCreate, LLVMContextDispose, LLVMCreateMemoryBufferWithMemoryRangeCopy,
+        LLVMDisposeMemoryBuffer, LLVMDisposeMessage, LLVMDisposeModule, LLVMGetBufferSize,
+        LLVMGetBufferStart,
+    },
+    error::{LLVMConsumeError, LLVMDisposeErrorMessage, LLVMErrorRef, LLVMGetErrorMessage},
+    orc2::{
+        lljit::{
+            LLVMOrcCreateLLJIT, LLVMOrcDisposeLLJIT, LLVMOrcLLJITAddObjectFile,
+            LLVMOrcLLJITGetGlobalPrefix, LLVMOrcLLJITGetMainJITDylib, LLVMOrcLLJITLookup,
+            LLVMOrcLLJITRef,
+        },
+        LLVMOrcCreateDynamicLibrarySearchGeneratorForProcess, LLVMOrcJITDylibAddGenerator,
     },
     prelude::{LLVMContextRef, LLVMMemoryBufferRef, LLVMModuleRef},
     target::{
@@ -12,23 +28,26 @@ use llvm_sys::{
         LLVMCodeGenFileType, LLVMCodeGenOptLevel, LLVMCodeModel, LLVMCreateTargetMachine,
         LLVMDisposeTargetMachine, LLVMGetDefaultTargetTriple, LLVMGetHostCPUFeatures,
         LLVMGetHostCPUName, LLVMGetTargetFromTriple, LLVMRelocMode,
-        LLVMTargetMachineEmitToMemoryBuffer, LLVMTargetRef,
+        LLVMTargetMachineEmitToMemoryBuffer, LLVMTargetMachineRef, LLVMTargetRef,
+    },
+    transforms::pass_builder::{
+        LLVMCreatePassBuilderOptions, LLVMDisposePassBuilderOptions, LLVMRunPasses,
     },
 };
 use melior::ir::{Module, Type, TypeLike};
 use mlir_sys::MlirOperation;
 use std::{
-    borrow::Cow,
-    error::Error,
-    ffi::{c_void, CStr},
+    ffi::{c_char, c_void, CStr, CString},
     fmt::Display,
     io::Write,
     mem::MaybeUninit,
-    path::Path,
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus},
     ptr::{addr_of_mut, null_mut},
     sync::OnceLock,
 };
 use tempfile::NamedTempFile;
+use thiserror::Error;
 
 extern "C" {
     fn LLVMStructType_getFieldTypeAt(ty_ptr: *const c_void, index: u32) -> *const c_void;
@@ -52,7 +71,7 @@ pub fn get_struct_field_type_at<'c>(r#type: &Type<'c>, index: usize) -> Type<'c>
 #[derive(Debug, Clone)]
 pub struct LLVMCompileError(String);
 
-impl Error for LLVMCompileError {}
+impl std::error::Error for LLVMCompileError {}
 
 impl Display for LLVMCompileError {
     #[inline]
@@ -61,8 +80,64 @@ impl Display for LLVMCompileError {
     }
 }
 
-/// Make sure to call
-pub fn module_to_object(module: &Module<'_>) -> Result<Vec<u8>, LLVMCompileError> {
+/// Optimization level used when generating code for a program.
+///
+/// Defaults to [`OptLevel::None`], like the command line tools.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum OptLevel {
+    /// No optimizations (`-O0`).
+    #[default]
+    None,
+    /// Few optimizations (`-O1`).
+    Less,
+    /// The default optimizations (`-O2`).
+    Default,
+    /// All optimizations (`-O3`).
+    Aggressive,
+}
+
+/// An optimization level outside of the `0..=3` range.
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+#[error("invalid optimization level {0}, expected a value between 0 and 3")]
+pub struct InvalidOptLevelError(pub u8);
+
+impl TryFrom<u8> for OptLevel {
+    type Error = InvalidOptLevelError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => Self::None,
+            1 => Self::Less,
+            2 => Self::Default,
+            3 => Self::Aggressive,
+            _ => return Err(InvalidOptLevelError(value)),
+        })
+    }
+}
+
+impl From<OptLevel> for usize {
+    fn from(value: OptLevel) -> Self {
+        match value {
+            OptLevel::None => 0,
+            OptLevel::Less => 1,
+            OptLevel::Default => 2,
+            OptLevel::Aggressive => 3,
+        }
+    }
+}
+
+impl From<OptLevel> for LLVMCodeGenOptLevel {
+    fn from(value: OptLevel) -> Self {
+        match value {
+            OptLevel::None => LLVMCodeGenOptLevel::LLVMCodeGenLevelNone,
+            OptLevel::Less => LLVMCodeGenOptLevel::LLVMCodeGenLevelLess,
+            OptLevel::Default => LLVMCodeGenOptLevel::LLVMCodeGenLevelDefault,
+            OptLevel::Aggressive => LLVMCodeGenOptLevel::LLVMCodeGenLevelAggressive,
+        }
+    }
+}
+
+fn initialize_llvm() {
     static INITIALIZED: OnceLock<()> = OnceLock::new();
 
     INITIALIZED.get_or_init(|| unsafe {
@@ -72,6 +147,40 @@ pub fn module_to_object(module: &Module<'_>) -> Result<Vec<u8>, LLVMCompileError
         LLVM_InitializeAllAsmPrinters();
         LLVM_InitializeAllAsmParsers();
     });
+}
+
+/// The LLVM objects created by [`module_to_object`].
+///
+/// They are disposed of when dropped, so that returning early on errors doesn't leak them.
+struct CodegenResources {
+    context: LLVMContextRef,
+    module: LLVMModuleRef,
+    machine: Option<LLVMTargetMachineRef>,
+    messages: Vec<*mut c_char>,
+}
+
+impl Drop for CodegenResources {
+    fn drop(&mut self) {
+        unsafe {
+            if let Some(machine) = self.machine {
+                LLVMDisposeTargetMachine(machine);
+            }
+            LLVMDisposeModule(self.module);
+            LLVMContextDispose(self.context);
+
+            for message in self.messages.drain(..) {
+                LLVMDisposeMessage(message);
+            }
+        }
+    }
+}
+
+/// Make sure to call
+pub fn module_to_object(
+    module: &Module<'_>,
+    opt_level: OptLevel,
+) -> Result<Vec<u8>, LLVMCompileError> {
+    initialize_llvm();
 
     unsafe {
         let llvm_context = LLVMContextCreate();
@@ -80,12 +189,22 @@ pub fn module_to_object(module: &Module<'_>) -> Result<Vec<u8>, LLVMCompileError
 
         let llvm_module = mlirTranslateModuleToLLVMIR(op, llvm_context);
 
+        let mut resources = CodegenResources {
+            context: llvm_context,
+            module: llvm_module,
+            machine: None,
+            messages: Vec::new(),
+        };
+
         let mut null = null_mut();
         let mut error_buffer = addr_of_mut!(null);
 
         let target_triple = LLVMGetDefaultTargetTriple();
         let target_cpu = LLVMGetHostCPUName();
         let target_cpu_features = LLVMGetHostCPUFeatures();
+        resources
+            .messages
+            .extend([target_triple, target_cpu, target_cpu_features]);
 
         let mut target: MaybeUninit<LLVMTargetRef> = MaybeUninit::uninit();
 
@@ -106,11 +225,26 @@ pub fn module_to_object(module: &Module<'_>) -> Result<Vec<u8>, LLVMCompileError
             target_triple.cast(),
             target_cpu.cast(),
             target_cpu_features.cast(),
-            // TODO: Convert this into a flag instead of hardcoding it to `-O0`.
-            LLVMCodeGenOptLevel::LLVMCodeGenLevelNone,
+            opt_level.into(),
             LLVMRelocMode::LLVMRelocDynamicNoPic,
             LLVMCodeModel::LLVMCodeModelDefault,
         );
+        resources.machine = Some(machine);
+
+        // Run LLVM's own optimization pipeline before generating code.
+        if opt_level != OptLevel::None {
+            let passes = CString::new(format!("default<O{}>", usize::from(opt_level))).unwrap();
+            let options = LLVMCreatePassBuilderOptions();
+            let error = LLVMRunPasses(llvm_module, passes.as_ptr(), machine, options);
+            LLVMDisposePassBuilderOptions(options);
+
+            if !error.is_null() {
+                let message = LLVMGetErrorMessage(error);
+                let err = CStr::from_ptr(message).to_string_lossy().to_string();
+                LLVMDisposeErrorMessage(message);
+                Err(LLVMCompileError(err))?;
+            }
+        }
 
         let mut out_buf: MaybeUninit<LLVMMemoryBufferRef> = MaybeUninit::uninit();
 
@@ -140,87 +274,312 @@ pub fn module_to_object(module: &Module<'_>) -> Result<Vec<u8>, LLVMCompileError
         let data = std::slice::from_raw_parts(out_buf_start, out_buf_size).to_vec();
 
         LLVMDisposeMemoryBuffer(out_buf);
-        LLVMDisposeTargetMachine(machine);
-        LLVMDisposeModule(llvm_module);
-        LLVMContextDispose(llvm_context);
 
         Ok(data)
     }
 }
 
-pub fn object_to_shared_lib(object: &[u8], output_filename: &Path) -> Result<(), std::io::Error> {
-    // linker seems to need a file and doesn't accept stdin
-    let mut file = NamedTempFile::new()?;
-    file.write_all(object)?;
-    let file = file.into_temp_path();
+/// An error produced while linking an object file.
+#[derive(Debug, Error)]
+pub enum LinkError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("failed to run linker `{linker}`: {source}")]
+    Spawn {
+        linker: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("linker `{linker}` failed ({status}):\n{stderr}")]
+    Failed {
+        linker: PathBuf,
+        status: ExitStatus,
+        stderr: String,
+    },
+    #[error("unsupported platform")]
+    UnsupportedPlatform,
+    #[error("failed to load object: {0}")]
+    Load(String),
+}
+
+/// Linker configuration used when converting object files into shared libraries.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LinkerConfig {
+    /// The linker binary. Defaults to `$CAIRO_NATIVE_LINKER`, or `ld` if unset.
+    pub linker: PathBuf,
+    /// Extra arguments, passed before the input object.
+    pub extra_args: Vec<String>,
+    /// Whether to pass the hardcoded library search paths of the platform.
+    pub default_search_paths: bool,
+}
 
-    let file_path = file.display().to_string();
-    let output_path = output_filename.display().to_string();
+impl Default for LinkerConfig {
+    fn default() -> Self {
+        Self {
+            linker: std::env::var_os("CAIRO_NATIVE_LINKER")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from("ld")),
+            extra_args: Vec::new(),
+            default_search_paths: true,
+        }
+    }
+}
 
-    let args: Vec<Cow<'static, str>> = {
+impl LinkerConfig {
+    /// Use a different linker binary.
+    pub fn with_linker(mut self, linker: impl Into<PathBuf>) -> Self {
+        self.linker = linker.into();
+        self
+    }
+
+    /// Append an extra argument for the linker.
+    pub fn with_arg(mut self, arg: impl Into<String>) -> Self {
+        self.extra_args.push(arg.into());
+        self
+    }
+
+    /// Enable or disable the hardcoded library search paths.
+    pub fn with_default_search_paths(mut self, enabled: bool) -> Self {
+        self.default_search_paths = enabled;
+        self
+    }
+
+    fn args(&self, file_paths: Vec<String>, output_path: String) -> Result<Vec<String>, LinkError> {
         #[cfg(target_os = "macos")]
         {
-            let mut args: Vec<Cow<'static, str>> = vec![
+            let mut args: Vec<String> = vec![
                 "-demangle".into(),
                 "-no_deduplicate".into(),
                 "-dynamic".into(),
                 "-dylib".into(),
-                "-L/usr/local/lib".into(),
-                "-L/Library/Developer/CommandLineTools/SDKs/MacOSX.sdk/usr/lib".into(),
+                // Keep the syscall handler setter of the runtime, which the executors look up.
+                "-u".into(),
+                "_cairo_native__set_syscall_handler".into(),
             ];
 
+            if self.default_search_paths {
+                args.extend([
+                    "-L/usr/local/lib".into(),
+                    "-L/Library/Developer/CommandLineTools/SDKs/MacOSX.sdk/usr/lib".into(),
+                ]);
+            }
+
             if let Ok(extra_dir) = std::env::var("CAIRO_NATIVE_RUNTIME_LIBDIR") {
-                args.extend([Cow::from(format!("-L{extra_dir}"))]);
+                args.push(format!("-L{extra_dir}"));
             }
 
+            args.extend(self.extra_args.iter().cloned());
+            args.extend(file_paths);
             args.extend([
-                Cow::from(file_path),
                 "-o".into(),
-                Cow::from(output_path),
+                output_path,
                 "-lSystem".into(),
                 "-lcairo_native_runtime".into(),
             ]);
-            args
+            Ok(args)
         }
         #[cfg(target_os = "linux")]
         {
-            let mut args: Vec<Cow<'static, str>> = vec![
+            let mut args: Vec<String> = vec![
                 "--hash-style=gnu".into(),
                 "--eh-frame-hdr".into(),
                 "-shared".into(),
-                "-L/lib/../lib64".into(),
-                "-L/usr/lib/../lib64".into(),
+                // Keep the syscall handler setter of the runtime, which the executors look up.
+                "-u".into(),
+                "cairo_native__set_syscall_handler".into(),
             ];
 
+            if self.default_search_paths {
+                args.extend(["-L/lib/../lib64".into(), "-L/usr/lib/../lib64".into()]);
+            }
+
             if let Ok(extra_dir) = std::env::var("CAIRO_NATIVE_RUNTIME_LIBDIR") {
                 args.extend([
-                    Cow::from(format!("-L{extra_dir}")),
-                    format!("-rpath={extra_dir}").into(),
-                    format!("-rpath-link={extra_dir}").into(),
+                    format!("-L{extra_dir}"),
+                    format!("-rpath={extra_dir}"),
+                    format!("-rpath-link={extra_dir}"),
                 ]);
             }
 
+            args.extend(self.extra_args.iter().cloned());
             args.extend([
                 "-o".into(),
-                Cow::from(output_path),
+                output_path,
                 "-lc".into(),
                 "-lcairo_native_runtime".into(),
-                Cow::from(file_path),
             ]);
-            args
+            args.extend(file_paths);
+            Ok(args)
         }
-        #[cfg(target_os = "windows")]
+        #[cfg(not(any(target_os = "macos", target_os = "linux")))]
         {
-            unimplemented!()
+            let _ = (file_paths, output_path);
+            Err(LinkError::UnsupportedPlatform)
         }
-    };
+    }
+}
+
+/// Link an object file into a shared library using the default [`LinkerConfig`].
+pub fn object_to_shared_lib(object: &[u8], output_filename: &Path) -> Result<(), LinkError> {
+    object_to_shared_lib_with_config(object, output_filename, &LinkerConfig::default())
+}
+
+/// Link an object file into a shared library using the provided linker configuration.
+pub fn object_to_shared_lib_with_config(
+    object: &[u8],
+    output_filename: &Path,
+    config: &LinkerConfig,
+) -> Result<(), LinkError> {
+    objects_to_shared_lib_with_config(&[object], output_filename, config)
+}
+
+/// Link several object files into a single shared library using the provided linker
+/// configuration.
+///
+/// The objects are passed to the linker in the same order as they are given.
+pub fn objects_to_shared_lib_with_config(
+    objects: &[&[u8]],
+    output_filename: &Path,
+    config: &LinkerConfig,
+) -> Result<(), LinkError> {
+    // linker seems to need a file and doesn't accept stdin
+    let files = objects
+        .iter()
+        .map(|object| {
+            let mut file = NamedTempFile::new()?;
+            file.write_all(object)?;
+            Ok(file.into_temp_path())
+        })
+        .collect::<Result<Vec<_>, std::io::Error>>()?;
+
+    let args = config.args(
+        files
+            .iter()
+            .map(|file| file.display().to_string())
+            .collect(),
+        output_filename.display().to_string(),
+    )?;
+
+    let proc = Command::new(&config.linker)
+        .args(&args)
+        .output()
+        .map_err(|source| LinkError::Spawn {
+            linker: config.linker.clone(),
+            source,
+        })?;
 
-    let mut linker = std::process::Command::new("ld");
-    let proc = linker.args(args.iter().map(|x| x.as_ref())).output()?;
     if proc.status.success() {
         Ok(())
     } else {
-        let msg = String::from_utf8_lossy(&proc.stderr);
-        panic!("error linking:\n{}", msg);
+        Err(LinkError::Failed {
+            linker: config.linker.clone(),
+            status: proc.status,
+            stderr: String::from_utf8_lossy(&proc.stderr).into_owned(),
+        })
     }
 }
+
+/// An object file loaded directly into the current process, without a linker or shared library.
+///
+/// Undefined symbols are resolved against the runtime library and the symbols already loaded
+/// into the process (ex. libc).
+#[derive(Debug)]
+pub struct InMemoryObject {
+    jit: LLVMOrcLLJITRef,
+}
+
+unsafe impl Send for InMemoryObject {}
+unsafe impl Sync for InMemoryObject {}
+
+impl InMemoryObject {
+    /// Load an object file (as returned by [`module_to_object`]) into memory.
+    pub fn load(object: &[u8]) -> Result<Self, LinkError> {
+        initialize_llvm();
+
+        unsafe {
+            let mut jit = null_mut();
+            check_llvm_error(LLVMOrcCreateLLJIT(&mut jit, null_mut()))?;
+            let this = Self { jit };
+
+            let dylib = LLVMOrcLLJITGetMainJITDylib(jit);
+
+            let mut generator = null_mut();
+            check_llvm_error(LLVMOrcCreateDynamicLibrarySearchGeneratorForProcess(
+                &mut generator,
+                LLVMOrcLLJITGetGlobalPrefix(jit),
+                None,
+                null_mut(),
+            ))?;
+            LLVMOrcJITDylibAddGenerator(dylib, generator);
+
+            #[cfg(feature = "with-runtime")]
+            {
+                let flags = LLVMJITSymbolFlags {
+                    GenericFlags: LLVMJITSymbolGenericFlags::LLVMJITSymbolGenericFlagsExported
+                        as u8
+                        | LLVMJITSymbolGenericFlags::LLVMJITSymbolGenericFlagsCallable as u8,
+                    TargetFlags: 0,
+                };
+                let mut symbols = crate::utils::runtime_symbols()
+                    .into_iter()
+                    .map(|(name, ptr)| {
+                        let name = CString::new(name).unwrap();
+                        LLVMOrcCSymbolMapPair {
+                            Name: LLVMOrcLLJITMangleAndIntern(jit, name.as_ptr()),
+                            Sym: LLVMJITEvaluatedSymbol {
+                                Address: ptr as LLVMOrcExecutorAddress,
+                                Flags: flags,
+                            },
+                        }
+                    })
+                    .collect::<Vec<_>>();
+
+                let unit = LLVMOrcAbsoluteSymbols(symbols.as_mut_ptr(), symbols.len());
+                check_llvm_error(LLVMOrcJITDylibDefine(dylib, unit))?;
+            }
+
+            let name = CString::new("object").unwrap();
+            let buffer = LLVMCreateMemoryBufferWithMemoryRangeCopy(
+                object.as_ptr().cast(),
+                object.len(),
+                name.as_ptr(),
+            );
+            check_llvm_error(LLVMOrcLLJITAddObjectFile(jit, dylib, buffer))?;
+
+            Ok(this)
+        }
+    }
+
+    /// Find the address of a symbol defined in the object.
+    pub fn lookup(&self, symbol: &str) -> Result<*mut c_void, LinkError> {
+        let name = CString::new(symbol).map_err(|e| LinkError::Load(e.to_string()))?;
+
+        unsafe {
+            let mut address = 0;
+            check_llvm_error(LLVMOrcLLJITLookup(self.jit, &mut address, name.as_ptr()))?;
+            Ok(address as *mut c_void)
+        }
+    }
+}
+
+impl Drop for InMemoryObject {
+    fn drop(&mut self) {
+        unsafe {
+            let error = LLVMOrcDisposeLLJIT(self.jit);
+            if !error.is_null() {
+                LLVMConsumeError(error);
+            }
+        }
+    }
+}
+
+unsafe fn check_llvm_error(error: LLVMErrorRef) -> Result<(), LinkError> {
+    if error.is_null() {
+        return Ok(());
+    }
+
+    let message = LLVMGetErrorMessage(error);
+    let err = CStr::from_ptr(message).to_string_lossy().into_owned();
+    LLVMDisposeErrorMessage(message);
+    Err(LinkError::Load(err))
+}
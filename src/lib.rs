@@ -19,6 +19,7 @@
 //! use cairo_native::context::NativeContext;
 //! use cairo_native::executor::NativeExecutor;
 //! use cairo_native::values::JitValue;
+//! use cairo_native::OptLevel;
 //! use std::path::Path;
 //!
 //! let program_path = Path::new("programs/examples/hello.cairo");
@@ -30,7 +31,9 @@
 //! let native_context = NativeContext::new();
 //!
 //! // Compile the sierra program into a MLIR module.
-//! let native_program = native_context.compile(&sierra_program).unwrap();
+//! let native_program = native_context
+//!     .compile(&sierra_program, OptLevel::default())
+//!     .unwrap();
 //!
 //! // The parameters of the entry point.
 //! let params = &[JitValue::Felt252(Felt252::from_bytes_be(b"user"))];
@@ -68,8 +71,12 @@
 #![allow(clippy::missing_safety_doc)]
 
 pub use self::{
-    compiler::compile,
-    ffi::{module_to_object, object_to_shared_lib, LLVMCompileError},
+    compiler::{compile, compile_with_entry_points},
+    ffi::{
+        module_to_object, object_to_shared_lib, object_to_shared_lib_with_config,
+        objects_to_shared_lib_with_config, InMemoryObject, InvalidOptLevelError, LLVMCompileError,
+        LinkError, LinkerConfig, OptLevel,
+    },
 };
 
 pub mod cache;
@@ -84,7 +91,9 @@ mod ffi;
 pub mod libfuncs;
 pub mod metadata;
 pub mod module;
+pub mod parallel;
 pub mod starknet;
+pub mod support;
 pub mod types;
 pub mod utils;
 pub mod values;
@@ -1172,6 +1172,7 @@ mod test {
         values::JitValue,
     };
     use pretty_assertions_sorted::assert_eq;
+    use starknet_types_core::felt::Felt;
 
     #[test]
     fn run_roundtrip() {
@@ -1203,6 +1204,35 @@ mod test {
         assert_eq!(result, [4u32].into());
     }
 
+    #[test]
+    fn run_span_return() {
+        let program = load_cairo! {
+            use array::ArrayTrait;
+            use array::SpanTrait;
+
+            fn run_test(x: Span<felt252>) -> Span<felt252> {
+                let mut numbers = ArrayTrait::new();
+                numbers.append(*x[1]);
+                numbers.append(*x[0]);
+                numbers.span()
+            }
+        };
+        let result = run_program(
+            &program,
+            "run_test",
+            &[jit_struct!([Felt::from(1), Felt::from(2)].into())],
+        )
+        .return_value;
+
+        assert_eq!(
+            result,
+            jit_enum!(
+                0,
+                jit_struct!(jit_struct!([Felt::from(2), Felt::from(1)].into()))
+            )
+        );
+    }
+
     #[test]
     fn run_len() {
         let program = load_cairo!(
@@ -204,6 +204,7 @@ mod test {
     use crate::utils::test::{
         jit_enum, jit_panic, jit_struct, load_cairo, run_program_assert_output,
     };
+    use crate::values::JitValue;
     use cairo_lang_sierra::program::Program;
     use lazy_static::lazy_static;
     use starknet_types_core::felt::Felt;
@@ -218,6 +219,13 @@ mod test {
                 bytes31_to_felt252(a)
             }
         };
+        static ref BYTES31_RETURN: (String, Program) = load_cairo! {
+            use core::bytes_31::bytes31_try_from_felt252;
+
+            fn run_test(value: felt252) -> bytes31 {
+                bytes31_try_from_felt252(value).unwrap()
+            }
+        };
     }
 
     #[test]
@@ -236,4 +244,17 @@ mod test {
             jit_panic!(Felt::from_bytes_be_slice(b"Option::unwrap failed.")),
         );
     }
+
+    #[test]
+    fn bytes31_return() {
+        let mut expected = [0; 31];
+        expected[..3].copy_from_slice(&[0x56, 0x34, 0x12]);
+
+        run_program_assert_output(
+            &BYTES31_RETURN,
+            "run_test",
+            &[Felt::from(0x123456).into()],
+            jit_enum!(0, jit_struct!(JitValue::Bytes31(expected))),
+        );
+    }
 }
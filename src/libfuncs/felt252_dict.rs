@@ -6,7 +6,10 @@ use crate::{
         libfuncs::{Error, Result},
         CoreTypeBuilderError,
     },
-    metadata::{runtime_bindings::RuntimeBindingsMeta, MetadataStorage},
+    metadata::{
+        builtin_counters::BuiltinCountersMeta, runtime_bindings::RuntimeBindingsMeta,
+        MetadataStorage,
+    },
     types::TypeBuilder,
     utils::ProgramRegistryExt,
 };
@@ -18,6 +21,7 @@ use cairo_lang_sierra::{
     program_registry::ProgramRegistry,
 };
 use melior::{
+    dialect::arith,
     ir::{Block, Location},
     Context,
 };
@@ -87,12 +91,12 @@ where
 }
 
 pub fn build_squash<'ctx, 'this, TType, TLibfunc>(
-    _context: &'ctx Context,
+    context: &'ctx Context,
     _registry: &ProgramRegistry<TType, TLibfunc>,
     entry: &'this Block<'ctx>,
     location: Location<'ctx>,
     helper: &LibfuncHelper<'ctx, 'this>,
-    _metadata: &MetadataStorage,
+    metadata: &mut MetadataStorage,
     _info: &SignatureOnlyConcreteLibfunc,
 ) -> Result<()>
 where
@@ -101,13 +105,33 @@ where
     <TType as GenericType>::Concrete: TypeBuilder<TType, TLibfunc, Error = CoreTypeBuilderError>,
     <TLibfunc as GenericLibfunc>::Concrete: LibfuncBuilder<TType, TLibfunc, Error = Error>,
 {
+    let mut range_check = entry.argument(0)?.into();
+    let dict_ptr = entry.argument(3)?.into();
+
+    // The range checks used by the VM depend on the accesses made to the dictionary, therefore they
+    // can't be part of the static increments of the builtin counters.
+    if metadata.get::<BuiltinCountersMeta>().is_some() {
+        let runtime_bindings = metadata
+            .get_mut::<RuntimeBindingsMeta>()
+            .expect("Runtime library not available.");
+
+        let range_checks = runtime_bindings
+            .dict_squash_range_checks(context, helper, entry, dict_ptr, location)?
+            .result(0)?
+            .into();
+        range_check = entry
+            .append_operation(arith::addi(range_check, range_checks, location))
+            .result(0)?
+            .into();
+    }
+
     entry.append_operation(helper.br(
         0,
         &[
-            entry.argument(0)?.into(),
+            range_check,
             entry.argument(1)?.into(),
             entry.argument(2)?.into(),
-            entry.argument(3)?.into(),
+            dict_ptr,
         ],
         location,
     ));
@@ -9,7 +9,9 @@ use crate::{
         libfuncs::{Error, Result},
         CoreTypeBuilderError,
     },
-    metadata::{tail_recursion::TailRecursionMeta, MetadataStorage},
+    metadata::{
+        builtin_counters::BuiltinCountersMeta, tail_recursion::TailRecursionMeta, MetadataStorage,
+    },
     types::TypeBuilder,
     utils::generate_function_name,
 };
@@ -50,13 +52,15 @@ where
     <TType as GenericType>::Concrete: TypeBuilder<TType, TLibfunc, Error = CoreTypeBuilderError>,
     <TLibfunc as GenericLibfunc>::Concrete: LibfuncBuilder<TType, TLibfunc, Error = Error>,
 {
+    let builtin_counters = metadata.get::<BuiltinCountersMeta>().is_some();
+
     let mut arguments = Vec::new();
     let mut result_types = Vec::new();
 
     for (idx, type_id) in info.function.signature.param_types.iter().enumerate() {
         let type_info = registry.get_type(type_id)?;
 
-        if !(type_info.is_builtin() && type_info.is_zst(registry)) {
+        if !(type_info.is_builtin() && type_info.is_zst_with_counters(registry, builtin_counters)) {
             arguments.push(entry.argument(idx)?.into());
         }
     }
@@ -68,7 +72,8 @@ where
         .iter()
         .filter_map(|type_id| {
             let type_info = registry.get_type(type_id).unwrap();
-            if type_info.is_builtin() && type_info.is_zst(registry) {
+            if type_info.is_builtin() && type_info.is_zst_with_counters(registry, builtin_counters)
+            {
                 None
             } else {
                 Some((type_id, type_info))
@@ -174,7 +179,8 @@ where
         for var_info in &info.signature.branch_signatures[0].vars {
             let type_info = registry.get_type(&var_info.ty)?;
 
-            if type_info.is_builtin() && type_info.is_zst(registry) {
+            if type_info.is_builtin() && type_info.is_zst_with_counters(registry, builtin_counters)
+            {
                 results.push(
                     cont_block
                         .append_operation(llvm::undef(
@@ -255,7 +261,9 @@ where
                 for (idx, type_id) in info.function.signature.ret_types.iter().enumerate() {
                     let type_info = registry.get_type(type_id)?;
 
-                    if type_info.is_builtin() && type_info.is_zst(registry) {
+                    if type_info.is_builtin()
+                        && type_info.is_zst_with_counters(registry, builtin_counters)
+                    {
                         results.push(entry.argument(idx)?.into());
                     } else {
                         let val = arguments[0];
@@ -302,7 +310,9 @@ where
                 for (idx, type_id) in info.function.signature.ret_types.iter().enumerate() {
                     let type_info = registry.get_type(type_id)?;
 
-                    if type_info.is_builtin() && type_info.is_zst(registry) {
+                    if type_info.is_builtin()
+                        && type_info.is_zst_with_counters(registry, builtin_counters)
+                    {
                         results.push(entry.argument(idx)?.into());
                     } else {
                         let val = op0.result(count)?.into();
@@ -366,7 +376,9 @@ where
                     let type_info = registry.get_type(type_id)?;
                     assert!(!type_info.is_memory_allocated(registry));
 
-                    if type_info.is_builtin() && type_info.is_zst(registry) {
+                    if type_info.is_builtin()
+                        && type_info.is_zst_with_counters(registry, builtin_counters)
+                    {
                         results.push(entry.argument(idx)?.into());
                     } else {
                         let value = op0.result(count)?.into();
@@ -48,7 +48,9 @@ where
         GasConcreteLibfunc::WithdrawGas(info) => {
             build_withdraw_gas(context, registry, entry, location, helper, metadata, info)
         }
-        GasConcreteLibfunc::RedepositGas(_) => todo!("implement redeposit gas libfunc"),
+        GasConcreteLibfunc::RedepositGas(info) => {
+            build_redeposit_gas(context, registry, entry, location, helper, metadata, info)
+        }
         GasConcreteLibfunc::GetAvailableGas(info) => {
             build_get_available_gas(context, registry, entry, location, helper, metadata, info)
         }
@@ -150,6 +152,55 @@ where
     Ok(())
 }
 
+/// Generate MLIR operations for the `redeposit_gas` libfunc.
+///
+/// The refunded amount is the statement's gas cost, as computed in the gas metadata.
+pub fn build_redeposit_gas<'ctx, 'this, TType, TLibfunc>(
+    context: &'ctx Context,
+    _registry: &ProgramRegistry<TType, TLibfunc>,
+    entry: &'this Block<'ctx>,
+    location: Location<'ctx>,
+    helper: &LibfuncHelper<'ctx, 'this>,
+    metadata: &MetadataStorage,
+    _info: &SignatureOnlyConcreteLibfunc,
+) -> Result<()>
+where
+    TType: GenericType,
+    TLibfunc: GenericLibfunc,
+    <TType as GenericType>::Concrete: TypeBuilder<TType, TLibfunc, Error = CoreTypeBuilderError>,
+    <TLibfunc as GenericLibfunc>::Concrete: LibfuncBuilder<TType, TLibfunc, Error = Error>,
+{
+    let current_gas = entry.argument(0)?.into();
+
+    let refund = metadata.get::<GasCost>().and_then(|x| x.0);
+
+    let u128_type: melior::ir::Type = IntegerType::new(context, 128).into();
+    let refund_val = entry
+        .append_operation(arith::constant(
+            context,
+            Attribute::parse(context, &format!("{} : {}", refund.unwrap_or(0), u128_type))
+                .ok_or(ErrorImpl::ParseAttributeError)?,
+            location,
+        ))
+        .result(0)?
+        .into();
+
+    let resulting_gas = entry
+        .append_operation(llvm::call_intrinsic(
+            context,
+            StringAttribute::new(context, "llvm.uadd.sat"),
+            &[current_gas, refund_val],
+            &[refund_val.r#type()],
+            location,
+        ))
+        .result(0)?
+        .into();
+
+    entry.append_operation(helper.br(0, &[resulting_gas], location));
+
+    Ok(())
+}
+
 /// Generate MLIR operations for the `withdraw_gas_all` libfunc.
 pub fn build_builtin_withdraw_gas<'ctx, 'this, TType, TLibfunc>(
     context: &'ctx Context,
@@ -9,15 +9,21 @@ use crate::{
         CoreTypeBuilderError,
     },
     ffi::get_struct_field_type_at,
-    metadata::MetadataStorage,
+    metadata::{runtime_bindings::RuntimeBindingsMeta, MetadataStorage},
     starknet::handler::StarkNetSyscallHandlerCallbacks,
     types::{felt252::PRIME, TypeBuilder},
     utils::{get_integer_layout, ProgramRegistryExt},
 };
 use cairo_lang_sierra::{
     extensions::{
-        consts::SignatureAndConstConcreteLibfunc, lib_func::SignatureOnlyConcreteLibfunc,
-        starknet::StarkNetConcreteLibfunc, ConcreteLibfunc, GenericLibfunc, GenericType,
+        consts::SignatureAndConstConcreteLibfunc,
+        lib_func::SignatureOnlyConcreteLibfunc,
+        starknet::{
+            secp256::{Secp256ConcreteLibfunc, Secp256OpConcreteLibfunc},
+            testing::TestingConcreteLibfunc,
+            StarkNetConcreteLibfunc,
+        },
+        ConcreteLibfunc, GenericLibfunc, GenericType,
     },
     program_registry::ProgramRegistry,
 };
@@ -32,7 +38,7 @@ use melior::{
         },
         operation::OperationBuilder,
         r#type::IntegerType,
-        Attribute, Block, Identifier, Location, Type, ValueLike,
+        Attribute, Block, Identifier, Location, Type, Value, ValueLike,
     },
     Context,
 };
@@ -137,10 +143,14 @@ where
         StarkNetConcreteLibfunc::SendMessageToL1(info) => {
             build_send_message_to_l1(context, registry, entry, location, helper, metadata, info)
         }
-        StarkNetConcreteLibfunc::Testing(_) => todo!("implement starknet testing libfunc"),
-        StarkNetConcreteLibfunc::Secp256(_) => todo!("implement starknet Secp256 libfunc"),
-        StarkNetConcreteLibfunc::GetExecutionInfoV2(_) => {
-            todo!("implement starknet GetExecutionInfoV2 libfunc")
+        StarkNetConcreteLibfunc::Testing(selector) => build_testing(
+            context, registry, entry, location, helper, metadata, selector,
+        ),
+        StarkNetConcreteLibfunc::Secp256(selector) => build_secp256(
+            context, registry, entry, location, helper, metadata, selector,
+        ),
+        StarkNetConcreteLibfunc::GetExecutionInfoV2(info) => {
+            build_get_execution_info_v2(context, registry, entry, location, helper, metadata, info)
         }
     }
 }
@@ -2571,7 +2581,7 @@ where
     Ok(())
 }
 
-pub fn build_deploy<'ctx, 'this, TType, TLibfunc>(
+pub fn build_get_execution_info_v2<'ctx, 'this, TType, TLibfunc>(
     context: &'ctx Context,
     registry: &ProgramRegistry<TType, TLibfunc>,
     entry: &'this Block<'ctx>,
@@ -2599,60 +2609,17 @@ where
         .into();
 
     // Allocate space for the return value.
-    let (result_layout, (result_tag_ty, result_tag_layout), variant_tys) = {
-        let tag_layout = get_integer_layout(1);
-        let tag_ty: Type = IntegerType::new(context, 1).into();
-
-        let mut layout = tag_layout;
-        let output = [
-            {
-                let (p0_ty, p0_layout) = registry.build_type_with_layout(
-                    context,
-                    helper,
-                    registry,
-                    metadata,
-                    &info.branch_signatures()[0].vars[2].ty,
-                )?;
-                let (p1_ty, p1_layout) = registry.build_type_with_layout(
-                    context,
-                    helper,
-                    registry,
-                    metadata,
-                    &info.branch_signatures()[0].vars[3].ty,
-                )?;
-
-                let payload_ty = llvm::r#type::r#struct(context, &[p0_ty, p1_ty], false);
-                let payload_layout = p0_layout.extend(p1_layout)?.0;
-
-                let full_layout = tag_layout.extend(payload_layout)?.0;
-                layout = Layout::from_size_align(
-                    layout.size().max(full_layout.size()),
-                    layout.align().max(full_layout.align()),
-                )?;
-
-                (payload_ty, payload_layout)
-            },
-            {
-                let (payload_ty, payload_layout) = registry.build_type_with_layout(
-                    context,
-                    helper,
-                    registry,
-                    metadata,
-                    &info.branch_signatures()[1].vars[2].ty,
-                )?;
-
-                let full_layout = tag_layout.extend(payload_layout)?.0;
-                layout = Layout::from_size_align(
-                    layout.size().max(full_layout.size()),
-                    layout.align().max(full_layout.align()),
-                )?;
-
-                (payload_ty, payload_layout)
-            },
-        ];
-
-        (layout, (tag_ty, tag_layout), output)
-    };
+    let (result_layout, (result_tag_ty, result_tag_layout), variant_tys) =
+        crate::types::r#enum::get_type_for_variants(
+            context,
+            helper,
+            registry,
+            metadata,
+            &[
+                info.branch_signatures()[0].vars[2].ty.clone(),
+                info.branch_signatures()[1].vars[2].ty.clone(),
+            ],
+        )?;
 
     let k1 = helper
         .init_block()
@@ -2707,7 +2674,7 @@ where
                 .add_attributes(&[(
                     Identifier::new(context, "alignment"),
                     IntegerAttribute::new(
-                        get_integer_layout(64).align().try_into()?,
+                        result_layout.align().try_into()?,
                         IntegerType::new(context, 64).into(),
                     )
                     .into(),
@@ -2729,102 +2696,6 @@ where
         LoadStoreOptions::default(),
     ));
 
-    // Allocate `class_hash` argument and write the value.
-    let class_hash_arg_ptr_ty = llvm::r#type::pointer(IntegerType::new(context, 252).into(), 0);
-    let class_hash_arg_ptr = helper
-        .init_block()
-        .append_operation(
-            OperationBuilder::new("llvm.alloca", location)
-                .add_attributes(&[(
-                    Identifier::new(context, "alignment"),
-                    IntegerAttribute::new(
-                        get_integer_layout(252).align().try_into()?,
-                        IntegerType::new(context, 64).into(),
-                    )
-                    .into(),
-                )])
-                .add_operands(&[k1])
-                .add_results(&[class_hash_arg_ptr_ty])
-                .build()?,
-        )
-        .result(0)?
-        .into();
-    entry.append_operation(llvm::store(
-        context,
-        entry.argument(2)?.into(),
-        class_hash_arg_ptr,
-        location,
-        LoadStoreOptions::default(),
-    ));
-
-    // Allocate `entry_point_selector` argument and write the value.
-    let contract_address_salt_arg_ptr_ty =
-        llvm::r#type::pointer(IntegerType::new(context, 252).into(), 0);
-    let contract_address_salt_arg_ptr = helper
-        .init_block()
-        .append_operation(
-            OperationBuilder::new("llvm.alloca", location)
-                .add_attributes(&[(
-                    Identifier::new(context, "alignment"),
-                    IntegerAttribute::new(
-                        get_integer_layout(252).align().try_into().unwrap(),
-                        IntegerType::new(context, 64).into(),
-                    )
-                    .into(),
-                )])
-                .add_operands(&[k1])
-                .add_results(&[contract_address_salt_arg_ptr_ty])
-                .build()?,
-        )
-        .result(0)?
-        .into();
-    entry.append_operation(llvm::store(
-        context,
-        entry.argument(3)?.into(),
-        contract_address_salt_arg_ptr,
-        location,
-        LoadStoreOptions::default(),
-    ));
-
-    // Allocate `calldata` argument and write the value.
-    let calldata_arg_ptr_ty = llvm::r#type::pointer(
-        llvm::r#type::r#struct(
-            context,
-            &[llvm::r#type::r#struct(
-                context,
-                &[
-                    llvm::r#type::pointer(IntegerType::new(context, 252).into(), 0),
-                    IntegerType::new(context, 32).into(),
-                    IntegerType::new(context, 32).into(),
-                ],
-                false,
-            )],
-            false,
-        ),
-        0,
-    );
-    let calldata_arg_ptr = helper
-        .init_block()
-        .append_operation(
-            OperationBuilder::new("llvm.alloca", location)
-                .add_attributes(&[(
-                    Identifier::new(context, "alignment"),
-                    IntegerAttribute::new(8, IntegerType::new(context, 64).into()).into(),
-                )])
-                .add_operands(&[k1])
-                .add_results(&[calldata_arg_ptr_ty])
-                .build()?,
-        )
-        .result(0)?
-        .into();
-    entry.append_operation(llvm::store(
-        context,
-        entry.argument(4)?.into(),
-        calldata_arg_ptr,
-        location,
-        LoadStoreOptions::default(),
-    ));
-
     // Extract function pointer.
     let fn_ptr_ty = llvm::r#type::function(
         llvm::r#type::void(context),
@@ -2832,10 +2703,6 @@ where
             llvm::r#type::opaque_pointer(context),
             llvm::r#type::opaque_pointer(context),
             llvm::r#type::pointer(IntegerType::new(context, 128).into(), 0),
-            class_hash_arg_ptr_ty,
-            contract_address_salt_arg_ptr_ty,
-            calldata_arg_ptr_ty,
-            IntegerType::new(context, 1).into(),
         ],
         false,
     );
@@ -2845,7 +2712,7 @@ where
             entry.argument(1)?.into(),
             DenseI32ArrayAttribute::new(
                 context,
-                &[StarkNetSyscallHandlerCallbacks::<()>::DEPLOY.try_into()?],
+                &[StarkNetSyscallHandlerCallbacks::<()>::GET_EXECUTION_INFO_V2.try_into()?],
             ),
             llvm::r#type::opaque_pointer(context),
             llvm::r#type::opaque_pointer(context),
@@ -2866,25 +2733,7 @@ where
 
     entry.append_operation(
         OperationBuilder::new("llvm.call", location)
-            .add_operands(&[
-                fn_ptr,
-                result_ptr,
-                ptr,
-                gas_builtin_ptr,
-                class_hash_arg_ptr,
-                contract_address_salt_arg_ptr,
-                calldata_arg_ptr,
-                entry
-                    .append_operation(llvm::extract_value(
-                        context,
-                        entry.argument(5)?.into(),
-                        DenseI64ArrayAttribute::new(context, &[0]),
-                        IntegerType::new(context, 1).into(),
-                        location,
-                    ))
-                    .result(0)?
-                    .into(),
-            ])
+            .add_operands(&[fn_ptr, result_ptr, ptr, gas_builtin_ptr])
             .build()?,
     );
 
@@ -3001,45 +2850,20 @@ where
         .result(0)?
         .into();
 
-    entry.append_operation(
-        helper.cond_br(
-            context,
-            result_tag,
-            [1, 0],
-            [
-                &[remaining_gas, entry.argument(1)?.into(), payload_err],
-                &[
-                    remaining_gas,
-                    entry.argument(1)?.into(),
-                    entry
-                        .append_operation(llvm::extract_value(
-                            context,
-                            payload_ok,
-                            DenseI64ArrayAttribute::new(context, &[0]),
-                            get_struct_field_type_at(&variant_tys[0].0, 0),
-                            location,
-                        ))
-                        .result(0)?
-                        .into(),
-                    entry
-                        .append_operation(llvm::extract_value(
-                            context,
-                            payload_ok,
-                            DenseI64ArrayAttribute::new(context, &[1]),
-                            get_struct_field_type_at(&variant_tys[0].0, 1),
-                            location,
-                        ))
-                        .result(0)?
-                        .into(),
-                ],
-            ],
-            location,
-        ),
-    );
+    entry.append_operation(helper.cond_br(
+        context,
+        result_tag,
+        [1, 0],
+        [
+            &[remaining_gas, entry.argument(1)?.into(), payload_err],
+            &[remaining_gas, entry.argument(1)?.into(), payload_ok],
+        ],
+        location,
+    ));
     Ok(())
 }
 
-pub fn build_keccak<'ctx, 'this, TType, TLibfunc>(
+pub fn build_deploy<'ctx, 'this, TType, TLibfunc>(
     context: &'ctx Context,
     registry: &ProgramRegistry<TType, TLibfunc>,
     entry: &'this Block<'ctx>,
@@ -3067,21 +2891,64 @@ where
         .into();
 
     // Allocate space for the return value.
-    let (result_layout, (result_tag_ty, result_tag_layout), variant_tys) =
-        crate::types::r#enum::get_type_for_variants(
-            context,
-            helper,
-            registry,
-            metadata,
-            &[
-                info.branch_signatures()[0].vars[2].ty.clone(),
-                info.branch_signatures()[1].vars[2].ty.clone(),
-            ],
-        )?;
+    let (result_layout, (result_tag_ty, result_tag_layout), variant_tys) = {
+        let tag_layout = get_integer_layout(1);
+        let tag_ty: Type = IntegerType::new(context, 1).into();
 
-    let k1 = helper
-        .init_block()
-        .append_operation(arith::constant(
+        let mut layout = tag_layout;
+        let output = [
+            {
+                let (p0_ty, p0_layout) = registry.build_type_with_layout(
+                    context,
+                    helper,
+                    registry,
+                    metadata,
+                    &info.branch_signatures()[0].vars[2].ty,
+                )?;
+                let (p1_ty, p1_layout) = registry.build_type_with_layout(
+                    context,
+                    helper,
+                    registry,
+                    metadata,
+                    &info.branch_signatures()[0].vars[3].ty,
+                )?;
+
+                let payload_ty = llvm::r#type::r#struct(context, &[p0_ty, p1_ty], false);
+                let payload_layout = p0_layout.extend(p1_layout)?.0;
+
+                let full_layout = tag_layout.extend(payload_layout)?.0;
+                layout = Layout::from_size_align(
+                    layout.size().max(full_layout.size()),
+                    layout.align().max(full_layout.align()),
+                )?;
+
+                (payload_ty, payload_layout)
+            },
+            {
+                let (payload_ty, payload_layout) = registry.build_type_with_layout(
+                    context,
+                    helper,
+                    registry,
+                    metadata,
+                    &info.branch_signatures()[1].vars[2].ty,
+                )?;
+
+                let full_layout = tag_layout.extend(payload_layout)?.0;
+                layout = Layout::from_size_align(
+                    layout.size().max(full_layout.size()),
+                    layout.align().max(full_layout.align()),
+                )?;
+
+                (payload_ty, payload_layout)
+            },
+        ];
+
+        (layout, (tag_ty, tag_layout), output)
+    };
+
+    let k1 = helper
+        .init_block()
+        .append_operation(arith::constant(
             context,
             IntegerAttribute::new(1, IntegerType::new(context, 64).into()).into(),
             location,
@@ -3132,7 +2999,7 @@ where
                 .add_attributes(&[(
                     Identifier::new(context, "alignment"),
                     IntegerAttribute::new(
-                        result_layout.align().try_into()?,
+                        get_integer_layout(64).align().try_into()?,
                         IntegerType::new(context, 64).into(),
                     )
                     .into(),
@@ -3154,20 +3021,81 @@ where
         LoadStoreOptions::default(),
     ));
 
-    // Allocate `input` argument and write the value.
-    let input_arg_ptr_ty = llvm::r#type::pointer(
+    // Allocate `class_hash` argument and write the value.
+    let class_hash_arg_ptr_ty = llvm::r#type::pointer(IntegerType::new(context, 252).into(), 0);
+    let class_hash_arg_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[(
+                    Identifier::new(context, "alignment"),
+                    IntegerAttribute::new(
+                        get_integer_layout(252).align().try_into()?,
+                        IntegerType::new(context, 64).into(),
+                    )
+                    .into(),
+                )])
+                .add_operands(&[k1])
+                .add_results(&[class_hash_arg_ptr_ty])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+    entry.append_operation(llvm::store(
+        context,
+        entry.argument(2)?.into(),
+        class_hash_arg_ptr,
+        location,
+        LoadStoreOptions::default(),
+    ));
+
+    // Allocate `entry_point_selector` argument and write the value.
+    let contract_address_salt_arg_ptr_ty =
+        llvm::r#type::pointer(IntegerType::new(context, 252).into(), 0);
+    let contract_address_salt_arg_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[(
+                    Identifier::new(context, "alignment"),
+                    IntegerAttribute::new(
+                        get_integer_layout(252).align().try_into().unwrap(),
+                        IntegerType::new(context, 64).into(),
+                    )
+                    .into(),
+                )])
+                .add_operands(&[k1])
+                .add_results(&[contract_address_salt_arg_ptr_ty])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+    entry.append_operation(llvm::store(
+        context,
+        entry.argument(3)?.into(),
+        contract_address_salt_arg_ptr,
+        location,
+        LoadStoreOptions::default(),
+    ));
+
+    // Allocate `calldata` argument and write the value.
+    let calldata_arg_ptr_ty = llvm::r#type::pointer(
         llvm::r#type::r#struct(
             context,
-            &[
-                llvm::r#type::pointer(IntegerType::new(context, 64).into(), 0),
-                IntegerType::new(context, 32).into(),
-                IntegerType::new(context, 32).into(),
-            ],
+            &[llvm::r#type::r#struct(
+                context,
+                &[
+                    llvm::r#type::pointer(IntegerType::new(context, 252).into(), 0),
+                    IntegerType::new(context, 32).into(),
+                    IntegerType::new(context, 32).into(),
+                ],
+                false,
+            )],
             false,
         ),
         0,
     );
-    let input_arg_ptr = helper
+    let calldata_arg_ptr = helper
         .init_block()
         .append_operation(
             OperationBuilder::new("llvm.alloca", location)
@@ -3176,15 +3104,15 @@ where
                     IntegerAttribute::new(8, IntegerType::new(context, 64).into()).into(),
                 )])
                 .add_operands(&[k1])
-                .add_results(&[input_arg_ptr_ty])
+                .add_results(&[calldata_arg_ptr_ty])
                 .build()?,
         )
         .result(0)?
         .into();
     entry.append_operation(llvm::store(
         context,
-        entry.argument(2)?.into(),
-        input_arg_ptr,
+        entry.argument(4)?.into(),
+        calldata_arg_ptr,
         location,
         LoadStoreOptions::default(),
     ));
@@ -3196,7 +3124,10 @@ where
             llvm::r#type::opaque_pointer(context),
             llvm::r#type::opaque_pointer(context),
             llvm::r#type::pointer(IntegerType::new(context, 128).into(), 0),
-            input_arg_ptr_ty,
+            class_hash_arg_ptr_ty,
+            contract_address_salt_arg_ptr_ty,
+            calldata_arg_ptr_ty,
+            IntegerType::new(context, 1).into(),
         ],
         false,
     );
@@ -3206,7 +3137,7 @@ where
             entry.argument(1)?.into(),
             DenseI32ArrayAttribute::new(
                 context,
-                &[StarkNetSyscallHandlerCallbacks::<()>::KECCAK.try_into()?],
+                &[StarkNetSyscallHandlerCallbacks::<()>::DEPLOY.try_into()?],
             ),
             llvm::r#type::opaque_pointer(context),
             llvm::r#type::opaque_pointer(context),
@@ -3227,7 +3158,25 @@ where
 
     entry.append_operation(
         OperationBuilder::new("llvm.call", location)
-            .add_operands(&[fn_ptr, result_ptr, ptr, gas_builtin_ptr, input_arg_ptr])
+            .add_operands(&[
+                fn_ptr,
+                result_ptr,
+                ptr,
+                gas_builtin_ptr,
+                class_hash_arg_ptr,
+                contract_address_salt_arg_ptr,
+                calldata_arg_ptr,
+                entry
+                    .append_operation(llvm::extract_value(
+                        context,
+                        entry.argument(5)?.into(),
+                        DenseI64ArrayAttribute::new(context, &[0]),
+                        IntegerType::new(context, 1).into(),
+                        location,
+                    ))
+                    .result(0)?
+                    .into(),
+            ])
             .build()?,
     );
 
@@ -3344,20 +3293,45 @@ where
         .result(0)?
         .into();
 
-    entry.append_operation(helper.cond_br(
-        context,
-        result_tag,
-        [1, 0],
-        [
-            &[remaining_gas, entry.argument(1)?.into(), payload_err],
-            &[remaining_gas, entry.argument(1)?.into(), payload_ok],
-        ],
-        location,
-    ));
+    entry.append_operation(
+        helper.cond_br(
+            context,
+            result_tag,
+            [1, 0],
+            [
+                &[remaining_gas, entry.argument(1)?.into(), payload_err],
+                &[
+                    remaining_gas,
+                    entry.argument(1)?.into(),
+                    entry
+                        .append_operation(llvm::extract_value(
+                            context,
+                            payload_ok,
+                            DenseI64ArrayAttribute::new(context, &[0]),
+                            get_struct_field_type_at(&variant_tys[0].0, 0),
+                            location,
+                        ))
+                        .result(0)?
+                        .into(),
+                    entry
+                        .append_operation(llvm::extract_value(
+                            context,
+                            payload_ok,
+                            DenseI64ArrayAttribute::new(context, &[1]),
+                            get_struct_field_type_at(&variant_tys[0].0, 1),
+                            location,
+                        ))
+                        .result(0)?
+                        .into(),
+                ],
+            ],
+            location,
+        ),
+    );
     Ok(())
 }
 
-pub fn build_library_call<'ctx, 'this, TType, TLibfunc>(
+pub fn build_keccak<'ctx, 'this, TType, TLibfunc>(
     context: &'ctx Context,
     registry: &ProgramRegistry<TType, TLibfunc>,
     entry: &'this Block<'ctx>,
@@ -3472,22 +3446,29 @@ where
         LoadStoreOptions::default(),
     ));
 
-    // Allocate `class_hash` argument and write the value.
-    let class_hash_arg_ptr_ty = llvm::r#type::pointer(IntegerType::new(context, 252).into(), 0);
-    let class_hash_arg_ptr = helper
+    // Allocate `input` argument and write the value.
+    let input_arg_ptr_ty = llvm::r#type::pointer(
+        llvm::r#type::r#struct(
+            context,
+            &[
+                llvm::r#type::pointer(IntegerType::new(context, 64).into(), 0),
+                IntegerType::new(context, 32).into(),
+                IntegerType::new(context, 32).into(),
+            ],
+            false,
+        ),
+        0,
+    );
+    let input_arg_ptr = helper
         .init_block()
         .append_operation(
             OperationBuilder::new("llvm.alloca", location)
                 .add_attributes(&[(
                     Identifier::new(context, "alignment"),
-                    IntegerAttribute::new(
-                        get_integer_layout(252).align().try_into().unwrap(),
-                        IntegerType::new(context, 64).into(),
-                    )
-                    .into(),
+                    IntegerAttribute::new(8, IntegerType::new(context, 64).into()).into(),
                 )])
                 .add_operands(&[k1])
-                .add_results(&[class_hash_arg_ptr_ty])
+                .add_results(&[input_arg_ptr_ty])
                 .build()?,
         )
         .result(0)?
@@ -3495,75 +3476,7 @@ where
     entry.append_operation(llvm::store(
         context,
         entry.argument(2)?.into(),
-        class_hash_arg_ptr,
-        location,
-        LoadStoreOptions::default(),
-    ));
-
-    // Allocate `entry_point_selector` argument and write the value.
-    let function_selector_arg_ptr_ty =
-        llvm::r#type::pointer(IntegerType::new(context, 252).into(), 0);
-    let function_selector_arg_ptr = helper
-        .init_block()
-        .append_operation(
-            OperationBuilder::new("llvm.alloca", location)
-                .add_attributes(&[(
-                    Identifier::new(context, "alignment"),
-                    IntegerAttribute::new(
-                        get_integer_layout(252).align().try_into().unwrap(),
-                        IntegerType::new(context, 64).into(),
-                    )
-                    .into(),
-                )])
-                .add_operands(&[k1])
-                .add_results(&[function_selector_arg_ptr_ty])
-                .build()?,
-        )
-        .result(0)?
-        .into();
-    entry.append_operation(llvm::store(
-        context,
-        entry.argument(3)?.into(),
-        function_selector_arg_ptr,
-        location,
-        LoadStoreOptions::default(),
-    ));
-
-    // Allocate `calldata` argument and write the value.
-    let calldata_arg_ptr_ty = llvm::r#type::pointer(
-        llvm::r#type::r#struct(
-            context,
-            &[llvm::r#type::r#struct(
-                context,
-                &[
-                    llvm::r#type::pointer(IntegerType::new(context, 252).into(), 0),
-                    IntegerType::new(context, 32).into(),
-                    IntegerType::new(context, 32).into(),
-                ],
-                false,
-            )],
-            false,
-        ),
-        0,
-    );
-    let calldata_arg_ptr = helper
-        .init_block()
-        .append_operation(
-            OperationBuilder::new("llvm.alloca", location)
-                .add_attributes(&[(
-                    Identifier::new(context, "alignment"),
-                    IntegerAttribute::new(8, IntegerType::new(context, 64).into()).into(),
-                )])
-                .add_operands(&[k1])
-                .add_results(&[calldata_arg_ptr_ty])
-                .build()?,
-        )
-        .result(0)?
-        .into();
-    entry.append_operation(llvm::store(
-        context,
-        entry.argument(4)?.into(),
-        calldata_arg_ptr,
+        input_arg_ptr,
         location,
         LoadStoreOptions::default(),
     ));
@@ -3575,9 +3488,7 @@ where
             llvm::r#type::opaque_pointer(context),
             llvm::r#type::opaque_pointer(context),
             llvm::r#type::pointer(IntegerType::new(context, 128).into(), 0),
-            class_hash_arg_ptr_ty,
-            function_selector_arg_ptr_ty,
-            calldata_arg_ptr_ty,
+            input_arg_ptr_ty,
         ],
         false,
     );
@@ -3587,7 +3498,7 @@ where
             entry.argument(1)?.into(),
             DenseI32ArrayAttribute::new(
                 context,
-                &[StarkNetSyscallHandlerCallbacks::<()>::LIBRARY_CALL.try_into()?],
+                &[StarkNetSyscallHandlerCallbacks::<()>::KECCAK.try_into()?],
             ),
             llvm::r#type::opaque_pointer(context),
             llvm::r#type::opaque_pointer(context),
@@ -3608,15 +3519,7 @@ where
 
     entry.append_operation(
         OperationBuilder::new("llvm.call", location)
-            .add_operands(&[
-                fn_ptr,
-                result_ptr,
-                ptr,
-                gas_builtin_ptr,
-                class_hash_arg_ptr,
-                function_selector_arg_ptr,
-                calldata_arg_ptr,
-            ])
+            .add_operands(&[fn_ptr, result_ptr, ptr, gas_builtin_ptr, input_arg_ptr])
             .build()?,
     );
 
@@ -3746,7 +3649,7 @@ where
     Ok(())
 }
 
-pub fn build_replace_class<'ctx, 'this, TType, TLibfunc>(
+pub fn build_library_call<'ctx, 'this, TType, TLibfunc>(
     context: &'ctx Context,
     registry: &ProgramRegistry<TType, TLibfunc>,
     entry: &'this Block<'ctx>,
@@ -3781,11 +3684,7 @@ where
             registry,
             metadata,
             &[
-                // The branch is deliberately duplicated because:
-                //   - There is no `[0].vars[2]` (it returns `()`).
-                //   - We need a variant to make the length be 2.
-                //   - It requires a `ConcreteTypeId`, we can't pass an MLIR type.
-                info.branch_signatures()[1].vars[2].ty.clone(),
+                info.branch_signatures()[0].vars[2].ty.clone(),
                 info.branch_signatures()[1].vars[2].ty.clone(),
             ],
         )?;
@@ -3893,6 +3792,74 @@ where
         LoadStoreOptions::default(),
     ));
 
+    // Allocate `entry_point_selector` argument and write the value.
+    let function_selector_arg_ptr_ty =
+        llvm::r#type::pointer(IntegerType::new(context, 252).into(), 0);
+    let function_selector_arg_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[(
+                    Identifier::new(context, "alignment"),
+                    IntegerAttribute::new(
+                        get_integer_layout(252).align().try_into().unwrap(),
+                        IntegerType::new(context, 64).into(),
+                    )
+                    .into(),
+                )])
+                .add_operands(&[k1])
+                .add_results(&[function_selector_arg_ptr_ty])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+    entry.append_operation(llvm::store(
+        context,
+        entry.argument(3)?.into(),
+        function_selector_arg_ptr,
+        location,
+        LoadStoreOptions::default(),
+    ));
+
+    // Allocate `calldata` argument and write the value.
+    let calldata_arg_ptr_ty = llvm::r#type::pointer(
+        llvm::r#type::r#struct(
+            context,
+            &[llvm::r#type::r#struct(
+                context,
+                &[
+                    llvm::r#type::pointer(IntegerType::new(context, 252).into(), 0),
+                    IntegerType::new(context, 32).into(),
+                    IntegerType::new(context, 32).into(),
+                ],
+                false,
+            )],
+            false,
+        ),
+        0,
+    );
+    let calldata_arg_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[(
+                    Identifier::new(context, "alignment"),
+                    IntegerAttribute::new(8, IntegerType::new(context, 64).into()).into(),
+                )])
+                .add_operands(&[k1])
+                .add_results(&[calldata_arg_ptr_ty])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+    entry.append_operation(llvm::store(
+        context,
+        entry.argument(4)?.into(),
+        calldata_arg_ptr,
+        location,
+        LoadStoreOptions::default(),
+    ));
+
     // Extract function pointer.
     let fn_ptr_ty = llvm::r#type::function(
         llvm::r#type::void(context),
@@ -3901,6 +3868,8 @@ where
             llvm::r#type::opaque_pointer(context),
             llvm::r#type::pointer(IntegerType::new(context, 128).into(), 0),
             class_hash_arg_ptr_ty,
+            function_selector_arg_ptr_ty,
+            calldata_arg_ptr_ty,
         ],
         false,
     );
@@ -3910,7 +3879,7 @@ where
             entry.argument(1)?.into(),
             DenseI32ArrayAttribute::new(
                 context,
-                &[StarkNetSyscallHandlerCallbacks::<()>::REPLACE_CLASS.try_into()?],
+                &[StarkNetSyscallHandlerCallbacks::<()>::LIBRARY_CALL.try_into()?],
             ),
             llvm::r#type::opaque_pointer(context),
             llvm::r#type::opaque_pointer(context),
@@ -3931,7 +3900,15 @@ where
 
     entry.append_operation(
         OperationBuilder::new("llvm.call", location)
-            .add_operands(&[fn_ptr, result_ptr, ptr, gas_builtin_ptr, class_hash_arg_ptr])
+            .add_operands(&[
+                fn_ptr,
+                result_ptr,
+                ptr,
+                gas_builtin_ptr,
+                class_hash_arg_ptr,
+                function_selector_arg_ptr,
+                calldata_arg_ptr,
+            ])
             .build()?,
     );
 
@@ -4061,7 +4038,7 @@ where
     Ok(())
 }
 
-pub fn build_send_message_to_l1<'ctx, 'this, TType, TLibfunc>(
+pub fn build_replace_class<'ctx, 'this, TType, TLibfunc>(
     context: &'ctx Context,
     registry: &ProgramRegistry<TType, TLibfunc>,
     entry: &'this Block<'ctx>,
@@ -4180,9 +4157,9 @@ where
         LoadStoreOptions::default(),
     ));
 
-    // Allocate `to_address` argument and write the value.
-    let to_address_arg_ptr_ty = llvm::r#type::pointer(IntegerType::new(context, 252).into(), 0);
-    let to_address_arg_ptr = helper
+    // Allocate `class_hash` argument and write the value.
+    let class_hash_arg_ptr_ty = llvm::r#type::pointer(IntegerType::new(context, 252).into(), 0);
+    let class_hash_arg_ptr = helper
         .init_block()
         .append_operation(
             OperationBuilder::new("llvm.alloca", location)
@@ -4195,7 +4172,7 @@ where
                     .into(),
                 )])
                 .add_operands(&[k1])
-                .add_results(&[to_address_arg_ptr_ty])
+                .add_results(&[class_hash_arg_ptr_ty])
                 .build()?,
         )
         .result(0)?
@@ -4203,46 +4180,7 @@ where
     entry.append_operation(llvm::store(
         context,
         entry.argument(2)?.into(),
-        to_address_arg_ptr,
-        location,
-        LoadStoreOptions::default(),
-    ));
-
-    // Allocate `payload` argument and write the value.
-    let payload_arg_ptr_ty = llvm::r#type::pointer(
-        llvm::r#type::r#struct(
-            context,
-            &[llvm::r#type::r#struct(
-                context,
-                &[
-                    llvm::r#type::pointer(IntegerType::new(context, 252).into(), 0),
-                    IntegerType::new(context, 32).into(),
-                    IntegerType::new(context, 32).into(),
-                ],
-                false,
-            )],
-            false,
-        ),
-        0,
-    );
-    let payload_arg_ptr = helper
-        .init_block()
-        .append_operation(
-            OperationBuilder::new("llvm.alloca", location)
-                .add_attributes(&[(
-                    Identifier::new(context, "alignment"),
-                    IntegerAttribute::new(8, IntegerType::new(context, 64).into()).into(),
-                )])
-                .add_operands(&[k1])
-                .add_results(&[payload_arg_ptr_ty])
-                .build()?,
-        )
-        .result(0)?
-        .into();
-    entry.append_operation(llvm::store(
-        context,
-        entry.argument(3)?.into(),
-        payload_arg_ptr,
+        class_hash_arg_ptr,
         location,
         LoadStoreOptions::default(),
     ));
@@ -4254,8 +4192,7 @@ where
             llvm::r#type::opaque_pointer(context),
             llvm::r#type::opaque_pointer(context),
             llvm::r#type::pointer(IntegerType::new(context, 128).into(), 0),
-            to_address_arg_ptr_ty,
-            payload_arg_ptr_ty,
+            class_hash_arg_ptr_ty,
         ],
         false,
     );
@@ -4265,7 +4202,7 @@ where
             entry.argument(1)?.into(),
             DenseI32ArrayAttribute::new(
                 context,
-                &[StarkNetSyscallHandlerCallbacks::<()>::SEND_MESSAGE_TO_L1.try_into()?],
+                &[StarkNetSyscallHandlerCallbacks::<()>::REPLACE_CLASS.try_into()?],
             ),
             llvm::r#type::opaque_pointer(context),
             llvm::r#type::opaque_pointer(context),
@@ -4286,14 +4223,7 @@ where
 
     entry.append_operation(
         OperationBuilder::new("llvm.call", location)
-            .add_operands(&[
-                fn_ptr,
-                result_ptr,
-                ptr,
-                gas_builtin_ptr,
-                to_address_arg_ptr,
-                payload_arg_ptr,
-            ])
+            .add_operands(&[fn_ptr, result_ptr, ptr, gas_builtin_ptr, class_hash_arg_ptr])
             .build()?,
     );
 
@@ -4423,14 +4353,2339 @@ where
     Ok(())
 }
 
-#[cfg(test)]
-mod test {
-    use crate::utils::test::{jit_enum, jit_struct, load_cairo, run_program_assert_output};
-    use cairo_lang_sierra::program::Program;
-    use lazy_static::lazy_static;
-    use starknet_types_core::felt::Felt;
+pub fn build_send_message_to_l1<'ctx, 'this, TType, TLibfunc>(
+    context: &'ctx Context,
+    registry: &ProgramRegistry<TType, TLibfunc>,
+    entry: &'this Block<'ctx>,
+    location: Location<'ctx>,
+    helper: &LibfuncHelper<'ctx, 'this>,
+    metadata: &mut MetadataStorage,
+    info: &SignatureOnlyConcreteLibfunc,
+) -> Result<()>
+where
+    TType: GenericType,
+    TLibfunc: GenericLibfunc,
+    <TType as GenericType>::Concrete: TypeBuilder<TType, TLibfunc, Error = CoreTypeBuilderError>,
+    <TLibfunc as GenericLibfunc>::Concrete: LibfuncBuilder<TType, TLibfunc, Error = Error>,
+{
+    // Extract self pointer.
+    let ptr = entry
+        .append_operation(llvm::load(
+            context,
+            entry.argument(1)?.into(),
+            llvm::r#type::opaque_pointer(context),
+            location,
+            LoadStoreOptions::default(),
+        ))
+        .result(0)?
+        .into();
 
-    lazy_static! {
+    // Allocate space for the return value.
+    let (result_layout, (result_tag_ty, result_tag_layout), variant_tys) =
+        crate::types::r#enum::get_type_for_variants(
+            context,
+            helper,
+            registry,
+            metadata,
+            &[
+                // The branch is deliberately duplicated because:
+                //   - There is no `[0].vars[2]` (it returns `()`).
+                //   - We need a variant to make the length be 2.
+                //   - It requires a `ConcreteTypeId`, we can't pass an MLIR type.
+                info.branch_signatures()[1].vars[2].ty.clone(),
+                info.branch_signatures()[1].vars[2].ty.clone(),
+            ],
+        )?;
+
+    let k1 = helper
+        .init_block()
+        .append_operation(arith::constant(
+            context,
+            IntegerAttribute::new(1, IntegerType::new(context, 64).into()).into(),
+            location,
+        ))
+        .result(0)?
+        .into();
+    let result_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[
+                    (
+                        Identifier::new(context, "alignment"),
+                        IntegerAttribute::new(
+                            result_layout.align().try_into()?,
+                            IntegerType::new(context, 64).into(),
+                        )
+                        .into(),
+                    ),
+                    (
+                        Identifier::new(context, "elem_type"),
+                        TypeAttribute::new(llvm::r#type::r#struct(
+                            context,
+                            &[
+                                result_tag_ty,
+                                llvm::r#type::array(
+                                    IntegerType::new(context, 8).into(),
+                                    (result_layout.size() - 1).try_into()?,
+                                ),
+                            ],
+                            false,
+                        ))
+                        .into(),
+                    ),
+                ])
+                .add_operands(&[k1])
+                .add_results(&[llvm::r#type::opaque_pointer(context)])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+
+    // Allocate space and write the current gas.
+    let gas_builtin_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[(
+                    Identifier::new(context, "alignment"),
+                    IntegerAttribute::new(
+                        result_layout.align().try_into()?,
+                        IntegerType::new(context, 64).into(),
+                    )
+                    .into(),
+                )])
+                .add_operands(&[k1])
+                .add_results(&[llvm::r#type::pointer(
+                    IntegerType::new(context, 128).into(),
+                    0,
+                )])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+    entry.append_operation(llvm::store(
+        context,
+        entry.argument(0)?.into(),
+        gas_builtin_ptr,
+        location,
+        LoadStoreOptions::default(),
+    ));
+
+    // Allocate `to_address` argument and write the value.
+    let to_address_arg_ptr_ty = llvm::r#type::pointer(IntegerType::new(context, 252).into(), 0);
+    let to_address_arg_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[(
+                    Identifier::new(context, "alignment"),
+                    IntegerAttribute::new(
+                        get_integer_layout(252).align().try_into().unwrap(),
+                        IntegerType::new(context, 64).into(),
+                    )
+                    .into(),
+                )])
+                .add_operands(&[k1])
+                .add_results(&[to_address_arg_ptr_ty])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+    entry.append_operation(llvm::store(
+        context,
+        entry.argument(2)?.into(),
+        to_address_arg_ptr,
+        location,
+        LoadStoreOptions::default(),
+    ));
+
+    // Allocate `payload` argument and write the value.
+    let payload_arg_ptr_ty = llvm::r#type::pointer(
+        llvm::r#type::r#struct(
+            context,
+            &[llvm::r#type::r#struct(
+                context,
+                &[
+                    llvm::r#type::pointer(IntegerType::new(context, 252).into(), 0),
+                    IntegerType::new(context, 32).into(),
+                    IntegerType::new(context, 32).into(),
+                ],
+                false,
+            )],
+            false,
+        ),
+        0,
+    );
+    let payload_arg_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[(
+                    Identifier::new(context, "alignment"),
+                    IntegerAttribute::new(8, IntegerType::new(context, 64).into()).into(),
+                )])
+                .add_operands(&[k1])
+                .add_results(&[payload_arg_ptr_ty])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+    entry.append_operation(llvm::store(
+        context,
+        entry.argument(3)?.into(),
+        payload_arg_ptr,
+        location,
+        LoadStoreOptions::default(),
+    ));
+
+    // Extract function pointer.
+    let fn_ptr_ty = llvm::r#type::function(
+        llvm::r#type::void(context),
+        &[
+            llvm::r#type::opaque_pointer(context),
+            llvm::r#type::opaque_pointer(context),
+            llvm::r#type::pointer(IntegerType::new(context, 128).into(), 0),
+            to_address_arg_ptr_ty,
+            payload_arg_ptr_ty,
+        ],
+        false,
+    );
+    let fn_ptr = entry
+        .append_operation(llvm::get_element_ptr(
+            context,
+            entry.argument(1)?.into(),
+            DenseI32ArrayAttribute::new(
+                context,
+                &[StarkNetSyscallHandlerCallbacks::<()>::SEND_MESSAGE_TO_L1.try_into()?],
+            ),
+            llvm::r#type::opaque_pointer(context),
+            llvm::r#type::opaque_pointer(context),
+            location,
+        ))
+        .result(0)?
+        .into();
+    let fn_ptr = entry
+        .append_operation(llvm::load(
+            context,
+            fn_ptr,
+            llvm::r#type::pointer(fn_ptr_ty, 0),
+            location,
+            LoadStoreOptions::default(),
+        ))
+        .result(0)?
+        .into();
+
+    entry.append_operation(
+        OperationBuilder::new("llvm.call", location)
+            .add_operands(&[
+                fn_ptr,
+                result_ptr,
+                ptr,
+                gas_builtin_ptr,
+                to_address_arg_ptr,
+                payload_arg_ptr,
+            ])
+            .build()?,
+    );
+
+    let result = entry
+        .append_operation(llvm::load(
+            context,
+            result_ptr,
+            llvm::r#type::r#struct(
+                context,
+                &[
+                    result_tag_ty,
+                    llvm::r#type::array(
+                        IntegerType::new(context, 8).into(),
+                        (result_layout.size() - 1).try_into()?,
+                    ),
+                ],
+                false,
+            ),
+            location,
+            LoadStoreOptions::default(),
+        ))
+        .result(0)?
+        .into();
+    let result_tag = entry
+        .append_operation(llvm::extract_value(
+            context,
+            result,
+            DenseI64ArrayAttribute::new(context, &[0]),
+            IntegerType::new(context, 1).into(),
+            location,
+        ))
+        .result(0)?
+        .into();
+
+    let payload_ok = {
+        let ptr = entry
+            .append_operation(
+                OperationBuilder::new("llvm.getelementptr", location)
+                    .add_attributes(&[
+                        (
+                            Identifier::new(context, "rawConstantIndices"),
+                            DenseI32ArrayAttribute::new(
+                                context,
+                                &[result_tag_layout.extend(variant_tys[0].1)?.1.try_into()?],
+                            )
+                            .into(),
+                        ),
+                        (
+                            Identifier::new(context, "elem_type"),
+                            TypeAttribute::new(IntegerType::new(context, 8).into()).into(),
+                        ),
+                    ])
+                    .add_operands(&[result_ptr])
+                    .add_results(&[llvm::r#type::opaque_pointer(context)])
+                    .build()?,
+            )
+            .result(0)?
+            .into();
+        entry
+            .append_operation(llvm::load(
+                context,
+                ptr,
+                variant_tys[0].0,
+                location,
+                LoadStoreOptions::default(),
+            ))
+            .result(0)?
+            .into()
+    };
+    let payload_err = {
+        let ptr = entry
+            .append_operation(
+                OperationBuilder::new("llvm.getelementptr", location)
+                    .add_attributes(&[
+                        (
+                            Identifier::new(context, "rawConstantIndices"),
+                            DenseI32ArrayAttribute::new(
+                                context,
+                                &[result_tag_layout.extend(variant_tys[1].1)?.1.try_into()?],
+                            )
+                            .into(),
+                        ),
+                        (
+                            Identifier::new(context, "elem_type"),
+                            TypeAttribute::new(IntegerType::new(context, 8).into()).into(),
+                        ),
+                    ])
+                    .add_operands(&[result_ptr])
+                    .add_results(&[llvm::r#type::opaque_pointer(context)])
+                    .build()?,
+            )
+            .result(0)?
+            .into();
+        entry
+            .append_operation(llvm::load(
+                context,
+                ptr,
+                variant_tys[1].0,
+                location,
+                LoadStoreOptions::default(),
+            ))
+            .result(0)?
+            .into()
+    };
+
+    let remaining_gas = entry
+        .append_operation(llvm::load(
+            context,
+            gas_builtin_ptr,
+            IntegerType::new(context, 128).into(),
+            location,
+            LoadStoreOptions::default(),
+        ))
+        .result(0)?
+        .into();
+
+    entry.append_operation(helper.cond_br(
+        context,
+        result_tag,
+        [1, 0],
+        [
+            &[remaining_gas, entry.argument(1)?.into(), payload_err],
+            &[remaining_gas, entry.argument(1)?.into(), payload_ok],
+        ],
+        location,
+    ));
+    Ok(())
+}
+
+/// Select and call the correct secp256 libfunc builder function from the selector.
+///
+/// Both curves share the same lowering, the only difference being the syscall handler callback
+/// which is invoked.
+pub fn build_secp256<'ctx, 'this, TType, TLibfunc>(
+    context: &'ctx Context,
+    registry: &ProgramRegistry<TType, TLibfunc>,
+    entry: &'this Block<'ctx>,
+    location: Location<'ctx>,
+    helper: &LibfuncHelper<'ctx, 'this>,
+    metadata: &mut MetadataStorage,
+    selector: &Secp256ConcreteLibfunc,
+) -> Result<()>
+where
+    TType: GenericType,
+    TLibfunc: GenericLibfunc,
+    <TType as GenericType>::Concrete: TypeBuilder<TType, TLibfunc, Error = CoreTypeBuilderError>,
+    <TLibfunc as GenericLibfunc>::Concrete: LibfuncBuilder<TType, TLibfunc, Error = Error>,
+{
+    match selector {
+        Secp256ConcreteLibfunc::K1(Secp256OpConcreteLibfunc::New(info)) => build_secp256_new(
+            context,
+            registry,
+            entry,
+            location,
+            helper,
+            metadata,
+            info,
+            StarkNetSyscallHandlerCallbacks::<()>::SECP256K1_NEW,
+        ),
+        Secp256ConcreteLibfunc::K1(Secp256OpConcreteLibfunc::Add(info)) => build_secp256_add(
+            context,
+            registry,
+            entry,
+            location,
+            helper,
+            metadata,
+            info,
+            StarkNetSyscallHandlerCallbacks::<()>::SECP256K1_ADD,
+        ),
+        Secp256ConcreteLibfunc::K1(Secp256OpConcreteLibfunc::Mul(info)) => build_secp256_mul(
+            context,
+            registry,
+            entry,
+            location,
+            helper,
+            metadata,
+            info,
+            StarkNetSyscallHandlerCallbacks::<()>::SECP256K1_MUL,
+        ),
+        Secp256ConcreteLibfunc::K1(Secp256OpConcreteLibfunc::GetPointFromX(info)) => {
+            build_secp256_get_point_from_x(
+                context,
+                registry,
+                entry,
+                location,
+                helper,
+                metadata,
+                info,
+                StarkNetSyscallHandlerCallbacks::<()>::SECP256K1_GET_POINT_FROM_X,
+            )
+        }
+        Secp256ConcreteLibfunc::K1(Secp256OpConcreteLibfunc::GetXy(info)) => build_secp256_get_xy(
+            context,
+            registry,
+            entry,
+            location,
+            helper,
+            metadata,
+            info,
+            StarkNetSyscallHandlerCallbacks::<()>::SECP256K1_GET_XY,
+        ),
+        Secp256ConcreteLibfunc::R1(Secp256OpConcreteLibfunc::New(info)) => build_secp256_new(
+            context,
+            registry,
+            entry,
+            location,
+            helper,
+            metadata,
+            info,
+            StarkNetSyscallHandlerCallbacks::<()>::SECP256R1_NEW,
+        ),
+        Secp256ConcreteLibfunc::R1(Secp256OpConcreteLibfunc::Add(info)) => build_secp256_add(
+            context,
+            registry,
+            entry,
+            location,
+            helper,
+            metadata,
+            info,
+            StarkNetSyscallHandlerCallbacks::<()>::SECP256R1_ADD,
+        ),
+        Secp256ConcreteLibfunc::R1(Secp256OpConcreteLibfunc::Mul(info)) => build_secp256_mul(
+            context,
+            registry,
+            entry,
+            location,
+            helper,
+            metadata,
+            info,
+            StarkNetSyscallHandlerCallbacks::<()>::SECP256R1_MUL,
+        ),
+        Secp256ConcreteLibfunc::R1(Secp256OpConcreteLibfunc::GetPointFromX(info)) => {
+            build_secp256_get_point_from_x(
+                context,
+                registry,
+                entry,
+                location,
+                helper,
+                metadata,
+                info,
+                StarkNetSyscallHandlerCallbacks::<()>::SECP256R1_GET_POINT_FROM_X,
+            )
+        }
+        Secp256ConcreteLibfunc::R1(Secp256OpConcreteLibfunc::GetXy(info)) => build_secp256_get_xy(
+            context,
+            registry,
+            entry,
+            location,
+            helper,
+            metadata,
+            info,
+            StarkNetSyscallHandlerCallbacks::<()>::SECP256R1_GET_XY,
+        ),
+    }
+}
+
+fn build_secp256_new<'ctx, 'this, TType, TLibfunc>(
+    context: &'ctx Context,
+    registry: &ProgramRegistry<TType, TLibfunc>,
+    entry: &'this Block<'ctx>,
+    location: Location<'ctx>,
+    helper: &LibfuncHelper<'ctx, 'this>,
+    metadata: &mut MetadataStorage,
+    info: &SignatureOnlyConcreteLibfunc,
+    callback_idx: usize,
+) -> Result<()>
+where
+    TType: GenericType,
+    TLibfunc: GenericLibfunc,
+    <TType as GenericType>::Concrete: TypeBuilder<TType, TLibfunc, Error = CoreTypeBuilderError>,
+    <TLibfunc as GenericLibfunc>::Concrete: LibfuncBuilder<TType, TLibfunc, Error = Error>,
+{
+    // Extract self pointer.
+    let ptr = entry
+        .append_operation(llvm::load(
+            context,
+            entry.argument(1)?.into(),
+            llvm::r#type::opaque_pointer(context),
+            location,
+            LoadStoreOptions::default(),
+        ))
+        .result(0)?
+        .into();
+
+    // Allocate space for the return value.
+    let (result_layout, (result_tag_ty, result_tag_layout), variant_tys) =
+        crate::types::r#enum::get_type_for_variants(
+            context,
+            helper,
+            registry,
+            metadata,
+            &[
+                info.branch_signatures()[0].vars[2].ty.clone(),
+                info.branch_signatures()[1].vars[2].ty.clone(),
+            ],
+        )?;
+
+    let k1 = helper
+        .init_block()
+        .append_operation(arith::constant(
+            context,
+            IntegerAttribute::new(1, IntegerType::new(context, 64).into()).into(),
+            location,
+        ))
+        .result(0)?
+        .into();
+    let result_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[
+                    (
+                        Identifier::new(context, "alignment"),
+                        IntegerAttribute::new(
+                            result_layout.align().try_into()?,
+                            IntegerType::new(context, 64).into(),
+                        )
+                        .into(),
+                    ),
+                    (
+                        Identifier::new(context, "elem_type"),
+                        TypeAttribute::new(llvm::r#type::r#struct(
+                            context,
+                            &[
+                                result_tag_ty,
+                                llvm::r#type::array(
+                                    IntegerType::new(context, 8).into(),
+                                    (result_layout.size() - 1).try_into()?,
+                                ),
+                            ],
+                            false,
+                        ))
+                        .into(),
+                    ),
+                ])
+                .add_operands(&[k1])
+                .add_results(&[llvm::r#type::opaque_pointer(context)])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+
+    // Allocate space and write the current gas.
+    let gas_builtin_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[(
+                    Identifier::new(context, "alignment"),
+                    IntegerAttribute::new(
+                        get_integer_layout(128).align().try_into()?,
+                        IntegerType::new(context, 64).into(),
+                    )
+                    .into(),
+                )])
+                .add_operands(&[k1])
+                .add_results(&[llvm::r#type::pointer(
+                    IntegerType::new(context, 128).into(),
+                    0,
+                )])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+    entry.append_operation(llvm::store(
+        context,
+        entry.argument(0)?.into(),
+        gas_builtin_ptr,
+        location,
+        LoadStoreOptions::default(),
+    ));
+
+    // Allocate `x` argument and write the value.
+    let x_arg_ptr_ty = llvm::r#type::pointer(
+        registry.build_type(
+            context,
+            helper,
+            registry,
+            metadata,
+            &info.param_signatures()[2].ty,
+        )?,
+        0,
+    );
+    let x_arg_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[(
+                    Identifier::new(context, "alignment"),
+                    IntegerAttribute::new(
+                        get_integer_layout(128).align().try_into()?,
+                        IntegerType::new(context, 64).into(),
+                    )
+                    .into(),
+                )])
+                .add_operands(&[k1])
+                .add_results(&[x_arg_ptr_ty])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+    entry.append_operation(llvm::store(
+        context,
+        entry.argument(2)?.into(),
+        x_arg_ptr,
+        location,
+        LoadStoreOptions::default(),
+    ));
+
+    // Allocate `y` argument and write the value.
+    let y_arg_ptr_ty = llvm::r#type::pointer(
+        registry.build_type(
+            context,
+            helper,
+            registry,
+            metadata,
+            &info.param_signatures()[3].ty,
+        )?,
+        0,
+    );
+    let y_arg_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[(
+                    Identifier::new(context, "alignment"),
+                    IntegerAttribute::new(
+                        get_integer_layout(128).align().try_into()?,
+                        IntegerType::new(context, 64).into(),
+                    )
+                    .into(),
+                )])
+                .add_operands(&[k1])
+                .add_results(&[y_arg_ptr_ty])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+    entry.append_operation(llvm::store(
+        context,
+        entry.argument(3)?.into(),
+        y_arg_ptr,
+        location,
+        LoadStoreOptions::default(),
+    ));
+
+    // Extract function pointer.
+    let fn_ptr_ty = llvm::r#type::function(
+        llvm::r#type::void(context),
+        &[
+            llvm::r#type::opaque_pointer(context),
+            llvm::r#type::opaque_pointer(context),
+            llvm::r#type::pointer(IntegerType::new(context, 128).into(), 0),
+            x_arg_ptr_ty,
+            y_arg_ptr_ty,
+        ],
+        false,
+    );
+    let fn_ptr = entry
+        .append_operation(llvm::get_element_ptr(
+            context,
+            entry.argument(1)?.into(),
+            DenseI32ArrayAttribute::new(context, &[callback_idx.try_into()?]),
+            llvm::r#type::opaque_pointer(context),
+            llvm::r#type::opaque_pointer(context),
+            location,
+        ))
+        .result(0)?
+        .into();
+    let fn_ptr = entry
+        .append_operation(llvm::load(
+            context,
+            fn_ptr,
+            llvm::r#type::pointer(fn_ptr_ty, 0),
+            location,
+            LoadStoreOptions::default(),
+        ))
+        .result(0)?
+        .into();
+
+    entry.append_operation(
+        OperationBuilder::new("llvm.call", location)
+            .add_operands(&[
+                fn_ptr,
+                result_ptr,
+                ptr,
+                gas_builtin_ptr,
+                x_arg_ptr,
+                y_arg_ptr,
+            ])
+            .build()?,
+    );
+
+    let result = entry
+        .append_operation(llvm::load(
+            context,
+            result_ptr,
+            llvm::r#type::r#struct(
+                context,
+                &[
+                    result_tag_ty,
+                    llvm::r#type::array(
+                        IntegerType::new(context, 8).into(),
+                        (result_layout.size() - 1).try_into()?,
+                    ),
+                ],
+                false,
+            ),
+            location,
+            LoadStoreOptions::default(),
+        ))
+        .result(0)?
+        .into();
+    let result_tag = entry
+        .append_operation(llvm::extract_value(
+            context,
+            result,
+            DenseI64ArrayAttribute::new(context, &[0]),
+            IntegerType::new(context, 1).into(),
+            location,
+        ))
+        .result(0)?
+        .into();
+
+    let payload_ok = {
+        let ptr = entry
+            .append_operation(
+                OperationBuilder::new("llvm.getelementptr", location)
+                    .add_attributes(&[
+                        (
+                            Identifier::new(context, "rawConstantIndices"),
+                            DenseI32ArrayAttribute::new(
+                                context,
+                                &[result_tag_layout.extend(variant_tys[0].1)?.1.try_into()?],
+                            )
+                            .into(),
+                        ),
+                        (
+                            Identifier::new(context, "elem_type"),
+                            TypeAttribute::new(IntegerType::new(context, 8).into()).into(),
+                        ),
+                    ])
+                    .add_operands(&[result_ptr])
+                    .add_results(&[llvm::r#type::opaque_pointer(context)])
+                    .build()?,
+            )
+            .result(0)?
+            .into();
+
+        // The `Option<Secp256Point>` is memory-allocated, therefore its pointer is forwarded instead.
+        if registry
+            .get_type(&info.branch_signatures()[0].vars[2].ty)?
+            .is_memory_allocated(registry)
+        {
+            ptr
+        } else {
+            entry
+                .append_operation(llvm::load(
+                    context,
+                    ptr,
+                    variant_tys[0].0,
+                    location,
+                    LoadStoreOptions::default(),
+                ))
+                .result(0)?
+                .into()
+        }
+    };
+    let payload_err = {
+        let ptr = entry
+            .append_operation(
+                OperationBuilder::new("llvm.getelementptr", location)
+                    .add_attributes(&[
+                        (
+                            Identifier::new(context, "rawConstantIndices"),
+                            DenseI32ArrayAttribute::new(
+                                context,
+                                &[result_tag_layout.extend(variant_tys[1].1)?.1.try_into()?],
+                            )
+                            .into(),
+                        ),
+                        (
+                            Identifier::new(context, "elem_type"),
+                            TypeAttribute::new(IntegerType::new(context, 8).into()).into(),
+                        ),
+                    ])
+                    .add_operands(&[result_ptr])
+                    .add_results(&[llvm::r#type::opaque_pointer(context)])
+                    .build()?,
+            )
+            .result(0)?
+            .into();
+
+        entry
+            .append_operation(llvm::load(
+                context,
+                ptr,
+                variant_tys[1].0,
+                location,
+                LoadStoreOptions::default(),
+            ))
+            .result(0)?
+            .into()
+    };
+
+    let remaining_gas = entry
+        .append_operation(llvm::load(
+            context,
+            gas_builtin_ptr,
+            IntegerType::new(context, 128).into(),
+            location,
+            LoadStoreOptions::default(),
+        ))
+        .result(0)?
+        .into();
+
+    entry.append_operation(helper.cond_br(
+        context,
+        result_tag,
+        [1, 0],
+        [
+            &[remaining_gas, entry.argument(1)?.into(), payload_err],
+            &[remaining_gas, entry.argument(1)?.into(), payload_ok],
+        ],
+        location,
+    ));
+    Ok(())
+}
+
+fn build_secp256_add<'ctx, 'this, TType, TLibfunc>(
+    context: &'ctx Context,
+    registry: &ProgramRegistry<TType, TLibfunc>,
+    entry: &'this Block<'ctx>,
+    location: Location<'ctx>,
+    helper: &LibfuncHelper<'ctx, 'this>,
+    metadata: &mut MetadataStorage,
+    info: &SignatureOnlyConcreteLibfunc,
+    callback_idx: usize,
+) -> Result<()>
+where
+    TType: GenericType,
+    TLibfunc: GenericLibfunc,
+    <TType as GenericType>::Concrete: TypeBuilder<TType, TLibfunc, Error = CoreTypeBuilderError>,
+    <TLibfunc as GenericLibfunc>::Concrete: LibfuncBuilder<TType, TLibfunc, Error = Error>,
+{
+    // Extract self pointer.
+    let ptr = entry
+        .append_operation(llvm::load(
+            context,
+            entry.argument(1)?.into(),
+            llvm::r#type::opaque_pointer(context),
+            location,
+            LoadStoreOptions::default(),
+        ))
+        .result(0)?
+        .into();
+
+    // Allocate space for the return value.
+    let (result_layout, (result_tag_ty, result_tag_layout), variant_tys) =
+        crate::types::r#enum::get_type_for_variants(
+            context,
+            helper,
+            registry,
+            metadata,
+            &[
+                info.branch_signatures()[0].vars[2].ty.clone(),
+                info.branch_signatures()[1].vars[2].ty.clone(),
+            ],
+        )?;
+
+    let k1 = helper
+        .init_block()
+        .append_operation(arith::constant(
+            context,
+            IntegerAttribute::new(1, IntegerType::new(context, 64).into()).into(),
+            location,
+        ))
+        .result(0)?
+        .into();
+    let result_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[
+                    (
+                        Identifier::new(context, "alignment"),
+                        IntegerAttribute::new(
+                            result_layout.align().try_into()?,
+                            IntegerType::new(context, 64).into(),
+                        )
+                        .into(),
+                    ),
+                    (
+                        Identifier::new(context, "elem_type"),
+                        TypeAttribute::new(llvm::r#type::r#struct(
+                            context,
+                            &[
+                                result_tag_ty,
+                                llvm::r#type::array(
+                                    IntegerType::new(context, 8).into(),
+                                    (result_layout.size() - 1).try_into()?,
+                                ),
+                            ],
+                            false,
+                        ))
+                        .into(),
+                    ),
+                ])
+                .add_operands(&[k1])
+                .add_results(&[llvm::r#type::opaque_pointer(context)])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+
+    // Allocate space and write the current gas.
+    let gas_builtin_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[(
+                    Identifier::new(context, "alignment"),
+                    IntegerAttribute::new(
+                        get_integer_layout(128).align().try_into()?,
+                        IntegerType::new(context, 64).into(),
+                    )
+                    .into(),
+                )])
+                .add_operands(&[k1])
+                .add_results(&[llvm::r#type::pointer(
+                    IntegerType::new(context, 128).into(),
+                    0,
+                )])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+    entry.append_operation(llvm::store(
+        context,
+        entry.argument(0)?.into(),
+        gas_builtin_ptr,
+        location,
+        LoadStoreOptions::default(),
+    ));
+
+    // Allocate `p0` argument and write the value.
+    let p0_arg_ptr_ty = llvm::r#type::pointer(
+        registry.build_type(
+            context,
+            helper,
+            registry,
+            metadata,
+            &info.param_signatures()[2].ty,
+        )?,
+        0,
+    );
+    let p0_arg_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[(
+                    Identifier::new(context, "alignment"),
+                    IntegerAttribute::new(
+                        get_integer_layout(128).align().try_into()?,
+                        IntegerType::new(context, 64).into(),
+                    )
+                    .into(),
+                )])
+                .add_operands(&[k1])
+                .add_results(&[p0_arg_ptr_ty])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+    entry.append_operation(llvm::store(
+        context,
+        entry.argument(2)?.into(),
+        p0_arg_ptr,
+        location,
+        LoadStoreOptions::default(),
+    ));
+
+    // Allocate `p1` argument and write the value.
+    let p1_arg_ptr_ty = llvm::r#type::pointer(
+        registry.build_type(
+            context,
+            helper,
+            registry,
+            metadata,
+            &info.param_signatures()[3].ty,
+        )?,
+        0,
+    );
+    let p1_arg_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[(
+                    Identifier::new(context, "alignment"),
+                    IntegerAttribute::new(
+                        get_integer_layout(128).align().try_into()?,
+                        IntegerType::new(context, 64).into(),
+                    )
+                    .into(),
+                )])
+                .add_operands(&[k1])
+                .add_results(&[p1_arg_ptr_ty])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+    entry.append_operation(llvm::store(
+        context,
+        entry.argument(3)?.into(),
+        p1_arg_ptr,
+        location,
+        LoadStoreOptions::default(),
+    ));
+
+    // Extract function pointer.
+    let fn_ptr_ty = llvm::r#type::function(
+        llvm::r#type::void(context),
+        &[
+            llvm::r#type::opaque_pointer(context),
+            llvm::r#type::opaque_pointer(context),
+            llvm::r#type::pointer(IntegerType::new(context, 128).into(), 0),
+            p0_arg_ptr_ty,
+            p1_arg_ptr_ty,
+        ],
+        false,
+    );
+    let fn_ptr = entry
+        .append_operation(llvm::get_element_ptr(
+            context,
+            entry.argument(1)?.into(),
+            DenseI32ArrayAttribute::new(context, &[callback_idx.try_into()?]),
+            llvm::r#type::opaque_pointer(context),
+            llvm::r#type::opaque_pointer(context),
+            location,
+        ))
+        .result(0)?
+        .into();
+    let fn_ptr = entry
+        .append_operation(llvm::load(
+            context,
+            fn_ptr,
+            llvm::r#type::pointer(fn_ptr_ty, 0),
+            location,
+            LoadStoreOptions::default(),
+        ))
+        .result(0)?
+        .into();
+
+    entry.append_operation(
+        OperationBuilder::new("llvm.call", location)
+            .add_operands(&[
+                fn_ptr,
+                result_ptr,
+                ptr,
+                gas_builtin_ptr,
+                p0_arg_ptr,
+                p1_arg_ptr,
+            ])
+            .build()?,
+    );
+
+    let result = entry
+        .append_operation(llvm::load(
+            context,
+            result_ptr,
+            llvm::r#type::r#struct(
+                context,
+                &[
+                    result_tag_ty,
+                    llvm::r#type::array(
+                        IntegerType::new(context, 8).into(),
+                        (result_layout.size() - 1).try_into()?,
+                    ),
+                ],
+                false,
+            ),
+            location,
+            LoadStoreOptions::default(),
+        ))
+        .result(0)?
+        .into();
+    let result_tag = entry
+        .append_operation(llvm::extract_value(
+            context,
+            result,
+            DenseI64ArrayAttribute::new(context, &[0]),
+            IntegerType::new(context, 1).into(),
+            location,
+        ))
+        .result(0)?
+        .into();
+
+    let payload_ok = {
+        let ptr = entry
+            .append_operation(
+                OperationBuilder::new("llvm.getelementptr", location)
+                    .add_attributes(&[
+                        (
+                            Identifier::new(context, "rawConstantIndices"),
+                            DenseI32ArrayAttribute::new(
+                                context,
+                                &[result_tag_layout.extend(variant_tys[0].1)?.1.try_into()?],
+                            )
+                            .into(),
+                        ),
+                        (
+                            Identifier::new(context, "elem_type"),
+                            TypeAttribute::new(IntegerType::new(context, 8).into()).into(),
+                        ),
+                    ])
+                    .add_operands(&[result_ptr])
+                    .add_results(&[llvm::r#type::opaque_pointer(context)])
+                    .build()?,
+            )
+            .result(0)?
+            .into();
+
+        entry
+            .append_operation(llvm::load(
+                context,
+                ptr,
+                variant_tys[0].0,
+                location,
+                LoadStoreOptions::default(),
+            ))
+            .result(0)?
+            .into()
+    };
+    let payload_err = {
+        let ptr = entry
+            .append_operation(
+                OperationBuilder::new("llvm.getelementptr", location)
+                    .add_attributes(&[
+                        (
+                            Identifier::new(context, "rawConstantIndices"),
+                            DenseI32ArrayAttribute::new(
+                                context,
+                                &[result_tag_layout.extend(variant_tys[1].1)?.1.try_into()?],
+                            )
+                            .into(),
+                        ),
+                        (
+                            Identifier::new(context, "elem_type"),
+                            TypeAttribute::new(IntegerType::new(context, 8).into()).into(),
+                        ),
+                    ])
+                    .add_operands(&[result_ptr])
+                    .add_results(&[llvm::r#type::opaque_pointer(context)])
+                    .build()?,
+            )
+            .result(0)?
+            .into();
+
+        entry
+            .append_operation(llvm::load(
+                context,
+                ptr,
+                variant_tys[1].0,
+                location,
+                LoadStoreOptions::default(),
+            ))
+            .result(0)?
+            .into()
+    };
+
+    let remaining_gas = entry
+        .append_operation(llvm::load(
+            context,
+            gas_builtin_ptr,
+            IntegerType::new(context, 128).into(),
+            location,
+            LoadStoreOptions::default(),
+        ))
+        .result(0)?
+        .into();
+
+    entry.append_operation(helper.cond_br(
+        context,
+        result_tag,
+        [1, 0],
+        [
+            &[remaining_gas, entry.argument(1)?.into(), payload_err],
+            &[remaining_gas, entry.argument(1)?.into(), payload_ok],
+        ],
+        location,
+    ));
+    Ok(())
+}
+
+fn build_secp256_mul<'ctx, 'this, TType, TLibfunc>(
+    context: &'ctx Context,
+    registry: &ProgramRegistry<TType, TLibfunc>,
+    entry: &'this Block<'ctx>,
+    location: Location<'ctx>,
+    helper: &LibfuncHelper<'ctx, 'this>,
+    metadata: &mut MetadataStorage,
+    info: &SignatureOnlyConcreteLibfunc,
+    callback_idx: usize,
+) -> Result<()>
+where
+    TType: GenericType,
+    TLibfunc: GenericLibfunc,
+    <TType as GenericType>::Concrete: TypeBuilder<TType, TLibfunc, Error = CoreTypeBuilderError>,
+    <TLibfunc as GenericLibfunc>::Concrete: LibfuncBuilder<TType, TLibfunc, Error = Error>,
+{
+    // Extract self pointer.
+    let ptr = entry
+        .append_operation(llvm::load(
+            context,
+            entry.argument(1)?.into(),
+            llvm::r#type::opaque_pointer(context),
+            location,
+            LoadStoreOptions::default(),
+        ))
+        .result(0)?
+        .into();
+
+    // Allocate space for the return value.
+    let (result_layout, (result_tag_ty, result_tag_layout), variant_tys) =
+        crate::types::r#enum::get_type_for_variants(
+            context,
+            helper,
+            registry,
+            metadata,
+            &[
+                info.branch_signatures()[0].vars[2].ty.clone(),
+                info.branch_signatures()[1].vars[2].ty.clone(),
+            ],
+        )?;
+
+    let k1 = helper
+        .init_block()
+        .append_operation(arith::constant(
+            context,
+            IntegerAttribute::new(1, IntegerType::new(context, 64).into()).into(),
+            location,
+        ))
+        .result(0)?
+        .into();
+    let result_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[
+                    (
+                        Identifier::new(context, "alignment"),
+                        IntegerAttribute::new(
+                            result_layout.align().try_into()?,
+                            IntegerType::new(context, 64).into(),
+                        )
+                        .into(),
+                    ),
+                    (
+                        Identifier::new(context, "elem_type"),
+                        TypeAttribute::new(llvm::r#type::r#struct(
+                            context,
+                            &[
+                                result_tag_ty,
+                                llvm::r#type::array(
+                                    IntegerType::new(context, 8).into(),
+                                    (result_layout.size() - 1).try_into()?,
+                                ),
+                            ],
+                            false,
+                        ))
+                        .into(),
+                    ),
+                ])
+                .add_operands(&[k1])
+                .add_results(&[llvm::r#type::opaque_pointer(context)])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+
+    // Allocate space and write the current gas.
+    let gas_builtin_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[(
+                    Identifier::new(context, "alignment"),
+                    IntegerAttribute::new(
+                        get_integer_layout(128).align().try_into()?,
+                        IntegerType::new(context, 64).into(),
+                    )
+                    .into(),
+                )])
+                .add_operands(&[k1])
+                .add_results(&[llvm::r#type::pointer(
+                    IntegerType::new(context, 128).into(),
+                    0,
+                )])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+    entry.append_operation(llvm::store(
+        context,
+        entry.argument(0)?.into(),
+        gas_builtin_ptr,
+        location,
+        LoadStoreOptions::default(),
+    ));
+
+    // Allocate `p` argument and write the value.
+    let p_arg_ptr_ty = llvm::r#type::pointer(
+        registry.build_type(
+            context,
+            helper,
+            registry,
+            metadata,
+            &info.param_signatures()[2].ty,
+        )?,
+        0,
+    );
+    let p_arg_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[(
+                    Identifier::new(context, "alignment"),
+                    IntegerAttribute::new(
+                        get_integer_layout(128).align().try_into()?,
+                        IntegerType::new(context, 64).into(),
+                    )
+                    .into(),
+                )])
+                .add_operands(&[k1])
+                .add_results(&[p_arg_ptr_ty])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+    entry.append_operation(llvm::store(
+        context,
+        entry.argument(2)?.into(),
+        p_arg_ptr,
+        location,
+        LoadStoreOptions::default(),
+    ));
+
+    // Allocate `m` argument and write the value.
+    let m_arg_ptr_ty = llvm::r#type::pointer(
+        registry.build_type(
+            context,
+            helper,
+            registry,
+            metadata,
+            &info.param_signatures()[3].ty,
+        )?,
+        0,
+    );
+    let m_arg_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[(
+                    Identifier::new(context, "alignment"),
+                    IntegerAttribute::new(
+                        get_integer_layout(128).align().try_into()?,
+                        IntegerType::new(context, 64).into(),
+                    )
+                    .into(),
+                )])
+                .add_operands(&[k1])
+                .add_results(&[m_arg_ptr_ty])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+    entry.append_operation(llvm::store(
+        context,
+        entry.argument(3)?.into(),
+        m_arg_ptr,
+        location,
+        LoadStoreOptions::default(),
+    ));
+
+    // Extract function pointer.
+    let fn_ptr_ty = llvm::r#type::function(
+        llvm::r#type::void(context),
+        &[
+            llvm::r#type::opaque_pointer(context),
+            llvm::r#type::opaque_pointer(context),
+            llvm::r#type::pointer(IntegerType::new(context, 128).into(), 0),
+            p_arg_ptr_ty,
+            m_arg_ptr_ty,
+        ],
+        false,
+    );
+    let fn_ptr = entry
+        .append_operation(llvm::get_element_ptr(
+            context,
+            entry.argument(1)?.into(),
+            DenseI32ArrayAttribute::new(context, &[callback_idx.try_into()?]),
+            llvm::r#type::opaque_pointer(context),
+            llvm::r#type::opaque_pointer(context),
+            location,
+        ))
+        .result(0)?
+        .into();
+    let fn_ptr = entry
+        .append_operation(llvm::load(
+            context,
+            fn_ptr,
+            llvm::r#type::pointer(fn_ptr_ty, 0),
+            location,
+            LoadStoreOptions::default(),
+        ))
+        .result(0)?
+        .into();
+
+    entry.append_operation(
+        OperationBuilder::new("llvm.call", location)
+            .add_operands(&[
+                fn_ptr,
+                result_ptr,
+                ptr,
+                gas_builtin_ptr,
+                p_arg_ptr,
+                m_arg_ptr,
+            ])
+            .build()?,
+    );
+
+    let result = entry
+        .append_operation(llvm::load(
+            context,
+            result_ptr,
+            llvm::r#type::r#struct(
+                context,
+                &[
+                    result_tag_ty,
+                    llvm::r#type::array(
+                        IntegerType::new(context, 8).into(),
+                        (result_layout.size() - 1).try_into()?,
+                    ),
+                ],
+                false,
+            ),
+            location,
+            LoadStoreOptions::default(),
+        ))
+        .result(0)?
+        .into();
+    let result_tag = entry
+        .append_operation(llvm::extract_value(
+            context,
+            result,
+            DenseI64ArrayAttribute::new(context, &[0]),
+            IntegerType::new(context, 1).into(),
+            location,
+        ))
+        .result(0)?
+        .into();
+
+    let payload_ok = {
+        let ptr = entry
+            .append_operation(
+                OperationBuilder::new("llvm.getelementptr", location)
+                    .add_attributes(&[
+                        (
+                            Identifier::new(context, "rawConstantIndices"),
+                            DenseI32ArrayAttribute::new(
+                                context,
+                                &[result_tag_layout.extend(variant_tys[0].1)?.1.try_into()?],
+                            )
+                            .into(),
+                        ),
+                        (
+                            Identifier::new(context, "elem_type"),
+                            TypeAttribute::new(IntegerType::new(context, 8).into()).into(),
+                        ),
+                    ])
+                    .add_operands(&[result_ptr])
+                    .add_results(&[llvm::r#type::opaque_pointer(context)])
+                    .build()?,
+            )
+            .result(0)?
+            .into();
+
+        entry
+            .append_operation(llvm::load(
+                context,
+                ptr,
+                variant_tys[0].0,
+                location,
+                LoadStoreOptions::default(),
+            ))
+            .result(0)?
+            .into()
+    };
+    let payload_err = {
+        let ptr = entry
+            .append_operation(
+                OperationBuilder::new("llvm.getelementptr", location)
+                    .add_attributes(&[
+                        (
+                            Identifier::new(context, "rawConstantIndices"),
+                            DenseI32ArrayAttribute::new(
+                                context,
+                                &[result_tag_layout.extend(variant_tys[1].1)?.1.try_into()?],
+                            )
+                            .into(),
+                        ),
+                        (
+                            Identifier::new(context, "elem_type"),
+                            TypeAttribute::new(IntegerType::new(context, 8).into()).into(),
+                        ),
+                    ])
+                    .add_operands(&[result_ptr])
+                    .add_results(&[llvm::r#type::opaque_pointer(context)])
+                    .build()?,
+            )
+            .result(0)?
+            .into();
+
+        entry
+            .append_operation(llvm::load(
+                context,
+                ptr,
+                variant_tys[1].0,
+                location,
+                LoadStoreOptions::default(),
+            ))
+            .result(0)?
+            .into()
+    };
+
+    let remaining_gas = entry
+        .append_operation(llvm::load(
+            context,
+            gas_builtin_ptr,
+            IntegerType::new(context, 128).into(),
+            location,
+            LoadStoreOptions::default(),
+        ))
+        .result(0)?
+        .into();
+
+    entry.append_operation(helper.cond_br(
+        context,
+        result_tag,
+        [1, 0],
+        [
+            &[remaining_gas, entry.argument(1)?.into(), payload_err],
+            &[remaining_gas, entry.argument(1)?.into(), payload_ok],
+        ],
+        location,
+    ));
+    Ok(())
+}
+
+fn build_secp256_get_point_from_x<'ctx, 'this, TType, TLibfunc>(
+    context: &'ctx Context,
+    registry: &ProgramRegistry<TType, TLibfunc>,
+    entry: &'this Block<'ctx>,
+    location: Location<'ctx>,
+    helper: &LibfuncHelper<'ctx, 'this>,
+    metadata: &mut MetadataStorage,
+    info: &SignatureOnlyConcreteLibfunc,
+    callback_idx: usize,
+) -> Result<()>
+where
+    TType: GenericType,
+    TLibfunc: GenericLibfunc,
+    <TType as GenericType>::Concrete: TypeBuilder<TType, TLibfunc, Error = CoreTypeBuilderError>,
+    <TLibfunc as GenericLibfunc>::Concrete: LibfuncBuilder<TType, TLibfunc, Error = Error>,
+{
+    // Extract self pointer.
+    let ptr = entry
+        .append_operation(llvm::load(
+            context,
+            entry.argument(1)?.into(),
+            llvm::r#type::opaque_pointer(context),
+            location,
+            LoadStoreOptions::default(),
+        ))
+        .result(0)?
+        .into();
+
+    // Allocate space for the return value.
+    let (result_layout, (result_tag_ty, result_tag_layout), variant_tys) =
+        crate::types::r#enum::get_type_for_variants(
+            context,
+            helper,
+            registry,
+            metadata,
+            &[
+                info.branch_signatures()[0].vars[2].ty.clone(),
+                info.branch_signatures()[1].vars[2].ty.clone(),
+            ],
+        )?;
+
+    let k1 = helper
+        .init_block()
+        .append_operation(arith::constant(
+            context,
+            IntegerAttribute::new(1, IntegerType::new(context, 64).into()).into(),
+            location,
+        ))
+        .result(0)?
+        .into();
+    let result_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[
+                    (
+                        Identifier::new(context, "alignment"),
+                        IntegerAttribute::new(
+                            result_layout.align().try_into()?,
+                            IntegerType::new(context, 64).into(),
+                        )
+                        .into(),
+                    ),
+                    (
+                        Identifier::new(context, "elem_type"),
+                        TypeAttribute::new(llvm::r#type::r#struct(
+                            context,
+                            &[
+                                result_tag_ty,
+                                llvm::r#type::array(
+                                    IntegerType::new(context, 8).into(),
+                                    (result_layout.size() - 1).try_into()?,
+                                ),
+                            ],
+                            false,
+                        ))
+                        .into(),
+                    ),
+                ])
+                .add_operands(&[k1])
+                .add_results(&[llvm::r#type::opaque_pointer(context)])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+
+    // Allocate space and write the current gas.
+    let gas_builtin_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[(
+                    Identifier::new(context, "alignment"),
+                    IntegerAttribute::new(
+                        get_integer_layout(128).align().try_into()?,
+                        IntegerType::new(context, 64).into(),
+                    )
+                    .into(),
+                )])
+                .add_operands(&[k1])
+                .add_results(&[llvm::r#type::pointer(
+                    IntegerType::new(context, 128).into(),
+                    0,
+                )])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+    entry.append_operation(llvm::store(
+        context,
+        entry.argument(0)?.into(),
+        gas_builtin_ptr,
+        location,
+        LoadStoreOptions::default(),
+    ));
+
+    // Allocate `x` argument and write the value.
+    let x_arg_ptr_ty = llvm::r#type::pointer(
+        registry.build_type(
+            context,
+            helper,
+            registry,
+            metadata,
+            &info.param_signatures()[2].ty,
+        )?,
+        0,
+    );
+    let x_arg_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[(
+                    Identifier::new(context, "alignment"),
+                    IntegerAttribute::new(
+                        get_integer_layout(128).align().try_into()?,
+                        IntegerType::new(context, 64).into(),
+                    )
+                    .into(),
+                )])
+                .add_operands(&[k1])
+                .add_results(&[x_arg_ptr_ty])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+    entry.append_operation(llvm::store(
+        context,
+        entry.argument(2)?.into(),
+        x_arg_ptr,
+        location,
+        LoadStoreOptions::default(),
+    ));
+
+    // Extract function pointer.
+    let fn_ptr_ty = llvm::r#type::function(
+        llvm::r#type::void(context),
+        &[
+            llvm::r#type::opaque_pointer(context),
+            llvm::r#type::opaque_pointer(context),
+            llvm::r#type::pointer(IntegerType::new(context, 128).into(), 0),
+            x_arg_ptr_ty,
+            IntegerType::new(context, 1).into(),
+        ],
+        false,
+    );
+    let fn_ptr = entry
+        .append_operation(llvm::get_element_ptr(
+            context,
+            entry.argument(1)?.into(),
+            DenseI32ArrayAttribute::new(context, &[callback_idx.try_into()?]),
+            llvm::r#type::opaque_pointer(context),
+            llvm::r#type::opaque_pointer(context),
+            location,
+        ))
+        .result(0)?
+        .into();
+    let fn_ptr = entry
+        .append_operation(llvm::load(
+            context,
+            fn_ptr,
+            llvm::r#type::pointer(fn_ptr_ty, 0),
+            location,
+            LoadStoreOptions::default(),
+        ))
+        .result(0)?
+        .into();
+
+    entry.append_operation(
+        OperationBuilder::new("llvm.call", location)
+            .add_operands(&[
+                fn_ptr,
+                result_ptr,
+                ptr,
+                gas_builtin_ptr,
+                x_arg_ptr,
+                entry
+                    .append_operation(llvm::extract_value(
+                        context,
+                        entry.argument(3)?.into(),
+                        DenseI64ArrayAttribute::new(context, &[0]),
+                        IntegerType::new(context, 1).into(),
+                        location,
+                    ))
+                    .result(0)?
+                    .into(),
+            ])
+            .build()?,
+    );
+
+    let result = entry
+        .append_operation(llvm::load(
+            context,
+            result_ptr,
+            llvm::r#type::r#struct(
+                context,
+                &[
+                    result_tag_ty,
+                    llvm::r#type::array(
+                        IntegerType::new(context, 8).into(),
+                        (result_layout.size() - 1).try_into()?,
+                    ),
+                ],
+                false,
+            ),
+            location,
+            LoadStoreOptions::default(),
+        ))
+        .result(0)?
+        .into();
+    let result_tag = entry
+        .append_operation(llvm::extract_value(
+            context,
+            result,
+            DenseI64ArrayAttribute::new(context, &[0]),
+            IntegerType::new(context, 1).into(),
+            location,
+        ))
+        .result(0)?
+        .into();
+
+    let payload_ok = {
+        let ptr = entry
+            .append_operation(
+                OperationBuilder::new("llvm.getelementptr", location)
+                    .add_attributes(&[
+                        (
+                            Identifier::new(context, "rawConstantIndices"),
+                            DenseI32ArrayAttribute::new(
+                                context,
+                                &[result_tag_layout.extend(variant_tys[0].1)?.1.try_into()?],
+                            )
+                            .into(),
+                        ),
+                        (
+                            Identifier::new(context, "elem_type"),
+                            TypeAttribute::new(IntegerType::new(context, 8).into()).into(),
+                        ),
+                    ])
+                    .add_operands(&[result_ptr])
+                    .add_results(&[llvm::r#type::opaque_pointer(context)])
+                    .build()?,
+            )
+            .result(0)?
+            .into();
+
+        // The `Option<Secp256Point>` is memory-allocated, therefore its pointer is forwarded instead.
+        if registry
+            .get_type(&info.branch_signatures()[0].vars[2].ty)?
+            .is_memory_allocated(registry)
+        {
+            ptr
+        } else {
+            entry
+                .append_operation(llvm::load(
+                    context,
+                    ptr,
+                    variant_tys[0].0,
+                    location,
+                    LoadStoreOptions::default(),
+                ))
+                .result(0)?
+                .into()
+        }
+    };
+    let payload_err = {
+        let ptr = entry
+            .append_operation(
+                OperationBuilder::new("llvm.getelementptr", location)
+                    .add_attributes(&[
+                        (
+                            Identifier::new(context, "rawConstantIndices"),
+                            DenseI32ArrayAttribute::new(
+                                context,
+                                &[result_tag_layout.extend(variant_tys[1].1)?.1.try_into()?],
+                            )
+                            .into(),
+                        ),
+                        (
+                            Identifier::new(context, "elem_type"),
+                            TypeAttribute::new(IntegerType::new(context, 8).into()).into(),
+                        ),
+                    ])
+                    .add_operands(&[result_ptr])
+                    .add_results(&[llvm::r#type::opaque_pointer(context)])
+                    .build()?,
+            )
+            .result(0)?
+            .into();
+
+        entry
+            .append_operation(llvm::load(
+                context,
+                ptr,
+                variant_tys[1].0,
+                location,
+                LoadStoreOptions::default(),
+            ))
+            .result(0)?
+            .into()
+    };
+
+    let remaining_gas = entry
+        .append_operation(llvm::load(
+            context,
+            gas_builtin_ptr,
+            IntegerType::new(context, 128).into(),
+            location,
+            LoadStoreOptions::default(),
+        ))
+        .result(0)?
+        .into();
+
+    entry.append_operation(helper.cond_br(
+        context,
+        result_tag,
+        [1, 0],
+        [
+            &[remaining_gas, entry.argument(1)?.into(), payload_err],
+            &[remaining_gas, entry.argument(1)?.into(), payload_ok],
+        ],
+        location,
+    ));
+    Ok(())
+}
+
+fn build_secp256_get_xy<'ctx, 'this, TType, TLibfunc>(
+    context: &'ctx Context,
+    registry: &ProgramRegistry<TType, TLibfunc>,
+    entry: &'this Block<'ctx>,
+    location: Location<'ctx>,
+    helper: &LibfuncHelper<'ctx, 'this>,
+    metadata: &mut MetadataStorage,
+    info: &SignatureOnlyConcreteLibfunc,
+    callback_idx: usize,
+) -> Result<()>
+where
+    TType: GenericType,
+    TLibfunc: GenericLibfunc,
+    <TType as GenericType>::Concrete: TypeBuilder<TType, TLibfunc, Error = CoreTypeBuilderError>,
+    <TLibfunc as GenericLibfunc>::Concrete: LibfuncBuilder<TType, TLibfunc, Error = Error>,
+{
+    // Extract self pointer.
+    let ptr = entry
+        .append_operation(llvm::load(
+            context,
+            entry.argument(1)?.into(),
+            llvm::r#type::opaque_pointer(context),
+            location,
+            LoadStoreOptions::default(),
+        ))
+        .result(0)?
+        .into();
+
+    // Allocate space for the return value.
+    let (result_layout, (result_tag_ty, result_tag_layout), variant_tys) = {
+        let tag_layout = get_integer_layout(1);
+        let tag_ty: Type = IntegerType::new(context, 1).into();
+
+        let mut layout = tag_layout;
+        let output = [
+            {
+                let (x_ty, x_layout) = registry.build_type_with_layout(
+                    context,
+                    helper,
+                    registry,
+                    metadata,
+                    &info.branch_signatures()[0].vars[2].ty,
+                )?;
+                let (y_ty, y_layout) = registry.build_type_with_layout(
+                    context,
+                    helper,
+                    registry,
+                    metadata,
+                    &info.branch_signatures()[0].vars[3].ty,
+                )?;
+
+                let payload_ty = llvm::r#type::r#struct(context, &[x_ty, y_ty], false);
+                let payload_layout = x_layout.extend(y_layout)?.0;
+
+                let full_layout = tag_layout.extend(payload_layout)?.0;
+                layout = Layout::from_size_align(
+                    layout.size().max(full_layout.size()),
+                    layout.align().max(full_layout.align()),
+                )?;
+
+                (payload_ty, payload_layout)
+            },
+            {
+                let (payload_ty, payload_layout) = registry.build_type_with_layout(
+                    context,
+                    helper,
+                    registry,
+                    metadata,
+                    &info.branch_signatures()[1].vars[2].ty,
+                )?;
+
+                let full_layout = tag_layout.extend(payload_layout)?.0;
+                layout = Layout::from_size_align(
+                    layout.size().max(full_layout.size()),
+                    layout.align().max(full_layout.align()),
+                )?;
+
+                (payload_ty, payload_layout)
+            },
+        ];
+
+        (layout, (tag_ty, tag_layout), output)
+    };
+
+    let k1 = helper
+        .init_block()
+        .append_operation(arith::constant(
+            context,
+            IntegerAttribute::new(1, IntegerType::new(context, 64).into()).into(),
+            location,
+        ))
+        .result(0)?
+        .into();
+    let result_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[
+                    (
+                        Identifier::new(context, "alignment"),
+                        IntegerAttribute::new(
+                            result_layout.align().try_into()?,
+                            IntegerType::new(context, 64).into(),
+                        )
+                        .into(),
+                    ),
+                    (
+                        Identifier::new(context, "elem_type"),
+                        TypeAttribute::new(llvm::r#type::r#struct(
+                            context,
+                            &[
+                                result_tag_ty,
+                                llvm::r#type::array(
+                                    IntegerType::new(context, 8).into(),
+                                    (result_layout.size() - 1).try_into()?,
+                                ),
+                            ],
+                            false,
+                        ))
+                        .into(),
+                    ),
+                ])
+                .add_operands(&[k1])
+                .add_results(&[llvm::r#type::opaque_pointer(context)])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+
+    // Allocate space and write the current gas.
+    let gas_builtin_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[(
+                    Identifier::new(context, "alignment"),
+                    IntegerAttribute::new(
+                        get_integer_layout(128).align().try_into()?,
+                        IntegerType::new(context, 64).into(),
+                    )
+                    .into(),
+                )])
+                .add_operands(&[k1])
+                .add_results(&[llvm::r#type::pointer(
+                    IntegerType::new(context, 128).into(),
+                    0,
+                )])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+    entry.append_operation(llvm::store(
+        context,
+        entry.argument(0)?.into(),
+        gas_builtin_ptr,
+        location,
+        LoadStoreOptions::default(),
+    ));
+
+    // Allocate `p` argument and write the value.
+    let p_arg_ptr_ty = llvm::r#type::pointer(
+        registry.build_type(
+            context,
+            helper,
+            registry,
+            metadata,
+            &info.param_signatures()[2].ty,
+        )?,
+        0,
+    );
+    let p_arg_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[(
+                    Identifier::new(context, "alignment"),
+                    IntegerAttribute::new(
+                        get_integer_layout(128).align().try_into()?,
+                        IntegerType::new(context, 64).into(),
+                    )
+                    .into(),
+                )])
+                .add_operands(&[k1])
+                .add_results(&[p_arg_ptr_ty])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+    entry.append_operation(llvm::store(
+        context,
+        entry.argument(2)?.into(),
+        p_arg_ptr,
+        location,
+        LoadStoreOptions::default(),
+    ));
+
+    // Extract function pointer.
+    let fn_ptr_ty = llvm::r#type::function(
+        llvm::r#type::void(context),
+        &[
+            llvm::r#type::opaque_pointer(context),
+            llvm::r#type::opaque_pointer(context),
+            llvm::r#type::pointer(IntegerType::new(context, 128).into(), 0),
+            p_arg_ptr_ty,
+        ],
+        false,
+    );
+    let fn_ptr = entry
+        .append_operation(llvm::get_element_ptr(
+            context,
+            entry.argument(1)?.into(),
+            DenseI32ArrayAttribute::new(context, &[callback_idx.try_into()?]),
+            llvm::r#type::opaque_pointer(context),
+            llvm::r#type::opaque_pointer(context),
+            location,
+        ))
+        .result(0)?
+        .into();
+    let fn_ptr = entry
+        .append_operation(llvm::load(
+            context,
+            fn_ptr,
+            llvm::r#type::pointer(fn_ptr_ty, 0),
+            location,
+            LoadStoreOptions::default(),
+        ))
+        .result(0)?
+        .into();
+
+    entry.append_operation(
+        OperationBuilder::new("llvm.call", location)
+            .add_operands(&[fn_ptr, result_ptr, ptr, gas_builtin_ptr, p_arg_ptr])
+            .build()?,
+    );
+
+    let result = entry
+        .append_operation(llvm::load(
+            context,
+            result_ptr,
+            llvm::r#type::r#struct(
+                context,
+                &[
+                    result_tag_ty,
+                    llvm::r#type::array(
+                        IntegerType::new(context, 8).into(),
+                        (result_layout.size() - 1).try_into()?,
+                    ),
+                ],
+                false,
+            ),
+            location,
+            LoadStoreOptions::default(),
+        ))
+        .result(0)?
+        .into();
+    let result_tag = entry
+        .append_operation(llvm::extract_value(
+            context,
+            result,
+            DenseI64ArrayAttribute::new(context, &[0]),
+            IntegerType::new(context, 1).into(),
+            location,
+        ))
+        .result(0)?
+        .into();
+
+    let payload_ok = {
+        let ptr = entry
+            .append_operation(
+                OperationBuilder::new("llvm.getelementptr", location)
+                    .add_attributes(&[
+                        (
+                            Identifier::new(context, "rawConstantIndices"),
+                            DenseI32ArrayAttribute::new(
+                                context,
+                                &[result_tag_layout.extend(variant_tys[0].1)?.1.try_into()?],
+                            )
+                            .into(),
+                        ),
+                        (
+                            Identifier::new(context, "elem_type"),
+                            TypeAttribute::new(IntegerType::new(context, 8).into()).into(),
+                        ),
+                    ])
+                    .add_operands(&[result_ptr])
+                    .add_results(&[llvm::r#type::opaque_pointer(context)])
+                    .build()?,
+            )
+            .result(0)?
+            .into();
+
+        entry
+            .append_operation(llvm::load(
+                context,
+                ptr,
+                variant_tys[0].0,
+                location,
+                LoadStoreOptions::default(),
+            ))
+            .result(0)?
+            .into()
+    };
+    let payload_err = {
+        let ptr = entry
+            .append_operation(
+                OperationBuilder::new("llvm.getelementptr", location)
+                    .add_attributes(&[
+                        (
+                            Identifier::new(context, "rawConstantIndices"),
+                            DenseI32ArrayAttribute::new(
+                                context,
+                                &[result_tag_layout.extend(variant_tys[1].1)?.1.try_into()?],
+                            )
+                            .into(),
+                        ),
+                        (
+                            Identifier::new(context, "elem_type"),
+                            TypeAttribute::new(IntegerType::new(context, 8).into()).into(),
+                        ),
+                    ])
+                    .add_operands(&[result_ptr])
+                    .add_results(&[llvm::r#type::opaque_pointer(context)])
+                    .build()?,
+            )
+            .result(0)?
+            .into();
+
+        entry
+            .append_operation(llvm::load(
+                context,
+                ptr,
+                variant_tys[1].0,
+                location,
+                LoadStoreOptions::default(),
+            ))
+            .result(0)?
+            .into()
+    };
+
+    let remaining_gas = entry
+        .append_operation(llvm::load(
+            context,
+            gas_builtin_ptr,
+            IntegerType::new(context, 128).into(),
+            location,
+            LoadStoreOptions::default(),
+        ))
+        .result(0)?
+        .into();
+
+    entry.append_operation(
+        helper.cond_br(
+            context,
+            result_tag,
+            [1, 0],
+            [
+                &[remaining_gas, entry.argument(1)?.into(), payload_err],
+                &[
+                    remaining_gas,
+                    entry.argument(1)?.into(),
+                    entry
+                        .append_operation(llvm::extract_value(
+                            context,
+                            payload_ok,
+                            DenseI64ArrayAttribute::new(context, &[0]),
+                            get_struct_field_type_at(&variant_tys[0].0, 0),
+                            location,
+                        ))
+                        .result(0)?
+                        .into(),
+                    entry
+                        .append_operation(llvm::extract_value(
+                            context,
+                            payload_ok,
+                            DenseI64ArrayAttribute::new(context, &[1]),
+                            get_struct_field_type_at(&variant_tys[0].0, 1),
+                            location,
+                        ))
+                        .result(0)?
+                        .into(),
+                ],
+            ],
+            location,
+        ),
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::utils::test::{jit_enum, jit_struct, load_cairo, run_program_assert_output};
+    use cairo_lang_sierra::program::Program;
+    use lazy_static::lazy_static;
+    use starknet_types_core::felt::Felt;
+
+    lazy_static! {
         static ref STORAGE_BASE_ADDRESS_FROM_FELT252: (String, Program) = load_cairo! {
             use starknet::storage_access::{StorageBaseAddress, storage_base_address_from_felt252};
 
@@ -4496,41 +6751,151 @@ mod test {
             Felt::from(1).into(),
         );
         run_program_assert_output(
-            &STORAGE_BASE_ADDRESS_FROM_FELT252,
+            &STORAGE_BASE_ADDRESS_FROM_FELT252,
+            "run_program",
+            &[Felt::from(-1).into()],
+            Felt::from_dec_str("106710729501573572985208420194530329073740042555888586719488")
+                .unwrap()
+                .into(),
+        );
+        run_program_assert_output(
+            &STORAGE_BASE_ADDRESS_FROM_FELT252,
+            "run_program",
+            &[Felt::from_dec_str(
+                "3618502788666131106986593281521497120414687020801267626233049500247285300992",
+            )
+            .unwrap()
+            .into()],
+            Felt::from(0).into(),
+        );
+    }
+
+    #[test]
+    fn storage_address_from_base() {
+        run_program_assert_output(
+            &STORAGE_ADDRESS_FROM_BASE,
+            "run_program",
+            &[Felt::from(0).into()],
+            Felt::from(0).into(),
+        );
+        run_program_assert_output(
+            &STORAGE_ADDRESS_FROM_BASE,
+            "run_program",
+            &[Felt::from(1).into()],
+            Felt::from(1).into(),
+        );
+        run_program_assert_output(
+            &STORAGE_ADDRESS_FROM_BASE,
+            "run_program",
+            &[
+                Felt::from_dec_str("106710729501573572985208420194530329073740042555888586719488")
+                    .unwrap()
+                    .into(),
+            ],
+            Felt::from_dec_str("106710729501573572985208420194530329073740042555888586719488")
+                .unwrap()
+                .into(),
+        );
+    }
+
+    #[test]
+    fn storage_address_from_base_and_offset() {
+        run_program_assert_output(
+            &STORAGE_ADDRESS_FROM_BASE_AND_OFFSET,
+            "run_program",
+            &[Felt::from(0).into(), 0u8.into()],
+            Felt::from(0).into(),
+        );
+        run_program_assert_output(
+            &STORAGE_ADDRESS_FROM_BASE_AND_OFFSET,
+            "run_program",
+            &[Felt::from(1).into(), 0u8.into()],
+            Felt::from(1).into(),
+        );
+        run_program_assert_output(
+            &STORAGE_ADDRESS_FROM_BASE_AND_OFFSET,
+            "run_program",
+            &[
+                Felt::from_dec_str("106710729501573572985208420194530329073740042555888586719488")
+                    .unwrap()
+                    .into(),
+                0u8.into(),
+            ],
+            Felt::from_dec_str("106710729501573572985208420194530329073740042555888586719488")
+                .unwrap()
+                .into(),
+        );
+
+        run_program_assert_output(
+            &STORAGE_ADDRESS_FROM_BASE_AND_OFFSET,
+            "run_program",
+            &[Felt::from(0).into(), 1u8.into()],
+            Felt::from(1).into(),
+        );
+        run_program_assert_output(
+            &STORAGE_ADDRESS_FROM_BASE_AND_OFFSET,
+            "run_program",
+            &[Felt::from(1).into(), 1u8.into()],
+            Felt::from(2).into(),
+        );
+        run_program_assert_output(
+            &STORAGE_ADDRESS_FROM_BASE_AND_OFFSET,
+            "run_program",
+            &[
+                Felt::from_dec_str("106710729501573572985208420194530329073740042555888586719488")
+                    .unwrap()
+                    .into(),
+                1u8.into(),
+            ],
+            Felt::from_dec_str("106710729501573572985208420194530329073740042555888586719489")
+                .unwrap()
+                .into(),
+        );
+
+        run_program_assert_output(
+            &STORAGE_ADDRESS_FROM_BASE_AND_OFFSET,
+            "run_program",
+            &[Felt::from(0).into(), 255u8.into()],
+            Felt::from(255).into(),
+        );
+        run_program_assert_output(
+            &STORAGE_ADDRESS_FROM_BASE_AND_OFFSET,
+            "run_program",
+            &[Felt::from(1).into(), 255u8.into()],
+            Felt::from(256).into(),
+        );
+
+        run_program_assert_output(
+            &STORAGE_ADDRESS_FROM_BASE_AND_OFFSET,
             "run_program",
-            &[Felt::from(-1).into()],
-            Felt::from_dec_str("106710729501573572985208420194530329073740042555888586719488")
+            &[
+                Felt::from_dec_str("106710729501573572985208420194530329073740042555888586719488")
+                    .unwrap()
+                    .into(),
+                255u8.into(),
+            ],
+            Felt::from_dec_str("106710729501573572985208420194530329073740042555888586719743")
                 .unwrap()
                 .into(),
         );
-        run_program_assert_output(
-            &STORAGE_BASE_ADDRESS_FROM_FELT252,
-            "run_program",
-            &[Felt::from_dec_str(
-                "3618502788666131106986593281521497120414687020801267626233049500247285300992",
-            )
-            .unwrap()
-            .into()],
-            Felt::from(0).into(),
-        );
     }
 
     #[test]
-    fn storage_address_from_base() {
+    fn storage_address_to_felt252() {
         run_program_assert_output(
-            &STORAGE_ADDRESS_FROM_BASE,
+            &STORAGE_ADDRESS_TO_FELT252,
             "run_program",
             &[Felt::from(0).into()],
             Felt::from(0).into(),
         );
         run_program_assert_output(
-            &STORAGE_ADDRESS_FROM_BASE,
+            &STORAGE_ADDRESS_TO_FELT252,
             "run_program",
             &[Felt::from(1).into()],
             Felt::from(1).into(),
         );
         run_program_assert_output(
-            &STORAGE_ADDRESS_FROM_BASE,
+            &STORAGE_ADDRESS_TO_FELT252,
             "run_program",
             &[
                 Felt::from_dec_str("106710729501573572985208420194530329073740042555888586719488")
@@ -4544,150 +6909,502 @@ mod test {
     }
 
     #[test]
-    fn storage_address_from_base_and_offset() {
+    fn storage_address_try_from_felt252() {
         run_program_assert_output(
-            &STORAGE_ADDRESS_FROM_BASE_AND_OFFSET,
+            &STORAGE_ADDRESS_TRY_FROM_FELT252,
             "run_program",
-            &[Felt::from(0).into(), 0u8.into()],
-            Felt::from(0).into(),
+            &[Felt::from(0).into()],
+            jit_enum!(0, Felt::from(0).into()),
         );
         run_program_assert_output(
-            &STORAGE_ADDRESS_FROM_BASE_AND_OFFSET,
+            &STORAGE_ADDRESS_TRY_FROM_FELT252,
             "run_program",
-            &[Felt::from(1).into(), 0u8.into()],
-            Felt::from(1).into(),
+            &[Felt::from(1).into()],
+            jit_enum!(0, Felt::from(1).into()),
         );
         run_program_assert_output(
-            &STORAGE_ADDRESS_FROM_BASE_AND_OFFSET,
+            &STORAGE_ADDRESS_TRY_FROM_FELT252,
             "run_program",
             &[
                 Felt::from_dec_str("106710729501573572985208420194530329073740042555888586719488")
                     .unwrap()
                     .into(),
-                0u8.into(),
             ],
-            Felt::from_dec_str("106710729501573572985208420194530329073740042555888586719488")
-                .unwrap()
-                .into(),
+            jit_enum!(
+                0,
+                Felt::from_dec_str("106710729501573572985208420194530329073740042555888586719488")
+                    .unwrap()
+                    .into()
+            ),
         );
 
-        run_program_assert_output(
-            &STORAGE_ADDRESS_FROM_BASE_AND_OFFSET,
-            "run_program",
-            &[Felt::from(0).into(), 1u8.into()],
-            Felt::from(1).into(),
-        );
-        run_program_assert_output(
-            &STORAGE_ADDRESS_FROM_BASE_AND_OFFSET,
-            "run_program",
-            &[Felt::from(1).into(), 1u8.into()],
-            Felt::from(2).into(),
-        );
-        run_program_assert_output(
-            &STORAGE_ADDRESS_FROM_BASE_AND_OFFSET,
-            "run_program",
-            &[
-                Felt::from_dec_str("106710729501573572985208420194530329073740042555888586719488")
-                    .unwrap()
-                    .into(),
-                1u8.into(),
-            ],
-            Felt::from_dec_str("106710729501573572985208420194530329073740042555888586719489")
-                .unwrap()
-                .into(),
-        );
+        run_program_assert_output(
+            &STORAGE_ADDRESS_TRY_FROM_FELT252,
+            "run_program",
+            &[Felt::from(-1).into()],
+            jit_enum!(1, jit_struct!()),
+        );
+    }
+}
+
+pub fn build_testing<'ctx, 'this, TType, TLibfunc>(
+    context: &'ctx Context,
+    registry: &ProgramRegistry<TType, TLibfunc>,
+    entry: &'this Block<'ctx>,
+    location: Location<'ctx>,
+    helper: &LibfuncHelper<'ctx, 'this>,
+    metadata: &mut MetadataStorage,
+    selector: &TestingConcreteLibfunc,
+) -> Result<()>
+where
+    TType: GenericType,
+    TLibfunc: GenericLibfunc,
+    <TType as GenericType>::Concrete: TypeBuilder<TType, TLibfunc, Error = CoreTypeBuilderError>,
+    <TLibfunc as GenericLibfunc>::Concrete: LibfuncBuilder<TType, TLibfunc, Error = Error>,
+{
+    match selector {
+        TestingConcreteLibfunc::SetAccountContractAddress(info) => build_testing_setter(
+            context,
+            registry,
+            entry,
+            location,
+            helper,
+            metadata,
+            info,
+            StarkNetSyscallHandlerCallbacks::<()>::SET_ACCOUNT_CONTRACT_ADDRESS,
+        ),
+        TestingConcreteLibfunc::SetBlockNumber(info) => build_testing_setter(
+            context,
+            registry,
+            entry,
+            location,
+            helper,
+            metadata,
+            info,
+            StarkNetSyscallHandlerCallbacks::<()>::SET_BLOCK_NUMBER,
+        ),
+        TestingConcreteLibfunc::SetBlockTimestamp(info) => build_testing_setter(
+            context,
+            registry,
+            entry,
+            location,
+            helper,
+            metadata,
+            info,
+            StarkNetSyscallHandlerCallbacks::<()>::SET_BLOCK_TIMESTAMP,
+        ),
+        TestingConcreteLibfunc::SetCallerAddress(info) => build_testing_setter(
+            context,
+            registry,
+            entry,
+            location,
+            helper,
+            metadata,
+            info,
+            StarkNetSyscallHandlerCallbacks::<()>::SET_CALLER_ADDRESS,
+        ),
+        TestingConcreteLibfunc::SetChainId(info) => build_testing_setter(
+            context,
+            registry,
+            entry,
+            location,
+            helper,
+            metadata,
+            info,
+            StarkNetSyscallHandlerCallbacks::<()>::SET_CHAIN_ID,
+        ),
+        TestingConcreteLibfunc::SetContractAddress(info) => build_testing_setter(
+            context,
+            registry,
+            entry,
+            location,
+            helper,
+            metadata,
+            info,
+            StarkNetSyscallHandlerCallbacks::<()>::SET_CONTRACT_ADDRESS,
+        ),
+        TestingConcreteLibfunc::SetMaxFee(info) => build_testing_setter(
+            context,
+            registry,
+            entry,
+            location,
+            helper,
+            metadata,
+            info,
+            StarkNetSyscallHandlerCallbacks::<()>::SET_MAX_FEE,
+        ),
+        TestingConcreteLibfunc::SetNonce(info) => build_testing_setter(
+            context,
+            registry,
+            entry,
+            location,
+            helper,
+            metadata,
+            info,
+            StarkNetSyscallHandlerCallbacks::<()>::SET_NONCE,
+        ),
+        TestingConcreteLibfunc::SetSequencerAddress(info) => build_testing_setter(
+            context,
+            registry,
+            entry,
+            location,
+            helper,
+            metadata,
+            info,
+            StarkNetSyscallHandlerCallbacks::<()>::SET_SEQUENCER_ADDRESS,
+        ),
+        TestingConcreteLibfunc::SetSignature(info) => build_testing_setter(
+            context,
+            registry,
+            entry,
+            location,
+            helper,
+            metadata,
+            info,
+            StarkNetSyscallHandlerCallbacks::<()>::SET_SIGNATURE,
+        ),
+        TestingConcreteLibfunc::SetTransactionHash(info) => build_testing_setter(
+            context,
+            registry,
+            entry,
+            location,
+            helper,
+            metadata,
+            info,
+            StarkNetSyscallHandlerCallbacks::<()>::SET_TRANSACTION_HASH,
+        ),
+        TestingConcreteLibfunc::SetVersion(info) => build_testing_setter(
+            context,
+            registry,
+            entry,
+            location,
+            helper,
+            metadata,
+            info,
+            StarkNetSyscallHandlerCallbacks::<()>::SET_VERSION,
+        ),
+        TestingConcreteLibfunc::PopLog(info) => {
+            build_testing_pop_log(context, registry, entry, location, helper, metadata, info)
+        }
+    }
+}
+
+/// Extract the self pointer and the callback at `callback_index` from the current syscall handler.
+///
+/// The testing libfuncs don't receive the `System` builtin, therefore the syscall handler is
+/// obtained from the runtime library instead.
+fn build_testing_callback<'ctx, 'this>(
+    context: &'ctx Context,
+    entry: &'this Block<'ctx>,
+    location: Location<'ctx>,
+    helper: &LibfuncHelper<'ctx, 'this>,
+    metadata: &mut MetadataStorage,
+    callback_index: usize,
+    fn_ptr_ty: Type<'ctx>,
+) -> Result<(Value<'ctx, 'this>, Value<'ctx, 'this>)> {
+    let handler_ptr = metadata
+        .get_mut::<RuntimeBindingsMeta>()
+        .expect("Runtime library not available.")
+        .get_syscall_handler(context, helper, entry, location)?
+        .result(0)?
+        .into();
+
+    // Extract self pointer.
+    let ptr = entry
+        .append_operation(llvm::load(
+            context,
+            handler_ptr,
+            llvm::r#type::opaque_pointer(context),
+            location,
+            LoadStoreOptions::default(),
+        ))
+        .result(0)?
+        .into();
+
+    // Extract function pointer.
+    let fn_ptr = entry
+        .append_operation(llvm::get_element_ptr(
+            context,
+            handler_ptr,
+            DenseI32ArrayAttribute::new(context, &[callback_index.try_into()?]),
+            llvm::r#type::opaque_pointer(context),
+            llvm::r#type::opaque_pointer(context),
+            location,
+        ))
+        .result(0)?
+        .into();
+    let fn_ptr = entry
+        .append_operation(llvm::load(
+            context,
+            fn_ptr,
+            llvm::r#type::pointer(fn_ptr_ty, 0),
+            location,
+            LoadStoreOptions::default(),
+        ))
+        .result(0)?
+        .into();
+
+    Ok((ptr, fn_ptr))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn build_testing_setter<'ctx, 'this, TType, TLibfunc>(
+    context: &'ctx Context,
+    registry: &ProgramRegistry<TType, TLibfunc>,
+    entry: &'this Block<'ctx>,
+    location: Location<'ctx>,
+    helper: &LibfuncHelper<'ctx, 'this>,
+    metadata: &mut MetadataStorage,
+    info: &SignatureOnlyConcreteLibfunc,
+    callback_index: usize,
+) -> Result<()>
+where
+    TType: GenericType,
+    TLibfunc: GenericLibfunc,
+    <TType as GenericType>::Concrete: TypeBuilder<TType, TLibfunc, Error = CoreTypeBuilderError>,
+    <TLibfunc as GenericLibfunc>::Concrete: LibfuncBuilder<TType, TLibfunc, Error = Error>,
+{
+    // Allocate the value argument and write it. Every setter receives its value by pointer.
+    let (value_ty, value_layout) = registry.build_type_with_layout(
+        context,
+        helper,
+        registry,
+        metadata,
+        &info.param_signatures()[0].ty,
+    )?;
+
+    let k1 = helper
+        .init_block()
+        .append_operation(arith::constant(
+            context,
+            IntegerAttribute::new(1, IntegerType::new(context, 64).into()).into(),
+            location,
+        ))
+        .result(0)?
+        .into();
+    let value_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[
+                    (
+                        Identifier::new(context, "alignment"),
+                        IntegerAttribute::new(
+                            value_layout.align().try_into()?,
+                            IntegerType::new(context, 64).into(),
+                        )
+                        .into(),
+                    ),
+                    (
+                        Identifier::new(context, "elem_type"),
+                        TypeAttribute::new(value_ty).into(),
+                    ),
+                ])
+                .add_operands(&[k1])
+                .add_results(&[llvm::r#type::opaque_pointer(context)])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+    entry.append_operation(llvm::store(
+        context,
+        entry.argument(0)?.into(),
+        value_ptr,
+        location,
+        LoadStoreOptions::default(),
+    ));
+
+    let fn_ptr_ty = llvm::r#type::function(
+        llvm::r#type::void(context),
+        &[
+            llvm::r#type::opaque_pointer(context),
+            llvm::r#type::opaque_pointer(context),
+        ],
+        false,
+    );
+    let (ptr, fn_ptr) = build_testing_callback(
+        context,
+        entry,
+        location,
+        helper,
+        metadata,
+        callback_index,
+        fn_ptr_ty,
+    )?;
+
+    entry.append_operation(
+        OperationBuilder::new("llvm.call", location)
+            .add_operands(&[fn_ptr, ptr, value_ptr])
+            .build()?,
+    );
 
-        run_program_assert_output(
-            &STORAGE_ADDRESS_FROM_BASE_AND_OFFSET,
-            "run_program",
-            &[Felt::from(0).into(), 255u8.into()],
-            Felt::from(255).into(),
-        );
-        run_program_assert_output(
-            &STORAGE_ADDRESS_FROM_BASE_AND_OFFSET,
-            "run_program",
-            &[Felt::from(1).into(), 255u8.into()],
-            Felt::from(256).into(),
-        );
+    entry.append_operation(helper.br(0, &[], location));
+    Ok(())
+}
 
-        run_program_assert_output(
-            &STORAGE_ADDRESS_FROM_BASE_AND_OFFSET,
-            "run_program",
-            &[
-                Felt::from_dec_str("106710729501573572985208420194530329073740042555888586719488")
-                    .unwrap()
-                    .into(),
-                255u8.into(),
-            ],
-            Felt::from_dec_str("106710729501573572985208420194530329073740042555888586719743")
-                .unwrap()
-                .into(),
-        );
-    }
+fn build_testing_pop_log<'ctx, 'this, TType, TLibfunc>(
+    context: &'ctx Context,
+    registry: &ProgramRegistry<TType, TLibfunc>,
+    entry: &'this Block<'ctx>,
+    location: Location<'ctx>,
+    helper: &LibfuncHelper<'ctx, 'this>,
+    metadata: &mut MetadataStorage,
+    info: &SignatureOnlyConcreteLibfunc,
+) -> Result<()>
+where
+    TType: GenericType,
+    TLibfunc: GenericLibfunc,
+    <TType as GenericType>::Concrete: TypeBuilder<TType, TLibfunc, Error = CoreTypeBuilderError>,
+    <TLibfunc as GenericLibfunc>::Concrete: LibfuncBuilder<TType, TLibfunc, Error = Error>,
+{
+    // The result is an `Option<(Span<felt252>, Span<felt252>)>`, where the tag is zero when a log
+    // was found (the first branch).
+    let span_ty = registry.build_type(
+        context,
+        helper,
+        registry,
+        metadata,
+        &info.branch_signatures()[0].vars[0].ty,
+    )?;
+    let result_ty = llvm::r#type::r#struct(
+        context,
+        &[
+            IntegerType::new(context, 1).into(),
+            llvm::r#type::r#struct(context, &[span_ty, span_ty], false),
+        ],
+        false,
+    );
 
-    #[test]
-    fn storage_address_to_felt252() {
-        run_program_assert_output(
-            &STORAGE_ADDRESS_TO_FELT252,
-            "run_program",
-            &[Felt::from(0).into()],
-            Felt::from(0).into(),
-        );
-        run_program_assert_output(
-            &STORAGE_ADDRESS_TO_FELT252,
-            "run_program",
-            &[Felt::from(1).into()],
-            Felt::from(1).into(),
-        );
-        run_program_assert_output(
-            &STORAGE_ADDRESS_TO_FELT252,
-            "run_program",
-            &[
-                Felt::from_dec_str("106710729501573572985208420194530329073740042555888586719488")
-                    .unwrap()
-                    .into(),
-            ],
-            Felt::from_dec_str("106710729501573572985208420194530329073740042555888586719488")
-                .unwrap()
-                .into(),
-        );
-    }
+    let k1 = helper
+        .init_block()
+        .append_operation(arith::constant(
+            context,
+            IntegerAttribute::new(1, IntegerType::new(context, 64).into()).into(),
+            location,
+        ))
+        .result(0)?
+        .into();
+    let result_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[
+                    (
+                        Identifier::new(context, "alignment"),
+                        IntegerAttribute::new(8, IntegerType::new(context, 64).into()).into(),
+                    ),
+                    (
+                        Identifier::new(context, "elem_type"),
+                        TypeAttribute::new(result_ty).into(),
+                    ),
+                ])
+                .add_operands(&[k1])
+                .add_results(&[llvm::r#type::opaque_pointer(context)])
+                .build()?,
+        )
+        .result(0)?
+        .into();
 
-    #[test]
-    fn storage_address_try_from_felt252() {
-        run_program_assert_output(
-            &STORAGE_ADDRESS_TRY_FROM_FELT252,
-            "run_program",
-            &[Felt::from(0).into()],
-            jit_enum!(0, Felt::from(0).into()),
-        );
-        run_program_assert_output(
-            &STORAGE_ADDRESS_TRY_FROM_FELT252,
-            "run_program",
-            &[Felt::from(1).into()],
-            jit_enum!(0, Felt::from(1).into()),
-        );
-        run_program_assert_output(
-            &STORAGE_ADDRESS_TRY_FROM_FELT252,
-            "run_program",
-            &[
-                Felt::from_dec_str("106710729501573572985208420194530329073740042555888586719488")
-                    .unwrap()
+    // Allocate `address` argument and write the value.
+    let address_arg_ptr_ty = llvm::r#type::pointer(IntegerType::new(context, 252).into(), 0);
+    let address_arg_ptr = helper
+        .init_block()
+        .append_operation(
+            OperationBuilder::new("llvm.alloca", location)
+                .add_attributes(&[(
+                    Identifier::new(context, "alignment"),
+                    IntegerAttribute::new(
+                        get_integer_layout(252).align().try_into().unwrap(),
+                        IntegerType::new(context, 64).into(),
+                    )
                     .into(),
-            ],
-            jit_enum!(
-                0,
-                Felt::from_dec_str("106710729501573572985208420194530329073740042555888586719488")
-                    .unwrap()
-                    .into()
-            ),
-        );
+                )])
+                .add_operands(&[k1])
+                .add_results(&[address_arg_ptr_ty])
+                .build()?,
+        )
+        .result(0)?
+        .into();
+    entry.append_operation(llvm::store(
+        context,
+        entry.argument(0)?.into(),
+        address_arg_ptr,
+        location,
+        LoadStoreOptions::default(),
+    ));
 
-        run_program_assert_output(
-            &STORAGE_ADDRESS_TRY_FROM_FELT252,
-            "run_program",
-            &[Felt::from(-1).into()],
-            jit_enum!(1, jit_struct!()),
-        );
-    }
+    let fn_ptr_ty = llvm::r#type::function(
+        llvm::r#type::void(context),
+        &[
+            llvm::r#type::opaque_pointer(context),
+            llvm::r#type::opaque_pointer(context),
+            address_arg_ptr_ty,
+        ],
+        false,
+    );
+    let (ptr, fn_ptr) = build_testing_callback(
+        context,
+        entry,
+        location,
+        helper,
+        metadata,
+        StarkNetSyscallHandlerCallbacks::<()>::POP_LOG,
+        fn_ptr_ty,
+    )?;
+
+    entry.append_operation(
+        OperationBuilder::new("llvm.call", location)
+            .add_operands(&[fn_ptr, result_ptr, ptr, address_arg_ptr])
+            .build()?,
+    );
+
+    let result = entry
+        .append_operation(llvm::load(
+            context,
+            result_ptr,
+            result_ty,
+            location,
+            LoadStoreOptions::default(),
+        ))
+        .result(0)?
+        .into();
+    let result_tag = entry
+        .append_operation(llvm::extract_value(
+            context,
+            result,
+            DenseI64ArrayAttribute::new(context, &[0]),
+            IntegerType::new(context, 1).into(),
+            location,
+        ))
+        .result(0)?
+        .into();
+    let keys = entry
+        .append_operation(llvm::extract_value(
+            context,
+            result,
+            DenseI64ArrayAttribute::new(context, &[1, 0]),
+            span_ty,
+            location,
+        ))
+        .result(0)?
+        .into();
+    let data = entry
+        .append_operation(llvm::extract_value(
+            context,
+            result,
+            DenseI64ArrayAttribute::new(context, &[1, 1]),
+            span_ty,
+            location,
+        ))
+        .result(0)?
+        .into();
+
+    entry.append_operation(helper.cond_br(
+        context,
+        result_tag,
+        [1, 0],
+        [&[], &[keys, data]],
+        location,
+    ));
+    Ok(())
 }
@@ -0,0 +1,228 @@
+//! # Builtin counters metadata
+//!
+//! When present, the compiler will increment the builtin values (which are `i64` counters) every
+//! time a libfunc uses them, in the same amount the Cairo VM would use. The final values are
+//! returned to the caller as a [`BuiltinStats`].
+//!
+//! The amounts are taken from the CASM implementation of every libfunc, and are the same used by
+//! the gas computation (`cairo-lang-sierra-gas`). Dictionary squashing uses a variable amount of
+//! range checks in the VM which depends on the accesses made, therefore they are computed at runtime
+//! by the `felt252_dict_squash` libfunc instead of being part of this table.
+
+use crate::execution_result::BuiltinStats;
+use cairo_lang_sierra::{
+    extensions::{
+        array::ArrayConcreteLibfunc,
+        bytes31::Bytes31ConcreteLibfunc,
+        casts::CastConcreteLibfunc,
+        core::{CoreConcreteLibfunc, CoreLibfunc, CoreType, CoreTypeConcrete},
+        ec::EcConcreteLibfunc,
+        felt252_dict::Felt252DictConcreteLibfunc,
+        gas::GasConcreteLibfunc,
+        int::{
+            signed::SintConcrete, signed128::Sint128Concrete, unsigned::UintConcrete,
+            unsigned128::Uint128Concrete, unsigned256::Uint256Concrete,
+            unsigned512::Uint512Concrete,
+        },
+        starknet::StarkNetConcreteLibfunc,
+        ConcreteLibfunc,
+    },
+    ids::ConcreteLibfuncId,
+    program::Program,
+    program_registry::{ProgramRegistry, ProgramRegistryError},
+};
+use std::collections::HashMap;
+
+/// Increments to apply to a libfunc's branch: the index of the returned builtin and the amount.
+pub type BranchIncrements = Vec<(usize, u64)>;
+
+/// Per-libfunc builtin increments.
+#[derive(Clone, Debug, Default)]
+pub struct BuiltinCountersMeta {
+    increments: HashMap<ConcreteLibfuncId, Vec<BranchIncrements>>,
+}
+
+impl BuiltinCountersMeta {
+    /// Compute the builtin increments of every libfunc declared in the program.
+    pub fn new(
+        program: &Program,
+        registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    ) -> Result<Self, Box<ProgramRegistryError>> {
+        let mut increments = HashMap::new();
+
+        for declaration in &program.libfunc_declarations {
+            let libfunc = registry.get_libfunc(&declaration.id)?;
+            let costs = libfunc_builtin_costs(libfunc);
+            if costs.iter().all(|x| *x == BuiltinStats::default()) {
+                continue;
+            }
+
+            let branches = libfunc
+                .branch_signatures()
+                .iter()
+                .zip(costs)
+                .map(|(branch, costs)| {
+                    branch
+                        .vars
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(idx, var)| {
+                            let amount = match registry.get_type(&var.ty).ok()? {
+                                CoreTypeConcrete::RangeCheck(_) => costs.range_check,
+                                CoreTypeConcrete::Pedersen(_) => costs.pedersen,
+                                CoreTypeConcrete::Poseidon(_) => costs.poseidon,
+                                CoreTypeConcrete::Bitwise(_) => costs.bitwise,
+                                CoreTypeConcrete::EcOp(_) => costs.ec_op,
+                                CoreTypeConcrete::SegmentArena(_) => costs.segment_arena,
+                                _ => 0,
+                            };
+
+                            (amount != 0).then_some((idx, amount as u64))
+                        })
+                        .collect()
+                })
+                .collect();
+
+            increments.insert(declaration.id.clone(), branches);
+        }
+
+        Ok(Self { increments })
+    }
+
+    /// Return the increments of every branch of a libfunc, if it uses any builtin.
+    pub fn get(&self, libfunc_id: &ConcreteLibfuncId) -> Option<&[BranchIncrements]> {
+        self.increments.get(libfunc_id).map(Vec::as_slice)
+    }
+}
+
+/// Return the builtin usage of the u8, u16, u32 and u64 libfuncs.
+macro_rules! uint_builtin_costs {
+    ($libfunc:expr, $n_branches:expr) => {
+        match $libfunc {
+            UintConcrete::Operation(_) => vec![range_checks(1), range_checks(1)],
+            UintConcrete::SquareRoot(_) => vec![range_checks(4)],
+            UintConcrete::FromFelt252(_) => vec![range_checks(2), range_checks(3)],
+            UintConcrete::Divmod(_) => vec![range_checks(3)],
+            UintConcrete::Bitwise(_) => vec![BuiltinStats {
+                bitwise: 1,
+                ..Default::default()
+            }],
+            _ => vec![BuiltinStats::default(); $n_branches],
+        }
+    };
+}
+
+/// Return the builtin usage of the i8, i16, i32 and i64 libfuncs.
+macro_rules! sint_builtin_costs {
+    ($libfunc:expr, $n_branches:expr) => {
+        match $libfunc {
+            SintConcrete::FromFelt252(_) => vec![range_checks(2), range_checks(3)],
+            SintConcrete::Operation(_) => vec![range_checks(2), range_checks(1), range_checks(1)],
+            SintConcrete::Diff(_) => vec![range_checks(1), range_checks(1)],
+            _ => vec![BuiltinStats::default(); $n_branches],
+        }
+    };
+}
+
+/// Return the builtin usage of every branch of a libfunc.
+fn libfunc_builtin_costs(libfunc: &CoreConcreteLibfunc) -> Vec<BuiltinStats> {
+    let n_branches = libfunc.branch_signatures().len();
+
+    match libfunc {
+        CoreConcreteLibfunc::Array(
+            ArrayConcreteLibfunc::Get(_) | ArrayConcreteLibfunc::Slice(_),
+        ) => vec![range_checks(1), range_checks(1)],
+        CoreConcreteLibfunc::Cast(CastConcreteLibfunc::Downcast(info)) => {
+            if info.from_nbits == info.to_nbits {
+                vec![BuiltinStats::default(); 2]
+            } else {
+                vec![range_checks(1), range_checks(1)]
+            }
+        }
+        CoreConcreteLibfunc::Ec(EcConcreteLibfunc::PointFromX(_)) => {
+            vec![range_checks(3), BuiltinStats::default()]
+        }
+        CoreConcreteLibfunc::Ec(EcConcreteLibfunc::StateAddMul(_)) => vec![BuiltinStats {
+            ec_op: 1,
+            ..Default::default()
+        }],
+        CoreConcreteLibfunc::Gas(
+            GasConcreteLibfunc::WithdrawGas(_) | GasConcreteLibfunc::BuiltinWithdrawGas(_),
+        ) => vec![range_checks(1), range_checks(1)],
+        CoreConcreteLibfunc::Uint8(libfunc) => uint_builtin_costs!(libfunc, n_branches),
+        CoreConcreteLibfunc::Uint16(libfunc) => uint_builtin_costs!(libfunc, n_branches),
+        CoreConcreteLibfunc::Uint32(libfunc) => uint_builtin_costs!(libfunc, n_branches),
+        CoreConcreteLibfunc::Uint64(libfunc) => uint_builtin_costs!(libfunc, n_branches),
+        CoreConcreteLibfunc::Uint128(libfunc) => match libfunc {
+            Uint128Concrete::Operation(_) => vec![range_checks(1), range_checks(1)],
+            Uint128Concrete::Divmod(_) | Uint128Concrete::SquareRoot(_) => vec![range_checks(4)],
+            Uint128Concrete::MulGuaranteeVerify(_) => vec![range_checks(9)],
+            Uint128Concrete::FromFelt252(_) => vec![range_checks(1), range_checks(3)],
+            Uint128Concrete::Bitwise(_) => vec![BuiltinStats {
+                bitwise: 1,
+                ..Default::default()
+            }],
+            Uint128Concrete::ByteReverse(_) => vec![BuiltinStats {
+                bitwise: 4,
+                ..Default::default()
+            }],
+            _ => vec![BuiltinStats::default(); n_branches],
+        },
+        CoreConcreteLibfunc::Uint256(libfunc) => match libfunc {
+            Uint256Concrete::Divmod(_) => vec![range_checks(6)],
+            Uint256Concrete::SquareRoot(_) => vec![range_checks(7)],
+            _ => vec![BuiltinStats::default(); n_branches],
+        },
+        CoreConcreteLibfunc::Uint512(Uint512Concrete::DivModU256(_)) => vec![range_checks(12)],
+        CoreConcreteLibfunc::Sint8(libfunc) => sint_builtin_costs!(libfunc, n_branches),
+        CoreConcreteLibfunc::Sint16(libfunc) => sint_builtin_costs!(libfunc, n_branches),
+        CoreConcreteLibfunc::Sint32(libfunc) => sint_builtin_costs!(libfunc, n_branches),
+        CoreConcreteLibfunc::Sint64(libfunc) => sint_builtin_costs!(libfunc, n_branches),
+        CoreConcreteLibfunc::Sint128(libfunc) => match libfunc {
+            Sint128Concrete::FromFelt252(_) => vec![range_checks(1), range_checks(3)],
+            Sint128Concrete::Operation(_) => vec![range_checks(1); 3],
+            Sint128Concrete::Diff(_) => vec![range_checks(1), range_checks(1)],
+            _ => vec![BuiltinStats::default(); n_branches],
+        },
+        CoreConcreteLibfunc::Bytes31(Bytes31ConcreteLibfunc::TryFromFelt252(_)) => {
+            vec![range_checks(3), range_checks(3)]
+        }
+        CoreConcreteLibfunc::Felt252Dict(libfunc) => match libfunc {
+            Felt252DictConcreteLibfunc::New(_) => vec![BuiltinStats {
+                segment_arena: 1,
+                ..Default::default()
+            }],
+            // The range checks depend on the dictionary accesses, and are counted by the libfunc.
+            Felt252DictConcreteLibfunc::Squash(_) => vec![BuiltinStats {
+                segment_arena: 1,
+                ..Default::default()
+            }],
+        },
+        CoreConcreteLibfunc::Pedersen(_) => vec![BuiltinStats {
+            pedersen: 1,
+            ..Default::default()
+        }],
+        CoreConcreteLibfunc::Poseidon(_) => vec![BuiltinStats {
+            poseidon: 1,
+            ..Default::default()
+        }],
+        CoreConcreteLibfunc::StarkNet(libfunc) => match libfunc {
+            StarkNetConcreteLibfunc::ClassHashTryFromFelt252(_)
+            | StarkNetConcreteLibfunc::ContractAddressTryFromFelt252(_)
+            | StarkNetConcreteLibfunc::StorageAddressTryFromFelt252(_) => {
+                vec![range_checks(3), range_checks(3)]
+            }
+            StarkNetConcreteLibfunc::StorageBaseAddressFromFelt252(_) => vec![range_checks(3)],
+            _ => vec![BuiltinStats::default(); n_branches],
+        },
+        _ => vec![BuiltinStats::default(); n_branches],
+    }
+}
+
+/// Return a [`BuiltinStats`] which only uses range checks.
+fn range_checks(range_check: usize) -> BuiltinStats {
+    BuiltinStats {
+        range_check,
+        ..Default::default()
+    }
+}
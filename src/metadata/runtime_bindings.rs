@@ -29,6 +29,8 @@ enum RuntimeBinding {
     DictGet,
     DictInsert,
     DictFree,
+    DictSquashRangeChecks,
+    GetSyscallHandler,
 }
 
 /// Runtime library bindings metadata.
@@ -657,6 +659,93 @@ impl RuntimeBindingsMeta {
             location,
         )))
     }
+
+    /// Register if necessary, then invoke the `dict_squash_range_checks()` function.
+    ///
+    /// Returns the number of range checks the VM would use to squash the dictionary as an `i64`.
+    pub fn dict_squash_range_checks<'c, 'a>(
+        &mut self,
+        context: &'c Context,
+        module: &Module,
+        block: &'a Block<'c>,
+        dict_ptr: Value<'c, 'a>, // ptr to the dict
+        location: Location<'c>,
+    ) -> Result<OperationRef<'c, 'a>>
+    where
+        'c: 'a,
+    {
+        if self
+            .active_map
+            .insert(RuntimeBinding::DictSquashRangeChecks)
+        {
+            module.body().append_operation(func::func(
+                context,
+                StringAttribute::new(context, "cairo_native__dict_squash_range_checks"),
+                TypeAttribute::new(
+                    FunctionType::new(
+                        context,
+                        &[llvm::r#type::opaque_pointer(context)],
+                        &[IntegerType::new(context, 64).into()],
+                    )
+                    .into(),
+                ),
+                Region::new(),
+                &[(
+                    Identifier::new(context, "sym_visibility"),
+                    StringAttribute::new(context, "private").into(),
+                )],
+                Location::unknown(context),
+            ));
+        }
+
+        Ok(block.append_operation(func::call(
+            context,
+            FlatSymbolRefAttribute::new(context, "cairo_native__dict_squash_range_checks"),
+            &[dict_ptr],
+            &[IntegerType::new(context, 64).into()],
+            location,
+        )))
+    }
+
+    /// Register if necessary, then invoke the `get_syscall_handler()` function.
+    ///
+    /// Returns the syscall handler of the current execution as an opaque pointer, or null if there
+    /// is none.
+    pub fn get_syscall_handler<'c, 'a>(
+        &mut self,
+        context: &'c Context,
+        module: &Module,
+        block: &'a Block<'c>,
+        location: Location<'c>,
+    ) -> Result<OperationRef<'c, 'a>>
+    where
+        'c: 'a,
+    {
+        if self.active_map.insert(RuntimeBinding::GetSyscallHandler) {
+            module.body().append_operation(func::func(
+                context,
+                StringAttribute::new(context, "cairo_native__get_syscall_handler"),
+                TypeAttribute::new(
+                    FunctionType::new(context, &[], &[llvm::r#type::opaque_pointer(context)])
+                        .into(),
+                ),
+                Region::new(),
+                &[(
+                    Identifier::new(context, "sym_visibility"),
+                    StringAttribute::new(context, "private").into(),
+                )],
+                Location::unknown(context),
+            ));
+        }
+
+        Ok(block.append_operation(func::call(
+            context,
+            FlatSymbolRefAttribute::new(context, "cairo_native__get_syscall_handler"),
+            &[],
+            &[llvm::r#type::opaque_pointer(context)],
+            location,
+        )))
+    }
 }
 
 impl Default for RuntimeBindingsMeta {
@@ -0,0 +1,370 @@
+//! # Parallel compilation
+//!
+//! Most of the compilation time of a large program is spent lowering and optimizing its functions,
+//! which are independent from each other. [`compile_to_objects`] splits the functions of a program
+//! into partitions, and compiles each one into an object file in its own thread, with its own MLIR
+//! context and module.
+//!
+//! Functions called from another partition are declared in the caller's module, and resolved when
+//! the objects are linked together (for example, using
+//! [`objects_to_shared_lib_with_config`](crate::objects_to_shared_lib_with_config)).
+//!
+//! The metadata (and the debug positions, if any) is computed once for the whole program and shared
+//! by every partition, so the objects behave exactly like one compiled in a single thread.
+//!
+//! The partitions only depend on the program and the number of partitions, and the objects are
+//! always returned in partition order, so the output is deterministic.
+
+use crate::{
+    compiler::compile_partition,
+    context::{create_mlir_context, ProgramMetadata},
+    debug_info::DebugPositions,
+    error::compile::CompileError,
+    ffi::{module_to_object, LLVMCompileError, OptLevel},
+    utils::{run_pass_manager, PassPipeline},
+};
+use cairo_lang_sierra::{
+    extensions::core::{CoreLibfunc, CoreType},
+    program::{Function, Program},
+    program_registry::ProgramRegistry,
+};
+use melior::{
+    ir::{Location, Module},
+    utility::register_all_passes,
+};
+use std::{num::NonZeroUsize, thread};
+use thiserror::Error;
+
+/// An error produced while compiling a partition of a program.
+#[derive(Debug, Error)]
+pub enum ParallelCompileError {
+    #[error(transparent)]
+    Compile(#[from] CompileError),
+    #[error(transparent)]
+    Mlir(#[from] melior::Error),
+    #[error(transparent)]
+    Llvm(#[from] LLVMCompileError),
+}
+
+/// Compile a program into an object file per partition, using a thread for each partition.
+///
+/// The program is split into (at most) `num_partitions` partitions. The returned objects have to be
+/// linked together, since they reference each other's functions.
+///
+/// Check out [`NativeContext::compile_to_objects`](crate::context::NativeContext::compile_to_objects)
+/// for a version which uses the context's settings.
+pub fn compile_to_objects(
+    program: &Program,
+    pipeline: &PassPipeline,
+    opt_level: OptLevel,
+    num_partitions: NonZeroUsize,
+    builtin_counters: bool,
+    debug_positions: Option<&DebugPositions>,
+) -> Result<Vec<Vec<u8>>, ParallelCompileError> {
+    // The metadata is computed for the whole program, so it's better to do it only once.
+    let registry =
+        ProgramRegistry::<CoreType, CoreLibfunc>::new(program).map_err(CompileError::from)?;
+    let program_metadata =
+        ProgramMetadata::new(program, &registry, builtin_counters).map_err(CompileError::from)?;
+
+    compile_to_objects_with_metadata(
+        program,
+        &program_metadata,
+        debug_positions,
+        pipeline,
+        opt_level,
+        num_partitions,
+    )
+}
+
+/// Same as [`compile_to_objects`], but with already computed metadata.
+pub(crate) fn compile_to_objects_with_metadata(
+    program: &Program,
+    program_metadata: &ProgramMetadata,
+    debug_positions: Option<&DebugPositions>,
+    pipeline: &PassPipeline,
+    opt_level: OptLevel,
+    num_partitions: NonZeroUsize,
+) -> Result<Vec<Vec<u8>>, ParallelCompileError> {
+    let partitions = partition_functions(program, num_partitions.get());
+    tracing::info!(
+        "Compiling {} functions in {} partitions.",
+        program.funcs.len(),
+        partitions.len()
+    );
+
+    // The pass registry is global, and populating it from multiple threads is not safe.
+    register_all_passes();
+
+    thread::scope(|scope| {
+        let handles = partitions
+            .iter()
+            .map(|partition| {
+                scope.spawn(move || {
+                    compile_partition_to_object(
+                        program,
+                        partition,
+                        program_metadata,
+                        debug_positions,
+                        pipeline,
+                        opt_level,
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|payload| std::panic::resume_unwind(payload))
+            })
+            .collect()
+    })
+}
+
+fn compile_partition_to_object(
+    program: &Program,
+    partition: &[&Function],
+    program_metadata: &ProgramMetadata,
+    debug_positions: Option<&DebugPositions>,
+    pipeline: &PassPipeline,
+    opt_level: OptLevel,
+) -> Result<Vec<u8>, ParallelCompileError> {
+    let context = create_mlir_context();
+    // Every partition already has its own thread.
+    context.enable_multi_threading(false);
+
+    let mut module = Module::new(Location::unknown(&context));
+    let registry =
+        ProgramRegistry::<CoreType, CoreLibfunc>::new(program).map_err(CompileError::from)?;
+
+    let mut metadata = program_metadata.clone().into_storage();
+    // MLIR locations belong to a context, so every partition needs its own.
+    let debug_locations =
+        debug_positions.map(|debug_positions| debug_positions.to_locations(&context));
+
+    compile_partition(
+        &context,
+        &module,
+        program,
+        &registry,
+        &mut metadata,
+        debug_locations.as_ref(),
+        partition,
+    )
+    .map_err(Box::new)?;
+    run_pass_manager(&context, &mut module, pipeline)?;
+
+    Ok(module_to_object(&module, opt_level)?)
+}
+
+/// Split the functions of a program into contiguous partitions of similar size.
+///
+/// The size of a function is estimated by the number of statements between its entry point and the
+/// next one. Empty partitions are removed.
+fn partition_functions(program: &Program, num_partitions: usize) -> Vec<Vec<&Function>> {
+    let mut entry_points = program
+        .funcs
+        .iter()
+        .map(|function| function.entry_point.0)
+        .collect::<Vec<_>>();
+    entry_points.sort_unstable();
+    entry_points.dedup();
+
+    let function_size = |function: &Function| {
+        let next_entry_point = entry_points
+            .get(entry_points.partition_point(|&x| x <= function.entry_point.0))
+            .copied()
+            .unwrap_or(program.statements.len());
+        (next_entry_point - function.entry_point.0).max(1)
+    };
+
+    let total_size = program.funcs.iter().map(function_size).sum::<usize>();
+
+    let mut partitions = vec![Vec::new(); num_partitions];
+    let mut offset = 0;
+    for function in &program.funcs {
+        partitions[offset * num_partitions / total_size].push(function);
+        offset += function_size(function);
+    }
+
+    partitions.retain(|partition| !partition.is_empty());
+    partitions
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        context::NativeContext,
+        execution_result::ExecutionResult,
+        executor::{AotNativeExecutor, JitNativeExecutor},
+        ffi::{objects_to_shared_lib_with_config, LinkerConfig},
+        metadata::gas::{GasMetadata, MetadataComputationConfig},
+        utils::{find_function_id, has_gas_builtin, test::load_cairo, SHARED_LIBRARY_EXT},
+        values::JitValue,
+    };
+    use libloading::Library;
+    use starknet_types_core::felt::Felt;
+
+    #[test]
+    fn partitions_are_deterministic() {
+        let (_, program) = load_cairo! {
+            fn run_test() -> felt252 {
+                a() + b() + c()
+            }
+
+            #[inline(never)]
+            fn a() -> felt252 {
+                1
+            }
+
+            #[inline(never)]
+            fn b() -> felt252 {
+                a() + 2
+            }
+
+            #[inline(never)]
+            fn c() -> felt252 {
+                b() + 3
+            }
+        };
+
+        let partition_ids = |num_partitions| {
+            partition_functions(&program, num_partitions)
+                .into_iter()
+                .map(|partition| {
+                    partition
+                        .into_iter()
+                        .map(|function| function.id.clone())
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let partitions = partition_ids(2);
+        assert!((1..=2).contains(&partitions.len()));
+        assert_eq!(
+            partitions.iter().map(Vec::len).sum::<usize>(),
+            program.funcs.len()
+        );
+        assert_eq!(partitions, partition_ids(2));
+
+        // There can't be more partitions than functions.
+        assert!(partition_ids(100).len() <= program.funcs.len());
+    }
+
+    /// Compile a program in two partitions, link them and run its `run_test` function.
+    fn run_partitioned(
+        module_name: &str,
+        program: &Program,
+        builtin_counters: bool,
+    ) -> ExecutionResult {
+        let objects = compile_to_objects(
+            program,
+            &PassPipeline::from(OptLevel::None),
+            OptLevel::None,
+            NonZeroUsize::new(2).unwrap(),
+            builtin_counters,
+            None,
+        )
+        .unwrap();
+        assert_eq!(objects.len(), partition_functions(program, 2).len());
+
+        let library_path = tempfile::Builder::new()
+            .prefix("lib")
+            .suffix(SHARED_LIBRARY_EXT)
+            .tempfile()
+            .unwrap()
+            .into_temp_path();
+        objects_to_shared_lib_with_config(
+            &objects.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+            &library_path,
+            &LinkerConfig::default(),
+        )
+        .unwrap();
+
+        let executor = AotNativeExecutor::new(
+            unsafe { Library::new(&library_path).unwrap() },
+            ProgramRegistry::new(program).unwrap(),
+            has_gas_builtin(program)
+                .then(|| GasMetadata::new(program, MetadataComputationConfig::default())),
+        )
+        .with_builtin_counters(builtin_counters);
+
+        executor
+            .invoke_dynamic(
+                find_function_id(program, &format!("{0}::{0}::run_test", module_name)),
+                &[],
+                Some(u128::MAX),
+                None,
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn compile_partitions() {
+        let (module_name, program) = load_cairo! {
+            fn run_test() -> felt252 {
+                a() + b() + c()
+            }
+
+            #[inline(never)]
+            fn a() -> felt252 {
+                1
+            }
+
+            #[inline(never)]
+            fn b() -> felt252 {
+                a() + 2
+            }
+
+            #[inline(never)]
+            fn c() -> felt252 {
+                b() + 3
+            }
+        };
+
+        let result = run_partitioned(&module_name, &program, false);
+        assert_eq!(result.return_value, JitValue::Felt252(Felt::from(10)));
+    }
+
+    #[test]
+    fn compile_partitions_with_builtin_counters() {
+        let (module_name, program) = load_cairo! {
+            fn run_test() -> u32 {
+                a(1) + b(2)
+            }
+
+            #[inline(never)]
+            fn a(x: u32) -> u32 {
+                x + 1
+            }
+
+            #[inline(never)]
+            fn b(x: u32) -> u32 {
+                a(x) * 2
+            }
+        };
+
+        // The partitions must use the same ABI (and count the same builtins) as a program compiled
+        // in a single module.
+        let context = NativeContext::new().with_builtin_counters(true);
+        let native_module = context.compile(&program, OptLevel::None).unwrap();
+        let expected = JitNativeExecutor::new(native_module, OptLevel::None)
+            .invoke_dynamic(
+                find_function_id(&program, &format!("{0}::{0}::run_test", module_name)),
+                &[],
+                Some(u128::MAX),
+                None,
+            )
+            .unwrap();
+        assert_ne!(expected.builtin_stats.range_check, 0);
+
+        let result = run_partitioned(&module_name, &program, true);
+        assert_eq!(result.return_value, expected.return_value);
+        assert_eq!(result.builtin_stats, expected.builtin_stats);
+    }
+}
@@ -3,10 +3,21 @@
 #![allow(clippy::type_complexity)]
 #![allow(dead_code)]
 
+pub use self::in_memory::{calculate_contract_address, ContractLogs, InMemorySyscallHandler};
 use starknet_types_core::felt::Felt;
 
+mod in_memory;
+
 pub type SyscallResult<T> = std::result::Result<T, Vec<Felt>>;
 
+/// Return the failure of a syscall that isn't supported: the short string `Unsupported syscall`.
+///
+/// It's what the default implementations of the optional [`StarkNetSyscallHandler`] methods
+/// return.
+pub fn unsupported_syscall<T>() -> SyscallResult<T> {
+    Err(vec![Felt::from_bytes_be_slice(b"Unsupported syscall")])
+}
+
 /// Binary representation of a `Felt` (in MLIR).
 #[derive(Debug, Clone)]
 #[cfg_attr(target_arch = "x86_64", repr(C, align(8)))]
@@ -19,6 +30,7 @@ pub struct Felt252Abi(pub [u8; 32]);
 #[cfg_attr(not(target_arch = "x86_64"), repr(C, align(16)))]
 pub struct U256(pub [u8; 32]);
 
+#[derive(Debug, Clone, Default)]
 pub struct ExecutionInfo {
     pub block_info: BlockInfo,
     pub tx_info: TxInfo,
@@ -27,12 +39,23 @@ pub struct ExecutionInfo {
     pub entry_point_selector: Felt,
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionInfoV2 {
+    pub block_info: BlockInfo,
+    pub tx_info: TxInfoV2,
+    pub caller_address: Felt,
+    pub contract_address: Felt,
+    pub entry_point_selector: Felt,
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct BlockInfo {
     pub block_number: u64,
     pub block_timestamp: u64,
     pub sequencer_address: Felt,
 }
 
+#[derive(Debug, Clone, Default)]
 pub struct TxInfo {
     pub version: Felt,
     pub account_contract_address: Felt,
@@ -43,14 +66,60 @@ pub struct TxInfo {
     pub nonce: Felt,
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct TxInfoV2 {
+    pub version: Felt,
+    pub account_contract_address: Felt,
+    pub max_fee: u128,
+    pub signature: Vec<Felt>,
+    pub transaction_hash: Felt,
+    pub chain_id: Felt,
+    pub nonce: Felt,
+    pub resource_bounds: Vec<ResourceBounds>,
+    pub tip: u128,
+    pub paymaster_data: Vec<Felt>,
+    pub nonce_data_availability_mode: u32,
+    pub fee_data_availability_mode: u32,
+    pub account_deployment_data: Vec<Felt>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResourceBounds {
+    pub resource: Felt,
+    pub max_amount: u64,
+    pub max_price_per_unit: u128,
+}
+
+/// Binary representation of a `Secp256k1Point` (in MLIR).
+#[derive(Debug, Clone)]
+#[repr(C)]
 pub struct Secp256k1Point {
-    // TODO: Add fields.
+    pub x: U256,
+    pub y: U256,
 }
 
+/// Binary representation of a `Secp256r1Point` (in MLIR).
+#[derive(Debug, Clone)]
+#[repr(C)]
 pub struct Secp256r1Point {
-    // TODO: Add fields.
+    pub x: U256,
+    pub y: U256,
 }
 
+/// The syscalls available to a program, implemented by the host.
+///
+/// The secp256k1, secp256r1 and testing (`starknet::testing::*`) syscalls are optional: when not
+/// implemented they fail with [`unsupported_syscall`]. Secp256 failures are returned to the program
+/// like those of any other syscall.
+///
+/// # Testing syscalls
+///
+/// The testing libfuncs have no failure branch, therefore a failed testing syscall can't be
+/// reported to the program. Instead, the first failure is recorded and the program keeps running
+/// as if the syscall had succeeded with the default value of its result: `pop_log` returns `None`
+/// and the setters do nothing. Once the invocation finishes, the executor discards its result and
+/// returns an [`ErrorImpl::TestingSyscallFailed`](crate::error::jit_engine::ErrorImpl) with the
+/// name of the syscall and its failure data.
 pub trait StarkNetSyscallHandler {
     fn get_block_hash(
         &mut self,
@@ -58,6 +127,8 @@ pub trait StarkNetSyscallHandler {
         remaining_gas: &mut u128,
     ) -> SyscallResult<Felt>;
     fn get_execution_info(&mut self, remaining_gas: &mut u128) -> SyscallResult<ExecutionInfo>;
+    fn get_execution_info_v2(&mut self, remaining_gas: &mut u128)
+        -> SyscallResult<ExecutionInfoV2>;
 
     fn deploy(
         &mut self,
@@ -116,103 +187,200 @@ pub trait StarkNetSyscallHandler {
 
     fn keccak(&mut self, input: &[u64], remaining_gas: &mut u128) -> SyscallResult<U256>;
 
-    // TODO: secp256k1 syscalls
+    fn secp256k1_new(
+        &mut self,
+        _x: U256,
+        _y: U256,
+        _remaining_gas: &mut u128,
+    ) -> SyscallResult<Option<Secp256k1Point>> {
+        unsupported_syscall()
+    }
+
     fn secp256k1_add(
         &mut self,
-        p0: Secp256k1Point,
-        p1: Secp256k1Point,
-        remaining_gas: &mut u128,
-    ) -> SyscallResult<Option<Secp256k1Point>>;
+        _p0: Secp256k1Point,
+        _p1: Secp256k1Point,
+        _remaining_gas: &mut u128,
+    ) -> SyscallResult<Secp256k1Point> {
+        unsupported_syscall()
+    }
+
+    fn secp256k1_mul(
+        &mut self,
+        _p: Secp256k1Point,
+        _m: U256,
+        _remaining_gas: &mut u128,
+    ) -> SyscallResult<Secp256k1Point> {
+        unsupported_syscall()
+    }
 
     fn secp256k1_get_point_from_x(
-        &self,
-        x: U256,
-        y_parity: bool,
-        remaining_gas: &mut u128,
-    ) -> SyscallResult<Option<Secp256k1Point>>;
+        &mut self,
+        _x: U256,
+        _y_parity: bool,
+        _remaining_gas: &mut u128,
+    ) -> SyscallResult<Option<Secp256k1Point>> {
+        unsupported_syscall()
+    }
 
     fn secp256k1_get_xy(
-        &self,
-        p: Secp256k1Point,
-        remaining_gas: &mut u128,
-    ) -> SyscallResult<(U256, U256)>;
-
-    fn secp256k1_mul(
-        &self,
-        p: Secp256k1Point,
-        m: U256,
-        remaining_gas: &mut u128,
-    ) -> SyscallResult<Option<Secp256k1Point>>;
+        &mut self,
+        _p: Secp256k1Point,
+        _remaining_gas: &mut u128,
+    ) -> SyscallResult<(U256, U256)> {
+        unsupported_syscall()
+    }
 
-    fn secp256k1_new(
-        &self,
-        x: U256,
-        y: U256,
-        remaining_gas: &mut u128,
-    ) -> SyscallResult<Option<Secp256k1Point>>;
+    fn secp256r1_new(
+        &mut self,
+        _x: U256,
+        _y: U256,
+        _remaining_gas: &mut u128,
+    ) -> SyscallResult<Option<Secp256r1Point>> {
+        unsupported_syscall()
+    }
 
-    // TODO: secp256r1 syscalls
     fn secp256r1_add(
-        &self,
-        p0: Secp256k1Point,
-        p1: Secp256k1Point,
-        remaining_gas: &mut u128,
-    ) -> SyscallResult<Option<Secp256k1Point>>;
+        &mut self,
+        _p0: Secp256r1Point,
+        _p1: Secp256r1Point,
+        _remaining_gas: &mut u128,
+    ) -> SyscallResult<Secp256r1Point> {
+        unsupported_syscall()
+    }
+
+    fn secp256r1_mul(
+        &mut self,
+        _p: Secp256r1Point,
+        _m: U256,
+        _remaining_gas: &mut u128,
+    ) -> SyscallResult<Secp256r1Point> {
+        unsupported_syscall()
+    }
 
     fn secp256r1_get_point_from_x(
-        &self,
-        x: U256,
-        y_parity: bool,
-        remaining_gas: &mut u128,
-    ) -> SyscallResult<Option<Secp256k1Point>>;
+        &mut self,
+        _x: U256,
+        _y_parity: bool,
+        _remaining_gas: &mut u128,
+    ) -> SyscallResult<Option<Secp256r1Point>> {
+        unsupported_syscall()
+    }
 
     fn secp256r1_get_xy(
-        &self,
-        p: Secp256k1Point,
-        remaining_gas: &mut u128,
-    ) -> SyscallResult<(U256, U256)>;
-
-    fn secp256r1_mul(
-        &self,
-        p: Secp256k1Point,
-        m: U256,
-        remaining_gas: &mut u128,
-    ) -> SyscallResult<Option<Secp256k1Point>>;
-
-    fn secp256r1_new(
         &mut self,
-        x: U256,
-        y: U256,
-        remaining_gas: &mut u128,
-    ) -> SyscallResult<Option<Secp256k1Point>>;
+        _p: Secp256r1Point,
+        _remaining_gas: &mut u128,
+    ) -> SyscallResult<(U256, U256)> {
+        unsupported_syscall()
+    }
 
     // Testing syscalls.
-    // TODO: Make them optional. Crash if called but not implemented.
-    fn pop_log(&mut self);
+    /// Pop the oldest event emitted by the contract at `address` which hasn't been popped yet.
+    ///
+    /// Returns the event's keys and data, or `Ok(None)` when the contract has no events left. An
+    /// `Err` means the events can't be retrieved (for example, because the handler doesn't record
+    /// them), and is handled as described in the [testing syscalls](Self#testing-syscalls)
+    /// section: the program sees `None` and the invocation fails afterwards.
+    fn pop_log(&mut self, _address: Felt) -> SyscallResult<Option<(Vec<Felt>, Vec<Felt>)>> {
+        unsupported_syscall()
+    }
 
-    fn set_account_contract_address(&mut self, contract_address: Felt);
+    /// Set the account contract address of the transaction info.
+    ///
+    /// On failure, the program continues as if the value had been set; see the
+    /// [testing syscalls](Self#testing-syscalls) section.
+    fn set_account_contract_address(&mut self, _contract_address: Felt) -> SyscallResult<()> {
+        unsupported_syscall()
+    }
 
-    fn set_block_number(&mut self, block_number: u64);
+    /// Set the block number of the block info.
+    ///
+    /// On failure, the program continues as if the value had been set; see the
+    /// [testing syscalls](Self#testing-syscalls) section.
+    fn set_block_number(&mut self, _block_number: u64) -> SyscallResult<()> {
+        unsupported_syscall()
+    }
 
-    fn set_block_timestamp(&mut self, block_timestamp: u64);
+    /// Set the block timestamp of the block info.
+    ///
+    /// On failure, the program continues as if the value had been set; see the
+    /// [testing syscalls](Self#testing-syscalls) section.
+    fn set_block_timestamp(&mut self, _block_timestamp: u64) -> SyscallResult<()> {
+        unsupported_syscall()
+    }
 
-    fn set_caller_address(&mut self, address: Felt);
+    /// Set the caller address of the execution info.
+    ///
+    /// On failure, the program continues as if the value had been set; see the
+    /// [testing syscalls](Self#testing-syscalls) section.
+    fn set_caller_address(&mut self, _address: Felt) -> SyscallResult<()> {
+        unsupported_syscall()
+    }
 
-    fn set_chain_id(&mut self, chain_id: Felt);
+    /// Set the chain id of the transaction info.
+    ///
+    /// On failure, the program continues as if the value had been set; see the
+    /// [testing syscalls](Self#testing-syscalls) section.
+    fn set_chain_id(&mut self, _chain_id: Felt) -> SyscallResult<()> {
+        unsupported_syscall()
+    }
 
-    fn set_contract_address(&mut self, address: Felt);
+    /// Set the contract address of the execution info.
+    ///
+    /// On failure, the program continues as if the value had been set; see the
+    /// [testing syscalls](Self#testing-syscalls) section.
+    fn set_contract_address(&mut self, _address: Felt) -> SyscallResult<()> {
+        unsupported_syscall()
+    }
 
-    fn set_max_fee(&mut self, max_fee: u128);
+    /// Set the max fee of the transaction info.
+    ///
+    /// On failure, the program continues as if the value had been set; see the
+    /// [testing syscalls](Self#testing-syscalls) section.
+    fn set_max_fee(&mut self, _max_fee: u128) -> SyscallResult<()> {
+        unsupported_syscall()
+    }
 
-    fn set_nonce(&mut self, nonce: Felt);
+    /// Set the nonce of the transaction info.
+    ///
+    /// On failure, the program continues as if the value had been set; see the
+    /// [testing syscalls](Self#testing-syscalls) section.
+    fn set_nonce(&mut self, _nonce: Felt) -> SyscallResult<()> {
+        unsupported_syscall()
+    }
 
-    fn set_sequencer_address(&mut self, address: Felt);
+    /// Set the sequencer address of the block info.
+    ///
+    /// On failure, the program continues as if the value had been set; see the
+    /// [testing syscalls](Self#testing-syscalls) section.
+    fn set_sequencer_address(&mut self, _address: Felt) -> SyscallResult<()> {
+        unsupported_syscall()
+    }
 
-    fn set_signature(&mut self, signature: &[Felt]);
+    /// Set the signature of the transaction info.
+    ///
+    /// On failure, the program continues as if the value had been set; see the
+    /// [testing syscalls](Self#testing-syscalls) section.
+    fn set_signature(&mut self, _signature: &[Felt]) -> SyscallResult<()> {
+        unsupported_syscall()
+    }
 
-    fn set_transaction_hash(&mut self, transaction_hash: Felt);
+    /// Set the transaction hash of the transaction info.
+    ///
+    /// On failure, the program continues as if the value had been set; see the
+    /// [testing syscalls](Self#testing-syscalls) section.
+    fn set_transaction_hash(&mut self, _transaction_hash: Felt) -> SyscallResult<()> {
+        unsupported_syscall()
+    }
 
-    fn set_version(&mut self, version: Felt);
+    /// Set the version of the transaction info.
+    ///
+    /// On failure, the program continues as if the value had been set; see the
+    /// [testing syscalls](Self#testing-syscalls) section.
+    fn set_version(&mut self, _version: Felt) -> SyscallResult<()> {
+        unsupported_syscall()
+    }
 }
 
 // TODO: Move to the correct place or remove if unused.
@@ -220,11 +388,31 @@ pub(crate) mod handler {
     use super::*;
     use std::{
         alloc::Layout,
+        cell::RefCell,
         fmt::Debug,
-        mem::{size_of, ManuallyDrop},
+        mem::{size_of, ManuallyDrop, MaybeUninit},
         ptr::NonNull,
     };
 
+    thread_local! {
+        /// The first failed testing syscall (and its error) of the invocation running in the
+        /// current thread.
+        static TESTING_SYSCALL_ERROR: RefCell<Option<(&'static str, Vec<Felt>)>> =
+            RefCell::new(None);
+    }
+
+    /// Replace the failed testing syscall of the current thread, returning the previous one.
+    ///
+    /// A failed testing syscall doesn't stop the program, which continues with the syscall's
+    /// default result (see [`StarkNetSyscallHandler`]'s testing syscalls section). The executors
+    /// clear the record before an invocation and, if it's set afterwards, return a
+    /// `TestingSyscallFailed` error instead of the invocation's result.
+    pub(crate) fn replace_testing_syscall_error(
+        value: Option<(&'static str, Vec<Felt>)>,
+    ) -> Option<(&'static str, Vec<Felt>)> {
+        TESTING_SYSCALL_ERROR.with(|x| x.replace(value))
+    }
+
     macro_rules! field_offset {
         ( $ident:path, $field:ident ) => {
             unsafe {
@@ -253,6 +441,12 @@ pub(crate) mod handler {
         pub payload: (NonNull<Felt252Abi>, u32, u32),
     }
 
+    #[repr(C)]
+    pub(crate) struct OptionAbi<T> {
+        pub tag: u8,
+        pub payload: MaybeUninit<T>,
+    }
+
     #[repr(C)]
     struct ExecutionInfoAbi {
         block_info: NonNull<BlockInfoAbi>,
@@ -262,6 +456,15 @@ pub(crate) mod handler {
         entry_point_selector: Felt252Abi,
     }
 
+    #[repr(C)]
+    struct ExecutionInfoV2Abi {
+        block_info: NonNull<BlockInfoAbi>,
+        tx_info: NonNull<TxInfoV2Abi>,
+        caller_address: Felt252Abi,
+        contract_address: Felt252Abi,
+        entry_point_selector: Felt252Abi,
+    }
+
     #[repr(C)]
     struct BlockInfoAbi {
         block_number: u64,
@@ -280,6 +483,31 @@ pub(crate) mod handler {
         nonce: Felt252Abi,
     }
 
+    #[repr(C)]
+    struct TxInfoV2Abi {
+        version: Felt252Abi,
+        account_contract_address: Felt252Abi,
+        max_fee: u128,
+        signature: (NonNull<Felt252Abi>, u32, u32),
+        transaction_hash: Felt252Abi,
+        chain_id: Felt252Abi,
+        nonce: Felt252Abi,
+        resource_bounds: (NonNull<ResourceBoundsAbi>, u32, u32),
+        tip: u128,
+        paymaster_data: (NonNull<Felt252Abi>, u32, u32),
+        nonce_data_availability_mode: u32,
+        fee_data_availability_mode: u32,
+        account_deployment_data: (NonNull<Felt252Abi>, u32, u32),
+    }
+
+    #[repr(C)]
+    #[derive(Debug, Clone)]
+    struct ResourceBoundsAbi {
+        resource: Felt252Abi,
+        max_amount: u64,
+        max_price_per_unit: u128,
+    }
+
     #[repr(C)]
     #[derive(Debug)]
     pub struct StarkNetSyscallHandlerCallbacks<'a, T> {
@@ -296,6 +524,11 @@ pub(crate) mod handler {
             ptr: &mut T,
             gas: &mut u128,
         ),
+        get_execution_info_v2: extern "C" fn(
+            result_ptr: &mut SyscallResultAbi<NonNull<ExecutionInfoV2Abi>>,
+            ptr: &mut T,
+            gas: &mut u128,
+        ),
         deploy: extern "C" fn(
             result_ptr: &mut SyscallResultAbi<(Felt252Abi, (NonNull<Felt252Abi>, u32, u32))>,
             ptr: &mut T,
@@ -363,6 +596,97 @@ pub(crate) mod handler {
             _gas: &mut u128,
             input: *const (*const u64, u32, u32),
         ),
+
+        secp256k1_new: extern "C" fn(
+            result_ptr: &mut SyscallResultAbi<OptionAbi<Secp256k1Point>>,
+            ptr: &mut T,
+            gas: &mut u128,
+            x: &U256,
+            y: &U256,
+        ),
+        secp256k1_add: extern "C" fn(
+            result_ptr: &mut SyscallResultAbi<Secp256k1Point>,
+            ptr: &mut T,
+            gas: &mut u128,
+            p0: &Secp256k1Point,
+            p1: &Secp256k1Point,
+        ),
+        secp256k1_mul: extern "C" fn(
+            result_ptr: &mut SyscallResultAbi<Secp256k1Point>,
+            ptr: &mut T,
+            gas: &mut u128,
+            p: &Secp256k1Point,
+            m: &U256,
+        ),
+        secp256k1_get_point_from_x: extern "C" fn(
+            result_ptr: &mut SyscallResultAbi<OptionAbi<Secp256k1Point>>,
+            ptr: &mut T,
+            gas: &mut u128,
+            x: &U256,
+            y_parity: bool,
+        ),
+        secp256k1_get_xy: extern "C" fn(
+            result_ptr: &mut SyscallResultAbi<(U256, U256)>,
+            ptr: &mut T,
+            gas: &mut u128,
+            p: &Secp256k1Point,
+        ),
+
+        secp256r1_new: extern "C" fn(
+            result_ptr: &mut SyscallResultAbi<OptionAbi<Secp256r1Point>>,
+            ptr: &mut T,
+            gas: &mut u128,
+            x: &U256,
+            y: &U256,
+        ),
+        secp256r1_add: extern "C" fn(
+            result_ptr: &mut SyscallResultAbi<Secp256r1Point>,
+            ptr: &mut T,
+            gas: &mut u128,
+            p0: &Secp256r1Point,
+            p1: &Secp256r1Point,
+        ),
+        secp256r1_mul: extern "C" fn(
+            result_ptr: &mut SyscallResultAbi<Secp256r1Point>,
+            ptr: &mut T,
+            gas: &mut u128,
+            p: &Secp256r1Point,
+            m: &U256,
+        ),
+        secp256r1_get_point_from_x: extern "C" fn(
+            result_ptr: &mut SyscallResultAbi<OptionAbi<Secp256r1Point>>,
+            ptr: &mut T,
+            gas: &mut u128,
+            x: &U256,
+            y_parity: bool,
+        ),
+        secp256r1_get_xy: extern "C" fn(
+            result_ptr: &mut SyscallResultAbi<(U256, U256)>,
+            ptr: &mut T,
+            gas: &mut u128,
+            p: &Secp256r1Point,
+        ),
+
+        pop_log: extern "C" fn(
+            result_ptr: &mut OptionAbi<(
+                (NonNull<Felt252Abi>, u32, u32),
+                (NonNull<Felt252Abi>, u32, u32),
+            )>,
+            ptr: &mut T,
+            address: &Felt252Abi,
+        ),
+        set_account_contract_address: extern "C" fn(ptr: &mut T, contract_address: &Felt252Abi),
+        set_block_number: extern "C" fn(ptr: &mut T, block_number: &u64),
+        set_block_timestamp: extern "C" fn(ptr: &mut T, block_timestamp: &u64),
+        set_caller_address: extern "C" fn(ptr: &mut T, address: &Felt252Abi),
+        set_chain_id: extern "C" fn(ptr: &mut T, chain_id: &Felt252Abi),
+        set_contract_address: extern "C" fn(ptr: &mut T, address: &Felt252Abi),
+        set_max_fee: extern "C" fn(ptr: &mut T, max_fee: &u128),
+        set_nonce: extern "C" fn(ptr: &mut T, nonce: &Felt252Abi),
+        set_sequencer_address: extern "C" fn(ptr: &mut T, address: &Felt252Abi),
+        set_signature: extern "C" fn(ptr: &mut T, signature: *const (*const Felt252Abi, u32, u32)),
+        set_transaction_hash: extern "C" fn(ptr: &mut T, transaction_hash: &Felt252Abi),
+        set_version: extern "C" fn(ptr: &mut T, version: &Felt252Abi),
     }
 
     impl<'a, T> StarkNetSyscallHandlerCallbacks<'a, T>
@@ -375,12 +699,39 @@ pub(crate) mod handler {
         pub const EMIT_EVENT: usize = field_offset!(Self, emit_event) >> 3;
         pub const GET_BLOCK_HASH: usize = field_offset!(Self, get_block_hash) >> 3;
         pub const GET_EXECUTION_INFO: usize = field_offset!(Self, get_execution_info) >> 3;
+        pub const GET_EXECUTION_INFO_V2: usize = field_offset!(Self, get_execution_info_v2) >> 3;
         pub const KECCAK: usize = field_offset!(Self, keccak) >> 3;
         pub const LIBRARY_CALL: usize = field_offset!(Self, library_call) >> 3;
         pub const REPLACE_CLASS: usize = field_offset!(Self, replace_class) >> 3;
         pub const SEND_MESSAGE_TO_L1: usize = field_offset!(Self, send_message_to_l1) >> 3;
         pub const STORAGE_READ: usize = field_offset!(Self, storage_read) >> 3;
         pub const STORAGE_WRITE: usize = field_offset!(Self, storage_write) >> 3;
+        pub const SECP256K1_NEW: usize = field_offset!(Self, secp256k1_new) >> 3;
+        pub const SECP256K1_ADD: usize = field_offset!(Self, secp256k1_add) >> 3;
+        pub const SECP256K1_MUL: usize = field_offset!(Self, secp256k1_mul) >> 3;
+        pub const SECP256K1_GET_POINT_FROM_X: usize =
+            field_offset!(Self, secp256k1_get_point_from_x) >> 3;
+        pub const SECP256K1_GET_XY: usize = field_offset!(Self, secp256k1_get_xy) >> 3;
+        pub const SECP256R1_NEW: usize = field_offset!(Self, secp256r1_new) >> 3;
+        pub const SECP256R1_ADD: usize = field_offset!(Self, secp256r1_add) >> 3;
+        pub const SECP256R1_MUL: usize = field_offset!(Self, secp256r1_mul) >> 3;
+        pub const SECP256R1_GET_POINT_FROM_X: usize =
+            field_offset!(Self, secp256r1_get_point_from_x) >> 3;
+        pub const SECP256R1_GET_XY: usize = field_offset!(Self, secp256r1_get_xy) >> 3;
+        pub const POP_LOG: usize = field_offset!(Self, pop_log) >> 3;
+        pub const SET_ACCOUNT_CONTRACT_ADDRESS: usize =
+            field_offset!(Self, set_account_contract_address) >> 3;
+        pub const SET_BLOCK_NUMBER: usize = field_offset!(Self, set_block_number) >> 3;
+        pub const SET_BLOCK_TIMESTAMP: usize = field_offset!(Self, set_block_timestamp) >> 3;
+        pub const SET_CALLER_ADDRESS: usize = field_offset!(Self, set_caller_address) >> 3;
+        pub const SET_CHAIN_ID: usize = field_offset!(Self, set_chain_id) >> 3;
+        pub const SET_CONTRACT_ADDRESS: usize = field_offset!(Self, set_contract_address) >> 3;
+        pub const SET_MAX_FEE: usize = field_offset!(Self, set_max_fee) >> 3;
+        pub const SET_NONCE: usize = field_offset!(Self, set_nonce) >> 3;
+        pub const SET_SEQUENCER_ADDRESS: usize = field_offset!(Self, set_sequencer_address) >> 3;
+        pub const SET_SIGNATURE: usize = field_offset!(Self, set_signature) >> 3;
+        pub const SET_TRANSACTION_HASH: usize = field_offset!(Self, set_transaction_hash) >> 3;
+        pub const SET_VERSION: usize = field_offset!(Self, set_version) >> 3;
     }
 
     impl<'a, T> StarkNetSyscallHandlerCallbacks<'a, T>
@@ -392,6 +743,7 @@ pub(crate) mod handler {
                 self_ptr: handler,
                 get_block_hash: Self::wrap_get_block_hash,
                 get_execution_info: Self::wrap_get_execution_info,
+                get_execution_info_v2: Self::wrap_get_execution_info_v2,
                 deploy: Self::wrap_deploy,
                 replace_class: Self::wrap_replace_class,
                 library_call: Self::wrap_library_call,
@@ -401,6 +753,29 @@ pub(crate) mod handler {
                 emit_event: Self::wrap_emit_event,
                 send_message_to_l1: Self::wrap_send_message_to_l1,
                 keccak: Self::wrap_keccak,
+                secp256k1_new: Self::wrap_secp256k1_new,
+                secp256k1_add: Self::wrap_secp256k1_add,
+                secp256k1_mul: Self::wrap_secp256k1_mul,
+                secp256k1_get_point_from_x: Self::wrap_secp256k1_get_point_from_x,
+                secp256k1_get_xy: Self::wrap_secp256k1_get_xy,
+                secp256r1_new: Self::wrap_secp256r1_new,
+                secp256r1_add: Self::wrap_secp256r1_add,
+                secp256r1_mul: Self::wrap_secp256r1_mul,
+                secp256r1_get_point_from_x: Self::wrap_secp256r1_get_point_from_x,
+                secp256r1_get_xy: Self::wrap_secp256r1_get_xy,
+                pop_log: Self::wrap_pop_log,
+                set_account_contract_address: Self::wrap_set_account_contract_address,
+                set_block_number: Self::wrap_set_block_number,
+                set_block_timestamp: Self::wrap_set_block_timestamp,
+                set_caller_address: Self::wrap_set_caller_address,
+                set_chain_id: Self::wrap_set_chain_id,
+                set_contract_address: Self::wrap_set_contract_address,
+                set_max_fee: Self::wrap_set_max_fee,
+                set_nonce: Self::wrap_set_nonce,
+                set_sequencer_address: Self::wrap_set_sequencer_address,
+                set_signature: Self::wrap_set_signature,
+                set_transaction_hash: Self::wrap_set_transaction_hash,
+                set_version: Self::wrap_set_version,
             }
         }
 
@@ -427,6 +802,19 @@ pub(crate) mod handler {
             }
         }
 
+        fn wrap_option<E>(value: Option<E>) -> OptionAbi<E> {
+            match value {
+                Some(x) => OptionAbi {
+                    tag: 0u8,
+                    payload: MaybeUninit::new(x),
+                },
+                None => OptionAbi {
+                    tag: 1u8,
+                    payload: MaybeUninit::uninit(),
+                },
+            }
+        }
+
         extern "C" fn wrap_get_block_hash(
             result_ptr: &mut SyscallResultAbi<Felt252Abi>,
             ptr: &mut T,
@@ -511,6 +899,101 @@ pub(crate) mod handler {
             };
         }
 
+        extern "C" fn wrap_get_execution_info_v2(
+            result_ptr: &mut SyscallResultAbi<NonNull<ExecutionInfoV2Abi>>,
+            ptr: &mut T,
+            gas: &mut u128,
+        ) {
+            let result = ptr.get_execution_info_v2(gas);
+
+            *result_ptr = match result {
+                Ok(x) => SyscallResultAbi {
+                    ok: ManuallyDrop::new(SyscallResultAbiOk {
+                        tag: 0u8,
+                        payload: unsafe {
+                            let mut block_info_ptr =
+                                NonNull::new(
+                                    libc::malloc(size_of::<BlockInfoAbi>()) as *mut BlockInfoAbi
+                                )
+                                .unwrap();
+                            block_info_ptr.as_mut().block_number = x.block_info.block_number;
+                            block_info_ptr.as_mut().block_timestamp = x.block_info.block_timestamp;
+                            block_info_ptr.as_mut().sequencer_address =
+                                Felt252Abi(x.block_info.sequencer_address.to_bytes_le());
+
+                            let mut tx_info_ptr = NonNull::new(
+                                libc::malloc(size_of::<TxInfoV2Abi>()) as *mut TxInfoV2Abi,
+                            )
+                            .unwrap();
+                            tx_info_ptr.as_mut().version =
+                                Felt252Abi(x.tx_info.version.to_bytes_le());
+                            tx_info_ptr.as_mut().account_contract_address =
+                                Felt252Abi(x.tx_info.account_contract_address.to_bytes_le());
+                            tx_info_ptr.as_mut().max_fee = x.tx_info.max_fee;
+                            tx_info_ptr.as_mut().signature = Self::alloc_mlir_array(
+                                &x.tx_info
+                                    .signature
+                                    .into_iter()
+                                    .map(|x| Felt252Abi(x.to_bytes_le()))
+                                    .collect::<Vec<_>>(),
+                            );
+                            tx_info_ptr.as_mut().transaction_hash =
+                                Felt252Abi(x.tx_info.transaction_hash.to_bytes_le());
+                            tx_info_ptr.as_mut().chain_id =
+                                Felt252Abi(x.tx_info.chain_id.to_bytes_le());
+                            tx_info_ptr.as_mut().nonce = Felt252Abi(x.tx_info.nonce.to_bytes_le());
+                            tx_info_ptr.as_mut().resource_bounds = Self::alloc_mlir_array(
+                                &x.tx_info
+                                    .resource_bounds
+                                    .into_iter()
+                                    .map(|x| ResourceBoundsAbi {
+                                        resource: Felt252Abi(x.resource.to_bytes_le()),
+                                        max_amount: x.max_amount,
+                                        max_price_per_unit: x.max_price_per_unit,
+                                    })
+                                    .collect::<Vec<_>>(),
+                            );
+                            tx_info_ptr.as_mut().tip = x.tx_info.tip;
+                            tx_info_ptr.as_mut().paymaster_data = Self::alloc_mlir_array(
+                                &x.tx_info
+                                    .paymaster_data
+                                    .into_iter()
+                                    .map(|x| Felt252Abi(x.to_bytes_le()))
+                                    .collect::<Vec<_>>(),
+                            );
+                            tx_info_ptr.as_mut().nonce_data_availability_mode =
+                                x.tx_info.nonce_data_availability_mode;
+                            tx_info_ptr.as_mut().fee_data_availability_mode =
+                                x.tx_info.fee_data_availability_mode;
+                            tx_info_ptr.as_mut().account_deployment_data = Self::alloc_mlir_array(
+                                &x.tx_info
+                                    .account_deployment_data
+                                    .into_iter()
+                                    .map(|x| Felt252Abi(x.to_bytes_le()))
+                                    .collect::<Vec<_>>(),
+                            );
+
+                            let mut execution_info_ptr =
+                                NonNull::new(libc::malloc(size_of::<ExecutionInfoV2Abi>())
+                                    as *mut ExecutionInfoV2Abi)
+                                .unwrap();
+                            execution_info_ptr.as_mut().block_info = block_info_ptr;
+                            execution_info_ptr.as_mut().tx_info = tx_info_ptr;
+                            execution_info_ptr.as_mut().caller_address =
+                                Felt252Abi(x.caller_address.to_bytes_le());
+                            execution_info_ptr.as_mut().contract_address =
+                                Felt252Abi(x.contract_address.to_bytes_le());
+                            execution_info_ptr.as_mut().entry_point_selector =
+                                Felt252Abi(x.entry_point_selector.to_bytes_le());
+
+                            ManuallyDrop::new(execution_info_ptr)
+                        },
+                    }),
+                },
+                Err(e) => Self::wrap_error(&e),
+            };
+        }
+
         // TODO: change all from_bytes_be to from_bytes_ne when added and undo byte swapping.
 
         extern "C" fn wrap_deploy(
@@ -861,5 +1344,327 @@ pub(crate) mod handler {
                 Err(e) => Self::wrap_error(&e),
             };
         }
+
+        extern "C" fn wrap_secp256k1_new(
+            result_ptr: &mut SyscallResultAbi<OptionAbi<Secp256k1Point>>,
+            ptr: &mut T,
+            gas: &mut u128,
+            x: &U256,
+            y: &U256,
+        ) {
+            let result = ptr.secp256k1_new(x.clone(), y.clone(), gas);
+
+            *result_ptr = match result {
+                Ok(x) => SyscallResultAbi {
+                    ok: ManuallyDrop::new(SyscallResultAbiOk {
+                        tag: 0u8,
+                        payload: ManuallyDrop::new(Self::wrap_option(x)),
+                    }),
+                },
+                Err(e) => Self::wrap_error(&e),
+            };
+        }
+
+        extern "C" fn wrap_secp256k1_add(
+            result_ptr: &mut SyscallResultAbi<Secp256k1Point>,
+            ptr: &mut T,
+            gas: &mut u128,
+            p0: &Secp256k1Point,
+            p1: &Secp256k1Point,
+        ) {
+            let result = ptr.secp256k1_add(p0.clone(), p1.clone(), gas);
+
+            *result_ptr = match result {
+                Ok(x) => SyscallResultAbi {
+                    ok: ManuallyDrop::new(SyscallResultAbiOk {
+                        tag: 0u8,
+                        payload: ManuallyDrop::new(x),
+                    }),
+                },
+                Err(e) => Self::wrap_error(&e),
+            };
+        }
+
+        extern "C" fn wrap_secp256k1_mul(
+            result_ptr: &mut SyscallResultAbi<Secp256k1Point>,
+            ptr: &mut T,
+            gas: &mut u128,
+            p: &Secp256k1Point,
+            m: &U256,
+        ) {
+            let result = ptr.secp256k1_mul(p.clone(), m.clone(), gas);
+
+            *result_ptr = match result {
+                Ok(x) => SyscallResultAbi {
+                    ok: ManuallyDrop::new(SyscallResultAbiOk {
+                        tag: 0u8,
+                        payload: ManuallyDrop::new(x),
+                    }),
+                },
+                Err(e) => Self::wrap_error(&e),
+            };
+        }
+
+        extern "C" fn wrap_secp256k1_get_point_from_x(
+            result_ptr: &mut SyscallResultAbi<OptionAbi<Secp256k1Point>>,
+            ptr: &mut T,
+            gas: &mut u128,
+            x: &U256,
+            y_parity: bool,
+        ) {
+            let result = ptr.secp256k1_get_point_from_x(x.clone(), y_parity, gas);
+
+            *result_ptr = match result {
+                Ok(x) => SyscallResultAbi {
+                    ok: ManuallyDrop::new(SyscallResultAbiOk {
+                        tag: 0u8,
+                        payload: ManuallyDrop::new(Self::wrap_option(x)),
+                    }),
+                },
+                Err(e) => Self::wrap_error(&e),
+            };
+        }
+
+        extern "C" fn wrap_secp256k1_get_xy(
+            result_ptr: &mut SyscallResultAbi<(U256, U256)>,
+            ptr: &mut T,
+            gas: &mut u128,
+            p: &Secp256k1Point,
+        ) {
+            let result = ptr.secp256k1_get_xy(p.clone(), gas);
+
+            *result_ptr = match result {
+                Ok(x) => SyscallResultAbi {
+                    ok: ManuallyDrop::new(SyscallResultAbiOk {
+                        tag: 0u8,
+                        payload: ManuallyDrop::new(x),
+                    }),
+                },
+                Err(e) => Self::wrap_error(&e),
+            };
+        }
+
+        extern "C" fn wrap_secp256r1_new(
+            result_ptr: &mut SyscallResultAbi<OptionAbi<Secp256r1Point>>,
+            ptr: &mut T,
+            gas: &mut u128,
+            x: &U256,
+            y: &U256,
+        ) {
+            let result = ptr.secp256r1_new(x.clone(), y.clone(), gas);
+
+            *result_ptr = match result {
+                Ok(x) => SyscallResultAbi {
+                    ok: ManuallyDrop::new(SyscallResultAbiOk {
+                        tag: 0u8,
+                        payload: ManuallyDrop::new(Self::wrap_option(x)),
+                    }),
+                },
+                Err(e) => Self::wrap_error(&e),
+            };
+        }
+
+        extern "C" fn wrap_secp256r1_add(
+            result_ptr: &mut SyscallResultAbi<Secp256r1Point>,
+            ptr: &mut T,
+            gas: &mut u128,
+            p0: &Secp256r1Point,
+            p1: &Secp256r1Point,
+        ) {
+            let result = ptr.secp256r1_add(p0.clone(), p1.clone(), gas);
+
+            *result_ptr = match result {
+                Ok(x) => SyscallResultAbi {
+                    ok: ManuallyDrop::new(SyscallResultAbiOk {
+                        tag: 0u8,
+                        payload: ManuallyDrop::new(x),
+                    }),
+                },
+                Err(e) => Self::wrap_error(&e),
+            };
+        }
+
+        extern "C" fn wrap_secp256r1_mul(
+            result_ptr: &mut SyscallResultAbi<Secp256r1Point>,
+            ptr: &mut T,
+            gas: &mut u128,
+            p: &Secp256r1Point,
+            m: &U256,
+        ) {
+            let result = ptr.secp256r1_mul(p.clone(), m.clone(), gas);
+
+            *result_ptr = match result {
+                Ok(x) => SyscallResultAbi {
+                    ok: ManuallyDrop::new(SyscallResultAbiOk {
+                        tag: 0u8,
+                        payload: ManuallyDrop::new(x),
+                    }),
+                },
+                Err(e) => Self::wrap_error(&e),
+            };
+        }
+
+        extern "C" fn wrap_secp256r1_get_point_from_x(
+            result_ptr: &mut SyscallResultAbi<OptionAbi<Secp256r1Point>>,
+            ptr: &mut T,
+            gas: &mut u128,
+            x: &U256,
+            y_parity: bool,
+        ) {
+            let result = ptr.secp256r1_get_point_from_x(x.clone(), y_parity, gas);
+
+            *result_ptr = match result {
+                Ok(x) => SyscallResultAbi {
+                    ok: ManuallyDrop::new(SyscallResultAbiOk {
+                        tag: 0u8,
+                        payload: ManuallyDrop::new(Self::wrap_option(x)),
+                    }),
+                },
+                Err(e) => Self::wrap_error(&e),
+            };
+        }
+
+        extern "C" fn wrap_secp256r1_get_xy(
+            result_ptr: &mut SyscallResultAbi<(U256, U256)>,
+            ptr: &mut T,
+            gas: &mut u128,
+            p: &Secp256r1Point,
+        ) {
+            let result = ptr.secp256r1_get_xy(p.clone(), gas);
+
+            *result_ptr = match result {
+                Ok(x) => SyscallResultAbi {
+                    ok: ManuallyDrop::new(SyscallResultAbiOk {
+                        tag: 0u8,
+                        payload: ManuallyDrop::new(x),
+                    }),
+                },
+                Err(e) => Self::wrap_error(&e),
+            };
+        }
+
+        fn felt_from_abi(value: &Felt252Abi) -> Felt {
+            Felt::from_bytes_be(&{
+                let mut data = value.0;
+                data.reverse();
+                data
+            })
+        }
+
+        /// The testing libfuncs have no failure branch, therefore the first error is recorded (to
+        /// be returned by the executor once the invocation finishes) and the program continues
+        /// with `R::default()`, which is `None` for `pop_log` and `()` for the setters.
+        fn unwrap_testing_syscall<R: Default>(name: &'static str, result: SyscallResult<R>) -> R {
+            result.unwrap_or_else(|e| {
+                TESTING_SYSCALL_ERROR.with(|x| {
+                    x.borrow_mut().get_or_insert((name, e));
+                });
+                R::default()
+            })
+        }
+
+        extern "C" fn wrap_pop_log(
+            result_ptr: &mut OptionAbi<(
+                (NonNull<Felt252Abi>, u32, u32),
+                (NonNull<Felt252Abi>, u32, u32),
+            )>,
+            ptr: &mut T,
+            address: &Felt252Abi,
+        ) {
+            let result =
+                Self::unwrap_testing_syscall("pop_log", ptr.pop_log(Self::felt_from_abi(address)));
+
+            *result_ptr = Self::wrap_option(result.map(|(keys, data)| {
+                let keys: Vec<_> = keys.iter().map(|x| Felt252Abi(x.to_bytes_le())).collect();
+                let data: Vec<_> = data.iter().map(|x| Felt252Abi(x.to_bytes_le())).collect();
+                unsafe { (Self::alloc_mlir_array(&keys), Self::alloc_mlir_array(&data)) }
+            }));
+        }
+
+        extern "C" fn wrap_set_account_contract_address(
+            ptr: &mut T,
+            contract_address: &Felt252Abi,
+        ) {
+            Self::unwrap_testing_syscall(
+                "set_account_contract_address",
+                ptr.set_account_contract_address(Self::felt_from_abi(contract_address)),
+            );
+        }
+
+        extern "C" fn wrap_set_block_number(ptr: &mut T, block_number: &u64) {
+            Self::unwrap_testing_syscall("set_block_number", ptr.set_block_number(*block_number));
+        }
+
+        extern "C" fn wrap_set_block_timestamp(ptr: &mut T, block_timestamp: &u64) {
+            Self::unwrap_testing_syscall(
+                "set_block_timestamp",
+                ptr.set_block_timestamp(*block_timestamp),
+            );
+        }
+
+        extern "C" fn wrap_set_caller_address(ptr: &mut T, address: &Felt252Abi) {
+            Self::unwrap_testing_syscall(
+                "set_caller_address",
+                ptr.set_caller_address(Self::felt_from_abi(address)),
+            );
+        }
+
+        extern "C" fn wrap_set_chain_id(ptr: &mut T, chain_id: &Felt252Abi) {
+            Self::unwrap_testing_syscall(
+                "set_chain_id",
+                ptr.set_chain_id(Self::felt_from_abi(chain_id)),
+            );
+        }
+
+        extern "C" fn wrap_set_contract_address(ptr: &mut T, address: &Felt252Abi) {
+            Self::unwrap_testing_syscall(
+                "set_contract_address",
+                ptr.set_contract_address(Self::felt_from_abi(address)),
+            );
+        }
+
+        extern "C" fn wrap_set_max_fee(ptr: &mut T, max_fee: &u128) {
+            Self::unwrap_testing_syscall("set_max_fee", ptr.set_max_fee(*max_fee));
+        }
+
+        extern "C" fn wrap_set_nonce(ptr: &mut T, nonce: &Felt252Abi) {
+            Self::unwrap_testing_syscall("set_nonce", ptr.set_nonce(Self::felt_from_abi(nonce)));
+        }
+
+        extern "C" fn wrap_set_sequencer_address(ptr: &mut T, address: &Felt252Abi) {
+            Self::unwrap_testing_syscall(
+                "set_sequencer_address",
+                ptr.set_sequencer_address(Self::felt_from_abi(address)),
+            );
+        }
+
+        extern "C" fn wrap_set_signature(
+            ptr: &mut T,
+            signature: *const (*const Felt252Abi, u32, u32),
+        ) {
+            let signature: Vec<_> = unsafe {
+                let len = (*signature).1 as usize;
+                std::slice::from_raw_parts((*signature).0, len)
+            }
+            .iter()
+            .map(Self::felt_from_abi)
+            .collect();
+
+            Self::unwrap_testing_syscall("set_signature", ptr.set_signature(&signature));
+        }
+
+        extern "C" fn wrap_set_transaction_hash(ptr: &mut T, transaction_hash: &Felt252Abi) {
+            Self::unwrap_testing_syscall(
+                "set_transaction_hash",
+                ptr.set_transaction_hash(Self::felt_from_abi(transaction_hash)),
+            );
+        }
+
+        extern "C" fn wrap_set_version(ptr: &mut T, version: &Felt252Abi) {
+            Self::unwrap_testing_syscall(
+                "set_version",
+                ptr.set_version(Self::felt_from_abi(version)),
+            );
+        }
     }
 }
@@ -0,0 +1,478 @@
+//! # In-memory syscall handler
+//!
+//! A [`StarkNetSyscallHandler`] which keeps the whole Starknet state in memory, meant for tests and
+//! local development. Its behaviour mimics the one from the Cairo test runner.
+//!
+//! Since it doesn't have access to other contracts' code, calls to other contracts (including
+//! library calls and the constructors of deployed contracts) are not supported. The secp256k1 and
+//! secp256r1 syscalls aren't supported either.
+
+use super::{
+    unsupported_syscall, BlockInfo, ExecutionInfo, ExecutionInfoV2, StarkNetSyscallHandler,
+    SyscallResult, TxInfo, U256,
+};
+use starknet_crypto::{pedersen_hash, FieldElement};
+use starknet_types_core::felt::Felt;
+use std::collections::{HashMap, VecDeque};
+
+/// 2 ** 251 - 256
+const ADDR_BOUND: FieldElement = FieldElement::from_mont([
+    18446743986131443745,
+    160989183,
+    18446744073709255680,
+    576459263475590224,
+]);
+
+/// Cairo string of "STARKNET_CONTRACT_ADDRESS"
+const CONTRACT_ADDRESS_PREFIX: FieldElement = FieldElement::from_mont([
+    3829237882463328880,
+    17289941567720117366,
+    8635008616843941496,
+    533439743893157637,
+]);
+
+/// The events and messages to L1 emitted by a contract.
+#[derive(Debug, Clone, Default)]
+pub struct ContractLogs {
+    /// Emitted events, as their keys and data.
+    pub events: VecDeque<(Vec<Felt>, Vec<Felt>)>,
+    /// Messages sent to L1, as their destination address and payload.
+    pub l2_to_l1_messages: VecDeque<(Felt, Vec<Felt>)>,
+}
+
+/// A syscall handler which keeps the Starknet state in memory.
+///
+/// The execution info is used both for the current contract's address (which determines the
+/// storage and logs used) and the values returned by the `get_execution_info` syscalls. It can be
+/// modified directly or through the testing syscalls.
+#[derive(Debug, Clone, Default)]
+pub struct InMemorySyscallHandler {
+    /// The storage of every contract, by contract address and storage key.
+    pub storage: HashMap<Felt, HashMap<Felt, Felt>>,
+    /// The class hash of every deployed contract, by contract address.
+    pub deployed_contracts: HashMap<Felt, Felt>,
+    /// The logs of every contract, by contract address.
+    pub logs: HashMap<Felt, ContractLogs>,
+    /// The block hashes returned by the `get_block_hash` syscall, by block number.
+    pub block_hashes: HashMap<u64, Felt>,
+    pub execution_info: ExecutionInfoV2,
+}
+
+impl InMemorySyscallHandler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the address of the contract being executed.
+    pub fn contract_address(&self) -> Felt {
+        self.execution_info.contract_address
+    }
+
+    fn contract_logs(&mut self) -> &mut ContractLogs {
+        let contract_address = self.contract_address();
+        self.logs.entry(contract_address).or_default()
+    }
+}
+
+impl StarkNetSyscallHandler for InMemorySyscallHandler {
+    fn get_block_hash(&mut self, block_number: u64, _gas: &mut u128) -> SyscallResult<Felt> {
+        match self.block_hashes.get(&block_number) {
+            Some(block_hash) => Ok(*block_hash),
+            None => fail(b"Block number out of range"),
+        }
+    }
+
+    fn get_execution_info(&mut self, _gas: &mut u128) -> SyscallResult<ExecutionInfo> {
+        let execution_info = &self.execution_info;
+        let tx_info = &execution_info.tx_info;
+
+        Ok(ExecutionInfo {
+            block_info: execution_info.block_info.clone(),
+            tx_info: TxInfo {
+                version: tx_info.version,
+                account_contract_address: tx_info.account_contract_address,
+                max_fee: tx_info.max_fee,
+                signature: tx_info.signature.clone(),
+                transaction_hash: tx_info.transaction_hash,
+                chain_id: tx_info.chain_id,
+                nonce: tx_info.nonce,
+            },
+            caller_address: execution_info.caller_address,
+            contract_address: execution_info.contract_address,
+            entry_point_selector: execution_info.entry_point_selector,
+        })
+    }
+
+    fn get_execution_info_v2(&mut self, _gas: &mut u128) -> SyscallResult<ExecutionInfoV2> {
+        Ok(self.execution_info.clone())
+    }
+
+    fn deploy(
+        &mut self,
+        class_hash: Felt,
+        contract_address_salt: Felt,
+        calldata: &[Felt],
+        deploy_from_zero: bool,
+        _gas: &mut u128,
+    ) -> SyscallResult<(Felt, Vec<Felt>)> {
+        // Constructors can't be executed, therefore only contracts without constructor arguments
+        // are deployed.
+        if !calldata.is_empty() {
+            return fail(b"INVALID_CALLDATA_LEN");
+        }
+
+        let deployer_address = if deploy_from_zero {
+            Felt::ZERO
+        } else {
+            self.contract_address()
+        };
+        let contract_address = calculate_contract_address(
+            contract_address_salt,
+            class_hash,
+            calldata,
+            deployer_address,
+        );
+
+        self.deployed_contracts.insert(contract_address, class_hash);
+        Ok((contract_address, Vec::new()))
+    }
+
+    fn replace_class(&mut self, class_hash: Felt, _gas: &mut u128) -> SyscallResult<()> {
+        let contract_address = self.contract_address();
+        self.deployed_contracts.insert(contract_address, class_hash);
+        Ok(())
+    }
+
+    fn library_call(
+        &mut self,
+        _class_hash: Felt,
+        _function_selector: Felt,
+        _calldata: &[Felt],
+        _gas: &mut u128,
+    ) -> SyscallResult<Vec<Felt>> {
+        unsupported_syscall()
+    }
+
+    fn call_contract(
+        &mut self,
+        _address: Felt,
+        _entry_point_selector: Felt,
+        _calldata: &[Felt],
+        _gas: &mut u128,
+    ) -> SyscallResult<Vec<Felt>> {
+        unsupported_syscall()
+    }
+
+    fn storage_read(
+        &mut self,
+        address_domain: u32,
+        address: Felt,
+        _gas: &mut u128,
+    ) -> SyscallResult<Felt> {
+        if address_domain != 0 {
+            return fail(b"Unsupported address domain");
+        }
+
+        Ok(self
+            .storage
+            .get(&self.contract_address())
+            .and_then(|storage| storage.get(&address))
+            .copied()
+            .unwrap_or(Felt::ZERO))
+    }
+
+    fn storage_write(
+        &mut self,
+        address_domain: u32,
+        address: Felt,
+        value: Felt,
+        _gas: &mut u128,
+    ) -> SyscallResult<()> {
+        if address_domain != 0 {
+            return fail(b"Unsupported address domain");
+        }
+
+        let contract_address = self.contract_address();
+        self.storage
+            .entry(contract_address)
+            .or_default()
+            .insert(address, value);
+        Ok(())
+    }
+
+    fn emit_event(&mut self, keys: &[Felt], data: &[Felt], _gas: &mut u128) -> SyscallResult<()> {
+        self.contract_logs()
+            .events
+            .push_back((keys.to_vec(), data.to_vec()));
+        Ok(())
+    }
+
+    fn send_message_to_l1(
+        &mut self,
+        to_address: Felt,
+        payload: &[Felt],
+        _gas: &mut u128,
+    ) -> SyscallResult<()> {
+        self.contract_logs()
+            .l2_to_l1_messages
+            .push_back((to_address, payload.to_vec()));
+        Ok(())
+    }
+
+    fn keccak(&mut self, input: &[u64], _gas: &mut u128) -> SyscallResult<U256> {
+        if input.len() % 17 != 0 {
+            return fail(b"Invalid keccak input size");
+        }
+
+        let mut state = [0u64; 25];
+        for chunk in input.chunks(17) {
+            for (lane, value) in state.iter_mut().zip(chunk) {
+                *lane ^= value;
+            }
+            keccak::f1600(&mut state);
+        }
+
+        // The result is the little-endian `u256` formed by the first four lanes.
+        let mut result = [0; 32];
+        for (bytes, lane) in result.chunks_mut(8).zip(&state) {
+            bytes.copy_from_slice(&lane.to_le_bytes());
+        }
+
+        Ok(U256(result))
+    }
+
+    fn pop_log(&mut self, address: Felt) -> SyscallResult<Option<(Vec<Felt>, Vec<Felt>)>> {
+        Ok(self
+            .logs
+            .get_mut(&address)
+            .and_then(|logs| logs.events.pop_front()))
+    }
+
+    fn set_account_contract_address(&mut self, contract_address: Felt) -> SyscallResult<()> {
+        self.execution_info.tx_info.account_contract_address = contract_address;
+        Ok(())
+    }
+
+    fn set_block_number(&mut self, block_number: u64) -> SyscallResult<()> {
+        self.execution_info.block_info.block_number = block_number;
+        Ok(())
+    }
+
+    fn set_block_timestamp(&mut self, block_timestamp: u64) -> SyscallResult<()> {
+        self.execution_info.block_info.block_timestamp = block_timestamp;
+        Ok(())
+    }
+
+    fn set_caller_address(&mut self, address: Felt) -> SyscallResult<()> {
+        self.execution_info.caller_address = address;
+        Ok(())
+    }
+
+    fn set_chain_id(&mut self, chain_id: Felt) -> SyscallResult<()> {
+        self.execution_info.tx_info.chain_id = chain_id;
+        Ok(())
+    }
+
+    fn set_contract_address(&mut self, address: Felt) -> SyscallResult<()> {
+        self.execution_info.contract_address = address;
+        Ok(())
+    }
+
+    fn set_max_fee(&mut self, max_fee: u128) -> SyscallResult<()> {
+        self.execution_info.tx_info.max_fee = max_fee;
+        Ok(())
+    }
+
+    fn set_nonce(&mut self, nonce: Felt) -> SyscallResult<()> {
+        self.execution_info.tx_info.nonce = nonce;
+        Ok(())
+    }
+
+    fn set_sequencer_address(&mut self, address: Felt) -> SyscallResult<()> {
+        self.execution_info.block_info.sequencer_address = address;
+        Ok(())
+    }
+
+    fn set_signature(&mut self, signature: &[Felt]) -> SyscallResult<()> {
+        self.execution_info.tx_info.signature = signature.to_vec();
+        Ok(())
+    }
+
+    fn set_transaction_hash(&mut self, transaction_hash: Felt) -> SyscallResult<()> {
+        self.execution_info.tx_info.transaction_hash = transaction_hash;
+        Ok(())
+    }
+
+    fn set_version(&mut self, version: Felt) -> SyscallResult<()> {
+        self.execution_info.tx_info.version = version;
+        Ok(())
+    }
+}
+
+/// Return a syscall failure with the given short string as its reason.
+fn fail<T>(reason: &[u8]) -> SyscallResult<T> {
+    Err(vec![Felt::from_bytes_be_slice(reason)])
+}
+
+fn felt_to_field_element(value: Felt) -> FieldElement {
+    FieldElement::from_bytes_be(&value.to_bytes_be()).unwrap()
+}
+
+/// Computes the Pedersen hash of an array of elements, as defined in
+/// <https://docs.starknet.io/documentation/architecture_and_concepts/Hashing/hash-functions/#array_hashing>.
+fn pedersen_hash_array(values: &[FieldElement]) -> FieldElement {
+    let hash = values.iter().fold(FieldElement::ZERO, |hash, value| {
+        pedersen_hash(&hash, value)
+    });
+    pedersen_hash(&hash, &FieldElement::from(values.len() as u64))
+}
+
+/// Calculate the address of a contract, as defined in
+/// <https://docs.starknet.io/documentation/architecture_and_concepts/Smart_Contracts/contract-address/>.
+pub fn calculate_contract_address(
+    salt: Felt,
+    class_hash: Felt,
+    constructor_calldata: &[Felt],
+    deployer_address: Felt,
+) -> Felt {
+    let constructor_calldata_hash = pedersen_hash_array(
+        &constructor_calldata
+            .iter()
+            .copied()
+            .map(felt_to_field_element)
+            .collect::<Vec<_>>(),
+    );
+    let address = pedersen_hash_array(&[
+        CONTRACT_ADDRESS_PREFIX,
+        felt_to_field_element(deployer_address),
+        felt_to_field_element(salt),
+        felt_to_field_element(class_hash),
+        constructor_calldata_hash,
+    ]) % ADDR_BOUND;
+
+    Felt::from_bytes_be_slice(&address.to_bytes_be())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn storage_per_contract() {
+        let mut handler = InMemorySyscallHandler::new();
+        let mut gas = 0;
+
+        handler.set_contract_address(Felt::from(1)).unwrap();
+        handler
+            .storage_write(0, Felt::from(10), Felt::from(100), &mut gas)
+            .unwrap();
+        assert_eq!(
+            handler.storage_read(0, Felt::from(10), &mut gas).unwrap(),
+            Felt::from(100)
+        );
+        assert!(handler.storage_read(1, Felt::from(10), &mut gas).is_err());
+
+        handler.set_contract_address(Felt::from(2)).unwrap();
+        assert_eq!(
+            handler.storage_read(0, Felt::from(10), &mut gas).unwrap(),
+            Felt::ZERO
+        );
+    }
+
+    #[test]
+    fn logs() {
+        let mut handler = InMemorySyscallHandler::new();
+        let mut gas = 0;
+
+        handler.set_contract_address(Felt::from(1)).unwrap();
+        handler
+            .emit_event(&[Felt::from(1)], &[Felt::from(2)], &mut gas)
+            .unwrap();
+        handler
+            .emit_event(&[Felt::from(3)], &[Felt::from(4)], &mut gas)
+            .unwrap();
+        handler
+            .send_message_to_l1(Felt::from(5), &[Felt::from(6)], &mut gas)
+            .unwrap();
+
+        assert_eq!(
+            handler.pop_log(Felt::from(1)).unwrap(),
+            Some((vec![Felt::from(1)], vec![Felt::from(2)]))
+        );
+        assert_eq!(handler.pop_log(Felt::from(2)).unwrap(), None);
+
+        let logs = &handler.logs[&Felt::from(1)];
+        assert_eq!(logs.events, [(vec![Felt::from(3)], vec![Felt::from(4)])]);
+        assert_eq!(
+            logs.l2_to_l1_messages,
+            [(Felt::from(5), vec![Felt::from(6)])]
+        );
+    }
+
+    #[test]
+    fn keccak_empty_input() {
+        let mut handler = InMemorySyscallHandler::new();
+
+        // The keccak of an empty input, padded into a single block.
+        let mut input = [0; 17];
+        input[0] = 1;
+        input[16] = 0x8000000000000000;
+
+        let result = handler.keccak(&input, &mut 0).unwrap();
+        assert_eq!(
+            hex::encode(result.0),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+
+        assert!(handler.keccak(&[0; 16], &mut 0).is_err());
+    }
+
+    #[test]
+    fn execution_info() {
+        let mut handler = InMemorySyscallHandler::new();
+
+        handler.set_block_number(10).unwrap();
+        handler.set_caller_address(Felt::from(20)).unwrap();
+        handler.set_signature(&[Felt::from(30)]).unwrap();
+
+        let execution_info = handler.get_execution_info(&mut 0).unwrap();
+        assert_eq!(execution_info.block_info.block_number, 10);
+        assert_eq!(execution_info.caller_address, Felt::from(20));
+        assert_eq!(execution_info.tx_info.signature, [Felt::from(30)]);
+    }
+
+    #[test]
+    fn deploy() {
+        let mut handler = InMemorySyscallHandler::new();
+        handler.set_contract_address(Felt::from(1)).unwrap();
+
+        let (address, _) = handler
+            .deploy(Felt::from(2), Felt::from(3), &[], false, &mut 0)
+            .unwrap();
+        assert_eq!(
+            address,
+            calculate_contract_address(Felt::from(3), Felt::from(2), &[], Felt::from(1))
+        );
+        assert_eq!(handler.deployed_contracts[&address], Felt::from(2));
+
+        assert!(handler
+            .deploy(
+                Felt::from(2),
+                Felt::from(3),
+                &[Felt::from(4)],
+                false,
+                &mut 0
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn unsupported_secp256() {
+        let mut handler = InMemorySyscallHandler::new();
+
+        let error = handler
+            .secp256k1_new(U256([0; 32]), U256([0; 32]), &mut 0)
+            .unwrap_err();
+        assert_eq!(Err(error), unsupported_syscall::<()>());
+    }
+}
@@ -0,0 +1,377 @@
+//! # Support checks
+//!
+//! Some Sierra types and libfuncs can't be compiled yet. Instead of finding out halfway through
+//! the compilation, [`check_support`] walks every type and libfunc declaration of a program, and the
+//! function bodies for the constructs which depend on how a libfunc is used, and reports the ones
+//! that aren't supported, so that the program can be executed somewhere else (for example, in the
+//! Cairo VM).
+
+use crate::{
+    compiler::foreach_statement_in_function, libfuncs::LibfuncBuilder, types::TypeBuilder,
+};
+use cairo_lang_sierra::{
+    extensions::core::{CoreConcreteLibfunc, CoreLibfunc, CoreType, CoreTypeConcrete},
+    ids::{ConcreteLibfuncId, ConcreteTypeId, VarId},
+    program::{Function, Program, Statement},
+    program_registry::{ProgramRegistry, ProgramRegistryError},
+};
+use std::{collections::HashSet, fmt};
+
+/// A type or libfunc declaration which can't be compiled.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UnsupportedItem {
+    Type {
+        id: ConcreteTypeId,
+        reason: &'static str,
+    },
+    Libfunc {
+        id: ConcreteLibfuncId,
+        reason: &'static str,
+    },
+}
+
+impl UnsupportedItem {
+    /// The Sierra id of the declaration.
+    pub fn id(&self) -> u64 {
+        match self {
+            Self::Type { id, .. } => id.id,
+            Self::Libfunc { id, .. } => id.id,
+        }
+    }
+
+    /// The debug name of the declaration, if the program has them.
+    pub fn debug_name(&self) -> Option<&str> {
+        match self {
+            Self::Type { id, .. } => id.debug_name.as_deref(),
+            Self::Libfunc { id, .. } => id.debug_name.as_deref(),
+        }
+    }
+
+    /// Why the declaration isn't supported.
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Self::Type { reason, .. } | Self::Libfunc { reason, .. } => reason,
+        }
+    }
+}
+
+impl fmt::Display for UnsupportedItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            Self::Type { .. } => "type",
+            Self::Libfunc { .. } => "libfunc",
+        };
+
+        write!(f, "{kind} #{}", self.id())?;
+        if let Some(debug_name) = self.debug_name() {
+            write!(f, " ({debug_name})")?;
+        }
+        write!(f, ": {}", self.reason())
+    }
+}
+
+/// Return every type and libfunc declaration of the program which can't be compiled.
+///
+/// Libfuncs which are only unsupported in some invocations, like the self-recursive calls which
+/// can't be compiled as loops, are also reported. Whether the builtin counters are enabled
+/// changes the functions' signatures, and therefore which of those invocations are supported.
+///
+/// An empty list means that the program is supported.
+pub fn check_support(
+    program: &Program,
+    builtin_counters: bool,
+) -> Result<Vec<UnsupportedItem>, Box<ProgramRegistryError>> {
+    let registry = ProgramRegistry::<CoreType, CoreLibfunc>::new(program)?;
+
+    let mut items = Vec::new();
+    for decl in &program.type_declarations {
+        if let Some(reason) = check_type(&registry, registry.get_type(&decl.id)?) {
+            items.push(UnsupportedItem::Type {
+                id: decl.id.clone(),
+                reason,
+            });
+        }
+    }
+    for decl in &program.libfunc_declarations {
+        if let Some(reason) = check_libfunc(&registry, registry.get_libfunc(&decl.id)?) {
+            items.push(UnsupportedItem::Libfunc {
+                id: decl.id.clone(),
+                reason,
+            });
+        }
+    }
+    for function in &program.funcs {
+        for id in check_tail_recursion(program, &registry, function, builtin_counters)? {
+            let item = UnsupportedItem::Libfunc {
+                id,
+                reason: "tail recursion in functions without a return pointer is not supported",
+            };
+            if !items.contains(&item) {
+                items.push(item);
+            }
+        }
+    }
+
+    Ok(items)
+}
+
+fn check_type(
+    _registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    info: &CoreTypeConcrete,
+) -> Option<&'static str> {
+    match info {
+        CoreTypeConcrete::Enum(info) if info.variants.is_empty() => {
+            Some("enums without variants are not supported")
+        }
+        _ => None,
+    }
+}
+
+fn check_libfunc(
+    registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    info: &CoreConcreteLibfunc,
+) -> Option<&'static str> {
+    match info {
+        CoreConcreteLibfunc::SnapshotTake(info) => {
+            // Arrays are cloned when taking a snapshot, but cloning their elements is not
+            // implemented yet.
+            match registry.get_type(&info.signature.param_signatures[0].ty) {
+                Ok(CoreTypeConcrete::Array(info))
+                    if matches!(registry.get_type(&info.ty), Ok(CoreTypeConcrete::Array(_))) =>
+                {
+                    Some("taking a snapshot of an array of arrays is not supported")
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Return the self-recursive calls of a function which the compiler can't implement.
+///
+/// A call to the function itself with no other live variables is compiled as a loop, which is only
+/// implemented when the function returns either multiple values or a single memory-allocated one.
+fn check_tail_recursion(
+    program: &Program,
+    registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    function: &Function,
+    builtin_counters: bool,
+) -> Result<Vec<ConcreteLibfuncId>, Box<ProgramRegistryError>> {
+    let is_builtin = <CoreTypeConcrete as TypeBuilder<CoreType, CoreLibfunc>>::is_builtin;
+    let is_function_call =
+        <CoreConcreteLibfunc as LibfuncBuilder<CoreType, CoreLibfunc>>::is_function_call;
+
+    // The zero-sized builtins aren't returned, the same as when compiling the function.
+    let mut return_types = Vec::new();
+    for type_id in &function.signature.ret_types {
+        let type_info = registry.get_type(type_id)?;
+        if !(is_builtin(type_info) && type_info.is_zst_with_counters(registry, builtin_counters)) {
+            return_types.push(type_info);
+        }
+    }
+    match return_types.as_slice() {
+        [] => {}
+        [type_info] if !type_info.is_memory_allocated(registry) => {}
+        _ => return Ok(Vec::new()),
+    }
+
+    // Follow the live variables like the compiler does, remembering whether a tail-recursive call
+    // has been found on the way to every statement.
+    let mut calls = Vec::new();
+    foreach_statement_in_function::<_, Box<ProgramRegistryError>>(
+        &program.statements,
+        function.entry_point,
+        (
+            function
+                .params
+                .iter()
+                .map(|param| param.id.clone())
+                .collect::<HashSet<VarId>>(),
+            None::<&ConcreteLibfuncId>,
+        ),
+        |statement_idx, (mut state, tail_call)| {
+            Ok(match &program.statements[statement_idx.0] {
+                Statement::Invocation(invocation) => {
+                    for var_id in &invocation.args {
+                        state.remove(var_id);
+                    }
+
+                    let tail_call =
+                        match is_function_call(registry.get_libfunc(&invocation.libfunc_id)?) {
+                            Some(target) if target == &function.id && state.is_empty() => {
+                                Some(&invocation.libfunc_id)
+                            }
+                            _ => tail_call,
+                        };
+
+                    invocation
+                        .branches
+                        .iter()
+                        .map(|branch| {
+                            let mut state = state.clone();
+                            state.extend(branch.results.iter().cloned());
+                            (state, tail_call)
+                        })
+                        .collect()
+                }
+                Statement::Return(_) => {
+                    if let Some(libfunc_id) = tail_call {
+                        if !calls.contains(libfunc_id) {
+                            calls.push(libfunc_id.clone());
+                        }
+                    }
+
+                    Vec::new()
+                }
+            })
+        },
+    )?;
+
+    Ok(calls)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        context::NativeContext,
+        error::{compile::ErrorImpl, CoreLibfuncBuilderError},
+        utils::test::load_cairo,
+        OptLevel,
+    };
+    use cairo_lang_sierra::ProgramParser;
+
+    #[test]
+    fn supported_program() {
+        let (_, program) = load_cairo! {
+            fn run_test(x: Array<felt252>) -> (usize, Array<felt252>) {
+                (x.len(), x)
+            }
+        };
+
+        assert_eq!(check_support(&program, false).unwrap(), []);
+    }
+
+    #[test]
+    fn snapshot_of_array_of_arrays() {
+        let (_, program) = load_cairo! {
+            fn run_test(x: Array<Array<felt252>>) -> (usize, Array<Array<felt252>>) {
+                (x.len(), x)
+            }
+        };
+
+        let items = check_support(&program, false).unwrap();
+        assert_eq!(items.len(), 1);
+        assert!(matches!(items[0], UnsupportedItem::Libfunc { .. }));
+        assert_eq!(
+            items[0].debug_name(),
+            Some("snapshot_take<Array<Array<felt252>>>")
+        );
+    }
+
+    #[test]
+    fn compile_snapshot_of_array_of_arrays() {
+        let (_, program) = load_cairo! {
+            fn run_test(x: Array<Array<felt252>>) -> (usize, Array<Array<felt252>>) {
+                (x.len(), x)
+            }
+        };
+
+        let error = NativeContext::new()
+            .compile(&program, OptLevel::None)
+            .unwrap_err();
+        match &error.source {
+            ErrorImpl::LibfuncBuilderError {
+                libfunc_id,
+                error: CoreLibfuncBuilderError { source, .. },
+                ..
+            } => {
+                assert_eq!(
+                    libfunc_id.debug_name.as_deref(),
+                    Some("snapshot_take<Array<Array<felt252>>>")
+                );
+                assert!(matches!(
+                    source,
+                    crate::error::libfuncs::ErrorImpl::Unsupported(_)
+                ));
+            }
+            _ => panic!("unexpected error: {error}"),
+        }
+    }
+
+    #[test]
+    fn tail_recursion_without_return_ptr() {
+        // A self-recursive call with no other live variables, in a function which returns nothing.
+        let program = ProgramParser::new()
+            .parse(
+                r#"
+                    type felt252 = felt252;
+                    type NonZero<felt252> = NonZero<felt252>;
+
+                    libfunc felt252_is_zero = felt252_is_zero;
+                    libfunc branch_align = branch_align;
+                    libfunc unwrap_non_zero<felt252> = unwrap_non_zero<felt252>;
+                    libfunc felt252_const<1> = felt252_const<1>;
+                    libfunc felt252_sub = felt252_sub;
+                    libfunc store_temp<felt252> = store_temp<felt252>;
+                    libfunc function_call<user@test::run_test> = function_call<user@test::run_test>;
+
+                    felt252_is_zero([0]) { fallthrough() 3([1]) };
+                    branch_align() -> ();
+                    return();
+                    branch_align() -> ();
+                    unwrap_non_zero<felt252>([1]) -> ([2]);
+                    felt252_const<1>() -> ([3]);
+                    felt252_sub([2], [3]) -> ([4]);
+                    store_temp<felt252>([4]) -> ([5]);
+                    function_call<user@test::run_test>([5]) -> ();
+                    return();
+
+                    test::run_test@0([0]: felt252) -> ();
+                "#,
+            )
+            .unwrap();
+
+        let items = check_support(&program, false).unwrap();
+        assert_eq!(items.len(), 1);
+        assert!(matches!(items[0], UnsupportedItem::Libfunc { .. }));
+        assert_eq!(
+            items[0].debug_name(),
+            Some("function_call<user@test::run_test>")
+        );
+
+        // The compiler must reject the same call.
+        let error = NativeContext::new()
+            .compile(&program, OptLevel::None)
+            .unwrap_err();
+        match &error.source {
+            ErrorImpl::UnsupportedTailRecursion { libfunc_id, .. } => {
+                assert_eq!(libfunc_id.id, items[0].id());
+            }
+            _ => panic!("unexpected error: {error}"),
+        }
+    }
+
+    #[test]
+    fn tail_recursion_with_return_values() {
+        // The gas builtin is returned along with the result, so the recursion is supported.
+        let (_, program) = load_cairo! {
+            fn run_test(n: felt252) -> felt252 {
+                fib(0, 1, n)
+            }
+
+            fn fib(a: felt252, b: felt252, n: felt252) -> felt252 {
+                match n {
+                    0 => a,
+                    _ => fib(b, a + b, n - 1),
+                }
+            }
+        };
+
+        assert_eq!(check_support(&program, false).unwrap(), []);
+        NativeContext::new()
+            .compile(&program, OptLevel::None)
+            .unwrap();
+    }
+}
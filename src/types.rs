@@ -85,6 +85,9 @@ where
 
     /// Return whether the type is a builtin.
     fn is_builtin(&self) -> bool;
+    /// Return whether the type is a builtin whose usage is tracked by the builtin counters (see
+    /// [BuiltinCountersMeta](crate::metadata::builtin_counters::BuiltinCountersMeta)).
+    fn is_counted_builtin(&self) -> bool;
     /// Return whether the type requires a return pointer when returning.
     fn is_complex(&self, registry: &ProgramRegistry<TType, TLibfunc>) -> bool;
     /// Return whether the Sierra type resolves to a zero-sized type.
@@ -99,6 +102,30 @@ where
     /// a function invocation argument or return value.
     fn is_memory_allocated(&self, registry: &ProgramRegistry<TType, TLibfunc>) -> bool;
 
+    /// Same as [is_zst](Self::is_zst), but taking into account that the counted builtins are `i64`
+    /// counters instead of zero-sized types when the builtin counters are enabled.
+    fn is_zst_with_counters(
+        &self,
+        registry: &ProgramRegistry<TType, TLibfunc>,
+        builtin_counters: bool,
+    ) -> bool {
+        !(builtin_counters && self.is_counted_builtin()) && self.is_zst(registry)
+    }
+
+    /// Same as [layout](Self::layout), but taking into account that the counted builtins are `i64`
+    /// counters instead of zero-sized types when the builtin counters are enabled.
+    fn layout_with_counters(
+        &self,
+        registry: &ProgramRegistry<TType, TLibfunc>,
+        builtin_counters: bool,
+    ) -> Result<Layout, Self::Error> {
+        if builtin_counters && self.is_counted_builtin() {
+            Ok(get_integer_layout(64))
+        } else {
+            self.layout(registry)
+        }
+    }
+
     /// If the type is an integer (felt not included) type, return its width in bits.
     ///
     /// TODO: How is it used?
@@ -275,7 +302,14 @@ where
                 metadata,
                 WithSelf::new(self_ty, info),
             ),
-            Self::Span(_) => todo!("implement span type"),
+            // A span shares the same representation as an array.
+            Self::Span(info) => self::array::build(
+                context,
+                module,
+                registry,
+                metadata,
+                WithSelf::new(self_ty, info),
+            ),
             Self::SquashedFelt252Dict(info) => self::squashed_felt252_dict::build(
                 context,
                 module,
@@ -397,7 +431,19 @@ where
                 | CoreTypeConcrete::RangeCheck(_)
                 | CoreTypeConcrete::Pedersen(_)
                 | CoreTypeConcrete::Poseidon(_)
-                | CoreTypeConcrete::StarkNet(_)
+                | CoreTypeConcrete::StarkNet(StarkNetTypeConcrete::System(_))
+                | CoreTypeConcrete::SegmentArena(_)
+        )
+    }
+
+    fn is_counted_builtin(&self) -> bool {
+        matches!(
+            self,
+            CoreTypeConcrete::Bitwise(_)
+                | CoreTypeConcrete::EcOp(_)
+                | CoreTypeConcrete::RangeCheck(_)
+                | CoreTypeConcrete::Pedersen(_)
+                | CoreTypeConcrete::Poseidon(_)
                 | CoreTypeConcrete::SegmentArena(_)
         )
     }
@@ -431,12 +477,13 @@ where
             | CoreTypeConcrete::Felt252Dict(_)
             | CoreTypeConcrete::SquashedFelt252Dict(_) => false,
 
-            CoreTypeConcrete::Array(_) => true,
+            CoreTypeConcrete::Array(_) | CoreTypeConcrete::Span(_) => true,
             CoreTypeConcrete::EcPoint(_) => true,
             CoreTypeConcrete::EcState(_) => true,
             CoreTypeConcrete::Felt252DictEntry(_) => true,
 
             CoreTypeConcrete::Felt252(_)
+            | CoreTypeConcrete::Bytes31(_)
             | CoreTypeConcrete::StarkNet(
                 StarkNetTypeConcrete::ClassHash(_)
                 | StarkNetTypeConcrete::ContractAddress(_)
@@ -459,9 +506,7 @@ where
             CoreTypeConcrete::Enum(_) => !self.is_zst(registry),
             CoreTypeConcrete::Struct(_) => true,
 
-            CoreTypeConcrete::Span(_) => todo!(),
-            CoreTypeConcrete::StarkNet(StarkNetTypeConcrete::Secp256Point(_)) => todo!(),
-            CoreTypeConcrete::Bytes31(_) => todo!(),
+            CoreTypeConcrete::StarkNet(StarkNetTypeConcrete::Secp256Point(_)) => true,
         }
     }
 
@@ -477,6 +522,7 @@ where
             | CoreTypeConcrete::Uint128MulGuarantee(_) => true,
 
             CoreTypeConcrete::Array(_)
+            | CoreTypeConcrete::Span(_)
             | CoreTypeConcrete::Box(_)
             | CoreTypeConcrete::Bytes31(_)
             | CoreTypeConcrete::EcPoint(_)
@@ -518,14 +564,12 @@ where
                 .members
                 .iter()
                 .all(|id| registry.get_type(id).unwrap().is_zst(registry)),
-
-            CoreTypeConcrete::Span(_) => todo!(),
         }
     }
 
     fn layout(&self, registry: &ProgramRegistry<TType, TLibfunc>) -> Result<Layout, Self::Error> {
         Ok(match self {
-            CoreTypeConcrete::Array(_) => {
+            CoreTypeConcrete::Array(_) | CoreTypeConcrete::Span(_) => {
                 Layout::new::<*mut ()>()
                     .extend(get_integer_layout(32))?
                     .0
@@ -590,14 +634,15 @@ where
             CoreTypeConcrete::SquashedFelt252Dict(_) => Layout::new::<*mut std::ffi::c_void>(), // ptr
             CoreTypeConcrete::Pedersen(_) => Layout::new::<()>(),
             CoreTypeConcrete::Poseidon(_) => Layout::new::<()>(),
-            CoreTypeConcrete::Span(_) => todo!(),
             CoreTypeConcrete::StarkNet(info) => match info {
                 StarkNetTypeConcrete::ClassHash(_) => get_integer_layout(252),
                 StarkNetTypeConcrete::ContractAddress(_) => get_integer_layout(252),
                 StarkNetTypeConcrete::StorageBaseAddress(_) => get_integer_layout(252),
                 StarkNetTypeConcrete::StorageAddress(_) => get_integer_layout(252),
                 StarkNetTypeConcrete::System(_) => Layout::new::<*mut ()>(),
-                StarkNetTypeConcrete::Secp256Point(_) => todo!("implement Secp256Point type"),
+                StarkNetTypeConcrete::Secp256Point(_) => {
+                    layout_repeat(&get_integer_layout(128), 4)?.0
+                }
             },
             CoreTypeConcrete::SegmentArena(_) => Layout::new::<()>(),
             CoreTypeConcrete::Snapshot(info) => registry.get_type(&info.ty)?.layout(registry)?,
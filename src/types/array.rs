@@ -194,7 +194,13 @@ where
     };
 
     match elem_snapshot_take {
-        Some(_) => todo!(),
+        Some(_) => {
+            return Err(libfuncs::ErrorImpl::Unsupported(format!(
+                "taking a snapshot of an array of `{}` is not supported",
+                info.ty
+            ))
+            .into())
+        }
         None => {
             let is_volatile = entry
                 .append_operation(arith::constant(
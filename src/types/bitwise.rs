@@ -1,12 +1,13 @@
 //! # Bitwise type
 //!
 //! The bitwise type is used in the VM for computing bitwise operations. Since this can be done
-//! natively in MLIR, this type is effectively an unit type.
+//! natively in MLIR, this type is effectively an unit type, unless the builtin counters are enabled
+//! (see [`BuiltinCountersMeta`]), in which case it's a counter of the builtin instances used.
 
 use super::{TypeBuilder, WithSelf};
 use crate::{
     error::types::{Error, Result},
-    metadata::MetadataStorage,
+    metadata::{builtin_counters::BuiltinCountersMeta, MetadataStorage},
 };
 use cairo_lang_sierra::{
     extensions::{types::InfoOnlyConcreteType, GenericLibfunc, GenericType},
@@ -25,7 +26,7 @@ pub fn build<'ctx, TType, TLibfunc>(
     context: &'ctx Context,
     _module: &Module<'ctx>,
     _registry: &ProgramRegistry<TType, TLibfunc>,
-    _metadata: &mut MetadataStorage,
+    metadata: &mut MetadataStorage,
     _info: WithSelf<InfoOnlyConcreteType>,
 ) -> Result<Type<'ctx>>
 where
@@ -33,5 +34,9 @@ where
     TLibfunc: GenericLibfunc,
     <TType as GenericType>::Concrete: TypeBuilder<TType, TLibfunc, Error = Error>,
 {
-    Ok(llvm::r#type::array(IntegerType::new(context, 8).into(), 0))
+    Ok(if metadata.get::<BuiltinCountersMeta>().is_some() {
+        IntegerType::new(context, 64).into()
+    } else {
+        llvm::r#type::array(IntegerType::new(context, 8).into(), 0)
+    })
 }
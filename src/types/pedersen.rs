@@ -1,11 +1,13 @@
 //! # Pedersen type
 //!
-//! Type representing the Pedersen hash builtin.
+//! Type representing the Pedersen hash builtin. It is effectively an unit type, unless the builtin
+//! counters are enabled (see [`BuiltinCountersMeta`]), in which case it's a counter of the builtin
+//! instances used.
 
 use super::{TypeBuilder, WithSelf};
 use crate::{
     error::types::{Error, Result},
-    metadata::MetadataStorage,
+    metadata::{builtin_counters::BuiltinCountersMeta, MetadataStorage},
 };
 use cairo_lang_sierra::{
     extensions::{types::InfoOnlyConcreteType, GenericLibfunc, GenericType},
@@ -24,7 +26,7 @@ pub fn build<'ctx, TType, TLibfunc>(
     context: &'ctx Context,
     _module: &Module<'ctx>,
     _registry: &ProgramRegistry<TType, TLibfunc>,
-    _metadata: &mut MetadataStorage,
+    metadata: &mut MetadataStorage,
     _info: WithSelf<InfoOnlyConcreteType>,
 ) -> Result<Type<'ctx>>
 where
@@ -32,5 +34,9 @@ where
     TLibfunc: GenericLibfunc,
     <TType as GenericType>::Concrete: TypeBuilder<TType, TLibfunc, Error = Error>,
 {
-    Ok(llvm::r#type::array(IntegerType::new(context, 8).into(), 0))
+    Ok(if metadata.get::<BuiltinCountersMeta>().is_some() {
+        IntegerType::new(context, 64).into()
+    } else {
+        llvm::r#type::array(IntegerType::new(context, 8).into(), 0)
+    })
 }
@@ -17,7 +17,9 @@
 //! Used to make system calls.
 //!
 //! ## Secp256Point
-//! TODO
+//! Type for a point on either the secp256k1 or the secp256r1 curves.
+//! Represented as a struct of two `u256` coordinates (`x` and `y`), each of them being in turn a
+//! struct of two `u128` limbs (`low` and `high`).
 
 // TODO: Maybe the types used here can be i251 instead of i252.
 
@@ -28,13 +30,15 @@ use crate::{
 };
 use cairo_lang_sierra::{
     extensions::{
-        starknet::StarkNetTypeConcrete, types::InfoOnlyConcreteType, GenericLibfunc, GenericType,
+        starknet::{secp256::Secp256PointTypeConcrete, StarkNetTypeConcrete},
+        types::InfoOnlyConcreteType,
+        GenericLibfunc, GenericType,
     },
     program_registry::ProgramRegistry,
 };
 use melior::{
     dialect::llvm,
-    ir::{Module, Type},
+    ir::{r#type::IntegerType, Module, Type},
     Context,
 };
 
@@ -89,7 +93,13 @@ where
             metadata,
             WithSelf::new(selector.self_ty(), info),
         ),
-        StarkNetTypeConcrete::Secp256Point(_) => todo!(),
+        StarkNetTypeConcrete::Secp256Point(info) => build_secp256_point(
+            context,
+            module,
+            registry,
+            metadata,
+            WithSelf::new(selector.self_ty(), info),
+        ),
     }
 }
 
@@ -171,3 +181,27 @@ where
 {
     Ok(llvm::r#type::opaque_pointer(context))
 }
+
+pub fn build_secp256_point<'ctx, TType, TLibfunc>(
+    context: &'ctx Context,
+    _module: &Module<'ctx>,
+    _registry: &ProgramRegistry<TType, TLibfunc>,
+    _metadata: &mut MetadataStorage,
+    _info: WithSelf<Secp256PointTypeConcrete>,
+) -> Result<Type<'ctx>>
+where
+    TType: GenericType,
+    TLibfunc: GenericLibfunc,
+    <TType as GenericType>::Concrete: TypeBuilder<TType, TLibfunc, Error = Error>,
+{
+    let u256_ty = llvm::r#type::r#struct(
+        context,
+        &[
+            IntegerType::new(context, 128).into(),
+            IntegerType::new(context, 128).into(),
+        ],
+        false,
+    );
+
+    Ok(llvm::r#type::r#struct(context, &[u256_ty, u256_ty], false))
+}
@@ -1,6 +1,7 @@
 //! # Various utilities
 
 use crate::{
+    ffi::OptLevel,
     metadata::MetadataStorage,
     types::{felt252::PRIME, TypeBuilder},
 };
@@ -14,16 +15,19 @@ use cairo_lang_sierra::{
 use melior::{
     ir::{Module, Type},
     pass::{self, PassManager},
+    utility::parse_pass_pipeline,
     Context, Error, ExecutionEngine,
 };
 use num_bigint::{BigInt, BigUint, Sign};
 use std::{
     alloc::Layout,
     borrow::Cow,
+    convert::Infallible,
     fmt::{self, Display},
     ops::Neg,
     path::Path,
     ptr::NonNull,
+    str::FromStr,
     sync::Arc,
 };
 use thiserror::Error;
@@ -106,6 +110,14 @@ pub fn cairo_to_sierra(program: &Path) -> Arc<Program> {
     }
 }
 
+/// Return whether the program uses the gas builtin, in which case it needs the gas metadata.
+pub fn has_gas_builtin(program: &Program) -> bool {
+    program
+        .type_declarations
+        .iter()
+        .any(|decl| decl.long_id.generic_id.0.as_str() == "GasBuiltin")
+}
+
 /// Returns the given entry point if present.
 pub fn find_entry_point<'a>(
     program: &'a Program,
@@ -180,9 +192,13 @@ pub fn felt252_short_str(value: &str) -> [u32; 8] {
 }
 
 /// Creates the execution engine, with all symbols registered.
-pub fn create_engine(module: &Module, _metadata: &MetadataStorage) -> ExecutionEngine {
+pub fn create_engine(
+    module: &Module,
+    _metadata: &MetadataStorage,
+    opt_level: OptLevel,
+) -> ExecutionEngine {
     // Create the JIT engine.
-    let engine = ExecutionEngine::new(module, 0, &[], false);
+    let engine = ExecutionEngine::new(module, opt_level.into(), &[], false);
 
     #[cfg(feature = "with-runtime")]
     register_runtime_symbols(&engine);
@@ -196,10 +212,87 @@ pub fn create_engine(module: &Module, _metadata: &MetadataStorage) -> ExecutionE
     engine
 }
 
-pub fn run_pass_manager(context: &Context, module: &mut Module) -> Result<(), Error> {
+/// The MLIR passes run on a module before it is translated into LLVM IR.
+///
+/// The passes required to lower the program into the LLVM dialect are always run. The pipeline only
+/// controls which optimizations are run around them.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PassPipeline {
+    /// Canonicalization only.
+    Minimal,
+    /// Canonicalization and common subexpression elimination.
+    Standard,
+    /// Inlining, constant propagation, loop-invariant code motion and common subexpression
+    /// elimination before lowering, followed by the promotion of allocas into SSA values (SROA and
+    /// mem2reg) once in the LLVM dialect.
+    Aggressive,
+    /// A custom list of passes run before lowering, by their MLIR pipeline names (for example
+    /// `cse` or `inline`).
+    Custom(Vec<String>),
+}
+
+impl From<OptLevel> for PassPipeline {
+    fn from(value: OptLevel) -> Self {
+        match value {
+            OptLevel::None => Self::Minimal,
+            OptLevel::Less => Self::Standard,
+            OptLevel::Default | OptLevel::Aggressive => Self::Aggressive,
+        }
+    }
+}
+
+impl FromStr for PassPipeline {
+    type Err = Infallible;
+
+    /// Parse either a preset name (`minimal`, `standard` or `aggressive`) or a comma-separated list
+    /// of passes.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "minimal" => Self::Minimal,
+            "standard" => Self::Standard,
+            "aggressive" => Self::Aggressive,
+            _ => Self::Custom(
+                s.split(',')
+                    .map(str::trim)
+                    .filter(|x| !x.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            ),
+        })
+    }
+}
+
+pub fn run_pass_manager(
+    context: &Context,
+    module: &mut Module,
+    pipeline: &PassPipeline,
+) -> Result<(), Error> {
     let pass_manager = PassManager::new(context);
     pass_manager.enable_verifier(true);
-    pass_manager.add_pass(pass::transform::create_canonicalizer());
+    match pipeline {
+        PassPipeline::Minimal => {
+            pass_manager.add_pass(pass::transform::create_canonicalizer());
+        }
+        PassPipeline::Standard => {
+            pass_manager.add_pass(pass::transform::create_canonicalizer());
+            pass_manager.add_pass(pass::transform::create_cse());
+        }
+        PassPipeline::Aggressive => {
+            pass_manager.add_pass(pass::transform::create_inliner());
+            pass_manager.add_pass(pass::transform::create_canonicalizer());
+            pass_manager.add_pass(pass::transform::create_sccp());
+            pass_manager.add_pass(pass::transform::create_loop_invariant_code_motion());
+            pass_manager.add_pass(pass::transform::create_cse());
+        }
+        PassPipeline::Custom(passes) => {
+            if !passes.is_empty() {
+                parse_pass_pipeline(
+                    pass_manager.as_operation_pass_manager(),
+                    &format!("builtin.module({})", passes.join(",")),
+                )?;
+            }
+        }
+    }
     pass_manager.add_pass(pass::conversion::create_scf_to_control_flow());
     pass_manager.add_pass(pass::conversion::create_arith_to_llvm());
     pass_manager.add_pass(pass::conversion::create_control_flow_to_llvm());
@@ -207,83 +300,79 @@ pub fn run_pass_manager(context: &Context, module: &mut Module) -> Result<(), Er
     pass_manager.add_pass(pass::conversion::create_index_to_llvm());
     pass_manager.add_pass(pass::conversion::create_finalize_mem_ref_to_llvm());
     pass_manager.add_pass(pass::conversion::create_reconcile_unrealized_casts());
+    if *pipeline == PassPipeline::Aggressive {
+        parse_pass_pipeline(
+            pass_manager.as_operation_pass_manager(),
+            "builtin.module(sroa,mem2reg,canonicalize,cse)",
+        )?;
+    }
     pass_manager.run(module)
 }
 
+/// The symbols provided by the runtime library, along with their addresses.
 #[cfg(feature = "with-runtime")]
-pub fn register_runtime_symbols(engine: &ExecutionEngine) {
-    unsafe {
-        engine.register_symbol(
+pub fn runtime_symbols() -> [(&'static str, *mut ()); 13] {
+    [
+        (
             "cairo_native__libfunc__debug__print",
             cairo_native_runtime::cairo_native__libfunc__debug__print
                 as *const fn(i32, *const [u8; 32], usize) -> i32 as *mut (),
-        );
-
-        engine.register_symbol(
+        ),
+        (
             "cairo_native__libfunc__pedersen",
             cairo_native_runtime::cairo_native__libfunc__pedersen
                 as *const fn(*mut u8, *mut u8, *mut u8) -> () as *mut (),
-        );
-
-        engine.register_symbol(
+        ),
+        (
             "cairo_native__libfunc__hades_permutation",
             cairo_native_runtime::cairo_native__libfunc__hades_permutation
                 as *const fn(*mut u8, *mut u8, *mut u8) -> () as *mut (),
-        );
-
-        engine.register_symbol(
+        ),
+        (
             "cairo_native__libfunc__ec__ec_point_from_x_nz",
             cairo_native_runtime::cairo_native__libfunc__ec__ec_point_from_x_nz
                 as *const fn(*mut [[u8; 32]; 2]) -> bool as *mut (),
-        );
-
-        engine.register_symbol(
+        ),
+        (
             "cairo_native__libfunc__ec__ec_state_add",
             cairo_native_runtime::cairo_native__libfunc__ec__ec_state_add
                 as *const fn(*mut [[u8; 32]; 4], *const [[u8; 32]; 2]) -> bool
                 as *mut (),
-        );
-
-        engine.register_symbol(
+        ),
+        (
             "cairo_native__libfunc__ec__ec_state_add_mul",
             cairo_native_runtime::cairo_native__libfunc__ec__ec_state_add_mul
                 as *const fn(*mut [[u8; 32]; 4], *const [u8; 32], *const [[u8; 32]; 2]) -> bool
                 as *mut (),
-        );
-
-        engine.register_symbol(
+        ),
+        (
             "cairo_native__libfunc__ec__ec_state_try_finalize_nz",
             cairo_native_runtime::cairo_native__libfunc__ec__ec_state_try_finalize_nz
                 as *const fn(*const [[u8; 32]; 2], *mut [[u8; 32]; 4]) -> bool
                 as *mut (),
-        );
-
-        engine.register_symbol(
+        ),
+        (
             "cairo_native__libfunc__ec__ec_point_try_new_nz",
             cairo_native_runtime::cairo_native__libfunc__ec__ec_point_try_new_nz
                 as *const fn(*const [[u8; 32]; 2]) -> bool as *mut (),
-        );
-
-        engine.register_symbol(
+        ),
+        (
             "cairo_native__alloc_dict",
             cairo_native_runtime::cairo_native__alloc_dict as *const fn() -> *mut std::ffi::c_void
                 as *mut (),
-        );
-
-        engine.register_symbol(
+        ),
+        (
             "cairo_native__dict_free",
             cairo_native_runtime::cairo_native__dict_free as *const fn(*mut std::ffi::c_void) -> ()
                 as *mut (),
-        );
-
-        engine.register_symbol(
+        ),
+        (
             "cairo_native__dict_get",
             cairo_native_runtime::cairo_native__dict_get
                 as *const fn(*mut std::ffi::c_void, &[u8; 32]) -> *mut std::ffi::c_void
                 as *mut (),
-        );
-
-        engine.register_symbol(
+        ),
+        (
             "cairo_native__dict_insert",
             cairo_native_runtime::cairo_native__dict_insert
                 as *const fn(
@@ -291,7 +380,26 @@ pub fn register_runtime_symbols(engine: &ExecutionEngine) {
                     &[u8; 32],
                     NonNull<std::ffi::c_void>,
                 ) -> *mut std::ffi::c_void as *mut (),
-        );
+        ),
+        (
+            "cairo_native__dict_squash_range_checks",
+            cairo_native_runtime::cairo_native__dict_squash_range_checks
+                as *const fn(*const std::ffi::c_void) -> u64 as *mut (),
+        ),
+        (
+            "cairo_native__get_syscall_handler",
+            cairo_native_runtime::cairo_native__get_syscall_handler
+                as *const fn() -> *mut std::ffi::c_void as *mut (),
+        ),
+    ]
+}
+
+#[cfg(feature = "with-runtime")]
+pub fn register_runtime_symbols(engine: &ExecutionEngine) {
+    for (name, ptr) in runtime_symbols() {
+        unsafe {
+            engine.register_symbol(name, ptr);
+        }
     }
 }
 
@@ -620,8 +728,10 @@ pub(crate) use codegen_ret_extr;
 #[cfg(test)]
 pub mod test {
     use crate::{
+        context::NativeContext,
         execution_result::ExecutionResult,
         executor::JitNativeExecutor,
+        ffi::OptLevel,
         metadata::{
             gas::{GasMetadata, MetadataComputationConfig},
             runtime_bindings::RuntimeBindingsMeta,
@@ -629,7 +739,10 @@ pub mod test {
             MetadataStorage,
         },
         module::NativeModule,
-        starknet::{BlockInfo, ExecutionInfo, StarkNetSyscallHandler, SyscallResult, TxInfo, U256},
+        starknet::{
+            BlockInfo, ExecutionInfo, ExecutionInfoV2, ResourceBounds, StarkNetSyscallHandler,
+            SyscallResult, TxInfo, TxInfoV2, U256,
+        },
         utils::*,
         values::JitValue,
     };
@@ -772,11 +885,7 @@ pub mod test {
             .insert(SyscallHandlerMeta::new(&mut TestSyscallHandler))
             .unwrap();
 
-        if program
-            .type_declarations
-            .iter()
-            .any(|decl| decl.long_id.generic_id.0 == "GasBuiltin")
-        {
+        if has_gas_builtin(program) {
             let gas_metadata = GasMetadata::new(program, MetadataComputationConfig::default());
             metadata.insert(gas_metadata);
         }
@@ -797,13 +906,13 @@ pub mod test {
             module.as_operation()
         );
 
-        run_pass_manager(&context, &mut module)
+        run_pass_manager(&context, &mut module, &PassPipeline::Minimal)
             .expect("Could not apply passes to the compiled test program.");
 
         let syscall_handler = metadata.remove::<SyscallHandlerMeta>();
 
         let native_module = NativeModule::new(module, registry, metadata);
-        let executor = JitNativeExecutor::new(native_module);
+        let executor = JitNativeExecutor::new(native_module, OptLevel::None);
         executor
             .invoke_dynamic(
                 entry_point_id,
@@ -876,6 +985,64 @@ pub mod test {
         assert_eq!(get_integer_layout(252).align(), 8);
     }
 
+    #[test]
+    fn test_pass_pipeline_from_str() {
+        assert_eq!("minimal".parse(), Ok(PassPipeline::Minimal));
+        assert_eq!("standard".parse(), Ok(PassPipeline::Standard));
+        assert_eq!("aggressive".parse(), Ok(PassPipeline::Aggressive));
+        assert_eq!(
+            "inline, cse,canonicalize".parse(),
+            Ok(PassPipeline::Custom(vec![
+                "inline".to_string(),
+                "cse".to_string(),
+                "canonicalize".to_string(),
+            ]))
+        );
+    }
+
+    /// Ensures that every pass pipeline preserves the program's semantics.
+    #[test]
+    fn test_pass_pipelines() {
+        let (module_name, program) = load_cairo! {
+            fn run_test(n: felt252) -> felt252 {
+                fib(0, 1, n)
+            }
+
+            fn fib(a: felt252, b: felt252, n: felt252) -> felt252 {
+                match n {
+                    0 => a,
+                    _ => fib(b, a + b, n - 1),
+                }
+            }
+        };
+        let function_id = find_function_id(&program, &format!("{0}::{0}::run_test", module_name));
+
+        let context = NativeContext::new();
+        for pipeline in [
+            PassPipeline::Minimal,
+            PassPipeline::Standard,
+            PassPipeline::Aggressive,
+            PassPipeline::Custom(vec!["inline".to_string(), "cse".to_string()]),
+        ] {
+            let module = context.compile_with_pipeline(&program, &pipeline).unwrap();
+            let executor = JitNativeExecutor::new(module, OptLevel::Default);
+            let result = executor
+                .invoke_dynamic(
+                    function_id,
+                    &[JitValue::Felt252(Felt::from(10))],
+                    Some(u128::MAX),
+                    None,
+                )
+                .unwrap();
+
+            assert_eq!(
+                result.return_value,
+                JitValue::Felt252(Felt::from(55)),
+                "{pipeline:?}"
+            );
+        }
+    }
+
     #[derive(Debug)]
     struct TestSyscallHandler;
 
@@ -909,6 +1076,41 @@ pub mod test {
             })
         }
 
+        fn get_execution_info_v2(
+            &mut self,
+            _gas: &mut u128,
+        ) -> SyscallResult<crate::starknet::ExecutionInfoV2> {
+            Ok(ExecutionInfoV2 {
+                block_info: BlockInfo {
+                    block_number: 1234,
+                    block_timestamp: 2345,
+                    sequencer_address: 3456.into(),
+                },
+                tx_info: TxInfoV2 {
+                    version: 4567.into(),
+                    account_contract_address: 5678.into(),
+                    max_fee: 6789,
+                    signature: vec![1248.into(), 2486.into()],
+                    transaction_hash: 9876.into(),
+                    chain_id: 8765.into(),
+                    nonce: 7654.into(),
+                    resource_bounds: vec![ResourceBounds {
+                        resource: 2345.into(),
+                        max_amount: 3456,
+                        max_price_per_unit: 4567,
+                    }],
+                    tip: 5678,
+                    paymaster_data: vec![6789.into()],
+                    nonce_data_availability_mode: 0,
+                    fee_data_availability_mode: 1,
+                    account_deployment_data: vec![7890.into(), 8901.into()],
+                },
+                caller_address: 6543.into(),
+                contract_address: 5432.into(),
+                entry_point_selector: 4321.into(),
+            })
+        }
+
         fn deploy(
             &mut self,
             class_hash: Felt,
@@ -992,145 +1194,5 @@ pub mod test {
             *gas -= 1000;
             Ok(U256(Felt::from(1234567890).to_bytes_le()))
         }
-
-        fn secp256k1_add(
-            &mut self,
-            _p0: crate::starknet::Secp256k1Point,
-            _p1: crate::starknet::Secp256k1Point,
-            _gas: &mut u128,
-        ) -> SyscallResult<Option<crate::starknet::Secp256k1Point>> {
-            todo!()
-        }
-
-        fn secp256k1_get_point_from_x(
-            &self,
-            _x: crate::starknet::U256,
-            _y_parity: bool,
-            _gas: &mut u128,
-        ) -> SyscallResult<Option<crate::starknet::Secp256k1Point>> {
-            todo!()
-        }
-
-        fn secp256k1_get_xy(
-            &self,
-            _p: crate::starknet::Secp256k1Point,
-            _gas: &mut u128,
-        ) -> SyscallResult<(crate::starknet::U256, crate::starknet::U256)> {
-            todo!()
-        }
-
-        fn secp256k1_mul(
-            &self,
-            _p: crate::starknet::Secp256k1Point,
-            _m: crate::starknet::U256,
-            _gas: &mut u128,
-        ) -> SyscallResult<Option<crate::starknet::Secp256k1Point>> {
-            todo!()
-        }
-
-        fn secp256k1_new(
-            &self,
-            _x: crate::starknet::U256,
-            _y: crate::starknet::U256,
-            _gas: &mut u128,
-        ) -> SyscallResult<Option<crate::starknet::Secp256k1Point>> {
-            todo!()
-        }
-
-        fn secp256r1_add(
-            &self,
-            _p0: crate::starknet::Secp256k1Point,
-            _p1: crate::starknet::Secp256k1Point,
-            _gas: &mut u128,
-        ) -> SyscallResult<Option<crate::starknet::Secp256k1Point>> {
-            todo!()
-        }
-
-        fn secp256r1_get_point_from_x(
-            &self,
-            _x: crate::starknet::U256,
-            _y_parity: bool,
-            _gas: &mut u128,
-        ) -> SyscallResult<Option<crate::starknet::Secp256k1Point>> {
-            todo!()
-        }
-
-        fn secp256r1_get_xy(
-            &self,
-            _p: crate::starknet::Secp256k1Point,
-            _gas: &mut u128,
-        ) -> SyscallResult<(crate::starknet::U256, crate::starknet::U256)> {
-            todo!()
-        }
-
-        fn secp256r1_mul(
-            &self,
-            _p: crate::starknet::Secp256k1Point,
-            _m: crate::starknet::U256,
-            _gas: &mut u128,
-        ) -> SyscallResult<Option<crate::starknet::Secp256k1Point>> {
-            todo!()
-        }
-
-        fn secp256r1_new(
-            &mut self,
-            _x: crate::starknet::U256,
-            _y: crate::starknet::U256,
-            _gas: &mut u128,
-        ) -> SyscallResult<Option<crate::starknet::Secp256k1Point>> {
-            todo!()
-        }
-
-        fn pop_log(&mut self) {
-            todo!()
-        }
-
-        fn set_account_contract_address(&mut self, _contract_address: Felt) {
-            todo!()
-        }
-
-        fn set_block_number(&mut self, _block_number: u64) {
-            todo!()
-        }
-
-        fn set_block_timestamp(&mut self, _block_timestamp: u64) {
-            todo!()
-        }
-
-        fn set_caller_address(&mut self, _address: Felt) {
-            todo!()
-        }
-
-        fn set_chain_id(&mut self, _chain_id: Felt) {
-            todo!()
-        }
-
-        fn set_contract_address(&mut self, _address: Felt) {
-            todo!()
-        }
-
-        fn set_max_fee(&mut self, _max_fee: u128) {
-            todo!()
-        }
-
-        fn set_nonce(&mut self, _nonce: Felt) {
-            todo!()
-        }
-
-        fn set_sequencer_address(&mut self, _address: Felt) {
-            todo!()
-        }
-
-        fn set_signature(&mut self, _signature: &[Felt]) {
-            todo!()
-        }
-
-        fn set_transaction_hash(&mut self, _transaction_hash: Felt) {
-            todo!()
-        }
-
-        fn set_version(&mut self, _version: Felt) {
-            todo!()
-        }
     }
 }
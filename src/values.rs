@@ -11,7 +11,7 @@ use bumpalo::Bump;
 use cairo_lang_sierra::{
     extensions::{
         core::{CoreLibfunc, CoreType, CoreTypeConcrete},
-        starknet::StarkNetTypeConcrete,
+        starknet::{secp256::Secp256PointTypeConcrete, StarkNetTypeConcrete},
     },
     ids::ConcreteTypeId,
     program_registry::ProgramRegistry,
@@ -21,6 +21,23 @@ use num_bigint::{BigInt, Sign};
 use starknet_types_core::felt::Felt;
 use std::{alloc::Layout, collections::HashMap, ops::Neg, ptr::NonNull};
 
+#[cfg(feature = "with-serde")]
+pub use self::seed::JitValueSeed;
+
+#[cfg(feature = "with-serde")]
+mod seed;
+
+/// The runtime representation of a `Felt252Dict`.
+///
+/// It must match `FeltDict` from the runtime library, which isn't always available.
+#[repr(C)]
+struct FeltDict {
+    mappings: HashMap<[u8; 32], NonNull<std::ffi::c_void>>,
+    /// Only used by the runtime library.
+    #[allow(dead_code)]
+    count: u64,
+}
+
 /// A JitValue is a value that can be passed to the JIT engine as an argument or received as a result.
 ///
 /// They map to the cairo/sierra types.
@@ -28,8 +45,13 @@ use std::{alloc::Layout, collections::HashMap, ops::Neg, ptr::NonNull};
 /// The debug_name field on some variants is `Some` when receiving a [`JitValue`] as a result.
 ///
 /// A Boxed value or a non-null Nullable value is returned with it's inner value.
+///
+/// With the `with-serde` feature it can be serialized and deserialized, keeping every variant
+/// (therefore the integer widths) and debug name. To decode plain values using their Sierra type
+/// instead, see `JitValueSeed`.
 #[derive(Clone, Educe)]
 #[educe(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum JitValue {
     Felt252(#[educe(Debug(method(std::fmt::Display::fmt)))] Felt),
     /// all elements need to be same type
@@ -62,6 +84,22 @@ pub enum JitValue {
     Sint128(i128),
     EcPoint(Felt, Felt),
     EcState(Felt, Felt, Felt, Felt),
+    /// A secp256k1 curve point, with each coordinate given as its `(low, high)` `u128` limbs.
+    Secp256K1Point {
+        x: (u128, u128),
+        y: (u128, u128),
+    },
+    /// A secp256r1 curve point, with each coordinate given as its `(low, high)` `u128` limbs.
+    Secp256R1Point {
+        x: (u128, u128),
+        y: (u128, u128),
+    },
+    /// A `bytes31` value, in little-endian byte order.
+    Bytes31([u8; 31]),
+    /// The (zero-sized) guarantee produced by `u128_guarantee_mul`.
+    Uint128MulGuarantee,
+    /// A value of type `Uninitialized<T>`, whose contents are meaningless.
+    Uninitialized,
     /// Used as return value for Nullables that are null.
     Null,
 }
@@ -182,7 +220,9 @@ impl JitValue {
                     ptr
                 }
                 Self::Array(data) => {
-                    if let CoreTypeConcrete::Array(info) = Self::resolve_type(ty, registry) {
+                    if let CoreTypeConcrete::Array(info) | CoreTypeConcrete::Span(info) =
+                        Self::resolve_type(ty, registry)
+                    {
                         let elem_ty = registry.get_type(&info.ty)?;
                         let elem_layout = elem_ty
                             .layout(registry)
@@ -249,6 +289,15 @@ impl JitValue {
                     fields: members, ..
                 } => {
                     if let CoreTypeConcrete::Struct(info) = Self::resolve_type(ty, registry) {
+                        if members.len() != info.members.len() {
+                            Err(ErrorImpl::UnexpectedValue(format!(
+                                "expected {} struct members for type {:?} but got {}",
+                                info.members.len(),
+                                type_id.debug_name,
+                                members.len(),
+                            )))?
+                        }
+
                         let mut layout: Option<Layout> = None;
                         let mut data = Vec::with_capacity(info.members.len());
 
@@ -312,14 +361,19 @@ impl JitValue {
                 }
                 Self::Enum { tag, value, .. } => {
                     if let CoreTypeConcrete::Enum(info) = Self::resolve_type(ty, registry) {
-                        assert!(*tag <= info.variants.len(), "Variant index out of range.");
+                        if *tag >= info.variants.len() {
+                            Err(ErrorImpl::UnexpectedValue(format!(
+                                "variant index {tag} out of range for type {:?}",
+                                type_id.debug_name
+                            )))?
+                        }
 
                         let payload_type_id = &info.variants[*tag];
                         let payload = value.to_jit(arena, registry, payload_type_id)?;
 
                         let (layout, tag_layout, variant_layouts) =
                             crate::types::r#enum::get_layout_for_variants(registry, &info.variants)
-                                .unwrap();
+                                .map_err(make_type_builder_error(type_id))?;
                         let ptr = arena.alloc_layout(layout).cast::<()>();
 
                         match tag_layout.size() {
@@ -335,7 +389,7 @@ impl JitValue {
                             payload.cast::<u8>().as_ptr(),
                             NonNull::new(
                                 ((ptr.as_ptr() as usize)
-                                    + tag_layout.extend(variant_layouts[*tag]).unwrap().1)
+                                    + tag_layout.extend(variant_layouts[*tag])?.1)
                                     as *mut u8,
                             )
                             .unwrap()
@@ -379,7 +433,11 @@ impl JitValue {
                             value_map.insert(key, value_malloc_ptr);
                         }
 
-                        NonNull::new_unchecked(Box::into_raw(Box::new(value_map))).cast()
+                        NonNull::new_unchecked(Box::into_raw(Box::new(FeltDict {
+                            mappings: value_map,
+                            count: 0,
+                        })))
+                        .cast()
                     } else {
                         Err(ErrorImpl::UnexpectedValue(format!(
                             "expected value of type {:?} but got a felt dict",
@@ -475,6 +533,75 @@ impl JitValue {
 
                     ptr
                 }
+                Self::Secp256K1Point { x, y } | Self::Secp256R1Point { x, y } => {
+                    match (self, Self::resolve_type(ty, registry)) {
+                        (
+                            Self::Secp256K1Point { .. },
+                            CoreTypeConcrete::StarkNet(StarkNetTypeConcrete::Secp256Point(
+                                Secp256PointTypeConcrete::K1(_),
+                            )),
+                        )
+                        | (
+                            Self::Secp256R1Point { .. },
+                            CoreTypeConcrete::StarkNet(StarkNetTypeConcrete::Secp256Point(
+                                Secp256PointTypeConcrete::R1(_),
+                            )),
+                        ) => {}
+                        _ => Err(ErrorImpl::UnexpectedValue(format!(
+                            "expected value of type {:?} but got a secp256 point of another curve",
+                            type_id.debug_name
+                        )))?,
+                    }
+
+                    let ptr = arena
+                        .alloc_layout(layout_repeat(&get_integer_layout(128), 4).unwrap().0)
+                        .cast();
+
+                    let data = [x.0, x.1, y.0, y.1];
+                    ptr.cast::<[u128; 4]>().as_mut().copy_from_slice(&data);
+
+                    ptr
+                }
+                Self::Bytes31(value) => {
+                    if let CoreTypeConcrete::Bytes31(_) = Self::resolve_type(ty, registry) {
+                        let ptr = arena.alloc_layout(get_integer_layout(248)).cast();
+                        ptr.cast::<[u8; 31]>().as_mut().copy_from_slice(value);
+
+                        ptr
+                    } else {
+                        Err(ErrorImpl::UnexpectedValue(format!(
+                            "expected value of type {:?} but got a bytes31",
+                            type_id.debug_name
+                        )))?
+                    }
+                }
+                Self::Uint128MulGuarantee => {
+                    if let CoreTypeConcrete::Uint128MulGuarantee(_) =
+                        Self::resolve_type(ty, registry)
+                    {
+                        arena.alloc_layout(Layout::new::<()>()).cast()
+                    } else {
+                        Err(ErrorImpl::UnexpectedValue(format!(
+                            "expected value of type {:?} but got a u128 mul guarantee",
+                            type_id.debug_name
+                        )))?
+                    }
+                }
+                Self::Uninitialized => {
+                    if let CoreTypeConcrete::Uninitialized(_) = Self::resolve_type(ty, registry) {
+                        arena
+                            .alloc_layout(
+                                ty.layout(registry)
+                                    .map_err(make_type_builder_error(type_id))?,
+                            )
+                            .cast()
+                    } else {
+                        Err(ErrorImpl::UnexpectedValue(format!(
+                            "expected value of type {:?} but got an uninitialized value",
+                            type_id.debug_name
+                        )))?
+                    }
+                }
                 Self::Null => {
                     unimplemented!("null is meant as return value for nullable for now")
                 }
@@ -492,7 +619,7 @@ impl JitValue {
 
         unsafe {
             match ty {
-                CoreTypeConcrete::Array(info) => {
+                CoreTypeConcrete::Array(info) | CoreTypeConcrete::Span(info) => {
                     let elem_ty = registry.get_type(&info.ty).unwrap();
 
                     let elem_layout = elem_ty.layout(registry).unwrap();
@@ -556,7 +683,7 @@ impl JitValue {
                 CoreTypeConcrete::Uint32(_) => Self::Uint32(*ptr.cast::<u32>().as_ref()),
                 CoreTypeConcrete::Uint64(_) => Self::Uint64(*ptr.cast::<u64>().as_ref()),
                 CoreTypeConcrete::Uint128(_) => Self::Uint128(*ptr.cast::<u128>().as_ref()),
-                CoreTypeConcrete::Uint128MulGuarantee(_) => todo!(),
+                CoreTypeConcrete::Uint128MulGuarantee(_) => Self::Uint128MulGuarantee,
                 CoreTypeConcrete::Sint8(_) => Self::Sint8(*ptr.cast::<i8>().as_ref()),
                 CoreTypeConcrete::Sint16(_) => Self::Sint16(*ptr.cast::<i16>().as_ref()),
                 CoreTypeConcrete::Sint32(_) => Self::Sint32(*ptr.cast::<i32>().as_ref()),
@@ -577,9 +704,7 @@ impl JitValue {
                         value
                     }
                 }
-                CoreTypeConcrete::Uninitialized(_) => {
-                    todo!("implement uninit from_jit or ignore the return value")
-                }
+                CoreTypeConcrete::Uninitialized(_) => Self::Uninitialized,
                 CoreTypeConcrete::Enum(info) => {
                     let tag_layout = crate::utils::get_integer_layout(match info.variants.len() {
                         0 | 1 => 0,
@@ -649,14 +774,11 @@ impl JitValue {
                 }
                 CoreTypeConcrete::Felt252Dict(info)
                 | CoreTypeConcrete::SquashedFelt252Dict(info) => {
-                    let map = Box::from_raw(
-                        ptr.cast::<HashMap<[u8; 32], NonNull<std::ffi::c_void>>>()
-                            .as_ptr(),
-                    );
+                    let dict = Box::from_raw(ptr.cast::<FeltDict>().as_ptr());
 
-                    let mut output_map = HashMap::with_capacity(map.len());
+                    let mut output_map = HashMap::with_capacity(dict.mappings.len());
 
-                    for (key, val_ptr) in map.iter() {
+                    for (key, val_ptr) in dict.mappings.iter() {
                         let key = Felt::from_bytes_le(key);
                         output_map.insert(key, Self::from_jit(val_ptr.cast(), &info.ty, registry));
                     }
@@ -693,11 +815,18 @@ impl JitValue {
                     StarkNetTypeConcrete::System(_) => {
                         unimplemented!("should be handled before")
                     }
-                    StarkNetTypeConcrete::Secp256Point(_) => todo!(),
+                    StarkNetTypeConcrete::Secp256Point(info) => {
+                        let data = ptr.cast::<[u128; 4]>().as_ref();
+                        let (x, y) = ((data[0], data[1]), (data[2], data[3]));
+
+                        match info {
+                            Secp256PointTypeConcrete::K1(_) => JitValue::Secp256K1Point { x, y },
+                            Secp256PointTypeConcrete::R1(_) => JitValue::Secp256R1Point { x, y },
+                        }
+                    }
                 },
-                CoreTypeConcrete::Span(_) => todo!("implement span from_jit"),
                 CoreTypeConcrete::Snapshot(info) => Self::from_jit(ptr, &info.ty, registry),
-                CoreTypeConcrete::Bytes31(_) => todo!("implement bytes31 from_jit"),
+                CoreTypeConcrete::Bytes31(_) => Self::Bytes31(*ptr.cast::<[u8; 31]>().as_ref()),
             }
         }
     }
@@ -713,3 +842,148 @@ impl JitValue {
         Self::Felt252(Felt::from(&value))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cairo_lang_sierra::ProgramParser;
+    use proptest::prelude::*;
+
+    const TYPES: &str = r#"
+        type felt252 = felt252;
+        type u128 = u128;
+        type i64 = i64;
+        type bytes31 = bytes31;
+        type ArrayFelt252 = Array<felt252>;
+        type SpanFelt252 = Span<felt252>;
+        type SnapshotArrayFelt252 = Snapshot<ArrayFelt252>;
+        type CoreSpanFelt252 = Struct<ut@core::array::Span::<core::felt252>, SnapshotArrayFelt252>;
+        type U128MulGuarantee = U128MulGuarantee;
+        type UninitializedFelt252 = Uninitialized<felt252>;
+        type Secp256K1Point = Secp256k1Point;
+        type Secp256R1Point = Secp256r1Point;
+    "#;
+
+    fn round_trip(value: &JitValue, type_id: &str) -> Result<JitValue, RunnerError> {
+        let program = ProgramParser::new().parse(TYPES).unwrap();
+        let registry = ProgramRegistry::<CoreType, CoreLibfunc>::new(&program).unwrap();
+        let type_id = ConcreteTypeId::from_string(type_id);
+
+        let arena = Bump::new();
+        let ptr = value.to_jit(&arena, &registry, &type_id)?;
+        Ok(JitValue::from_jit(ptr, &type_id, &registry))
+    }
+
+    fn felt252() -> impl Strategy<Value = Felt> {
+        any::<[u8; 31]>().prop_map(|data| Felt::from_bytes_be_slice(&data))
+    }
+
+    proptest! {
+        #[test]
+        fn felt252_round_trip(value in felt252()) {
+            let value = JitValue::Felt252(value);
+            prop_assert_eq!(round_trip(&value, "felt252").unwrap(), value);
+        }
+
+        #[test]
+        fn uint128_round_trip(value: u128) {
+            let value = JitValue::Uint128(value);
+            prop_assert_eq!(round_trip(&value, "u128").unwrap(), value);
+        }
+
+        #[test]
+        fn sint64_round_trip(value: i64) {
+            let value = JitValue::Sint64(value);
+            prop_assert_eq!(round_trip(&value, "i64").unwrap(), value);
+        }
+
+        #[test]
+        fn bytes31_round_trip(value: [u8; 31]) {
+            let value = JitValue::Bytes31(value);
+            prop_assert_eq!(round_trip(&value, "bytes31").unwrap(), value);
+        }
+
+        #[test]
+        fn secp256_point_round_trip(x: (u128, u128), y: (u128, u128)) {
+            let value = JitValue::Secp256K1Point { x, y };
+            prop_assert_eq!(round_trip(&value, "Secp256K1Point").unwrap(), value);
+
+            let value = JitValue::Secp256R1Point { x, y };
+            prop_assert_eq!(round_trip(&value, "Secp256R1Point").unwrap(), value);
+        }
+
+        #[test]
+        fn array_round_trip(values in prop::collection::vec(felt252(), 0..16)) {
+            let value = JitValue::from(values);
+            prop_assert_eq!(round_trip(&value, "ArrayFelt252").unwrap(), value.clone());
+            prop_assert_eq!(round_trip(&value, "SpanFelt252").unwrap(), value);
+        }
+
+        #[test]
+        fn core_span_round_trip(values in prop::collection::vec(felt252(), 0..16)) {
+            let value = JitValue::Struct {
+                fields: vec![JitValue::from(values)],
+                debug_name: None,
+            };
+            prop_assert_eq!(round_trip(&value, "CoreSpanFelt252").unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn uint128_mul_guarantee_round_trip() {
+        let value = JitValue::Uint128MulGuarantee;
+        assert_eq!(round_trip(&value, "U128MulGuarantee").unwrap(), value);
+    }
+
+    #[test]
+    fn uninitialized_round_trip() {
+        let value = JitValue::Uninitialized;
+        assert_eq!(round_trip(&value, "UninitializedFelt252").unwrap(), value);
+    }
+
+    #[cfg(feature = "with-serde")]
+    #[test]
+    fn serde_round_trip() {
+        let value = JitValue::Enum {
+            tag: 1,
+            value: Box::new(JitValue::Struct {
+                fields: vec![
+                    JitValue::Uint8(1),
+                    JitValue::Uint16(1),
+                    JitValue::Sint128(-1),
+                    JitValue::Felt252(Felt::from(1)),
+                ],
+                debug_name: Some("Tuple<u8, u16, i128, felt252>".to_string()),
+            }),
+            debug_name: Some("core::option::Option".to_string()),
+        };
+
+        let result: JitValue =
+            serde_json::from_str(&serde_json::to_string(&value).unwrap()).unwrap();
+        assert_eq!(result, value);
+        assert_eq!(format!("{result:?}"), format!("{value:?}"));
+    }
+
+    #[test]
+    fn mismatched_type() {
+        assert!(round_trip(&JitValue::Bytes31([0; 31]), "felt252").is_err());
+        assert!(round_trip(&JitValue::Uint128MulGuarantee, "felt252").is_err());
+        assert!(round_trip(&JitValue::Uninitialized, "felt252").is_err());
+        assert!(round_trip(
+            &JitValue::Secp256K1Point {
+                x: (1, 0),
+                y: (2, 0)
+            },
+            "Secp256R1Point"
+        )
+        .is_err());
+        assert!(round_trip(
+            &JitValue::Secp256R1Point {
+                x: (1, 0),
+                y: (2, 0)
+            },
+            "Secp256K1Point"
+        )
+        .is_err());
+    }
+}
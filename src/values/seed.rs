@@ -0,0 +1,389 @@
+//! # Typed JitValue deserialization
+//!
+//! Plain JSON values are ambiguous (a number may be any integer type or a felt, an array may be a
+//! struct or an array, etc.), therefore they're decoded using the Sierra type they should have as a
+//! hint. Every value is decoded as follows:
+//!   - `felt252`: a number, or a string with a decimal number, a `0x`-prefixed hex number or a
+//!     single-quoted short string (`'hello'`).
+//!   - Integers: a number, or a string with a decimal or hex number. A `u256` may also be given this
+//!     way instead of as a struct.
+//!   - Structs: an array with their members.
+//!   - Enums: an object like `{ "variant": 1, "value": ... }`. The value may be omitted for unit
+//!     variants. A `bool` may also be given as `true` or `false`.
+//!   - Arrays and spans: an array with their elements.
+//!   - Dictionaries: an object whose keys are felts.
+//!   - Nullables: `null`, or their inner value.
+
+use super::JitValue;
+use cairo_lang_sierra::{
+    extensions::{
+        core::{CoreLibfunc, CoreType, CoreTypeConcrete},
+        types::TypeInfo,
+    },
+    ids::ConcreteTypeId,
+    program::GenericArg,
+    program_registry::ProgramRegistry,
+};
+use num_bigint::{BigInt, BigUint, Sign};
+use serde::{
+    de::{DeserializeSeed, Error},
+    Deserialize, Deserializer,
+};
+use serde_json::Value;
+use starknet_types_core::felt::Felt;
+use std::collections::HashMap;
+
+/// A [`DeserializeSeed`] which decodes a [`JitValue`] of the given type.
+#[derive(Clone, Copy)]
+pub struct JitValueSeed<'a> {
+    registry: &'a ProgramRegistry<CoreType, CoreLibfunc>,
+    type_id: &'a ConcreteTypeId,
+}
+
+impl<'a> JitValueSeed<'a> {
+    pub fn new(
+        registry: &'a ProgramRegistry<CoreType, CoreLibfunc>,
+        type_id: &'a ConcreteTypeId,
+    ) -> Self {
+        Self { registry, type_id }
+    }
+}
+
+impl<'a, 'de> DeserializeSeed<'de> for JitValueSeed<'a> {
+    type Value = JitValue;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = Value::deserialize(deserializer)?;
+        parse_value(self.registry, self.type_id, &value).map_err(D::Error::custom)
+    }
+}
+
+fn parse_value(
+    registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    type_id: &ConcreteTypeId,
+    value: &Value,
+) -> Result<JitValue, String> {
+    let debug_name = type_id.debug_name.as_ref().map(ToString::to_string);
+
+    Ok(
+        match registry.get_type(type_id).map_err(|e| e.to_string())? {
+            CoreTypeConcrete::Felt252(_) => JitValue::Felt252(parse_felt(value)?),
+            CoreTypeConcrete::Uint8(_) => JitValue::Uint8(parse_integer(value)?),
+            CoreTypeConcrete::Uint16(_) => JitValue::Uint16(parse_integer(value)?),
+            CoreTypeConcrete::Uint32(_) => JitValue::Uint32(parse_integer(value)?),
+            CoreTypeConcrete::Uint64(_) => JitValue::Uint64(parse_integer(value)?),
+            CoreTypeConcrete::Uint128(_) => JitValue::Uint128(parse_integer(value)?),
+            CoreTypeConcrete::Sint8(_) => JitValue::Sint8(parse_integer(value)?),
+            CoreTypeConcrete::Sint16(_) => JitValue::Sint16(parse_integer(value)?),
+            CoreTypeConcrete::Sint32(_) => JitValue::Sint32(parse_integer(value)?),
+            CoreTypeConcrete::Sint64(_) => JitValue::Sint64(parse_integer(value)?),
+            CoreTypeConcrete::Sint128(_) => JitValue::Sint128(parse_integer(value)?),
+            CoreTypeConcrete::Bytes31(_) => {
+                let bytes = parse_felt(value)?.to_bytes_be();
+                if bytes[0] != 0 {
+                    return Err(format!("value {value} does not fit in a bytes31"));
+                }
+
+                let mut data = [0; 31];
+                data.copy_from_slice(&bytes[1..]);
+                data.reverse();
+                JitValue::Bytes31(data)
+            }
+            CoreTypeConcrete::EcPoint(_) => match value.as_array().map(Vec::as_slice) {
+                Some([x, y]) => JitValue::EcPoint(parse_felt(x)?, parse_felt(y)?),
+                _ => return Err(format!("expected an array with 2 felts, got {value}")),
+            },
+            CoreTypeConcrete::Struct(info) => match value {
+                Value::Array(values) => {
+                    if values.len() != info.members.len() {
+                        return Err(format!(
+                            "expected {} members, got {}",
+                            info.members.len(),
+                            values.len()
+                        ));
+                    }
+
+                    JitValue::Struct {
+                        fields: info
+                            .members
+                            .iter()
+                            .zip(values)
+                            .map(|(ty, value)| parse_value(registry, ty, value))
+                            .collect::<Result<_, _>>()?,
+                        debug_name,
+                    }
+                }
+                _ if user_type_name(&info.info) == Some("core::integer::u256") => {
+                    let value: BigUint = parse_integer(value)?;
+                    let low = u128::try_from(&value & BigUint::from(u128::MAX)).unwrap();
+                    let high = u128::try_from(value >> 128u32)
+                        .map_err(|_| "value does not fit in a u256".to_string())?;
+
+                    JitValue::Struct {
+                        fields: vec![JitValue::Uint128(low), JitValue::Uint128(high)],
+                        debug_name,
+                    }
+                }
+                _ => return Err(format!("expected an array, got {value}")),
+            },
+            CoreTypeConcrete::Enum(info) => {
+                let (tag, value) = match value {
+                    Value::Bool(value) if user_type_name(&info.info) == Some("core::bool") => {
+                        (*value as usize, None)
+                    }
+                    Value::Object(fields) => (
+                        fields
+                            .get("variant")
+                            .and_then(Value::as_u64)
+                            .ok_or_else(|| format!("missing variant index in {value}"))?
+                            as usize,
+                        fields.get("value"),
+                    ),
+                    _ => return Err(format!("expected an object, got {value}")),
+                };
+
+                let variant_ty = info
+                    .variants
+                    .get(tag)
+                    .ok_or_else(|| format!("variant index {tag} out of range"))?;
+                JitValue::Enum {
+                    tag,
+                    value: Box::new(parse_value(
+                        registry,
+                        variant_ty,
+                        value.unwrap_or(&Value::Array(Vec::new())),
+                    )?),
+                    debug_name,
+                }
+            }
+            CoreTypeConcrete::Array(info) | CoreTypeConcrete::Span(info) => match value {
+                Value::Array(values) => JitValue::Array(
+                    values
+                        .iter()
+                        .map(|value| parse_value(registry, &info.ty, value))
+                        .collect::<Result<_, _>>()?,
+                ),
+                _ => return Err(format!("expected an array, got {value}")),
+            },
+            CoreTypeConcrete::Felt252Dict(info) => match value {
+                Value::Object(entries) => JitValue::Felt252Dict {
+                    value: entries
+                        .iter()
+                        .map(|(key, value)| {
+                            Ok((
+                                parse_felt_str(key)?,
+                                parse_value(registry, &info.ty, value)?,
+                            ))
+                        })
+                        .collect::<Result<HashMap<_, _>, String>>()?,
+                    debug_name,
+                },
+                _ => return Err(format!("expected an object, got {value}")),
+            },
+            CoreTypeConcrete::Nullable(_) if value.is_null() => JitValue::Null,
+            CoreTypeConcrete::Box(info)
+            | CoreTypeConcrete::NonZero(info)
+            | CoreTypeConcrete::Nullable(info) => parse_value(registry, &info.ty, value)?,
+            CoreTypeConcrete::Snapshot(info) => parse_value(registry, &info.ty, value)?,
+            _ => {
+                return Err(format!(
+                    "unsupported type `{}`",
+                    debug_name.unwrap_or_else(|| type_id.id.to_string())
+                ))
+            }
+        },
+    )
+}
+
+/// Return the name of a struct or enum's user type.
+fn user_type_name(info: &TypeInfo) -> Option<&str> {
+    match info.long_id.generic_args.first() {
+        Some(GenericArg::UserType(id)) => id.debug_name.as_deref(),
+        _ => None,
+    }
+}
+
+fn parse_felt(value: &Value) -> Result<Felt, String> {
+    match value {
+        Value::String(value) => parse_felt_str(value),
+        _ => Ok(bigint_to_felt(parse_integer(value)?)),
+    }
+}
+
+/// Parse a decimal number, a `0x`-prefixed hex number or a single-quoted short string into a felt.
+fn parse_felt_str(value: &str) -> Result<Felt, String> {
+    match value
+        .strip_prefix('\'')
+        .and_then(|value| value.strip_suffix('\''))
+    {
+        Some(short_string) => {
+            if short_string.len() > 31 || !short_string.is_ascii() {
+                return Err(format!("invalid short string '{short_string}'"));
+            }
+
+            Ok(Felt::from_bytes_be_slice(short_string.as_bytes()))
+        }
+        None => Ok(bigint_to_felt(parse_integer(&Value::String(
+            value.to_string(),
+        ))?)),
+    }
+}
+
+/// Parse a number, or a string with a decimal or `0x`-prefixed hex number.
+fn parse_integer<T>(value: &Value) -> Result<T, String>
+where
+    T: TryFrom<BigInt>,
+{
+    let number = match value {
+        Value::Number(number) => match (number.as_u64(), number.as_i64()) {
+            (Some(number), _) => BigInt::from(number),
+            (_, Some(number)) => BigInt::from(number),
+            _ => {
+                return Err(format!(
+                    "invalid integer {number}, large numbers should be given as strings"
+                ))
+            }
+        },
+        Value::String(number) => {
+            let (sign, number) = match number.strip_prefix('-') {
+                Some(number) => (Sign::Minus, number),
+                None => (Sign::Plus, number.as_str()),
+            };
+            let magnitude = match number.strip_prefix("0x") {
+                Some(number) => BigUint::parse_bytes(number.as_bytes(), 16),
+                None => BigUint::parse_bytes(number.as_bytes(), 10),
+            }
+            .ok_or_else(|| format!("invalid integer {value}"))?;
+
+            BigInt::from_biguint(sign, magnitude)
+        }
+        _ => return Err(format!("expected an integer, got {value}")),
+    };
+
+    T::try_from(number).map_err(|_| format!("integer {value} out of range"))
+}
+
+/// Convert an integer into a felt, wrapping negative values around the prime.
+fn bigint_to_felt(value: BigInt) -> Felt {
+    let (sign, magnitude) = value.into_parts();
+    let magnitude = Felt::from_bytes_be_slice(&magnitude.to_bytes_be());
+
+    match sign {
+        Sign::Minus => Felt::ZERO - magnitude,
+        _ => magnitude,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cairo_lang_sierra::ProgramParser;
+
+    const TYPES: &str = r#"
+        type felt252 = felt252;
+        type u8 = u8;
+        type u128 = u128;
+        type i8 = i8;
+        type Unit = Struct<ut@Tuple>;
+        type u256 = Struct<ut@core::integer::u256, u128, u128>;
+        type bool = Enum<ut@core::bool, Unit, Unit>;
+        type OptionU8 = Enum<ut@core::option::Option::<core::integer::u8>, u8, Unit>;
+        type ArrayU8 = Array<u8>;
+    "#;
+
+    fn parse(type_id: &str, value: &str) -> Result<JitValue, serde_json::Error> {
+        let program = ProgramParser::new().parse(TYPES).unwrap();
+        let registry = ProgramRegistry::<CoreType, CoreLibfunc>::new(&program).unwrap();
+        let type_id = ConcreteTypeId::from_string(type_id);
+
+        JitValueSeed::new(&registry, &type_id)
+            .deserialize(&mut serde_json::Deserializer::from_str(value))
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(parse("u8", "5").unwrap(), JitValue::Uint8(5));
+        assert_eq!(parse("i8", "-5").unwrap(), JitValue::Sint8(-5));
+        assert_eq!(parse("u128", "\"0x10\"").unwrap(), JitValue::Uint128(16));
+        assert_eq!(
+            parse("felt252", "5").unwrap(),
+            JitValue::Felt252(Felt::from(5))
+        );
+        assert_eq!(
+            parse("felt252", "-1").unwrap(),
+            JitValue::Felt252(Felt::ZERO - Felt::from(1))
+        );
+        assert_eq!(
+            parse("felt252", "\"'hello'\"").unwrap(),
+            JitValue::Felt252(Felt::from_bytes_be_slice(b"hello"))
+        );
+
+        assert!(parse("u8", "256").is_err());
+        assert!(parse("i8", "\"abc\"").is_err());
+    }
+
+    #[test]
+    fn u256() {
+        let expected = JitValue::Struct {
+            fields: vec![JitValue::Uint128(1), JitValue::Uint128(2)],
+            debug_name: None,
+        };
+
+        assert_eq!(parse("u256", "[1, 2]").unwrap(), expected);
+        assert_eq!(
+            parse("u256", "\"0x200000000000000000000000000000001\"").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn enums() {
+        let unit = JitValue::Struct {
+            fields: Vec::new(),
+            debug_name: None,
+        };
+
+        assert_eq!(
+            parse("bool", "true").unwrap(),
+            JitValue::Enum {
+                tag: 1,
+                value: Box::new(unit.clone()),
+                debug_name: None,
+            }
+        );
+        assert_eq!(
+            parse("OptionU8", r#"{ "variant": 0, "value": 3 }"#).unwrap(),
+            JitValue::Enum {
+                tag: 0,
+                value: Box::new(JitValue::Uint8(3)),
+                debug_name: None,
+            }
+        );
+        assert_eq!(
+            parse("OptionU8", r#"{ "variant": 1 }"#).unwrap(),
+            JitValue::Enum {
+                tag: 1,
+                value: Box::new(unit),
+                debug_name: None,
+            }
+        );
+
+        assert!(parse("OptionU8", r#"{ "variant": 2 }"#).is_err());
+    }
+
+    #[test]
+    fn arrays() {
+        assert_eq!(
+            parse("ArrayU8", "[1, 2, 3]").unwrap(),
+            JitValue::Array(vec![
+                JitValue::Uint8(1),
+                JitValue::Uint8(2),
+                JitValue::Uint8(3)
+            ])
+        );
+        assert!(parse("ArrayU8", "[1, 256]").is_err());
+    }
+}
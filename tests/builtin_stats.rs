@@ -0,0 +1,322 @@
+use crate::common::{
+    compare_outputs, load_cairo, run_native_program, run_native_program_with_builtin_counters,
+    run_vm_program, DEFAULT_GAS,
+};
+use cairo_felt::Felt252 as DeprecatedFelt;
+use cairo_lang_runner::{Arg, RunResultStarknet, SierraCasmRunner};
+use cairo_lang_sierra::program::Program;
+use cairo_native::{execution_result::BuiltinStats, values::JitValue};
+use lazy_static::lazy_static;
+use starknet_types_core::felt::Felt;
+
+mod common;
+
+// The builtin counters are checked against the usage of the same run on the Cairo VM, and against
+// the amounts used by the CASM generated for each libfunc.
+lazy_static! {
+    static ref U8_ADD: (String, Program, SierraCasmRunner) = load_cairo! {
+        use traits::TryInto;
+        use core::option::OptionTrait;
+
+        fn run_test(lhs: felt252, rhs: felt252) -> u8 {
+            let lhs: u8 = lhs.try_into().unwrap();
+            let rhs: u8 = rhs.try_into().unwrap();
+            lhs + rhs
+        }
+    };
+    static ref U128_BITWISE: (String, Program, SierraCasmRunner) = load_cairo! {
+        use traits::TryInto;
+        use core::option::OptionTrait;
+
+        fn run_test(lhs: felt252, rhs: felt252) -> u128 {
+            let lhs: u128 = lhs.try_into().unwrap();
+            let rhs: u128 = rhs.try_into().unwrap();
+            lhs & rhs
+        }
+    };
+    static ref PEDERSEN: (String, Program, SierraCasmRunner) = load_cairo! {
+        fn run_test(lhs: felt252, rhs: felt252) -> felt252 {
+            core::pedersen::pedersen(lhs, rhs)
+        }
+    };
+    static ref POSEIDON: (String, Program, SierraCasmRunner) = load_cairo! {
+        fn run_test(a: felt252, b: felt252, c: felt252) -> (felt252, felt252, felt252) {
+            core::poseidon::hades_permutation(a, b, c)
+        }
+    };
+    static ref EC_OP: (String, Program, SierraCasmRunner) = load_cairo! {
+        use core::ec::{
+            ec_point_from_x_nz, ec_point_unwrap, ec_state_add_mul, ec_state_init,
+            ec_state_try_finalize_nz,
+        };
+        use core::option::OptionTrait;
+
+        fn run_test(x: felt252, scalar: felt252) -> felt252 {
+            let point = ec_point_from_x_nz(x).unwrap();
+            let mut state = ec_state_init();
+            ec_state_add_mul(ref state, scalar, point);
+            let (x, _) = ec_point_unwrap(ec_state_try_finalize_nz(state).unwrap());
+            x
+        }
+    };
+    static ref DICT: (String, Program, SierraCasmRunner) = load_cairo! {
+        use traits::Default;
+        use dict::Felt252DictTrait;
+
+        fn run_test(a: felt252, b: felt252) -> felt252 {
+            let mut dict: Felt252Dict<felt252> = Default::default();
+            dict.insert(a, 1);
+            dict.insert(b, 2);
+            dict.get(a) + dict.get(b)
+        }
+    };
+}
+
+/// Extract the builtin usage of a run on the Cairo VM from its relocated memory.
+///
+/// The execution segment starts with the initial stack: the builtin bases (pedersen, range_check,
+/// bitwise, ec_op and poseidon), the return fp and the end pc. The segments are relocated one after
+/// another, so the builtin bases are also their segments' boundaries, and the end pc (which is the
+/// end of the program segment) is the start of the execution segment itself. When the function takes
+/// a segment arena, it's the first segment allocated by the entry code, right after the initial
+/// stack, and is followed by the infos segment.
+fn vm_builtin_stats(result: &RunResultStarknet, has_segment_arena: bool) -> BuiltinStats {
+    let cell = |idx: usize| -> Option<usize> {
+        result
+            .memory
+            .get(idx)
+            .cloned()
+            .flatten()
+            .and_then(|x| x.to_biguint().try_into().ok())
+    };
+
+    let exec_base = (1..result.memory.len())
+        .find(|&idx| {
+            cell(idx + 6) == Some(idx)
+                && (0..6).all(
+                    |i| matches!((cell(idx + i), cell(idx + i + 1)), (Some(a), Some(b)) if a <= b),
+                )
+        })
+        .expect("the execution segment should start with the initial stack");
+    let segment_size = |i: usize| cell(exec_base + i + 1).unwrap() - cell(exec_base + i).unwrap();
+    let instances = |size: usize, cells_per_instance: usize| {
+        (size + cells_per_instance - 1) / cells_per_instance
+    };
+
+    BuiltinStats {
+        pedersen: instances(segment_size(0), 3),
+        range_check: instances(segment_size(1), 1),
+        bitwise: instances(segment_size(2), 5),
+        ec_op: instances(segment_size(3), 7),
+        poseidon: instances(segment_size(4), 6),
+        // The entry code writes the initial arena state (3 cells), and every instance adds another.
+        segment_arena: if has_segment_arena {
+            instances(segment_size(7), 3) - 1
+        } else {
+            0
+        },
+    }
+}
+
+#[track_caller]
+fn check_builtin_stats(
+    program: &(String, Program, SierraCasmRunner),
+    args: &[Felt],
+    expected: BuiltinStats,
+) {
+    check_builtin_stats_inner(program, args, expected, true);
+}
+
+#[track_caller]
+fn check_builtin_stats_inner(
+    program: &(String, Program, SierraCasmRunner),
+    args: &[Felt],
+    expected: BuiltinStats,
+    compare_gas: bool,
+) {
+    let result_vm = run_vm_program(
+        program,
+        "run_test",
+        &args
+            .iter()
+            .map(|x| Arg::Value(DeprecatedFelt::from_bytes_be(&x.to_bytes_be())))
+            .collect::<Vec<_>>(),
+        Some(DEFAULT_GAS as usize),
+    )
+    .unwrap();
+    let mut result_native = run_native_program_with_builtin_counters(
+        program,
+        "run_test",
+        &args
+            .iter()
+            .map(|x| JitValue::Felt252(*x))
+            .collect::<Vec<_>>(),
+        Some(DEFAULT_GAS as u128),
+    );
+    let builtin_stats = result_native.builtin_stats;
+
+    if !compare_gas {
+        // The gas refunds of the dict squash aren't implemented yet (see `tests/dict.rs`).
+        result_native.remaining_gas = Some(
+            result_vm
+                .gas_counter
+                .clone()
+                .unwrap()
+                .to_biguint()
+                .try_into()
+                .unwrap(),
+        );
+    }
+
+    compare_outputs(
+        &program.1,
+        &program.2.find_function("run_test").unwrap().id,
+        &result_vm,
+        &result_native,
+    )
+    .unwrap();
+    assert_eq!(
+        builtin_stats,
+        vm_builtin_stats(&result_vm, expected.segment_arena != 0)
+    );
+    assert_eq!(builtin_stats, expected);
+}
+
+#[test]
+fn builtin_stats_u8_add() {
+    // Two `u8_try_from_felt252` (2 each) and one `u8_overflowing_add` (1).
+    check_builtin_stats(
+        &U8_ADD,
+        &[Felt::from(4), Felt::from(5)],
+        BuiltinStats {
+            range_check: 5,
+            ..Default::default()
+        },
+    );
+}
+
+#[test]
+fn builtin_stats_u128_bitwise() {
+    // Two `u128s_from_felt252` (1 each) and one `bitwise`.
+    check_builtin_stats(
+        &U128_BITWISE,
+        &[Felt::from(12), Felt::from(10)],
+        BuiltinStats {
+            range_check: 2,
+            bitwise: 1,
+            ..Default::default()
+        },
+    );
+}
+
+#[test]
+fn builtin_stats_pedersen() {
+    check_builtin_stats(
+        &PEDERSEN,
+        &[Felt::from(1), Felt::from(2)],
+        BuiltinStats {
+            pedersen: 1,
+            ..Default::default()
+        },
+    );
+}
+
+#[test]
+fn builtin_stats_poseidon() {
+    check_builtin_stats(
+        &POSEIDON,
+        &[Felt::from(1), Felt::from(2), Felt::from(3)],
+        BuiltinStats {
+            poseidon: 1,
+            ..Default::default()
+        },
+    );
+}
+
+#[test]
+fn builtin_stats_ec_op() {
+    // One `ec_point_from_x_nz` (3) and one `ec_state_add_mul`.
+    check_builtin_stats(
+        &EC_OP,
+        &[
+            Felt::from_dec_str(
+                "874739451078007766457464989774322083649278607533249481151382481072868806602",
+            )
+            .unwrap(),
+            Felt::from(2),
+        ],
+        BuiltinStats {
+            range_check: 3,
+            ec_op: 1,
+            ..Default::default()
+        },
+    );
+}
+
+#[test]
+fn builtin_stats_dict_small_keys() {
+    // One `felt252_dict_new` and one `felt252_dict_squash` (segment arena), and the squash of 2 keys
+    // and 4 accesses: 3 + 2 * keys + repeated accesses + (keys - 1) range checks, as computed by the
+    // runtime's `cairo_native__dict_squash_range_checks`.
+    check_builtin_stats_inner(
+        &DICT,
+        &[Felt::from(1), Felt::from(2)],
+        BuiltinStats {
+            range_check: 10,
+            segment_arena: 2,
+            ..Default::default()
+        },
+        false,
+    );
+}
+
+#[test]
+fn builtin_stats_dict_repeated_keys() {
+    // Both arguments are the same key, so the squash has a single key with 4 accesses (3 of them
+    // repeated) and no key transitions: 3 + 2 * 1 + 3 + 0 range checks.
+    check_builtin_stats_inner(
+        &DICT,
+        &[Felt::from(1), Felt::from(1)],
+        BuiltinStats {
+            range_check: 8,
+            segment_arena: 2,
+            ..Default::default()
+        },
+        false,
+    );
+}
+
+#[test]
+fn builtin_stats_dict_big_keys() {
+    // With keys above 2^128 the squash checks every key transition with 4 range checks instead of
+    // one, and skips the check of the first key: 2 + 2 * keys + repeated accesses + 4 * transitions.
+    check_builtin_stats_inner(
+        &DICT,
+        &[
+            Felt::from(1),
+            Felt::from_dec_str("1606938044258990275541962092341162602522202993782792835301376")
+                .unwrap(),
+        ],
+        BuiltinStats {
+            range_check: 12,
+            segment_arena: 2,
+            ..Default::default()
+        },
+        false,
+    );
+}
+
+#[test]
+fn builtin_stats_disabled() {
+    let result = run_native_program(
+        &U8_ADD,
+        "run_test",
+        &[
+            JitValue::Felt252(Felt::from(4)),
+            JitValue::Felt252(Felt::from(5)),
+        ],
+        Some(DEFAULT_GAS as u128),
+    );
+
+    assert_eq!(result.builtin_stats, BuiltinStats::default());
+}
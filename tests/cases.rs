@@ -75,6 +75,8 @@ mod common;
 #[test_case("tests/cases/structs/struct_snapshot_deconstruct.cairo")]
 // gas
 #[test_case("tests/cases/gas/available_gas.cairo")]
+#[test_case("tests/cases/gas/redeposit_gas.cairo")]
+#[test_case("tests/cases/gas/redeposit_gas_branches.cairo")]
 // bool
 #[test_case("tests/cases/bool/and.cairo")]
 #[test_case("tests/cases/bool/eq.cairo")]
@@ -0,0 +1,6 @@
+use gas::redeposit_gas;
+
+fn main() -> u128 {
+    redeposit_gas();
+    testing::get_available_gas()
+}
@@ -0,0 +1,24 @@
+use gas::redeposit_gas;
+
+fn heavy(mut value: felt252) -> felt252 {
+    value = value * value + 1;
+    value = value * value + 2;
+    value = value * value + 3;
+    value = value * value + 4;
+    value
+}
+
+fn branch(value: felt252) -> felt252 {
+    if value == 0 {
+        redeposit_gas();
+        value
+    } else {
+        heavy(value)
+    }
+}
+
+fn main() -> (felt252, felt252, u128) {
+    let a = branch(0);
+    let b = branch(5);
+    (a, b, testing::get_available_gas())
+}
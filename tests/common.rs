@@ -24,6 +24,7 @@ use cairo_native::{
     execution_result::{ContractExecutionResult, ExecutionResult},
     executor::JitNativeExecutor,
     metadata::{
+        builtin_counters::BuiltinCountersMeta,
         gas::{GasMetadata, MetadataComputationConfig},
         runtime_bindings::RuntimeBindingsMeta,
         syscall_handler::SyscallHandlerMeta,
@@ -32,8 +33,9 @@ use cairo_native::{
     module::NativeModule,
     starknet::StarkNetSyscallHandler,
     types::felt252::{HALF_PRIME, PRIME},
-    utils::{find_entry_point_by_idx, run_pass_manager},
+    utils::{find_entry_point_by_idx, run_pass_manager, PassPipeline},
     values::JitValue,
+    OptLevel,
 };
 use lambdaworks_math::{
     field::{
@@ -190,6 +192,48 @@ pub fn run_native_program(
     entry_point: &str,
     args: &[JitValue],
     gas: Option<u128>,
+) -> ExecutionResult {
+    run_native_program_impl(program, entry_point, args, gas, false, None)
+}
+
+/// Runs the program using cairo-native JIT with the builtin counters enabled.
+pub fn run_native_program_with_builtin_counters(
+    program: &(String, Program, SierraCasmRunner),
+    entry_point: &str,
+    args: &[JitValue],
+    gas: Option<u128>,
+) -> ExecutionResult {
+    run_native_program_impl(program, entry_point, args, gas, true, None)
+}
+
+/// Runs the program using cairo-native JIT with the given syscall handler.
+pub fn run_native_program_with_syscall_handler<T>(
+    program: &(String, Program, SierraCasmRunner),
+    entry_point: &str,
+    args: &[JitValue],
+    gas: Option<u128>,
+    handler: &mut T,
+) -> ExecutionResult
+where
+    T: StarkNetSyscallHandler,
+{
+    run_native_program_impl(
+        program,
+        entry_point,
+        args,
+        gas,
+        false,
+        Some(&SyscallHandlerMeta::new(handler)),
+    )
+}
+
+fn run_native_program_impl(
+    program: &(String, Program, SierraCasmRunner),
+    entry_point: &str,
+    args: &[JitValue],
+    gas: Option<u128>,
+    builtin_counters: bool,
+    syscall_handler: Option<&SyscallHandlerMeta>,
 ) -> ExecutionResult {
     let entry_point = format!("{0}::{0}::{1}", program.0, entry_point);
     let program = &program.1;
@@ -222,6 +266,12 @@ pub fn run_native_program(
         program,
         MetadataComputationConfig::default(),
     ));
+    if builtin_counters {
+        metadata.insert(
+            BuiltinCountersMeta::new(program, &registry)
+                .expect("Could not compute the builtin counters."),
+        );
+    }
 
     cairo_native::compile::<CoreType, CoreLibfunc>(
         &context,
@@ -239,13 +289,17 @@ pub fn run_native_program(
         module.as_operation()
     );
 
-    run_pass_manager(&context, &mut module)
-        .expect("Could not apply passes to the compiled test program.");
+    run_pass_manager(
+        &context,
+        &mut module,
+        &PassPipeline::from(OptLevel::default()),
+    )
+    .expect("Could not apply passes to the compiled test program.");
 
     let native_module = NativeModule::new(module, registry, metadata);
-    let executor = JitNativeExecutor::new(native_module);
+    let executor = JitNativeExecutor::new(native_module, OptLevel::default());
     executor
-        .invoke_dynamic(entry_point_id, args, gas, None)
+        .invoke_dynamic(entry_point_id, args, gas, syscall_handler)
         .unwrap()
 }
 
@@ -294,12 +348,14 @@ where
 {
     let native_context = NativeContext::new();
 
-    let native_program = native_context.compile(sierra_program).unwrap();
+    let native_program = native_context
+        .compile(sierra_program, OptLevel::default())
+        .unwrap();
 
     let entry_point_fn = find_entry_point_by_idx(sierra_program, entry_point_function_idx).unwrap();
     let entry_point_id = &entry_point_fn.id;
 
-    let native_executor = JitNativeExecutor::new(native_program);
+    let native_executor = JitNativeExecutor::new(native_program, OptLevel::default());
 
     native_executor
         .invoke_contract_dynamic(
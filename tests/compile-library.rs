@@ -2,7 +2,11 @@ use cairo_lang_sierra::{
     extensions::core::{CoreLibfunc, CoreType},
     program_registry::ProgramRegistry,
 };
-use cairo_native::metadata::{runtime_bindings::RuntimeBindingsMeta, MetadataStorage};
+use cairo_native::{
+    metadata::{runtime_bindings::RuntimeBindingsMeta, MetadataStorage},
+    utils::{find_function_id, generate_function_name},
+    InMemoryObject, LinkError, LinkerConfig, OptLevel,
+};
 use common::load_cairo;
 use melior::{
     dialect::DialectRegistry,
@@ -16,8 +20,7 @@ use tempfile::NamedTempFile;
 
 mod common;
 
-#[test]
-pub fn compile_library() -> Result<(), Box<dyn Error>> {
+fn compile_object() -> Result<(String, Vec<u8>), Box<dyn Error>> {
     // Load the program.
     let context = Context::new();
 
@@ -66,10 +69,58 @@ pub fn compile_library() -> Result<(), Box<dyn Error>> {
     pass_manager.add_pass(pass::conversion::create_reconcile_unrealized_casts());
     pass_manager.run(&mut module)?;
 
-    let object = cairo_native::module_to_object(&module)?;
+    let object = cairo_native::module_to_object(&module, OptLevel::default())?;
+
+    let entry_point = find_function_id(&program.1, &format!("{0}::{0}::run_test", program.0));
+    let symbol = format!("_mlir_ciface_{}", generate_function_name(entry_point));
+
+    Ok((symbol, object))
+}
+
+#[test]
+pub fn compile_library() -> Result<(), Box<dyn Error>> {
+    let (_, object) = compile_object()?;
 
     let file = NamedTempFile::new()?.into_temp_path();
     cairo_native::object_to_shared_lib(&object, &file)?;
 
     Ok(())
 }
+
+#[test]
+pub fn compile_library_with_config() -> Result<(), Box<dyn Error>> {
+    let (_, object) = compile_object()?;
+
+    let file = NamedTempFile::new()?.into_temp_path();
+    let config = LinkerConfig::default().with_arg("-O1");
+    cairo_native::object_to_shared_lib_with_config(&object, &file, &config)?;
+
+    Ok(())
+}
+
+#[test]
+pub fn compile_library_missing_linker() -> Result<(), Box<dyn Error>> {
+    let (_, object) = compile_object()?;
+
+    let file = NamedTempFile::new()?.into_temp_path();
+    let config = LinkerConfig::default().with_linker("/nonexistent/ld");
+    let result = cairo_native::object_to_shared_lib_with_config(&object, &file, &config);
+    assert!(matches!(result, Err(LinkError::Spawn { .. })));
+
+    let config = LinkerConfig::default().with_arg("--this-flag-does-not-exist");
+    let result = cairo_native::object_to_shared_lib_with_config(&object, &file, &config);
+    assert!(matches!(result, Err(LinkError::Failed { .. })));
+
+    Ok(())
+}
+
+#[test]
+pub fn load_object_in_memory() -> Result<(), Box<dyn Error>> {
+    let (symbol, object) = compile_object()?;
+
+    let object = InMemoryObject::load(&object)?;
+    assert!(!object.lookup(&symbol)?.is_null());
+    assert!(object.lookup("this_symbol_does_not_exist").is_err());
+
+    Ok(())
+}
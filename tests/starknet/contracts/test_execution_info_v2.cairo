@@ -0,0 +1,44 @@
+#[starknet::interface]
+trait IExecutionInfoV2<TContractState> {
+    fn get_tx_info_v2(self: @TContractState) -> Array<felt252>;
+}
+
+#[starknet::contract]
+mod ExecutionInfoV2 {
+    use array::{ArrayTrait, SpanTrait};
+    use box::BoxTrait;
+    use core::traits::Into;
+    use starknet::SyscallResultTrait;
+    use starknet::syscalls::get_execution_info_v2_syscall;
+
+    #[storage]
+    struct Storage {}
+
+    #[external(v0)]
+    impl ExecutionInfoV2 of super::IExecutionInfoV2<ContractState> {
+        fn get_tx_info_v2(self: @ContractState) -> Array<felt252> {
+            let info = get_execution_info_v2_syscall().unwrap_syscall().unbox();
+            let tx_info = info.tx_info.unbox();
+            let resource_bounds = *tx_info.resource_bounds.at(0);
+
+            let mut result = ArrayTrait::new();
+            result.append(info.block_info.unbox().block_number.into());
+            result.append(tx_info.max_fee.into());
+            result.append(tx_info.nonce);
+            result.append(tx_info.resource_bounds.len().into());
+            result.append(resource_bounds.resource);
+            result.append(resource_bounds.max_amount.into());
+            result.append(resource_bounds.max_price_per_unit.into());
+            result.append(tx_info.tip.into());
+            result.append(tx_info.paymaster_data.len().into());
+            result.append(*tx_info.paymaster_data.at(0));
+            result.append(tx_info.nonce_data_availability_mode.into());
+            result.append(tx_info.fee_data_availability_mode.into());
+            result.append(tx_info.account_deployment_data.len().into());
+            result.append(*tx_info.account_deployment_data.at(0));
+            result.append(*tx_info.account_deployment_data.at(1));
+            result.append(info.entry_point_selector);
+            result
+        }
+    }
+}
@@ -0,0 +1,55 @@
+#[starknet::interface]
+trait ISecp256<TContractState> {
+    fn secp256k1_test(self: @TContractState) -> (u256, u256);
+    fn secp256r1_test(self: @TContractState) -> (u256, u256);
+}
+
+#[starknet::contract]
+mod Secp256 {
+    use option::OptionTrait;
+    use starknet::SyscallResultTrait;
+    use starknet::secp256k1::{
+        secp256k1_add_syscall, secp256k1_get_point_from_x_syscall, secp256k1_get_xy_syscall,
+        secp256k1_mul_syscall, secp256k1_new_syscall
+    };
+    use starknet::secp256r1::{
+        secp256r1_add_syscall, secp256r1_get_point_from_x_syscall, secp256r1_get_xy_syscall,
+        secp256r1_mul_syscall, secp256r1_new_syscall
+    };
+
+    #[storage]
+    struct Storage {}
+
+    #[external(v0)]
+    impl Secp256 of super::ISecp256<ContractState> {
+        fn secp256k1_test(self: @ContractState) -> (u256, u256) {
+            let x = 0x79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798;
+            let y = 0x483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8;
+
+            assert(secp256k1_new_syscall(1, 2).unwrap_syscall().is_none(), 'expected none');
+
+            let p0 = secp256k1_new_syscall(x, y).unwrap_syscall().unwrap();
+            let p1 = secp256k1_get_point_from_x_syscall(x, false).unwrap_syscall().unwrap();
+
+            let p = secp256k1_add_syscall(p0, p1).unwrap_syscall();
+            let p = secp256k1_mul_syscall(p, 3).unwrap_syscall();
+
+            secp256k1_get_xy_syscall(p).unwrap_syscall()
+        }
+
+        fn secp256r1_test(self: @ContractState) -> (u256, u256) {
+            let x = 0x6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296;
+            let y = 0x4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5;
+
+            assert(secp256r1_new_syscall(1, 2).unwrap_syscall().is_none(), 'expected none');
+
+            let p0 = secp256r1_new_syscall(x, y).unwrap_syscall().unwrap();
+            let p1 = secp256r1_get_point_from_x_syscall(x, true).unwrap_syscall().unwrap();
+
+            let p = secp256r1_add_syscall(p0, p1).unwrap_syscall();
+            let p = secp256r1_mul_syscall(p, 3).unwrap_syscall();
+
+            secp256r1_get_xy_syscall(p).unwrap_syscall()
+        }
+    }
+}
@@ -0,0 +1,50 @@
+#[starknet::interface]
+trait ITesting<TContractState> {
+    fn testing_test(self: @TContractState) -> Span<felt252>;
+}
+
+#[starknet::contract]
+mod Testing {
+    use array::{ArrayTrait, SpanTrait};
+    use box::BoxTrait;
+    use option::OptionTrait;
+    use starknet::{
+        contract_address_const, get_block_info, get_caller_address, get_contract_address,
+        get_tx_info, syscalls::emit_event_syscall, SyscallResultTrait,
+    };
+    use starknet::testing::{pop_log, set_block_number, set_caller_address, set_signature};
+    use traits::Into;
+
+    #[storage]
+    struct Storage {}
+
+    #[external(v0)]
+    impl Testing of super::ITesting<ContractState> {
+        fn testing_test(self: @ContractState) -> Span<felt252> {
+            set_block_number(1234);
+            set_caller_address(contract_address_const::<5678>());
+            set_signature(array![9, 10].span());
+
+            emit_event_syscall(array![1].span(), array![2, 3].span()).unwrap_syscall();
+            let (keys, data) = pop_log(get_contract_address()).unwrap();
+
+            let block_number: felt252 = get_block_info().unbox().block_number.into();
+            let caller_address: felt252 = get_caller_address().into();
+            let signature = get_tx_info().unbox().signature;
+            let no_more_logs = pop_log(get_contract_address()).is_none();
+
+            array![
+                block_number,
+                caller_address,
+                signature.len().into(),
+                *signature.at(1),
+                keys.len().into(),
+                *keys.at(0),
+                data.len().into(),
+                *data.at(1),
+                no_more_logs.into(),
+            ]
+                .span()
+        }
+    }
+}
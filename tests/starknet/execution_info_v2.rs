@@ -0,0 +1,163 @@
+use cairo_lang_compiler::CompilerConfig;
+use cairo_lang_starknet::contract_class::{compile_path, ContractClass};
+use cairo_native::starknet::{
+    BlockInfo, ExecutionInfo, ExecutionInfoV2, ResourceBounds, StarkNetSyscallHandler,
+    SyscallResult, TxInfoV2, U256,
+};
+use lazy_static::lazy_static;
+use starknet_types_core::felt::Felt;
+use std::path::Path;
+
+use crate::common::run_native_starknet_contract;
+
+#[derive(Debug)]
+struct SyscallHandler;
+
+impl StarkNetSyscallHandler for SyscallHandler {
+    fn get_block_hash(&mut self, _block_number: u64, _gas: &mut u128) -> SyscallResult<Felt> {
+        todo!()
+    }
+
+    fn get_execution_info(&mut self, _gas: &mut u128) -> SyscallResult<ExecutionInfo> {
+        todo!()
+    }
+
+    fn get_execution_info_v2(&mut self, _gas: &mut u128) -> SyscallResult<ExecutionInfoV2> {
+        Ok(ExecutionInfoV2 {
+            block_info: BlockInfo {
+                block_number: 1234,
+                block_timestamp: 2345,
+                sequencer_address: 3456.into(),
+            },
+            tx_info: TxInfoV2 {
+                version: 4567.into(),
+                account_contract_address: 5678.into(),
+                max_fee: 6789,
+                signature: vec![1248.into(), 2486.into()],
+                transaction_hash: 9876.into(),
+                chain_id: 8765.into(),
+                nonce: 7654.into(),
+                resource_bounds: vec![ResourceBounds {
+                    resource: 2345.into(),
+                    max_amount: 3456,
+                    max_price_per_unit: 4567,
+                }],
+                tip: 5678,
+                paymaster_data: vec![6789.into()],
+                nonce_data_availability_mode: 0,
+                fee_data_availability_mode: 1,
+                account_deployment_data: vec![7890.into(), 8901.into()],
+            },
+            caller_address: 6543.into(),
+            contract_address: 5432.into(),
+            entry_point_selector: 4321.into(),
+        })
+    }
+
+    fn deploy(
+        &mut self,
+        _class_hash: Felt,
+        _contract_address_salt: Felt,
+        _calldata: &[Felt],
+        _deploy_from_zero: bool,
+        _gas: &mut u128,
+    ) -> SyscallResult<(Felt, Vec<Felt>)> {
+        todo!()
+    }
+
+    fn replace_class(&mut self, _class_hash: Felt, _gas: &mut u128) -> SyscallResult<()> {
+        todo!()
+    }
+
+    fn library_call(
+        &mut self,
+        _class_hash: Felt,
+        _function_selector: Felt,
+        _calldata: &[Felt],
+        _gas: &mut u128,
+    ) -> SyscallResult<Vec<Felt>> {
+        todo!()
+    }
+
+    fn call_contract(
+        &mut self,
+        _address: Felt,
+        _entry_point_selector: Felt,
+        _calldata: &[Felt],
+        _gas: &mut u128,
+    ) -> SyscallResult<Vec<Felt>> {
+        todo!()
+    }
+
+    fn storage_read(
+        &mut self,
+        _address_domain: u32,
+        _address: Felt,
+        _gas: &mut u128,
+    ) -> SyscallResult<Felt> {
+        todo!()
+    }
+
+    fn storage_write(
+        &mut self,
+        _address_domain: u32,
+        _address: Felt,
+        _value: Felt,
+        _gas: &mut u128,
+    ) -> SyscallResult<()> {
+        todo!()
+    }
+
+    fn emit_event(&mut self, _keys: &[Felt], _data: &[Felt], _gas: &mut u128) -> SyscallResult<()> {
+        todo!()
+    }
+
+    fn send_message_to_l1(
+        &mut self,
+        _to_address: Felt,
+        _payload: &[Felt],
+        _gas: &mut u128,
+    ) -> SyscallResult<()> {
+        todo!()
+    }
+
+    fn keccak(&mut self, _input: &[u64], _gas: &mut u128) -> SyscallResult<U256> {
+        todo!()
+    }
+}
+
+lazy_static! {
+    static ref EXECUTION_INFO_V2_CONTRACT: ContractClass = {
+        let path = Path::new("tests/starknet/contracts/test_execution_info_v2.cairo");
+
+        compile_path(
+            path,
+            None,
+            CompilerConfig {
+                replace_ids: true,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+    };
+}
+
+#[test]
+fn get_execution_info_v2_test() {
+    let contract = &EXECUTION_INFO_V2_CONTRACT;
+
+    let entry_point = contract.entry_points_by_type.external.get(0).unwrap();
+
+    let program = contract.extract_sierra_program().unwrap();
+    let result =
+        run_native_starknet_contract(&program, entry_point.function_idx, &[], &mut SyscallHandler);
+
+    assert!(!result.failure_flag);
+    assert_eq!(
+        result.return_values,
+        [16, 1234, 6789, 7654, 1, 2345, 3456, 4567, 5678, 1, 6789, 0, 1, 2, 7890, 8901, 4321]
+            .into_iter()
+            .map(Felt::from)
+            .collect::<Vec<_>>(),
+    );
+}
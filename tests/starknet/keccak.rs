@@ -1,11 +1,19 @@
 use cairo_lang_compiler::CompilerConfig;
 use cairo_lang_starknet::contract_class::{compile_path, ContractClass};
-use cairo_native::starknet::{
-    BlockInfo, ExecutionInfo, StarkNetSyscallHandler, SyscallResult, TxInfo, U256,
+use cairo_native::{
+    cache::DiskCache,
+    context::NativeContext,
+    executor::{ContractExecutor, EntryPointType},
+    metadata::syscall_handler::SyscallHandlerMeta,
+    starknet::{
+        BlockInfo, ExecutionInfo, ExecutionInfoV2, ResourceBounds, StarkNetSyscallHandler,
+        SyscallResult, TxInfo, TxInfoV2, U256,
+    },
+    OptLevel,
 };
 use lazy_static::lazy_static;
 use starknet_types_core::felt::Felt;
-use std::path::Path;
+use std::{num::NonZeroUsize, path::Path};
 
 use crate::common::run_native_starknet_contract;
 
@@ -44,6 +52,42 @@ impl StarkNetSyscallHandler for SyscallHandler {
         })
     }
 
+    fn get_execution_info_v2(
+        &mut self,
+        _gas: &mut u128,
+    ) -> SyscallResult<cairo_native::starknet::ExecutionInfoV2> {
+        println!("Called `get_execution_info_v2()` from MLIR.");
+        Ok(ExecutionInfoV2 {
+            block_info: BlockInfo {
+                block_number: 1234,
+                block_timestamp: 2345,
+                sequencer_address: 3456.into(),
+            },
+            tx_info: TxInfoV2 {
+                version: 4567.into(),
+                account_contract_address: 5678.into(),
+                max_fee: 6789,
+                signature: vec![1248.into(), 2486.into()],
+                transaction_hash: 9876.into(),
+                chain_id: 8765.into(),
+                nonce: 7654.into(),
+                resource_bounds: vec![ResourceBounds {
+                    resource: 2345.into(),
+                    max_amount: 3456,
+                    max_price_per_unit: 4567,
+                }],
+                tip: 5678,
+                paymaster_data: vec![6789.into()],
+                nonce_data_availability_mode: 0,
+                fee_data_availability_mode: 1,
+                account_deployment_data: vec![7890.into(), 8901.into()],
+            },
+            caller_address: 6543.into(),
+            contract_address: 5432.into(),
+            entry_point_selector: 4321.into(),
+        })
+    }
+
     fn deploy(
         &mut self,
         class_hash: Felt,
@@ -135,146 +179,6 @@ impl StarkNetSyscallHandler for SyscallHandler {
         *gas -= 1000;
         Ok(U256(Felt::from(1234567890).to_bytes_le()))
     }
-
-    fn secp256k1_add(
-        &mut self,
-        _p0: cairo_native::starknet::Secp256k1Point,
-        _p1: cairo_native::starknet::Secp256k1Point,
-        _gas: &mut u128,
-    ) -> SyscallResult<Option<cairo_native::starknet::Secp256k1Point>> {
-        todo!()
-    }
-
-    fn secp256k1_get_point_from_x(
-        &self,
-        _x: cairo_native::starknet::U256,
-        _y_parity: bool,
-        _gas: &mut u128,
-    ) -> SyscallResult<Option<cairo_native::starknet::Secp256k1Point>> {
-        todo!()
-    }
-
-    fn secp256k1_get_xy(
-        &self,
-        _p: cairo_native::starknet::Secp256k1Point,
-        _gas: &mut u128,
-    ) -> SyscallResult<(cairo_native::starknet::U256, cairo_native::starknet::U256)> {
-        todo!()
-    }
-
-    fn secp256k1_mul(
-        &self,
-        _p: cairo_native::starknet::Secp256k1Point,
-        _m: cairo_native::starknet::U256,
-        _gas: &mut u128,
-    ) -> SyscallResult<Option<cairo_native::starknet::Secp256k1Point>> {
-        todo!()
-    }
-
-    fn secp256k1_new(
-        &self,
-        _x: cairo_native::starknet::U256,
-        _y: cairo_native::starknet::U256,
-        _gas: &mut u128,
-    ) -> SyscallResult<Option<cairo_native::starknet::Secp256k1Point>> {
-        todo!()
-    }
-
-    fn secp256r1_add(
-        &self,
-        _p0: cairo_native::starknet::Secp256k1Point,
-        _p1: cairo_native::starknet::Secp256k1Point,
-        _gas: &mut u128,
-    ) -> SyscallResult<Option<cairo_native::starknet::Secp256k1Point>> {
-        todo!()
-    }
-
-    fn secp256r1_get_point_from_x(
-        &self,
-        _x: cairo_native::starknet::U256,
-        _y_parity: bool,
-        _gas: &mut u128,
-    ) -> SyscallResult<Option<cairo_native::starknet::Secp256k1Point>> {
-        todo!()
-    }
-
-    fn secp256r1_get_xy(
-        &self,
-        _p: cairo_native::starknet::Secp256k1Point,
-        _gas: &mut u128,
-    ) -> SyscallResult<(cairo_native::starknet::U256, cairo_native::starknet::U256)> {
-        todo!()
-    }
-
-    fn secp256r1_mul(
-        &self,
-        _p: cairo_native::starknet::Secp256k1Point,
-        _m: cairo_native::starknet::U256,
-        _gas: &mut u128,
-    ) -> SyscallResult<Option<cairo_native::starknet::Secp256k1Point>> {
-        todo!()
-    }
-
-    fn secp256r1_new(
-        &mut self,
-        _x: cairo_native::starknet::U256,
-        _y: cairo_native::starknet::U256,
-        _gas: &mut u128,
-    ) -> SyscallResult<Option<cairo_native::starknet::Secp256k1Point>> {
-        todo!()
-    }
-
-    fn pop_log(&mut self) {
-        todo!()
-    }
-
-    fn set_account_contract_address(&mut self, _contract_address: Felt) {
-        todo!()
-    }
-
-    fn set_block_number(&mut self, _block_number: u64) {
-        todo!()
-    }
-
-    fn set_block_timestamp(&mut self, _block_timestamp: u64) {
-        todo!()
-    }
-
-    fn set_caller_address(&mut self, _address: Felt) {
-        todo!()
-    }
-
-    fn set_chain_id(&mut self, _chain_id: Felt) {
-        todo!()
-    }
-
-    fn set_contract_address(&mut self, _address: Felt) {
-        todo!()
-    }
-
-    fn set_max_fee(&mut self, _max_fee: u128) {
-        todo!()
-    }
-
-    fn set_nonce(&mut self, _nonce: Felt) {
-        todo!()
-    }
-
-    fn set_sequencer_address(&mut self, _address: Felt) {
-        todo!()
-    }
-
-    fn set_signature(&mut self, _signature: &[Felt]) {
-        todo!()
-    }
-
-    fn set_transaction_hash(&mut self, _transaction_hash: Felt) {
-        todo!()
-    }
-
-    fn set_version(&mut self, _version: Felt) {
-        todo!()
-    }
 }
 
 lazy_static! {
@@ -310,3 +214,69 @@ fn keccak_test() {
     );
     assert_eq!(result.return_values, vec![1.into()]);
 }
+
+#[test]
+fn keccak_contract_executor() {
+    let contract: ContractClass =
+        serde_json::from_str(&serde_json::to_string(&*KECCAK_CONTRACT).unwrap()).unwrap();
+    let selector = &contract
+        .entry_points_by_type
+        .external
+        .get(0)
+        .unwrap()
+        .selector;
+    let selector = Felt::from_bytes_be_slice(&selector.to_bytes_be());
+
+    let context = NativeContext::new();
+    let parallel_context = NativeContext::new().with_jobs(NonZeroUsize::new(2).unwrap());
+    let cache_dir = tempfile::tempdir().unwrap();
+    let disk_cache = DiskCache::open(cache_dir.path()).unwrap();
+    let executors = [
+        ContractExecutor::new_jit(&context, &contract, OptLevel::default()).unwrap(),
+        ContractExecutor::new_aot(&context, &contract, OptLevel::default()).unwrap(),
+        ContractExecutor::new_aot(&parallel_context, &contract, OptLevel::default()).unwrap(),
+        // The second one is loaded from the disk cache.
+        ContractExecutor::new_aot_with_disk_cache(
+            &context,
+            &disk_cache,
+            &contract,
+            OptLevel::default(),
+        )
+        .unwrap(),
+        ContractExecutor::new_aot_with_disk_cache(
+            &context,
+            &disk_cache,
+            &contract,
+            OptLevel::default(),
+        )
+        .unwrap(),
+    ];
+
+    for executor in &executors {
+        assert_eq!(
+            executor.entry_point_type(&selector),
+            Some(EntryPointType::External)
+        );
+
+        let result = executor
+            .call(
+                &selector,
+                &[],
+                Some(u128::MAX),
+                Some(&SyscallHandlerMeta::new(&mut SyscallHandler)),
+            )
+            .unwrap();
+
+        assert!(!result.failure_flag);
+        assert_eq!(result.return_values, vec![1.into()]);
+
+        assert!(executor
+            .call(
+                &(selector + Felt::from(1)),
+                &[],
+                Some(u128::MAX),
+                Some(&SyscallHandlerMeta::new(&mut SyscallHandler)),
+            )
+            .is_err());
+    }
+}
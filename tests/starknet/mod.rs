@@ -1 +1,4 @@
+mod execution_info_v2;
 mod keccak;
+mod secp256;
+mod testing;
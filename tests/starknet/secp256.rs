@@ -0,0 +1,497 @@
+use ark_ec::{
+    short_weierstrass::{Affine, SWCurveConfig},
+    AffineRepr, CurveGroup,
+};
+use ark_ff::{BigInteger, PrimeField};
+use cairo_lang_compiler::CompilerConfig;
+use cairo_lang_runner::SierraCasmRunner;
+use cairo_lang_sierra::program::Program;
+use cairo_lang_starknet::contract_class::{compile_path, ContractClass};
+use cairo_native::{
+    execution_result::ExecutionResult,
+    starknet::{
+        unsupported_syscall, ExecutionInfo, ExecutionInfoV2, Secp256k1Point, Secp256r1Point,
+        StarkNetSyscallHandler, SyscallResult, U256,
+    },
+    values::JitValue,
+};
+use lazy_static::lazy_static;
+use num_bigint::BigUint;
+use num_traits::Zero;
+use starknet_types_core::felt::Felt;
+use std::path::Path;
+
+use crate::common::{
+    compare_outputs, load_cairo, run_native_program_with_syscall_handler,
+    run_native_starknet_contract, run_vm_program, DEFAULT_GAS,
+};
+
+/// Syscall handler implementing the secp256k1 and secp256r1 syscalls like the Cairo VM's runner
+/// does.
+///
+/// Points are passed by value, so there's no need to keep track of them.
+#[derive(Debug)]
+struct SyscallHandler;
+
+fn u256_to_biguint(value: &U256) -> BigUint {
+    BigUint::from_bytes_le(&value.0)
+}
+
+fn biguint_to_u256(value: BigUint) -> U256 {
+    let mut data = [0; 32];
+    let bytes = value.to_bytes_le();
+    data[..bytes.len()].copy_from_slice(&bytes);
+    U256(data)
+}
+
+fn to_affine<C>(x: &U256, y: &U256) -> Affine<C>
+where
+    C: SWCurveConfig,
+    C::BaseField: PrimeField,
+{
+    let (x, y) = (u256_to_biguint(x), u256_to_biguint(y));
+    if x.is_zero() && y.is_zero() {
+        Affine::identity()
+    } else {
+        Affine::new_unchecked(x.into(), y.into())
+    }
+}
+
+fn from_affine<C>(p: Affine<C>) -> (U256, U256)
+where
+    C: SWCurveConfig,
+    C::BaseField: PrimeField,
+{
+    if p.infinity {
+        (U256([0; 32]), U256([0; 32]))
+    } else {
+        (biguint_to_u256(p.x.into()), biguint_to_u256(p.y.into()))
+    }
+}
+
+fn out_of_range<T>() -> SyscallResult<T> {
+    Err(vec![Felt::from_bytes_be_slice(b"Coordinates out of range")])
+}
+
+fn curve_new<C>(x: U256, y: U256) -> SyscallResult<Option<(U256, U256)>>
+where
+    C: SWCurveConfig,
+    C::BaseField: PrimeField,
+{
+    let modulus: BigUint = C::BaseField::MODULUS.into();
+    if u256_to_biguint(&x) >= modulus || u256_to_biguint(&y) >= modulus {
+        return out_of_range();
+    }
+
+    let p = to_affine::<C>(&x, &y);
+    Ok((p.is_on_curve() && p.is_in_correct_subgroup_assuming_on_curve()).then_some((x, y)))
+}
+
+fn curve_add<C>(p0: (&U256, &U256), p1: (&U256, &U256)) -> (U256, U256)
+where
+    C: SWCurveConfig,
+    C::BaseField: PrimeField,
+{
+    from_affine((to_affine::<C>(p0.0, p0.1) + to_affine::<C>(p1.0, p1.1)).into_affine())
+}
+
+fn curve_mul<C>(p: (&U256, &U256), m: &U256) -> (U256, U256)
+where
+    C: SWCurveConfig,
+    C::BaseField: PrimeField,
+{
+    let m = C::ScalarField::from(u256_to_biguint(m));
+    from_affine((to_affine::<C>(p.0, p.1) * m).into_affine())
+}
+
+fn curve_get_point_from_x<C>(x: U256, y_parity: bool) -> SyscallResult<Option<(U256, U256)>>
+where
+    C: SWCurveConfig,
+    C::BaseField: PrimeField,
+{
+    let x = u256_to_biguint(&x);
+    if x >= C::BaseField::MODULUS.into() {
+        return out_of_range();
+    }
+
+    let x = C::BaseField::from(x);
+    Ok(Affine::<C>::get_ys_from_x_unchecked(x)
+        .map(|(smaller, greater)| {
+            // Return the correct y coordinate based on the parity.
+            if smaller.into_bigint().is_odd() == y_parity {
+                smaller
+            } else {
+                greater
+            }
+        })
+        .map(|y| Affine::<C>::new_unchecked(x, y))
+        .filter(|p| p.is_in_correct_subgroup_assuming_on_curve())
+        .map(from_affine))
+}
+
+impl StarkNetSyscallHandler for SyscallHandler {
+    fn get_block_hash(&mut self, _block_number: u64, _gas: &mut u128) -> SyscallResult<Felt> {
+        unsupported_syscall()
+    }
+
+    fn get_execution_info(&mut self, _gas: &mut u128) -> SyscallResult<ExecutionInfo> {
+        unsupported_syscall()
+    }
+
+    fn get_execution_info_v2(&mut self, _gas: &mut u128) -> SyscallResult<ExecutionInfoV2> {
+        unsupported_syscall()
+    }
+
+    fn deploy(
+        &mut self,
+        _class_hash: Felt,
+        _contract_address_salt: Felt,
+        _calldata: &[Felt],
+        _deploy_from_zero: bool,
+        _gas: &mut u128,
+    ) -> SyscallResult<(Felt, Vec<Felt>)> {
+        unsupported_syscall()
+    }
+
+    fn replace_class(&mut self, _class_hash: Felt, _gas: &mut u128) -> SyscallResult<()> {
+        unsupported_syscall()
+    }
+
+    fn library_call(
+        &mut self,
+        _class_hash: Felt,
+        _function_selector: Felt,
+        _calldata: &[Felt],
+        _gas: &mut u128,
+    ) -> SyscallResult<Vec<Felt>> {
+        unsupported_syscall()
+    }
+
+    fn call_contract(
+        &mut self,
+        _address: Felt,
+        _entry_point_selector: Felt,
+        _calldata: &[Felt],
+        _gas: &mut u128,
+    ) -> SyscallResult<Vec<Felt>> {
+        unsupported_syscall()
+    }
+
+    fn storage_read(
+        &mut self,
+        _address_domain: u32,
+        _address: Felt,
+        _gas: &mut u128,
+    ) -> SyscallResult<Felt> {
+        unsupported_syscall()
+    }
+
+    fn storage_write(
+        &mut self,
+        _address_domain: u32,
+        _address: Felt,
+        _value: Felt,
+        _gas: &mut u128,
+    ) -> SyscallResult<()> {
+        unsupported_syscall()
+    }
+
+    fn emit_event(&mut self, _keys: &[Felt], _data: &[Felt], _gas: &mut u128) -> SyscallResult<()> {
+        unsupported_syscall()
+    }
+
+    fn send_message_to_l1(
+        &mut self,
+        _to_address: Felt,
+        _payload: &[Felt],
+        _gas: &mut u128,
+    ) -> SyscallResult<()> {
+        unsupported_syscall()
+    }
+
+    fn keccak(&mut self, _input: &[u64], _gas: &mut u128) -> SyscallResult<U256> {
+        unsupported_syscall()
+    }
+
+    fn secp256k1_new(
+        &mut self,
+        x: U256,
+        y: U256,
+        _gas: &mut u128,
+    ) -> SyscallResult<Option<Secp256k1Point>> {
+        curve_new::<ark_secp256k1::Config>(x, y).map(|p| p.map(|(x, y)| Secp256k1Point { x, y }))
+    }
+
+    fn secp256k1_add(
+        &mut self,
+        p0: Secp256k1Point,
+        p1: Secp256k1Point,
+        _gas: &mut u128,
+    ) -> SyscallResult<Secp256k1Point> {
+        let (x, y) = curve_add::<ark_secp256k1::Config>((&p0.x, &p0.y), (&p1.x, &p1.y));
+        Ok(Secp256k1Point { x, y })
+    }
+
+    fn secp256k1_mul(
+        &mut self,
+        p: Secp256k1Point,
+        m: U256,
+        _gas: &mut u128,
+    ) -> SyscallResult<Secp256k1Point> {
+        let (x, y) = curve_mul::<ark_secp256k1::Config>((&p.x, &p.y), &m);
+        Ok(Secp256k1Point { x, y })
+    }
+
+    fn secp256k1_get_point_from_x(
+        &mut self,
+        x: U256,
+        y_parity: bool,
+        _gas: &mut u128,
+    ) -> SyscallResult<Option<Secp256k1Point>> {
+        curve_get_point_from_x::<ark_secp256k1::Config>(x, y_parity)
+            .map(|p| p.map(|(x, y)| Secp256k1Point { x, y }))
+    }
+
+    fn secp256k1_get_xy(
+        &mut self,
+        p: Secp256k1Point,
+        _gas: &mut u128,
+    ) -> SyscallResult<(U256, U256)> {
+        Ok((p.x, p.y))
+    }
+
+    fn secp256r1_new(
+        &mut self,
+        x: U256,
+        y: U256,
+        _gas: &mut u128,
+    ) -> SyscallResult<Option<Secp256r1Point>> {
+        curve_new::<ark_secp256r1::Config>(x, y).map(|p| p.map(|(x, y)| Secp256r1Point { x, y }))
+    }
+
+    fn secp256r1_add(
+        &mut self,
+        p0: Secp256r1Point,
+        p1: Secp256r1Point,
+        _gas: &mut u128,
+    ) -> SyscallResult<Secp256r1Point> {
+        let (x, y) = curve_add::<ark_secp256r1::Config>((&p0.x, &p0.y), (&p1.x, &p1.y));
+        Ok(Secp256r1Point { x, y })
+    }
+
+    fn secp256r1_mul(
+        &mut self,
+        p: Secp256r1Point,
+        m: U256,
+        _gas: &mut u128,
+    ) -> SyscallResult<Secp256r1Point> {
+        let (x, y) = curve_mul::<ark_secp256r1::Config>((&p.x, &p.y), &m);
+        Ok(Secp256r1Point { x, y })
+    }
+
+    fn secp256r1_get_point_from_x(
+        &mut self,
+        x: U256,
+        y_parity: bool,
+        _gas: &mut u128,
+    ) -> SyscallResult<Option<Secp256r1Point>> {
+        curve_get_point_from_x::<ark_secp256r1::Config>(x, y_parity)
+            .map(|p| p.map(|(x, y)| Secp256r1Point { x, y }))
+    }
+
+    fn secp256r1_get_xy(
+        &mut self,
+        p: Secp256r1Point,
+        _gas: &mut u128,
+    ) -> SyscallResult<(U256, U256)> {
+        Ok((p.x, p.y))
+    }
+}
+
+lazy_static! {
+    static ref SECP256_CONTRACT: ContractClass = {
+        let path = Path::new("tests/starknet/contracts/test_secp256.cairo");
+
+        compile_path(
+            path,
+            None,
+            CompilerConfig {
+                replace_ids: true,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+    };
+    static ref SECP256K1_OPS: (String, Program, SierraCasmRunner) = load_cairo! {
+        use option::OptionTrait;
+        use starknet::SyscallResultTrait;
+        use starknet::secp256k1::{
+            secp256k1_add_syscall, secp256k1_get_point_from_x_syscall, secp256k1_get_xy_syscall,
+            secp256k1_mul_syscall, secp256k1_new_syscall
+        };
+
+        fn run_test() -> (bool, u256, u256) {
+            let x = 0x79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798;
+            let y = 0x483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8;
+
+            let not_on_curve = secp256k1_new_syscall(1, 2).unwrap_syscall().is_none();
+            let p0 = secp256k1_new_syscall(x, y).unwrap_syscall().unwrap();
+            let p1 = secp256k1_get_point_from_x_syscall(x, true).unwrap_syscall().unwrap();
+
+            let p = secp256k1_add_syscall(p0, p0).unwrap_syscall();
+            let p = secp256k1_add_syscall(p, p1).unwrap_syscall();
+            let p = secp256k1_mul_syscall(p, 3).unwrap_syscall();
+
+            let (x, y) = secp256k1_get_xy_syscall(p).unwrap_syscall();
+            (not_on_curve, x, y)
+        }
+    };
+    static ref SECP256R1_OPS: (String, Program, SierraCasmRunner) = load_cairo! {
+        use option::OptionTrait;
+        use starknet::SyscallResultTrait;
+        use starknet::secp256r1::{
+            secp256r1_add_syscall, secp256r1_get_point_from_x_syscall, secp256r1_get_xy_syscall,
+            secp256r1_mul_syscall, secp256r1_new_syscall
+        };
+
+        fn run_test() -> (bool, u256, u256) {
+            let x = 0x6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296;
+            let y = 0x4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5;
+
+            let not_on_curve = secp256r1_new_syscall(1, 2).unwrap_syscall().is_none();
+            let p0 = secp256r1_new_syscall(x, y).unwrap_syscall().unwrap();
+            let p1 = secp256r1_get_point_from_x_syscall(x, false).unwrap_syscall().unwrap();
+
+            let p = secp256r1_add_syscall(p0, p0).unwrap_syscall();
+            let p = secp256r1_add_syscall(p, p1).unwrap_syscall();
+            let p = secp256r1_mul_syscall(p, 3).unwrap_syscall();
+
+            let (x, y) = secp256r1_get_xy_syscall(p).unwrap_syscall();
+            (not_on_curve, x, y)
+        }
+    };
+    static ref SECP256K1_OUT_OF_RANGE: (String, Program, SierraCasmRunner) = load_cairo! {
+        use array::ArrayTrait;
+        use starknet::secp256k1::secp256k1_new_syscall;
+
+        fn run_test() -> felt252 {
+            // The field's modulus.
+            let x = 0xfffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f;
+
+            match secp256k1_new_syscall(x, 0) {
+                Result::Ok(_) => 0,
+                Result::Err(err) => *err.at(0),
+            }
+        }
+    };
+}
+
+/// Run the program on both the Cairo VM and cairo-native and compare their results.
+#[track_caller]
+fn compare_secp256_program(program: &(String, Program, SierraCasmRunner)) -> ExecutionResult {
+    let result_vm = run_vm_program(program, "run_test", &[], Some(DEFAULT_GAS as usize)).unwrap();
+    let mut result_native = run_native_program_with_syscall_handler(
+        program,
+        "run_test",
+        &[],
+        Some(DEFAULT_GAS as u128),
+        &mut SyscallHandler,
+    );
+
+    // The test handler doesn't charge the syscalls' gas.
+    result_native.remaining_gas = Some(
+        result_vm
+            .gas_counter
+            .clone()
+            .unwrap()
+            .to_biguint()
+            .try_into()
+            .unwrap(),
+    );
+
+    compare_outputs(
+        &program.1,
+        &program.2.find_function("run_test").unwrap().id,
+        &result_vm,
+        &result_native,
+    )
+    .unwrap();
+
+    result_native
+}
+
+fn run_secp256_entry_point(name: &str) -> Vec<Felt> {
+    let contract = &SECP256_CONTRACT;
+    let program = contract.extract_sierra_program().unwrap();
+
+    let entry_point = contract
+        .entry_points_by_type
+        .external
+        .iter()
+        .find(|entry_point| {
+            program.funcs[entry_point.function_idx]
+                .id
+                .debug_name
+                .as_deref()
+                .is_some_and(|debug_name| debug_name.ends_with(name))
+        })
+        .unwrap();
+
+    let result =
+        run_native_starknet_contract(&program, entry_point.function_idx, &[], &mut SyscallHandler);
+
+    assert!(!result.failure_flag);
+    result.return_values
+}
+
+/// Return `6 * G` as the `u128` limbs of its coordinates, as returned by the contract.
+fn six_times_generator<C>() -> Vec<Felt>
+where
+    C: SWCurveConfig,
+    C::BaseField: PrimeField,
+{
+    let p = (Affine::<C>::generator() * C::ScalarField::from(6u64)).into_affine();
+    let (x, y) = from_affine(p);
+
+    [&x.0[..16], &x.0[16..], &y.0[..16], &y.0[16..]]
+        .into_iter()
+        .map(|limb| Felt::from(u128::from_le_bytes(limb.try_into().unwrap())))
+        .collect()
+}
+
+#[test]
+fn secp256k1_test() {
+    // (G + G) * 3
+    assert_eq!(
+        run_secp256_entry_point("secp256k1_test"),
+        six_times_generator::<ark_secp256k1::Config>(),
+    );
+}
+
+#[test]
+fn secp256r1_test() {
+    // (G + G) * 3
+    assert_eq!(
+        run_secp256_entry_point("secp256r1_test"),
+        six_times_generator::<ark_secp256r1::Config>(),
+    );
+}
+
+#[test]
+fn secp256k1_ops() {
+    compare_secp256_program(&SECP256K1_OPS);
+}
+
+#[test]
+fn secp256r1_ops() {
+    compare_secp256_program(&SECP256R1_OPS);
+}
+
+#[test]
+fn secp256k1_out_of_range() {
+    let result = compare_secp256_program(&SECP256K1_OUT_OF_RANGE);
+    assert_eq!(
+        result.return_value,
+        JitValue::Felt252(Felt::from_bytes_be_slice(b"Coordinates out of range")),
+    );
+}
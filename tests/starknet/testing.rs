@@ -0,0 +1,220 @@
+use cairo_lang_compiler::CompilerConfig;
+use cairo_lang_runner::SierraCasmRunner;
+use cairo_lang_sierra::program::Program;
+use cairo_lang_starknet::contract_class::{compile_path, ContractClass};
+use cairo_native::{
+    context::NativeContext,
+    error::jit_engine::{ErrorImpl, RunnerError},
+    execution_result::ExecutionResult,
+    executor::JitNativeExecutor,
+    metadata::syscall_handler::SyscallHandlerMeta,
+    starknet::{
+        unsupported_syscall, ExecutionInfo, ExecutionInfoV2, InMemorySyscallHandler,
+        StarkNetSyscallHandler, SyscallResult, U256,
+    },
+    utils::find_function_id,
+    values::JitValue,
+    OptLevel,
+};
+use lazy_static::lazy_static;
+use starknet_types_core::felt::Felt;
+use std::path::Path;
+
+use crate::common::{load_cairo, run_native_starknet_contract};
+
+lazy_static! {
+    static ref TESTING_CONTRACT: ContractClass = {
+        let path = Path::new("tests/starknet/contracts/test_testing.cairo");
+
+        compile_path(
+            path,
+            None,
+            CompilerConfig {
+                replace_ids: true,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+    };
+}
+
+#[test]
+fn testing_test() {
+    let contract = &TESTING_CONTRACT;
+
+    let entry_point = contract.entry_points_by_type.external.get(0).unwrap();
+
+    let program = contract.extract_sierra_program().unwrap();
+    let mut handler = InMemorySyscallHandler::new();
+    let result =
+        run_native_starknet_contract(&program, entry_point.function_idx, &[], &mut handler);
+
+    assert!(!result.failure_flag);
+    assert_eq!(
+        result.return_values,
+        vec![
+            9.into(),
+            1234.into(),
+            5678.into(),
+            2.into(),
+            10.into(),
+            1.into(),
+            1.into(),
+            2.into(),
+            3.into(),
+            1.into(),
+        ]
+    );
+
+    assert_eq!(handler.execution_info.block_info.block_number, 1234);
+    assert_eq!(handler.execution_info.caller_address, 5678.into());
+}
+
+/// Syscall handler which only implements the required syscalls.
+#[derive(Debug)]
+struct MinimalSyscallHandler;
+
+impl StarkNetSyscallHandler for MinimalSyscallHandler {
+    fn get_block_hash(&mut self, _block_number: u64, _gas: &mut u128) -> SyscallResult<Felt> {
+        unsupported_syscall()
+    }
+
+    fn get_execution_info(&mut self, _gas: &mut u128) -> SyscallResult<ExecutionInfo> {
+        unsupported_syscall()
+    }
+
+    fn get_execution_info_v2(&mut self, _gas: &mut u128) -> SyscallResult<ExecutionInfoV2> {
+        unsupported_syscall()
+    }
+
+    fn deploy(
+        &mut self,
+        _class_hash: Felt,
+        _contract_address_salt: Felt,
+        _calldata: &[Felt],
+        _deploy_from_zero: bool,
+        _gas: &mut u128,
+    ) -> SyscallResult<(Felt, Vec<Felt>)> {
+        unsupported_syscall()
+    }
+
+    fn replace_class(&mut self, _class_hash: Felt, _gas: &mut u128) -> SyscallResult<()> {
+        unsupported_syscall()
+    }
+
+    fn library_call(
+        &mut self,
+        _class_hash: Felt,
+        _function_selector: Felt,
+        _calldata: &[Felt],
+        _gas: &mut u128,
+    ) -> SyscallResult<Vec<Felt>> {
+        unsupported_syscall()
+    }
+
+    fn call_contract(
+        &mut self,
+        _address: Felt,
+        _entry_point_selector: Felt,
+        _calldata: &[Felt],
+        _gas: &mut u128,
+    ) -> SyscallResult<Vec<Felt>> {
+        unsupported_syscall()
+    }
+
+    fn storage_read(
+        &mut self,
+        _address_domain: u32,
+        _address: Felt,
+        _gas: &mut u128,
+    ) -> SyscallResult<Felt> {
+        unsupported_syscall()
+    }
+
+    fn storage_write(
+        &mut self,
+        _address_domain: u32,
+        _address: Felt,
+        _value: Felt,
+        _gas: &mut u128,
+    ) -> SyscallResult<()> {
+        unsupported_syscall()
+    }
+
+    fn emit_event(&mut self, _keys: &[Felt], _data: &[Felt], _gas: &mut u128) -> SyscallResult<()> {
+        unsupported_syscall()
+    }
+
+    fn send_message_to_l1(
+        &mut self,
+        _to_address: Felt,
+        _payload: &[Felt],
+        _gas: &mut u128,
+    ) -> SyscallResult<()> {
+        unsupported_syscall()
+    }
+
+    fn keccak(&mut self, _input: &[u64], _gas: &mut u128) -> SyscallResult<U256> {
+        unsupported_syscall()
+    }
+}
+
+fn invoke_with_minimal_handler(
+    (module_name, program, _): &(String, Program, SierraCasmRunner),
+) -> Result<ExecutionResult, RunnerError> {
+    let context = NativeContext::new();
+    let executor = JitNativeExecutor::new(
+        context.compile(program, OptLevel::default()).unwrap(),
+        OptLevel::default(),
+    );
+
+    executor.invoke_dynamic(
+        find_function_id(program, &format!("{0}::{0}::run_test", module_name)),
+        &[],
+        Some(u128::MAX),
+        Some(&SyscallHandlerMeta::new(&mut MinimalSyscallHandler)),
+    )
+}
+
+#[test]
+fn unsupported_testing_syscall() {
+    let program = load_cairo! {
+        use starknet::testing::set_block_number;
+
+        fn run_test() -> felt252 {
+            set_block_number(1234);
+            1
+        }
+    };
+
+    // The process isn't aborted: the invocation fails instead.
+    let error = invoke_with_minimal_handler(&program).unwrap_err();
+    assert!(matches!(
+        error.source,
+        ErrorImpl::TestingSyscallFailed { ref name, ref reason }
+            if name == "set_block_number"
+                && reason == &[Felt::from_bytes_be_slice(b"Unsupported syscall")]
+    ));
+}
+
+#[test]
+fn unsupported_secp256_syscall() {
+    let program = load_cairo! {
+        use array::ArrayTrait;
+        use starknet::secp256k1::secp256k1_new_syscall;
+
+        fn run_test() -> felt252 {
+            match secp256k1_new_syscall(1, 2) {
+                Result::Ok(_) => 0,
+                Result::Err(err) => *err.at(0),
+            }
+        }
+    };
+
+    // The failure is returned to the program.
+    let result = invoke_with_minimal_handler(&program).unwrap();
+    assert_eq!(
+        result.return_value,
+        JitValue::Felt252(Felt::from_bytes_be_slice(b"Unsupported syscall")),
+    );
+}
@@ -1,8 +1,12 @@
 use crate::common::load_cairo;
 use cairo_lang_sierra::program::Program;
 use cairo_native::{
-    context::NativeContext, execution_result::ExecutionResult, executor::JitNativeExecutor,
-    utils::find_function_id, values::JitValue,
+    context::NativeContext,
+    execution_result::{BuiltinStats, ExecutionResult},
+    executor::JitNativeExecutor,
+    utils::find_function_id,
+    values::JitValue,
+    OptLevel,
 };
 use starknet_types_core::felt::Felt;
 
@@ -12,8 +16,8 @@ fn run_program(program: &Program, entry_point: &str, args: &[JitValue]) -> Execu
     let entry_point_id = find_function_id(program, entry_point);
 
     let context = NativeContext::new();
-    let module = context.compile(program).unwrap();
-    let executor = JitNativeExecutor::new(module);
+    let module = context.compile(program, OptLevel::default()).unwrap();
+    let executor = JitNativeExecutor::new(module, OptLevel::default());
 
     executor
         .invoke_dynamic(entry_point_id, args, None, None)
@@ -34,6 +38,7 @@ fn invoke0() {
                 fields: Vec::new(),
                 debug_name: None,
             },
+            builtin_stats: BuiltinStats::default(),
         },
     );
 }
@@ -57,6 +62,7 @@ fn invoke1_felt252() {
             ExecutionResult {
                 remaining_gas: None,
                 return_value: x,
+                builtin_stats: BuiltinStats::default(),
             },
         );
     };
@@ -86,6 +92,7 @@ fn invoke1_u8() {
             ExecutionResult {
                 remaining_gas: None,
                 return_value: x,
+                builtin_stats: BuiltinStats::default(),
             },
         );
     };
@@ -115,6 +122,7 @@ fn invoke1_u16() {
             ExecutionResult {
                 remaining_gas: None,
                 return_value: x,
+                builtin_stats: BuiltinStats::default(),
             },
         );
     };
@@ -144,6 +152,7 @@ fn invoke1_u32() {
             ExecutionResult {
                 remaining_gas: None,
                 return_value: x,
+                builtin_stats: BuiltinStats::default(),
             },
         );
     };
@@ -173,6 +182,7 @@ fn invoke1_u64() {
             ExecutionResult {
                 remaining_gas: None,
                 return_value: x,
+                builtin_stats: BuiltinStats::default(),
             },
         );
     };
@@ -202,6 +212,7 @@ fn invoke1_u128() {
             ExecutionResult {
                 remaining_gas: None,
                 return_value: x,
+                builtin_stats: BuiltinStats::default(),
             },
         );
     };
@@ -234,6 +245,7 @@ fn invoke1_tuple1_felt252() {
             ExecutionResult {
                 remaining_gas: None,
                 return_value: x,
+                builtin_stats: BuiltinStats::default(),
             },
         );
     };
@@ -266,6 +278,7 @@ fn invoke1_tuple1_u64() {
             ExecutionResult {
                 remaining_gas: None,
                 return_value: x,
+                builtin_stats: BuiltinStats::default(),
             },
         );
     };
@@ -304,6 +317,7 @@ fn invoke1_tuple5_u8_u16_u32_u64_u128() {
             ExecutionResult {
                 remaining_gas: None,
                 return_value: x,
+                builtin_stats: BuiltinStats::default(),
             },
         );
     };
@@ -333,6 +347,7 @@ fn invoke1_array_felt252() {
             ExecutionResult {
                 remaining_gas: None,
                 return_value: x,
+                builtin_stats: BuiltinStats::default(),
             },
         );
     };
@@ -373,6 +388,7 @@ fn invoke1_enum1_unit() {
         ExecutionResult {
             remaining_gas: None,
             return_value: x,
+            builtin_stats: BuiltinStats::default(),
         },
     );
 }
@@ -404,6 +420,7 @@ fn invoke1_enum1_u64() {
             ExecutionResult {
                 remaining_gas: None,
                 return_value: x,
+                builtin_stats: BuiltinStats::default(),
             },
         );
     };
@@ -441,6 +458,7 @@ fn invoke1_enum1_felt252() {
             ExecutionResult {
                 remaining_gas: None,
                 return_value: x,
+                builtin_stats: BuiltinStats::default(),
             },
         );
     };
@@ -491,6 +509,7 @@ fn invoke1_enum2_u8_u16() {
             ExecutionResult {
                 remaining_gas: None,
                 return_value: x,
+                builtin_stats: BuiltinStats::default(),
             },
         );
     };